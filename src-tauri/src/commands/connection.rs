@@ -0,0 +1,40 @@
+//! Connection attempt and session-wide Tauri commands
+
+use crate::core::error::CommandError;
+use tauri::State;
+use crate::core::session::{ActiveSession, SessionCapabilities};
+use crate::managers::{ConnectionAttempts, FileTransferManager, TerminalManager};
+
+/// Cancel an in-progress connection attempt (SSH, Telnet, file session)
+/// Returns false if the attempt already finished
+#[tauri::command]
+pub async fn cancel_connection(
+    attempt_id: String,
+    attempts: State<'_, ConnectionAttempts>,
+) -> Result<bool, CommandError> {
+    Ok(attempts.cancel(&attempt_id).await)
+}
+
+/// What a terminal or file session supports, so the UI can hide unsupported actions
+#[tauri::command]
+pub async fn get_session_capabilities(
+    session_id: String,
+    terminal_manager: State<'_, TerminalManager>,
+    transfer_manager: State<'_, FileTransferManager>,
+) -> Result<SessionCapabilities, CommandError> {
+    if let Ok(capabilities) = terminal_manager.get_session_capabilities(&session_id).await {
+        return Ok(capabilities);
+    }
+    transfer_manager.capabilities(&session_id).await.map_err(CommandError::from)
+}
+
+/// Every open terminal and file session, oldest first within each kind
+#[tauri::command]
+pub async fn list_active_sessions(
+    terminal_manager: State<'_, TerminalManager>,
+    transfer_manager: State<'_, FileTransferManager>,
+) -> Result<Vec<ActiveSession>, CommandError> {
+    let mut sessions = terminal_manager.list_sessions().await;
+    sessions.extend(transfer_manager.list_sessions().await);
+    Ok(sessions)
+}
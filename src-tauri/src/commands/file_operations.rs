@@ -1,80 +1,56 @@
-use tauri::{AppHandle, State};
-use crate::managers::FileTransferManager;
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::managers::{ConflictPolicy, FileTransferManager};
+use crate::file_watcher::{FileChangeKind, FileWatcherManager, RemoteFileWatcherManager};
+use serde::Serialize;
+use crate::core::app_associations::{extension_of, AppAssociations, ASSOCIATIONS_FILE};
+use crate::core::error::{CommandError, ConnectionError};
+use crate::core::file_content::{check_size, FileContent, FileRange, MAX_RANGE_LENGTH};
+use base64::Engine;
 
-/// Get file stat/info (local)
-#[tauri::command]
-pub async fn get_local_file_stat(path: String) -> Result<serde_json::Value, String> {
-    use tokio::fs;
-    use std::time::UNIX_EPOCH;
-    
-    let metadata = fs::metadata(&path)
-        .await
-        .map_err(|e| format!("Failed to get file stat: {}", e))?;
-    
-    let modified = metadata.modified()
-        .ok()
-        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-        .map(|d| d.as_secs());
-    
-    let accessed = metadata.accessed()
-        .ok()
-        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-        .map(|d| d.as_secs());
-    
-    #[cfg(unix)]
-    let permissions: Option<u32> = {
-        use std::os::unix::fs::PermissionsExt;
-        Some(metadata.permissions().mode())
-    };
-    
-    #[cfg(not(unix))]
-    let permissions: Option<u32> = None;
-    
-    Ok(serde_json::json!({
-        "size": metadata.len(),
-        "isDirectory": metadata.is_dir(),
-        "isFile": metadata.is_file(),
-        "modified": modified,
-        "accessed": accessed,
-        "permissions": permissions.map(|p| format!("{:o}", p)),
-        "mode": permissions
-    }))
+/// Seconds since the Unix epoch for a metadata timestamp
+fn unix_secs(time: std::io::Result<std::time::SystemTime>) -> Option<u64> {
+    time.ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
 }
 
-/// Get local file info including symlink detection
-/// Uses symlink_metadata to detect symlinks without following them
-#[tauri::command]
-pub async fn get_local_file_info(path: String) -> Result<serde_json::Value, String> {
-    use std::fs;
-    use std::time::UNIX_EPOCH;
+/// Local file metadata shared by get_local_file_stat and get_local_file_info
+/// `follow` selects whether size/times/mode describe the symlink target or the link itself
+async fn local_file_metadata(path: &str, follow: bool) -> Result<serde_json::Value, CommandError> {
+    use tokio::fs;
 
-    // Use symlink_metadata to not follow symlinks
-    let metadata = fs::symlink_metadata(&path)
+    // Use symlink_metadata to detect symlinks without following them
+    let link_metadata = fs::symlink_metadata(path)
+        .await
         .map_err(|e| format!("Failed to get file info: {}", e))?;
+    let is_symlink = link_metadata.file_type().is_symlink();
 
-    let is_symlink = metadata.file_type().is_symlink();
+    let metadata = if follow && is_symlink {
+        fs::metadata(path)
+            .await
+            .map_err(|e| format!("Failed to get file stat: {}", e))?
+    } else {
+        link_metadata.clone()
+    };
 
     // Get symlink target if applicable
     let symlink_target = if is_symlink {
-        fs::read_link(&path)
+        fs::read_link(path)
+            .await
             .ok()
             .map(|p| p.to_string_lossy().to_string())
     } else {
         None
     };
 
-    // For symlinks, also get the target's metadata to determine if it points to a directory
-    let target_is_directory = if is_symlink {
-        fs::metadata(&path).map(|m| m.is_dir()).unwrap_or(false)
+    // For symlinks, report whether the target is a directory
+    let is_directory = if is_symlink {
+        fs::metadata(path).await.map(|m| m.is_dir()).unwrap_or(false)
     } else {
-        metadata.is_dir()
+        link_metadata.is_dir()
     };
 
-    let modified = metadata.modified()
-        .ok()
-        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-        .map(|d| d.as_secs());
-
     #[cfg(unix)]
     let permissions: Option<u32> = {
         use std::os::unix::fs::PermissionsExt;
@@ -84,17 +60,47 @@ pub async fn get_local_file_info(path: String) -> Result<serde_json::Value, Stri
     #[cfg(not(unix))]
     let permissions: Option<u32> = None;
 
+    // Numeric uid/gid locally (remote sessions report names when available)
+    #[cfg(unix)]
+    let (owner, group) = {
+        use std::os::unix::fs::MetadataExt;
+        (Some(metadata.uid().to_string()), Some(metadata.gid().to_string()))
+    };
+
+    #[cfg(not(unix))]
+    let (owner, group): (Option<String>, Option<String>) = (None, None);
+
     Ok(serde_json::json!({
         "size": metadata.len(),
-        "isDirectory": target_is_directory,
+        "isDirectory": is_directory,
         "isFile": metadata.is_file(),
         "isSymlink": is_symlink,
         "symlinkTarget": symlink_target,
-        "modified": modified,
-        "permissions": permissions.map(|p| format!("{:o}", p))
+        // created() is unsupported on some platforms/filesystems -> null
+        "created": unix_secs(metadata.created()),
+        "modified": unix_secs(metadata.modified()),
+        "accessed": unix_secs(metadata.accessed()),
+        "permissions": permissions.map(|p| format!("{:o}", p)),
+        "mode": permissions,
+        "owner": owner,
+        "group": group
     }))
 }
 
+/// Get file stat/info (local)
+/// Follows symlinks: size, times and mode describe the target
+#[tauri::command]
+pub async fn get_local_file_stat(path: String) -> Result<serde_json::Value, CommandError> {
+    local_file_metadata(&path, true).await
+}
+
+/// Get local file info including symlink detection
+/// Does not follow symlinks: size, times and mode describe the link itself
+#[tauri::command]
+pub async fn get_local_file_info(path: String) -> Result<serde_json::Value, CommandError> {
+    local_file_metadata(&path, false).await
+}
+
 /// Convert Unix permissions string (e.g., "drwxr-xr-x") to octal mode
 fn parse_permissions_string(perm_str: &str) -> Option<u32> {
     log::debug!("[get_remote_file_stat] Parsing permissions string: {}", perm_str);
@@ -134,9 +140,8 @@ pub async fn get_remote_file_stat(
     session_id: String,
     path: String,
     manager: State<'_, FileTransferManager>,
-) -> Result<serde_json::Value, String> {
-    let stat = manager.stat(&session_id, &path).await
-        .map_err(|e| format!("Failed to get file stat: {}", e))?;
+) -> Result<serde_json::Value, CommandError> {
+    let stat = manager.stat(&session_id, &path).await?;
     
     log::debug!("[get_remote_file_stat] File: {}, permissions: {:?}", path, stat.permissions);
     let mode = stat.permissions.as_ref().and_then(|p| {
@@ -155,6 +160,9 @@ pub async fn get_remote_file_stat(
         "size": stat.size,
         "isDirectory": stat.is_directory,
         "isFile": !stat.is_directory,
+        "isSymlink": stat.is_symlink,
+        "symlinkTarget": stat.symlink_target,
+        "created": null,
         "modified": stat.modified,
         "accessed": null,
         "permissions": stat.permissions,
@@ -167,7 +175,7 @@ pub async fn get_remote_file_stat(
 /// List Windows drives (C:, D:, E:, etc.)
 /// Returns empty array on non-Windows systems
 #[tauri::command]
-pub async fn list_windows_drives() -> Result<Vec<String>, String> {
+pub async fn list_windows_drives() -> Result<Vec<String>, CommandError> {
     #[cfg(target_os = "windows")]
     {
         use std::process::Command;
@@ -210,54 +218,207 @@ pub async fn list_windows_drives() -> Result<Vec<String>, String> {
 
 /// Open file with system default app (local only)
 #[tauri::command]
-pub async fn open_file_with_system(path: String) -> Result<(), String> {
-    open::that(&path).map_err(|e| format!("Failed to open file: {}", e))
+pub async fn open_file_with_system(path: String) -> Result<(), CommandError> {
+    open::that(&path).map_err(|e| ConnectionError::io("Failed to open file", &e).into())
+}
+
+/// Load the extension -> application associations from the app config dir
+fn load_app_associations(app_handle: &AppHandle) -> Result<AppAssociations, CommandError> {
+    let dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to resolve config dir: {}", e))?;
+    Ok(AppAssociations::load(dir.join(ASSOCIATIONS_FILE)))
 }
 
 /// Open file with specific application
+/// Without `app_path`, uses the remembered app for the file's extension,
+/// falling back to the system default
 #[tauri::command]
-pub async fn open_file_with_app(path: String, app_path: Option<String>) -> Result<(), String> {
-    #[cfg(target_os = "windows")]
+pub async fn open_file_with_app(
+    app_handle: AppHandle,
+    path: String,
+    app_path: Option<String>,
+) -> Result<(), CommandError> {
+    let app_path = match app_path {
+        Some(app) => Some(app),
+        None => extension_of(&path).and_then(|ext| {
+            load_app_associations(&app_handle)
+                .ok()
+                .and_then(|associations| associations.get(&ext).map(String::from))
+        }),
+    };
+
+    let Some(app) = app_path else {
+        return open::that(&path).map_err(|e| ConnectionError::io("Failed to open file", &e).into());
+    };
+
+    #[cfg(target_os = "macos")]
     {
-        if let Some(app) = app_path {
-            std::process::Command::new(&app)
-                .arg(&path)
-                .spawn()
-                .map_err(|e| format!("Failed to open file with {}: {}", app, e))?;
-        } else {
-            std::process::Command::new("rundll32.exe")
-                .args(&["shell32.dll", "OpenAs_RunDLL", &path])
-                .spawn()
-                .map_err(|e| format!("Failed to open file dialog: {}", e))?;
-        }
+        std::process::Command::new("open")
+            .args(["-a", &app, &path])
+            .spawn()
+            .map_err(|e| format!("Failed to open file with {}: {}", app, e))?;
     }
-    
-    #[cfg(target_os = "macos")]
+
+    #[cfg(not(target_os = "macos"))]
     {
-        if let Some(app) = app_path {
-            std::process::Command::new("open")
-                .args(&["-a", &app, &path])
-                .spawn()
-                .map_err(|e| format!("Failed to open file with {}: {}", app, e))?;
-        } else {
-            open::that(&path).map_err(|e| format!("Failed to open file: {}", e))?;
+        std::process::Command::new(&app)
+            .arg(&path)
+            .spawn()
+            .map_err(|e| format!("Failed to open file with {}: {}", app, e))?;
+    }
+
+    Ok(())
+}
+
+/// Sync status event for remote files opened in an external app
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteEditSyncEvent {
+    session_id: String,
+    remote_path: String,
+    local_path: String,
+    status: &'static str, // "uploading" | "synced" | "error"
+    error: Option<String>,
+}
+
+/// Re-upload an edited temp copy, emitting remote-edit-sync events
+async fn sync_remote_edit(app_handle: AppHandle, session_id: String, remote_path: String, local_path: String) {
+    let emit = |status: &'static str, error: Option<String>| {
+        let event = RemoteEditSyncEvent {
+            session_id: session_id.clone(),
+            remote_path: remote_path.clone(),
+            local_path: local_path.clone(),
+            status,
+            error,
+        };
+        if let Err(e) = app_handle.emit("remote-edit-sync", &event) {
+            log::error!("[RemoteEdit] Failed to emit sync event: {}", e);
+        }
+    };
+
+    emit("uploading", None);
+    let manager = app_handle.state::<FileTransferManager>();
+    let transfer_id = uuid::Uuid::new_v4().to_string();
+    match manager
+        .upload_file(&app_handle, &session_id, &local_path, &remote_path, None, ConflictPolicy::Overwrite, &transfer_id, false, None)
+        .await
+    {
+        Ok(_) => {
+            log::info!("[RemoteEdit] Synced {} -> {}", local_path, remote_path);
+            emit("synced", None);
+        }
+        Err(e) => {
+            log::error!("[RemoteEdit] Failed to sync {}: {}", remote_path, e);
+            emit("error", Some(e.to_string()));
         }
     }
-    
-    #[cfg(target_os = "linux")]
+}
+
+/// Open a remote file in an external application
+/// Downloads to a temp file, opens it, and re-uploads whenever it is saved
+/// (remote-edit-sync events report progress). Returns the temp path;
+/// call close_remote_edit with it to stop syncing and delete the copy.
+#[tauri::command]
+pub async fn open_remote_with_app(
+    app_handle: AppHandle,
+    session_id: String,
+    remote_path: String,
+    app_path: Option<String>,
+    manager: State<'_, FileTransferManager>,
+    watcher: State<'_, FileWatcherManager>,
+) -> Result<String, CommandError> {
+    let file_name = std::path::Path::new(&remote_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Not a file path: {}", remote_path))?
+        .to_string();
+
+    // One directory per edit so the file keeps its name (editors pick syntax by name)
+    let temp_dir = std::env::temp_dir().join(format!("rermius-edit-{}", uuid::Uuid::new_v4()));
+    tokio::fs::create_dir_all(&temp_dir)
+        .await
+        .map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let local_path = temp_dir.join(&file_name).to_string_lossy().to_string();
+
+    let transfer_id = uuid::Uuid::new_v4().to_string();
+    if let Err(e) = manager
+        .download_file(&app_handle, &session_id, &remote_path, &local_path, &transfer_id, false, None, false)
+        .await
     {
-        if let Some(app) = app_path {
-            std::process::Command::new("xdg-open")
-                .arg(&path)
-                .env("DESKTOP_STARTUP_ID", "")
-                .spawn()
-                .map_err(|e| format!("Failed to open file with {}: {}", app, e))?;
-        } else {
-            open::that(&path).map_err(|e| format!("Failed to open file: {}", e))?;
+        let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+        return Err(e.into());
+    }
+
+    // One upload at a time per file; saves made during an upload collapse into
+    // a single follow-up upload of whatever the copy holds by then
+    let (save_tx, mut save_rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+    let sync_app = app_handle.clone();
+    let sync_session = session_id.clone();
+    let sync_remote = remote_path.clone();
+    let sync_local = local_path.clone();
+    // Ends when the watch (and with it the sender) is dropped
+    tauri::async_runtime::spawn(async move {
+        while save_rx.recv().await.is_some() {
+            while save_rx.try_recv().is_ok() {}
+            sync_remote_edit(sync_app.clone(), sync_session.clone(), sync_remote.clone(), sync_local.clone()).await;
+        }
+    });
+
+    let watched_remote = remote_path.clone();
+    watcher.watch_temp_file(local_path.clone(), move |change| {
+        // Nothing to upload if the app deleted the copy
+        if change.kind == FileChangeKind::Removed {
+            log::warn!("[RemoteEdit] Temp copy of {} was removed", watched_remote);
+            return;
         }
+        let _ = save_tx.send(());
+    })?;
+
+    if let Err(e) = open_file_with_app(app_handle, local_path.clone(), app_path).await {
+        let _ = watcher.unwatch_file(&local_path);
+        return Err(e);
     }
-    
-    Ok(())
+
+    log::info!("[RemoteEdit] Editing {} via {}", remote_path, local_path);
+    Ok(local_path)
+}
+
+/// Stop syncing a remote file opened with open_remote_with_app and delete its temp copy
+#[tauri::command]
+pub async fn close_remote_edit(
+    local_path: String,
+    watcher: State<'_, FileWatcherManager>,
+) -> Result<(), CommandError> {
+    watcher.unwatch_file(&local_path).map_err(CommandError::from)
+}
+
+/// Remember (or with no `app_path`, forget) the application for a file extension
+#[tauri::command]
+pub async fn set_default_app(
+    app_handle: AppHandle,
+    extension: String,
+    app_path: Option<String>,
+) -> Result<(), CommandError> {
+    let mut associations = load_app_associations(&app_handle)?;
+    associations
+        .set(&extension, app_path)
+        .map_err(|e| format!("Failed to save app association: {}", e).into())
+}
+
+/// Get the remembered application for a file extension
+#[tauri::command]
+pub async fn get_default_app(app_handle: AppHandle, extension: String) -> Result<Option<String>, CommandError> {
+    let associations = load_app_associations(&app_handle)?;
+    Ok(associations.get(&extension).map(String::from))
+}
+
+/// Get all remembered extension -> application associations
+#[tauri::command]
+pub async fn list_default_apps(app_handle: AppHandle) -> Result<BTreeMap<String, String>, CommandError> {
+    let associations = load_app_associations(&app_handle)?;
+    Ok(associations.all().clone())
 }
 
 /// Show file picker dialog to select application
@@ -265,7 +426,7 @@ pub async fn open_file_with_app(path: String, app_path: Option<String>) -> Resul
 pub async fn show_open_with_dialog(
     app_handle: AppHandle,
     _path: String,
-) -> Result<Option<String>, String> {
+) -> Result<Option<String>, CommandError> {
     use tauri_plugin_dialog::DialogExt;
     use std::sync::mpsc;
     
@@ -331,7 +492,7 @@ pub async fn show_open_with_dialog(
 
 /// Show file in system file manager (local only)
 #[tauri::command]
-pub async fn show_in_file_manager(path: String) -> Result<(), String> {
+pub async fn show_in_file_manager(path: String) -> Result<(), CommandError> {
     #[cfg(target_os = "windows")]
     {
         std::process::Command::new("explorer")
@@ -364,27 +525,103 @@ pub async fn show_in_file_manager(path: String) -> Result<(), String> {
     Ok(())
 }
 
+/// Read raw file bytes (local or remote), refusing files over `max_bytes`
+async fn read_file_bytes(
+    session_id: Option<String>,
+    path: &str,
+    is_local: bool,
+    max_bytes: Option<u64>,
+    manager: &FileTransferManager,
+) -> Result<Vec<u8>, CommandError> {
+    if is_local {
+        let meta = tokio::fs::metadata(path)
+            .await
+            .map_err(|e| ConnectionError::io("Failed to read local file", &e))?;
+        check_size(meta.len(), max_bytes)?;
+        Ok(tokio::fs::read(path)
+            .await
+            .map_err(|e| ConnectionError::io("Failed to read local file", &e))?)
+    } else {
+        let session_id = session_id.ok_or("No session ID provided for remote file")?;
+        Ok(manager.read_file(&session_id, path, max_bytes).await?)
+    }
+}
+
 /// Read file content for editing (small files)
+/// Returns the text, or `{ binary: true, size }` for non-text files
+/// Files over `max_bytes` fail with "File too large: ..." before being downloaded
 #[tauri::command]
 pub async fn read_file_content(
     session_id: Option<String>,
     path: String,
     is_local: bool,
+    max_bytes: Option<u64>,
     manager: State<'_, FileTransferManager>,
-) -> Result<String, String> {
-    if is_local {
-        tokio::fs::read_to_string(&path)
+) -> Result<FileContent, CommandError> {
+    let content = read_file_bytes(session_id, &path, is_local, max_bytes, &manager).await?;
+    Ok(FileContent::from_bytes(content))
+}
+
+/// Read file bytes as base64 (for binary files the UI wants to display)
+/// Files over `max_bytes` fail with "File too large: ..."
+#[tauri::command]
+pub async fn read_file_base64(
+    session_id: Option<String>,
+    path: String,
+    is_local: bool,
+    max_bytes: Option<u64>,
+    manager: State<'_, FileTransferManager>,
+) -> Result<String, CommandError> {
+    let content = read_file_bytes(session_id, &path, is_local, max_bytes, &manager).await?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(content))
+}
+
+/// A window of a file, as returned by read_file_range
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileRangeContent {
+    pub data_base64: String,
+    /// Nothing follows this window
+    pub eof: bool,
+}
+
+/// Read `length` bytes from `offset` (at most 16 MiB), so the editor can page through large files
+/// Returns the bytes as base64 (a window may split a UTF-8 character) and whether the file ends there
+#[tauri::command]
+pub async fn read_file_range(
+    session_id: Option<String>,
+    path: String,
+    offset: u64,
+    length: u64,
+    is_local: bool,
+    manager: State<'_, FileTransferManager>,
+) -> Result<FileRangeContent, CommandError> {
+    let range = if is_local {
+        read_local_range(&path, offset, length.min(MAX_RANGE_LENGTH))
             .await
-            .map_err(|e| format!("Failed to read local file: {}", e))
+            .map_err(|e| ConnectionError::io("Failed to read local file", &e))?
     } else {
         let session_id = session_id.ok_or("No session ID provided for remote file")?;
-        let content = manager.read_file(&session_id, &path).await
-            .map_err(|e| e.to_string())?;
-        String::from_utf8(content).map_err(|e| format!("Failed to decode file content: {}", e))
-    }
+        manager.read_file_range(&session_id, &path, offset, length).await?
+    };
+    Ok(FileRangeContent {
+        data_base64: base64::engine::general_purpose::STANDARD.encode(&range.data),
+        eof: range.eof,
+    })
+}
+
+async fn read_local_range(path: &str, offset: u64, length: u64) -> std::io::Result<FileRange> {
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    let mut file = tokio::fs::File::open(path).await?;
+    file.seek(std::io::SeekFrom::Start(offset)).await?;
+    let mut data = Vec::new();
+    file.take(length.saturating_add(1)).read_to_end(&mut data).await?;
+    Ok(FileRange::from_read(data, length))
 }
 
 /// Write file content after editing
+/// A watch_remote_file poll won't report the change this save makes
 #[tauri::command]
 pub async fn write_file_content(
     session_id: Option<String>,
@@ -392,15 +629,17 @@ pub async fn write_file_content(
     content: String,
     is_local: bool,
     manager: State<'_, FileTransferManager>,
-) -> Result<(), String> {
+    remote_watcher: State<'_, RemoteFileWatcherManager>,
+) -> Result<(), CommandError> {
     if is_local {
         tokio::fs::write(&path, content.as_bytes())
             .await
-            .map_err(|e| format!("Failed to write local file: {}", e))
+            .map_err(|e| ConnectionError::io("Failed to write local file", &e).into())
     } else {
         let session_id = session_id.ok_or("No session ID provided for remote file")?;
-        manager.write_file(&session_id, &path, content.as_bytes()).await
-            .map_err(|e| e.to_string())
+        manager.write_file(&session_id, &path, content.as_bytes()).await?;
+        remote_watcher.mark_saved(&session_id, &path);
+        Ok(())
     }
 }
 
@@ -1,27 +1,48 @@
+use base64::Engine;
 use tauri::{AppHandle, State, Emitter};
-use crate::managers::{FileTransferManager, FileSessionConfig, FileInfoDto};
+use crate::core::error::{CommandError, ConnectionError};
+use crate::core::fs_stats::FilesystemStats;
+use crate::core::local_copy::{copy_path_with_progress, prepare_destination, CopyProgress};
+use std::path::Path;
+use serde::Serialize;
+use crate::managers::{BatchResult, ChmodItem, CommandOutput, ConflictPolicy, ConnectionAttempts, DeleteItem, DirectoryDownloadResult, DirectoryListingDto, DirectorySize, DirectoryUploadResult, FileTransferManager, FileSessionConfig, SyncResult, UploadItem, UploadResult};
+use crate::core::session::ListingWarning;
+use crate::core::sync::SyncOptions;
+use crate::core::upload_tree::SymlinkPolicy;
+use crate::file_watcher::{RemoteFileWatcherManager, RemoteTailManager};
 
 /// Create a new file transfer session (SFTP/FTP/FTPS)
+/// Cancellable via cancel_connection when attempt_id is given
 #[tauri::command]
 pub async fn create_file_session(
     config: FileSessionConfig,
+    attempt_id: Option<String>,
     manager: State<'_, FileTransferManager>,
+    attempts: State<'_, ConnectionAttempts>,
     app_handle: AppHandle,
-) -> Result<String, String> {
-    manager.create_session(config, app_handle).await.map_err(|e| e.to_string())
+) -> Result<String, CommandError> {
+    attempts
+        .run(attempt_id, async {
+            manager.create_session(config, app_handle).await.map_err(CommandError::from)
+        })
+        .await
 }
 
 /// List directory contents
+/// Returns { entries, warnings }; unreadable entries become warnings instead of failing the listing
 #[tauri::command]
 pub async fn list_directory(
     session_id: String,
     path: String,
     manager: State<'_, FileTransferManager>,
-) -> Result<Vec<FileInfoDto>, String> {
-    manager.list_directory(&session_id, &path).await.map_err(|e| e.to_string())
+) -> Result<DirectoryListingDto, CommandError> {
+    manager.list_directory(&session_id, &path).await.map_err(CommandError::from)
 }
 
 /// Download file from remote to local
+/// `resume` continues a partial local file over SFTP when the remote file is unchanged
+/// `max_bytes_per_sec` overrides the session's bandwidth cap (0 = unlimited)
+/// `verify_checksum` compares the result with the server's sha256sum/md5sum (SFTP)
 #[tauri::command]
 pub async fn download_file(
     app_handle: tauri::AppHandle,
@@ -29,33 +50,202 @@ pub async fn download_file(
     remote_path: String,
     local_path: String,
     transfer_id: String,
+    resume: Option<bool>,
+    max_bytes_per_sec: Option<u64>,
+    verify_checksum: Option<bool>,
     manager: State<'_, FileTransferManager>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     manager
-        .download_file(&app_handle, &session_id, &remote_path, &local_path, &transfer_id)
+        .download_file(
+            &app_handle,
+            &session_id,
+            &remote_path,
+            &local_path,
+            &transfer_id,
+            resume.unwrap_or(false),
+            max_bytes_per_sec,
+            verify_checksum.unwrap_or(false),
+        )
         .await
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 /// Upload file from local to remote
+/// `remote_dir` (the drop target) is used to reject paths that escape it
+/// `on_conflict` is overwrite/skip/rename (default rename)
+/// `preserve_attributes` copies the local permissions and mtime to the remote file (SFTP)
+/// `max_bytes_per_sec` overrides the session's bandwidth cap (0 = unlimited)
 #[tauri::command]
 pub async fn upload_file(
     app_handle: tauri::AppHandle,
     session_id: String,
     local_path: String,
     remote_path: String,
+    remote_dir: Option<String>,
+    on_conflict: Option<ConflictPolicy>,
+    transfer_id: String,
+    preserve_attributes: Option<bool>,
+    max_bytes_per_sec: Option<u64>,
+    manager: State<'_, FileTransferManager>,
+) -> Result<UploadResult, CommandError> {
+    manager
+        .upload_file(
+            &app_handle,
+            &session_id,
+            &local_path,
+            &remote_path,
+            remote_dir.as_deref(),
+            on_conflict.unwrap_or_default(),
+            &transfer_id,
+            preserve_attributes.unwrap_or(false),
+            max_bytes_per_sec,
+        )
+        .await
+        .map_err(CommandError::from)
+}
+
+/// Upload a local directory tree into remote_dir (renamed `name (N)` if it exists there)
+/// `symlinks`: "skip" (default) or "follow"
+/// Progress is emitted as 'directory-upload-progress', each finished file as 'directory-upload-file'
+#[tauri::command]
+pub async fn upload_directory(
+    app_handle: tauri::AppHandle,
+    session_id: String,
+    local_dir: String,
+    remote_dir: String,
+    symlinks: Option<SymlinkPolicy>,
+    transfer_id: String,
+    manager: State<'_, FileTransferManager>,
+) -> Result<DirectoryUploadResult, CommandError> {
+    manager
+        .upload_directory(
+            &app_handle,
+            &session_id,
+            &local_dir,
+            &remote_dir,
+            symlinks.unwrap_or_default(),
+            &transfer_id,
+        )
+        .await
+        .map_err(CommandError::from)
+}
+
+/// Download a remote directory tree into local_dir (renamed `name (N)` if it exists there)
+/// Progress is emitted as 'directory-download-progress', each finished file as 'directory-download-file'
+#[tauri::command]
+pub async fn download_directory(
+    app_handle: tauri::AppHandle,
+    session_id: String,
+    remote_dir: String,
+    local_dir: String,
+    transfer_id: String,
+    manager: State<'_, FileTransferManager>,
+) -> Result<DirectoryDownloadResult, CommandError> {
+    manager
+        .download_directory(&app_handle, &session_id, &remote_dir, &local_dir, &transfer_id)
+        .await
+        .map_err(CommandError::from)
+}
+
+/// Upload many files, `concurrency` at a time (default 4, capped at 16); destinations are overwritten
+/// Emits file-batch-progress (operation "upload") per finished file; cancel_transfer(batch_id) stops the rest
+#[tauri::command]
+pub async fn upload_files(
+    app_handle: AppHandle,
+    session_id: String,
+    items: Vec<UploadItem>,
+    concurrency: Option<usize>,
+    batch_id: Option<String>,
+    manager: State<'_, FileTransferManager>,
+) -> Result<BatchResult, CommandError> {
+    manager
+        .upload_files(&app_handle, &session_id, items, concurrency.unwrap_or(4), batch_id)
+        .await
+        .map_err(CommandError::from)
+}
+
+/// Cancel a running upload/download by its transfer_id or batch_id (partial downloads are deleted)
+/// Returns false when the transfer already finished or was never started
+#[tauri::command]
+pub fn cancel_transfer(
+    transfer_id: String,
+    manager: State<'_, FileTransferManager>,
+) -> Result<bool, CommandError> {
+    Ok(manager.cancel_transfer(&transfer_id))
+}
+
+/// Total size and file count of a remote directory tree
+/// Emits 'directory-size-progress' while scanning; unreadable subdirectories are counted, not fatal
+#[tauri::command]
+pub async fn calculate_directory_size(
+    app_handle: AppHandle,
+    session_id: String,
+    path: String,
+    scan_id: Option<String>,
+    manager: State<'_, FileTransferManager>,
+) -> Result<DirectorySize, CommandError> {
+    manager
+        .calculate_directory_size(&app_handle, &session_id, &path, scan_id)
+        .await
+        .map_err(CommandError::from)
+}
+
+/// Mirror a local directory to a remote one: upload new/changed files (size + mtime)
+/// `options.delete` removes remote paths missing locally; `options.dryRun` only returns the plan
+/// Progress is emitted as 'directory-sync-progress' events
+#[tauri::command]
+pub async fn sync_directory(
+    app_handle: tauri::AppHandle,
+    session_id: String,
+    local_dir: String,
+    remote_dir: String,
+    options: Option<SyncOptions>,
+    sync_id: Option<String>,
+    manager: State<'_, FileTransferManager>,
+) -> Result<SyncResult, CommandError> {
+    manager
+        .sync_directory(&app_handle, &session_id, &local_dir, &remote_dir, options.unwrap_or_default(), sync_id)
+        .await
+        .map_err(CommandError::from)
+}
+
+/// Upload one chunk of a file that only exists in the webview (e.g. a dropped File object)
+/// Chunks are sent in order starting at offset 0; `is_last` completes the upload
+/// Returns the number of bytes written so far
+#[tauri::command]
+pub async fn upload_bytes(
+    app_handle: tauri::AppHandle,
+    session_id: String,
+    remote_path: String,
+    chunk_base64: String,
+    offset: u64,
+    is_last: bool,
+    total_bytes: Option<u64>,
     transfer_id: String,
     manager: State<'_, FileTransferManager>,
-) -> Result<(), String> {
+) -> Result<u64, CommandError> {
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(chunk_base64.as_bytes())
+        .map_err(|e| format!("Invalid base64 chunk: {}", e))?;
+
     manager
-        .upload_file(&app_handle, &session_id, &local_path, &remote_path, &transfer_id)
+        .upload_chunk(
+            &app_handle,
+            &session_id,
+            &remote_path,
+            &data,
+            offset,
+            is_last,
+            total_bytes,
+            &transfer_id,
+        )
         .await
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 /// Test event emission (for debugging)
 #[tauri::command]
-pub async fn test_file_transfer_event(app_handle: tauri::AppHandle) -> Result<(), String> {
+pub async fn test_file_transfer_event(app_handle: tauri::AppHandle) -> Result<(), CommandError> {
     use serde_json::json;
     log::info!("[Test] Emitting test event");
     if let Err(e) = app_handle.emit("file-transfer-progress", &json!({
@@ -69,7 +259,7 @@ pub async fn test_file_transfer_event(app_handle: tauri::AppHandle) -> Result<()
         "done": false
     })) {
         log::error!("[Test] Failed to emit test event: {}", e);
-        Err(format!("Failed to emit test event: {}", e))
+        Err(format!("Failed to emit test event: {}", e).into())
     } else {
         log::info!("[Test] Successfully emitted test event");
         Ok(())
@@ -77,13 +267,29 @@ pub async fn test_file_transfer_event(app_handle: tauri::AppHandle) -> Result<()
 }
 
 /// Create directory on remote
+/// With `recursive`, missing parents are created too and an existing directory is not an error (mkdir -p)
 #[tauri::command]
 pub async fn create_remote_directory(
+    session_id: String,
+    path: String,
+    recursive: Option<bool>,
+    manager: State<'_, FileTransferManager>,
+) -> Result<(), CommandError> {
+    if recursive.unwrap_or(false) {
+        manager.create_directory_recursive(&session_id, &path).await.map_err(CommandError::from)
+    } else {
+        manager.create_directory(&session_id, &path).await.map_err(CommandError::from)
+    }
+}
+
+/// Total/free/available bytes of the remote filesystem holding `path` (SFTP only; FTP fails with UNSUPPORTED)
+#[tauri::command]
+pub async fn statfs(
     session_id: String,
     path: String,
     manager: State<'_, FileTransferManager>,
-) -> Result<(), String> {
-    manager.create_directory(&session_id, &path).await.map_err(|e| e.to_string())
+) -> Result<FilesystemStats, CommandError> {
+    manager.statfs(&session_id, &path).await.map_err(CommandError::from)
 }
 
 /// Delete file or directory on remote
@@ -93,8 +299,43 @@ pub async fn delete_remote_path(
     path: String,
     is_directory: bool,
     manager: State<'_, FileTransferManager>,
-) -> Result<(), String> {
-    manager.delete(&session_id, &path, is_directory).await.map_err(|e| e.to_string())
+) -> Result<(), CommandError> {
+    manager.delete(&session_id, &path, is_directory).await.map_err(CommandError::from)
+}
+
+/// Delete many files/directories on remote in one call
+/// Emits file-batch-progress per item; failures are reported, not fatal
+/// `dry_run` returns the planned deletions in `planned` without deleting anything
+#[tauri::command]
+pub async fn delete_remote_paths(
+    app_handle: AppHandle,
+    session_id: String,
+    items: Vec<DeleteItem>,
+    batch_id: Option<String>,
+    dry_run: Option<bool>,
+    manager: State<'_, FileTransferManager>,
+) -> Result<BatchResult, CommandError> {
+    manager
+        .delete_batch(&app_handle, &session_id, items, batch_id, dry_run.unwrap_or(false))
+        .await
+        .map_err(CommandError::from)
+}
+
+/// Delete a file or a non-empty directory on remote, contents first
+/// Emits file-batch-progress per item; failures (e.g. a locked file) are reported, not fatal.
+/// Cancel with cancel_transfer(transfer_id)
+#[tauri::command]
+pub async fn delete_remote_recursive(
+    app_handle: AppHandle,
+    session_id: String,
+    path: String,
+    transfer_id: Option<String>,
+    manager: State<'_, FileTransferManager>,
+) -> Result<BatchResult, CommandError> {
+    manager
+        .delete_recursive(&app_handle, &session_id, &path, transfer_id)
+        .await
+        .map_err(CommandError::from)
 }
 
 /// Rename file or directory on remote
@@ -104,8 +345,8 @@ pub async fn rename_remote_path(
     old_path: String,
     new_path: String,
     manager: State<'_, FileTransferManager>,
-) -> Result<(), String> {
-    manager.rename(&session_id, &old_path, &new_path).await.map_err(|e| e.to_string())
+) -> Result<(), CommandError> {
+    manager.rename(&session_id, &old_path, &new_path).await.map_err(CommandError::from)
 }
 
 /// Rename file or directory locally
@@ -113,11 +354,11 @@ pub async fn rename_remote_path(
 pub async fn rename_local_path(
     old_path: String,
     new_path: String,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     use tokio::fs;
     fs::rename(&old_path, &new_path)
         .await
-        .map_err(|e| format!("Failed to rename: {}", e))
+        .map_err(|e| ConnectionError::io("Failed to rename", &e).into())
 }
 
 /// Close file transfer session
@@ -125,64 +366,195 @@ pub async fn rename_local_path(
 pub async fn close_file_session(
     session_id: String,
     manager: State<'_, FileTransferManager>,
-) -> Result<(), String> {
-    manager.close_session(&session_id).await.map_err(|e| e.to_string())
+    remote_watcher: State<'_, RemoteFileWatcherManager>,
+    tails: State<'_, RemoteTailManager>,
+) -> Result<(), CommandError> {
+    remote_watcher.unwatch_session(&session_id);
+    tails.stop_session(&session_id);
+    manager.close_session(&session_id).await.map_err(CommandError::from)
+}
+
+/// Close every file transfer session (e.g. on exit), returning how many were closed
+#[tauri::command]
+pub async fn close_all_transfers(
+    manager: State<'_, FileTransferManager>,
+    remote_watcher: State<'_, RemoteFileWatcherManager>,
+    tails: State<'_, RemoteTailManager>,
+) -> Result<usize, CommandError> {
+    for session in manager.list_sessions().await {
+        remote_watcher.unwatch_session(&session.id);
+        tails.stop_session(&session.id);
+    }
+    Ok(manager.close_all_sessions().await)
 }
 
-/// Change file permissions (SFTP only)
+/// Change file permissions (FTP via SITE CHMOD where supported)
 #[tauri::command]
 pub async fn chmod_remote(
     session_id: String,
     path: String,
     mode: u32,
     manager: State<'_, FileTransferManager>,
-) -> Result<(), String> {
-    manager.chmod(&session_id, &path, mode).await.map_err(|e| e.to_string())
+) -> Result<(), CommandError> {
+    manager.chmod(&session_id, &path, mode).await.map_err(CommandError::from)
 }
 
-/// Copy file or directory locally (recursive)
+/// Create a symlink on remote at link_path pointing to target (SFTP only)
 #[tauri::command]
-pub async fn copy_local_path(
+pub async fn create_remote_symlink(
+    session_id: String,
+    target: String,
+    link_path: String,
+    manager: State<'_, FileTransferManager>,
+) -> Result<(), CommandError> {
+    manager
+        .create_symlink(&session_id, &target, &link_path)
+        .await
+        .map_err(CommandError::from)
+}
+
+/// Run a shell command on the file session's host (SFTP only; FTP sessions return an error)
+#[tauri::command]
+pub async fn run_remote_command(
+    session_id: String,
+    command: String,
+    manager: State<'_, FileTransferManager>,
+) -> Result<CommandOutput, CommandError> {
+    manager.run_command(&session_id, &command).await.map_err(CommandError::from)
+}
+
+/// Change permissions of many files in one call
+/// Emits file-batch-progress per item; failures are reported, not fatal
+/// `dry_run` returns current -> new permissions in `planned` without changing anything
+#[tauri::command]
+pub async fn chmod_remote_batch(
+    app_handle: AppHandle,
+    session_id: String,
+    items: Vec<ChmodItem>,
+    batch_id: Option<String>,
+    dry_run: Option<bool>,
+    manager: State<'_, FileTransferManager>,
+) -> Result<BatchResult, CommandError> {
+    manager
+        .chmod_batch(&app_handle, &session_id, items, batch_id, dry_run.unwrap_or(false))
+        .await
+        .map_err(CommandError::from)
+}
+
+/// Local copy/move progress event payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LocalTransferProgressEvent {
+    transfer_id: Option<String>,
+    operation: &'static str, // "copy" | "move"
     source_path: String,
     dest_path: String,
-) -> Result<(), String> {
-    use tokio::fs;
+    #[serde(flatten)]
+    progress: CopyProgress,
+    done: bool,
+}
 
-    // Check if source exists
-    let metadata = fs::metadata(&source_path)
+/// Copy locally, emitting local-transfer-progress events
+async fn copy_local_with_progress(
+    app_handle: &AppHandle,
+    transfer_id: Option<String>,
+    operation: &'static str,
+    source_path: &str,
+    dest_path: &str,
+) -> Result<CopyProgress, CommandError> {
+    // Check if source exists (without following symlinks)
+    let metadata = tokio::fs::symlink_metadata(source_path)
         .await
         .map_err(|e| format!("Source not found: {}", e))?;
 
-    if metadata.is_dir() {
-        // Recursive directory copy
-        copy_dir_recursive(&source_path, &dest_path)
-            .await
-            .map_err(|e| format!("Failed to copy directory: {}", e))
-    } else {
-        // File copy
-        fs::copy(&source_path, &dest_path)
-            .await
-            .map_err(|e| format!("Failed to copy file: {}", e))?;
-        Ok(())
+    let event = LocalTransferProgressEvent {
+        transfer_id,
+        operation,
+        source_path: source_path.to_string(),
+        dest_path: dest_path.to_string(),
+        progress: CopyProgress::default(),
+        done: false,
+    };
+    let app = app_handle.clone();
+    let mut progress_event = event.clone();
+    let mut on_progress = move |progress: &CopyProgress| {
+        progress_event.progress = *progress;
+        emit_local_progress(&app, &progress_event);
+    };
+
+    let progress = copy_path_with_progress(Path::new(source_path), Path::new(dest_path), &mut on_progress)
+        .await
+        .map_err(|e| {
+            if metadata.is_dir() {
+                format!("Failed to copy directory: {}", e)
+            } else {
+                format!("Failed to copy file: {}", e)
+            }
+        })?;
+
+    if operation == "copy" {
+        emit_local_progress(app_handle, &LocalTransferProgressEvent { progress, done: true, ..event });
     }
+    Ok(progress)
+}
+
+fn emit_local_progress(app_handle: &AppHandle, event: &LocalTransferProgressEvent) {
+    if let Err(e) = app_handle.emit("local-transfer-progress", event) {
+        log::error!("[FileTransfer] Failed to emit local progress event: {}", e);
+    }
+}
+
+/// Copy file or directory locally (recursive)
+/// Keeps permission bits and copies symlinks as symlinks
+/// An existing destination is refused unless `overwrite` (then it is replaced)
+/// Emits local-transfer-progress events (files/bytes done of total)
+#[tauri::command]
+pub async fn copy_local_path(
+    app_handle: AppHandle,
+    source_path: String,
+    dest_path: String,
+    transfer_id: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<(), CommandError> {
+    prepare_destination(Path::new(&source_path), Path::new(&dest_path), overwrite.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())?;
+    copy_local_with_progress(&app_handle, transfer_id, "copy", &source_path, &dest_path).await?;
+    Ok(())
 }
 
-/// Move file or directory locally (rename is atomic, fallback to copy+delete)
+/// Move file or directory locally (rename is atomic, fallback to copy+delete across filesystems)
+/// An existing destination is refused unless `overwrite` (then it is replaced)
+/// Emits local-transfer-progress events while falling back to copy
 #[tauri::command]
 pub async fn move_local_path(
+    app_handle: AppHandle,
     source_path: String,
     dest_path: String,
-) -> Result<(), String> {
+    transfer_id: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<(), CommandError> {
     use tokio::fs;
 
+    prepare_destination(Path::new(&source_path), Path::new(&dest_path), overwrite.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())?;
+
     // Try atomic rename first (works if on same filesystem)
-    match fs::rename(&source_path, &dest_path).await {
-        Ok(_) => Ok(()),
+    let progress = match fs::rename(&source_path, &dest_path).await {
+        Ok(_) => CopyProgress {
+            files_done: 1,
+            files_total: 1,
+            ..Default::default()
+        },
+        Err(e) if e.kind() != std::io::ErrorKind::CrossesDevices => {
+            return Err(ConnectionError::io("Failed to move", &e).into());
+        }
         Err(_) => {
             // Fallback: copy then delete (for cross-filesystem moves)
-            copy_local_path(source_path.clone(), dest_path).await?;
+            let progress = copy_local_with_progress(&app_handle, transfer_id.clone(), "move", &source_path, &dest_path).await?;
 
-            let metadata = fs::metadata(&source_path)
+            let metadata = fs::symlink_metadata(&source_path)
                 .await
                 .map_err(|e| format!("Failed to check source: {}", e))?;
 
@@ -195,70 +567,49 @@ pub async fn move_local_path(
                     .await
                     .map_err(|e| format!("Failed to remove source file: {}", e))?;
             }
-            Ok(())
+            progress
         }
-    }
-}
-
-/// Helper: Recursive directory copy
-async fn copy_dir_recursive(source: &str, dest: &str) -> Result<(), std::io::Error> {
-    use tokio::fs;
-    use std::path::Path;
-
-    // Create destination directory
-    fs::create_dir_all(dest).await?;
-
-    // Read source directory entries
-    let mut entries = fs::read_dir(source).await?;
-
-    while let Some(entry) = entries.next_entry().await? {
-        let source_path = entry.path();
-        let file_name = entry.file_name();
-        let dest_path = Path::new(dest).join(&file_name);
-
-        let metadata = entry.metadata().await?;
-
-        if metadata.is_dir() {
-            // Recursive copy for subdirectories
-            Box::pin(copy_dir_recursive(
-                source_path.to_str().unwrap(),
-                dest_path.to_str().unwrap()
-            )).await?;
-        } else {
-            // Copy file
-            fs::copy(&source_path, &dest_path).await?;
-        }
-    }
+    };
 
+    emit_local_progress(&app_handle, &LocalTransferProgressEvent {
+        transfer_id,
+        operation: "move",
+        source_path,
+        dest_path,
+        progress,
+        done: true,
+    });
     Ok(())
 }
 
-/// Copy file or directory on remote (SFTP/FTP)
+/// Copy file or directory on remote (SFTP/FTP); directories are copied recursively
+/// SFTP streams file contents server-to-server over the session and keeps permissions
+/// Returns the entries that couldn't be read and were skipped ([{path, message}])
 #[tauri::command]
 pub async fn copy_remote_path(
     session_id: String,
     source_path: String,
     dest_path: String,
     manager: State<'_, FileTransferManager>,
-) -> Result<(), String> {
+) -> Result<Vec<ListingWarning>, CommandError> {
     manager
         .copy_remote(&session_id, &source_path, &dest_path)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
-/// Move file or directory on remote (uses rename from trait)
+/// Move file or directory on remote
+/// Renames when possible; across filesystems it copies, then deletes the source
 #[tauri::command]
 pub async fn move_remote_path(
     session_id: String,
     source_path: String,
     dest_path: String,
     manager: State<'_, FileTransferManager>,
-) -> Result<(), String> {
-    // Reuse existing rename command (rename = move in SFTP/FTP)
+) -> Result<(), CommandError> {
     manager
-        .rename(&session_id, &source_path, &dest_path)
+        .move_remote(&session_id, &source_path, &dest_path)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
@@ -1,14 +1,18 @@
+use crate::core::error::CommandError;
 use tauri::{AppHandle, State};
-use crate::file_watcher::FileWatcherManager;
+use crate::file_watcher::{FileWatcherManager, RemoteFileWatcherManager, RemoteTailManager};
 
 /// Start watching a file for changes
+/// Emits "file-changed" {path, kind: 'modified' | 'created' | 'removed'} once events have
+/// been quiet for `debounce_ms` (default 500); 'created' means the file was replaced (e.g. by rename)
 #[tauri::command]
 pub fn watch_file(
     path: String,
+    debounce_ms: Option<u64>,
     app_handle: AppHandle,
     watcher_manager: State<'_, FileWatcherManager>,
-) -> Result<(), String> {
-    watcher_manager.watch_file(path, app_handle)
+) -> Result<(), CommandError> {
+    watcher_manager.watch_file(path, debounce_ms, app_handle).map_err(CommandError::from)
 }
 
 /// Stop watching a file
@@ -16,7 +20,53 @@ pub fn watch_file(
 pub fn unwatch_file(
     path: String,
     watcher_manager: State<'_, FileWatcherManager>,
-) -> Result<(), String> {
-    watcher_manager.unwatch_file(&path)
+) -> Result<(), CommandError> {
+    watcher_manager.unwatch_file(&path).map_err(CommandError::from)
 }
 
+
+/// Start polling a remote file for changes (size/mtime via stat, every `interval_secs`, default 5)
+/// Emits "remote-file-changed" {sessionId, path, size, modified}; polling backs off while stat fails
+#[tauri::command]
+pub fn watch_remote_file(
+    session_id: String,
+    path: String,
+    interval_secs: Option<u64>,
+    app_handle: AppHandle,
+    remote_watcher: State<'_, RemoteFileWatcherManager>,
+) {
+    remote_watcher.watch_remote_file(session_id, path, interval_secs, app_handle)
+}
+
+/// Stop polling a remote file
+#[tauri::command]
+pub fn unwatch_remote_file(
+    session_id: String,
+    path: String,
+    remote_watcher: State<'_, RemoteFileWatcherManager>,
+) -> Result<(), CommandError> {
+    remote_watcher.unwatch_remote_file(&session_id, &path).map_err(CommandError::from)
+}
+
+/// Follow a remote log like `tail -f` (SFTP sessions only), returning the follow id
+/// New lines arrive as "remote-file-tail:{id}" (text); "remote-file-tail-end:{id}" {error} when it stops.
+/// With `restart` (default true) tail is started again if it exits
+#[tauri::command]
+pub fn follow_remote_file(
+    session_id: String,
+    path: String,
+    restart: Option<bool>,
+    app_handle: AppHandle,
+    tails: State<'_, RemoteTailManager>,
+) -> String {
+    tails.follow(session_id, path, restart.unwrap_or(true), app_handle)
+}
+
+/// Stop following a remote file
+#[tauri::command]
+pub fn stop_follow(
+    follow_id: String,
+    tails: State<'_, RemoteTailManager>,
+) -> Result<(), CommandError> {
+    tails.stop_follow(&follow_id).map_err(CommandError::from)
+}
@@ -2,6 +2,7 @@
 pub mod terminal;
 pub mod ssh;
 pub mod telnet;
+pub mod connection;
 pub mod file_transfer;
 pub mod file_operations;
 pub mod file_watcher;
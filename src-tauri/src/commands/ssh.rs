@@ -1,59 +1,152 @@
+use crate::core::error::{CommandError, ErrorCode};
 use tauri::{AppHandle, State};
-use crate::managers::TerminalManager;
-use crate::ssh::config::HostConfigInput;
+use crate::core::initial_command::InitialCommand;
+use crate::managers::{ConnectionAttempts, TerminalManager};
+use crate::ssh::config::{HostConfigInput, SshConfig, SshSessionInput};
+use crate::ssh::host_key::HostKeyPrompts;
+use crate::ssh::interactive::AuthPrompts;
+use crate::ssh::ssh_config;
 
-/// Create a new SSH session
+/// Create a new SSH session (port 0 or omitted = 22)
+/// An encrypted key without `keyPassphrase` fails with "Key passphrase required: ..."
+/// Cancellable via cancel_connection when attempt_id is given
+/// Options (`SshSessionInput`):
+/// `connectTimeoutSecs` bounds TCP connect + handshake (default 30s)
+/// `compression` prefers zlib (off by default)
+/// `algorithms` overrides kex/cipher/MAC/host key preferences ("+name" adds to the defaults)
+/// `proxyCommand` connects through an external program instead of TCP (e.g. "cloudflared access ssh --hostname %h")
+/// `scrollbackBytes` of recent output are kept for get_scrollback (default 256KB, 0 = none)
+/// `term` is the terminal type requested with the PTY (default xterm-256color)
+/// `initialCommand` is typed into the shell once its prompt is ready
+/// `fallbackAuthMethods` are tried in order on the same connection if `authMethod` is rejected
 #[tauri::command]
 pub async fn create_ssh_session(
-    hostname: String,
-    port: u16,
-    username: String,
-    auth_method: String,
-    key_path: Option<String>,
-    password: Option<String>,
-    _connection_type: Option<String>,
+    options: SshSessionInput,
+    attempt_id: Option<String>,
     manager: State<'_, TerminalManager>,
+    attempts: State<'_, ConnectionAttempts>,
     app_handle: AppHandle,
-) -> Result<String, String> {
-    let cols = 80;
-    let rows = 24;
-
-    manager
-        .create_ssh_session(
-            hostname,
-            port,
-            username,
-            auth_method,
-            key_path,
-            password,
-            cols,
-            rows,
-            app_handle,
-        )
-        .await
+) -> Result<String, CommandError> {
+    let connect = manager.create_ssh_session(options, app_handle);
+    attempts.run(attempt_id, connect).await
 }
 
 /// Create a chained SSH session through jump hosts (ProxyJump)
+/// Each hop's `connect_timeout_secs` also bounds opening the tunnel to it
+/// `initial_command` is typed into the target's shell once its prompt is ready
+/// The agent is forwarded when the target hop has `forward_agent` set
+/// Cancellable via cancel_connection when attempt_id is given
 #[tauri::command]
 pub async fn create_chained_ssh_session(
     chain: Vec<HostConfigInput>,
     cols: u16,
     rows: u16,
+    keepalive_secs: Option<u64>,
+    scrollback_bytes: Option<usize>,
+    term: Option<String>,
+    initial_command: Option<InitialCommand>,
     _connection_type: Option<String>,
+    attempt_id: Option<String>,
     manager: State<'_, TerminalManager>,
+    attempts: State<'_, ConnectionAttempts>,
     app_handle: AppHandle,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
     if chain.is_empty() {
-        return Err("Chain cannot be empty".to_string());
+        return Err(CommandError::new(ErrorCode::InvalidConfig, "Chain cannot be empty"));
     }
 
     let chain: Vec<_> = chain
         .into_iter()
         .map(|h| h.into_host_config())
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| CommandError::new(ErrorCode::InvalidConfig, e))?;
+
+    let connect = manager.create_chained_ssh_session(
+        chain,
+        cols,
+        rows,
+        keepalive_secs,
+        scrollback_bytes,
+        term,
+        initial_command,
+        app_handle,
+    );
+
+    attempts.run(attempt_id, connect).await
+}
+
+/// Look up a host alias in ~/.ssh/config (HostName, User, Port, IdentityFile, ProxyJump, ...)
+/// Returns the target with `jumps` for ProxyJump, to pre-fill the connection form;
+/// an alias without a config entry comes back as a plain host on port 22
+#[tauri::command]
+pub fn resolve_ssh_host(alias: String) -> Result<SshConfig, CommandError> {
+    ssh_config::resolve_host(&alias).map_err(CommandError::from)
+}
+
+/// Answer a keyboard-interactive prompt ("ssh-auth-prompt" event)
+/// `responses` = None cancels authentication; returns false if the prompt expired
+#[tauri::command]
+pub async fn respond_auth_prompt(
+    prompt_id: String,
+    responses: Option<Vec<String>>,
+    prompts: State<'_, AuthPrompts>,
+) -> Result<bool, CommandError> {
+    Ok(prompts.respond(&prompt_id, responses))
+}
+
+/// Answer a new or changed host key prompt ("ssh-host-key-prompt" event)
+/// `trust` = false aborts the connection; returns false if the prompt expired
+#[tauri::command]
+pub async fn respond_host_key_prompt(
+    prompt_id: String,
+    trust: bool,
+    prompts: State<'_, HostKeyPrompts>,
+) -> Result<bool, CommandError> {
+    Ok(prompts.respond(&prompt_id, trust))
+}
+
+/// Start a SOCKS5 proxy on 127.0.0.1 tunneled through an SSH session (ssh -D)
+/// bind_port 0 picks a free port; returns the bound port
+#[tauri::command]
+pub async fn start_dynamic_forward(
+    session_id: String,
+    bind_port: u16,
+    manager: State<'_, TerminalManager>,
+) -> Result<u16, CommandError> {
+    manager.start_dynamic_forward(&session_id, bind_port).await.map_err(CommandError::from)
+}
+
+/// Stop a SOCKS5 proxy started with start_dynamic_forward
+#[tauri::command]
+pub async fn stop_dynamic_forward(
+    session_id: String,
+    bind_port: u16,
+    manager: State<'_, TerminalManager>,
+) -> Result<(), CommandError> {
+    manager.stop_dynamic_forward(&session_id, bind_port).await.map_err(CommandError::from)
+}
 
+/// Forward 127.0.0.1:local_port to remote_host:remote_port through an SSH session (ssh -L)
+/// local_port 0 picks a free port; returns the bound port
+#[tauri::command]
+pub async fn add_local_forward(
+    session_id: String,
+    local_port: u16,
+    remote_host: String,
+    remote_port: u16,
+    manager: State<'_, TerminalManager>,
+) -> Result<u16, CommandError> {
     manager
-        .create_chained_ssh_session(chain, cols, rows, app_handle)
-        .await
+        .add_local_forward(&session_id, local_port, remote_host, remote_port)
+        .await.map_err(CommandError::from)
 }
 
+/// Tear down a forward created with add_local_forward
+#[tauri::command]
+pub async fn remove_local_forward(
+    session_id: String,
+    local_port: u16,
+    manager: State<'_, TerminalManager>,
+) -> Result<(), CommandError> {
+    manager.remove_local_forward(&session_id, local_port).await.map_err(CommandError::from)
+}
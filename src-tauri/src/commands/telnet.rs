@@ -1,24 +1,28 @@
 //! Telnet Tauri commands
 
+use crate::core::error::CommandError;
 use tauri::{AppHandle, State};
-use crate::managers::TerminalManager;
+use crate::managers::{ConnectionAttempts, TerminalManager};
+use crate::telnet::TelnetSessionInput;
 
-/// Create a new Telnet session
+/// Create a new Telnet session (port 0 or omitted = 23)
+/// Cancellable via cancel_connection when attempt_id is given
+/// Options (`TelnetSessionInput`):
+/// `environment` is sent to servers that ask via NEW-ENVIRON
+/// `autoLogin` overrides the login/password prompts and how long to wait for each;
+/// on timeout 'telnet-login-timeout:{id}' is emitted and the user types the credentials
+/// `connectTimeoutSecs` bounds the TCP connect (default 30s)
+/// `scrollbackBytes` of recent output are kept for get_scrollback (default 256KB, 0 = none)
+/// `term` is the only terminal type offered for TTYPE (default: xterm-256color, then xterm, vt100)
+/// `initialCommand` is typed in once auto-login finished and a shell prompt shows
 #[tauri::command]
 pub async fn create_telnet_session(
-    hostname: String,
-    port: u16,
-    username: Option<String>,
-    password: Option<String>,
-    cols: Option<u16>,
-    rows: Option<u16>,
+    options: TelnetSessionInput,
+    attempt_id: Option<String>,
     manager: State<'_, TerminalManager>,
+    attempts: State<'_, ConnectionAttempts>,
     app_handle: AppHandle,
-) -> Result<String, String> {
-    let cols = cols.unwrap_or(80);
-    let rows = rows.unwrap_or(24);
-
-    manager
-        .create_telnet_session(hostname, port, username, password, cols, rows, app_handle)
-        .await
+) -> Result<String, CommandError> {
+    let connect = manager.create_telnet_session(options, app_handle);
+    attempts.run(attempt_id, connect).await
 }
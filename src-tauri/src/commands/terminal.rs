@@ -1,19 +1,47 @@
+use crate::core::error::CommandError;
 use tauri::{AppHandle, State};
 use crate::managers::TerminalManager;
-use crate::core::history::parse_history_output;
-use tokio::time::{timeout, Duration};
+use crate::core::initial_command::InitialCommand;
+use crate::core::paste::sanitize_paste;
+use crate::core::session::SessionInfo;
+use crate::core::history::{parse_history_output, remote_history_commands, RemoteShell, SHELL_DETECT_COMMAND};
+use std::collections::HashMap;
+use tokio::time::Duration;
+use base64::Engine;
 
 /// Create a new terminal session
+/// args are passed to the shell as-is, one per element (e.g. ["--login"], ["-d", "Ubuntu"] for wsl.exe)
+/// env entries are added to the shell's environment (TERM may be overridden); cwd must exist
+/// binary_output = true emits base64 bytes on "terminal-output-bytes:{id}" instead of text
+/// scrollback_bytes of recent output are kept for get_scrollback (default 256KB, 0 = none)
+/// initial_command is typed in once the shell prompt is ready
 #[tauri::command]
 pub async fn create_terminal(
     shell: Option<String>,
+    args: Option<Vec<String>>,
     cols: u16,
     rows: u16,
+    env: Option<HashMap<String, String>>,
+    cwd: Option<String>,
+    binary_output: Option<bool>,
+    scrollback_bytes: Option<usize>,
+    initial_command: Option<InitialCommand>,
     manager: State<'_, TerminalManager>,
     app_handle: AppHandle,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
     manager
-        .create_local_session(shell, cols, rows, app_handle)
+        .create_local_session(
+            shell,
+            args.unwrap_or_default(),
+            cols,
+            rows,
+            env.unwrap_or_default(),
+            cwd,
+            binary_output.unwrap_or(false),
+            scrollback_bytes,
+            initial_command,
+            app_handle,
+        )
         .await
 }
 
@@ -23,8 +51,45 @@ pub async fn write_terminal(
     session_id: String,
     data: String,
     manager: State<'_, TerminalManager>,
-) -> Result<(), String> {
-    manager.write_to_session(&session_id, data.as_bytes()).await
+) -> Result<(), CommandError> {
+    manager.write_to_session(&session_id, data.as_bytes()).await.map_err(CommandError::from)
+}
+
+/// Write raw bytes (base64-encoded) to a terminal session
+/// For control bytes and non-UTF-8 data that can't round-trip through a string
+#[tauri::command]
+pub async fn write_terminal_bytes(
+    session_id: String,
+    data_base64: String,
+    manager: State<'_, TerminalManager>,
+) -> Result<(), CommandError> {
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(data_base64.trim())
+        .map_err(|e| format!("Invalid base64 data: {}", e))?;
+    manager.write_to_session(&session_id, &data).await.map_err(CommandError::from)
+}
+
+/// Paste data into a terminal session (bracketed paste when supported, chunked when large)
+#[tauri::command]
+pub async fn paste_terminal(
+    session_id: String,
+    data: String,
+    manager: State<'_, TerminalManager>,
+) -> Result<(), CommandError> {
+    manager.paste_to_session(&session_id, data.as_bytes()).await.map_err(CommandError::from)
+}
+
+/// Paste data as one bracketed paste (`\e[200~` ... `\e[201~`), so a supporting shell doesn't run each line
+/// `sanitize` strips control characters other than newlines and tabs (paste injection)
+#[tauri::command]
+pub async fn write_terminal_paste(
+    session_id: String,
+    data: String,
+    sanitize: Option<bool>,
+    manager: State<'_, TerminalManager>,
+) -> Result<(), CommandError> {
+    let data = if sanitize.unwrap_or(false) { sanitize_paste(&data) } else { data };
+    manager.bracketed_paste_to_session(&session_id, data.as_bytes()).await.map_err(CommandError::from)
 }
 
 /// Resize a terminal session
@@ -34,8 +99,8 @@ pub async fn resize_terminal(
     cols: u16,
     rows: u16,
     manager: State<'_, TerminalManager>,
-) -> Result<(), String> {
-    manager.resize_session(&session_id, cols, rows).await
+) -> Result<(), CommandError> {
+    manager.resize_session(&session_id, cols, rows).await.map_err(CommandError::from)
 }
 
 /// Close a terminal session
@@ -44,8 +109,28 @@ pub async fn close_terminal(
     session_id: String,
     manager: State<'_, TerminalManager>,
     app_handle: AppHandle,
-) -> Result<(), String> {
-    manager.close_session(&session_id, &app_handle).await
+) -> Result<(), CommandError> {
+    manager.close_session(&session_id, &app_handle).await.map_err(CommandError::from)
+}
+
+/// Close every terminal session (e.g. on exit), returning how many were closed
+#[tauri::command]
+pub async fn close_all_terminals(
+    manager: State<'_, TerminalManager>,
+    app_handle: AppHandle,
+) -> Result<usize, CommandError> {
+    Ok(manager.close_all_sessions(&app_handle).await)
+}
+
+/// Reconnect an SSH/Telnet session whose connection dropped (exit event with reconnectable = true)
+/// Reuses the session id, so existing listeners keep working and streaming restarts right away
+#[tauri::command]
+pub async fn reconnect_session(
+    session_id: String,
+    manager: State<'_, TerminalManager>,
+    app_handle: AppHandle,
+) -> Result<String, CommandError> {
+    manager.reconnect_session(&session_id, app_handle).await
 }
 
 /// Start streaming for a terminal session (call after FE listener is ready)
@@ -53,54 +138,127 @@ pub async fn close_terminal(
 pub async fn start_terminal_streaming(
     session_id: String,
     manager: State<'_, TerminalManager>,
-) -> Result<(), String> {
-    manager.start_streaming(&session_id).await
+) -> Result<(), CommandError> {
+    manager.start_streaming(&session_id).await.map_err(CommandError::from)
 }
 
 /// Ping a terminal session (keepalive check)
+/// False when the connection (or local shell) is dead even though the session still exists
 #[tauri::command]
 pub async fn ping_terminal(
     session_id: String,
     manager: State<'_, TerminalManager>,
-) -> Result<bool, String> {
-    manager.ping_session(&session_id).await
+) -> Result<bool, CommandError> {
+    manager.ping_session(&session_id).await.map_err(CommandError::from)
 }
 
-/// Execute a command on a terminal session and return output (SSH only)
+/// Execute a command on a terminal session and return output (SSH, or local via the session's shell)
+/// `timeout_ms` bounds how long to wait for the command to exit
 #[tauri::command]
 pub async fn execute_terminal_command(
     session_id: String,
     command: String,
+    timeout_ms: Option<u64>,
     manager: State<'_, TerminalManager>,
-) -> Result<String, String> {
-    manager.execute_command(&session_id, &command).await
+) -> Result<String, CommandError> {
+    let output = match timeout_ms {
+        Some(ms) => manager.execute_command_with_timeout(&session_id, &command, Duration::from_millis(ms)).await,
+        None => manager.execute_command(&session_id, &command).await,
+    };
+    output.map_err(CommandError::from)
 }
 
+/// Get information about a terminal session (type, charset, SSH banner and algorithms)
+#[tauri::command]
+pub async fn get_terminal_session_info(
+    session_id: String,
+    manager: State<'_, TerminalManager>,
+) -> Result<SessionInfo, CommandError> {
+    manager.get_session_info(&session_id).await.map_err(CommandError::from)
+}
+
+/// Record a terminal session to an asciinema v2 .cast file (relative timestamps, resizes included)
+/// record_input = true also records typed input, passwords included (default false)
+#[tauri::command]
+pub async fn start_recording(
+    session_id: String,
+    path: String,
+    record_input: Option<bool>,
+    manager: State<'_, TerminalManager>,
+) -> Result<(), CommandError> {
+    manager.start_recording(&session_id, &path, record_input.unwrap_or(false)).await.map_err(CommandError::from)
+}
+
+/// Stop recording a terminal session (also stopped when the session closes)
+#[tauri::command]
+pub async fn stop_recording(
+    session_id: String,
+    manager: State<'_, TerminalManager>,
+) -> Result<(), CommandError> {
+    manager.stop_recording(&session_id).await.map_err(CommandError::from)
+}
+
+/// Recent output of a terminal session, to repaint it after the window reloads
+/// Text, or base64 for sessions created with binary_output; empty when scrollback is disabled
+#[tauri::command]
+pub async fn get_scrollback(
+    session_id: String,
+    manager: State<'_, TerminalManager>,
+) -> Result<String, CommandError> {
+    manager.get_scrollback(&session_id).await.map_err(CommandError::from)
+}
+
+/// Total time fetch_command_history may spend on a remote host
+const HISTORY_FETCH_BUDGET: Duration = Duration::from_secs(2);
+/// Longest a single history attempt may take within the budget
+const HISTORY_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Fetch command history from an SSH session
+/// Detects the remote shell first so usually only one history command runs;
+/// gives up once HISTORY_FETCH_BUDGET is spent
 #[tauri::command]
 pub async fn fetch_command_history(
     session_id: String,
     limit: Option<u32>,
     manager: State<'_, TerminalManager>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, CommandError> {
     let limit = limit.unwrap_or(100);
+    let deadline = tokio::time::Instant::now() + HISTORY_FETCH_BUDGET;
+    let attempt_timeout = || {
+        deadline
+            .saturating_duration_since(tokio::time::Instant::now())
+            .min(HISTORY_ATTEMPT_TIMEOUT)
+    };
+
+    let shell = match manager
+        .execute_command_with_timeout(&session_id, SHELL_DETECT_COMMAND, attempt_timeout())
+        .await
+    {
+        Ok(output) => RemoteShell::detect(&output),
+        Err(e) => {
+            log::debug!("[fetch_command_history] Shell detection failed: {}", e);
+            RemoteShell::Unknown
+        }
+    };
+    log::info!("[fetch_command_history] Remote shell: {:?}", shell);
 
-    let commands_to_try = vec![
-        format!("tail -n {} ~/.bash_history 2>/dev/null", limit),
-        format!("tail -n {} ~/.zsh_history 2>/dev/null | cut -d ';' -f 2-", limit),
-        format!("HISTFILE=~/.bash_history bash -c 'set -o history; history -r; history {}' 2>/dev/null", limit),
-        format!("fc -l -{} 2>/dev/null", limit),
-    ];
+    let commands_to_try = remote_history_commands(shell, limit);
 
     for (idx, command) in commands_to_try.iter().enumerate() {
+        let timeout = attempt_timeout();
+        if timeout.is_zero() {
+            log::warn!("[fetch_command_history] Time budget spent after {} attempt(s)", idx);
+            break;
+        }
+
         log::info!("[fetch_command_history] Attempt {}: {}", idx + 1, command);
         let attempt_start = std::time::Instant::now();
 
-        match timeout(
-            Duration::from_secs(1),
-            manager.execute_command(&session_id, command)
-        ).await {
-            Ok(Ok(output)) => {
+        match manager
+            .execute_command_with_timeout(&session_id, command, timeout)
+            .await
+        {
+            Ok(output) => {
                 let elapsed = attempt_start.elapsed();
                 log::info!(
                     "[fetch_command_history] Attempt {} completed in {:?}, output length: {} bytes",
@@ -125,7 +283,7 @@ pub async fn fetch_command_history(
                     }
                 }
             }
-            Ok(Err(e)) => {
+            Err(e) => {
                 let elapsed = attempt_start.elapsed();
                 log::debug!(
                     "[fetch_command_history] Attempt {} failed after {:?}: {}",
@@ -134,19 +292,10 @@ pub async fn fetch_command_history(
                     e
                 );
             }
-            Err(_) => {
-                let elapsed = attempt_start.elapsed();
-                log::warn!(
-                    "[fetch_command_history] Attempt {} timeout after {:?}: {}",
-                    idx + 1,
-                    elapsed,
-                    command
-                );
-            }
         }
     }
 
-    log::warn!("[fetch_command_history] All methods failed to fetch history");
+    log::warn!("[fetch_command_history] No history found");
     Ok(Vec::new())
 }
 
@@ -155,11 +304,12 @@ pub async fn fetch_command_history(
 pub async fn fetch_local_shell_history(
     shell: Option<String>,
     limit: Option<u32>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, CommandError> {
     let limit = limit.unwrap_or(100);
 
     tauri::async_runtime::spawn_blocking(move || crate::core::history::read_local_shell_history(shell, limit))
         .await
         .map_err(|e| format!("Failed to join history task: {}", e))?
+        .map_err(CommandError::from)
 }
 
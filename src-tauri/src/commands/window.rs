@@ -1,7 +1,8 @@
+use crate::core::error::CommandError;
 use tauri::AppHandle;
 use log::info;
 
-fn spawn_new_instance() -> Result<(), String> {
+fn spawn_new_instance() -> Result<(), CommandError> {
     use std::process::Command;
     
     let exe_path = std::env::current_exe()
@@ -17,11 +18,11 @@ fn spawn_new_instance() -> Result<(), String> {
 
 /// Create a new window (spawns new instance)
 #[tauri::command]
-pub async fn create_new_window(_app_handle: AppHandle) -> Result<(), String> {
+pub async fn create_new_window(_app_handle: AppHandle) -> Result<(), CommandError> {
     spawn_new_instance()
 }
 
-pub fn spawn_new_instance_for_menu() -> Result<(), String> {
+pub fn spawn_new_instance_for_menu() -> Result<(), CommandError> {
     spawn_new_instance()
 }
 
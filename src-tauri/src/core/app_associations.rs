@@ -0,0 +1,96 @@
+//! Persisted "open with" choices: file extension -> application path
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File name inside the app config dir
+pub const ASSOCIATIONS_FILE: &str = "app_associations.json";
+
+/// Extension -> application map backed by a JSON file
+pub struct AppAssociations {
+    path: PathBuf,
+    apps: BTreeMap<String, String>,
+}
+
+impl AppAssociations {
+    /// Load associations from `path`; a missing or unreadable file means none are set
+    pub fn load(path: PathBuf) -> Self {
+        let apps = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(apps) => Some(apps),
+                Err(e) => {
+                    log::warn!("[AppAssociations] Ignoring corrupt {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self { path, apps }
+    }
+
+    /// Application for an extension, if one was chosen
+    pub fn get(&self, extension: &str) -> Option<&str> {
+        self.apps.get(&normalize_extension(extension)).map(String::as_str)
+    }
+
+    /// Remember (Some) or forget (None) the application for an extension
+    pub fn set(&mut self, extension: &str, app_path: Option<String>) -> io::Result<()> {
+        let extension = normalize_extension(extension);
+        match app_path {
+            Some(app_path) => self.apps.insert(extension, app_path),
+            None => self.apps.remove(&extension),
+        };
+        self.save()
+    }
+
+    /// All associations
+    pub fn all(&self) -> &BTreeMap<String, String> {
+        &self.apps
+    }
+
+    fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(&self.apps).map_err(io::Error::other)?;
+        std::fs::write(&self.path, content)
+    }
+}
+
+/// Lowercase extension without a leading dot ("TXT", ".txt" -> "txt")
+fn normalize_extension(extension: &str) -> String {
+    extension.trim().trim_start_matches('.').to_lowercase()
+}
+
+/// Extension of a file path (None for extensionless files and dotfiles)
+pub fn extension_of(path: &str) -> Option<String> {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(normalize_extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_associations_persist_and_normalize() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("config").join(ASSOCIATIONS_FILE);
+
+        let mut associations = AppAssociations::load(file.clone());
+        assert_eq!(associations.get("log"), None);
+        associations.set(".LOG", Some("/usr/bin/code".to_string())).unwrap();
+
+        let reloaded = AppAssociations::load(file.clone());
+        assert_eq!(reloaded.get("log"), Some("/usr/bin/code"));
+        assert_eq!(extension_of("/var/log/syslog.Log"), Some("log".to_string()));
+        assert_eq!(extension_of("/home/user/.bashrc"), None);
+
+        let mut reloaded = reloaded;
+        reloaded.set("log", None).unwrap();
+        assert!(AppAssociations::load(file).all().is_empty());
+    }
+}
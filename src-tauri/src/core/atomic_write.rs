@@ -0,0 +1,129 @@
+//! Remote saves through a sibling temp file, so an interrupted write can't truncate the original
+
+use crate::core::error::ConnectionError;
+use crate::core::session::FileTransferSession;
+use log::{debug, warn};
+
+/// Name prefix of in-progress saves (left behind only if the connection dies mid-save)
+pub const TEMP_PREFIX: &str = ".rermius-tmp-";
+
+/// Write `content` to a temp file next to `path`, then rename it over `path`
+/// The original keeps its permissions; if anything fails it is left as it was
+pub async fn write_via_temp<S>(session: &S, path: &str, content: &[u8]) -> Result<(), ConnectionError>
+where
+    S: FileTransferSession + ?Sized,
+{
+    session.check_writable(path).await?;
+    let existing = session.stat(path).await.ok();
+    if existing.as_ref().is_some_and(|info| info.is_symlink) {
+        // Renaming over a link would replace it with a regular file
+        return session.write_chunk(path, 0, content, true).await;
+    }
+
+    let temp = temp_path(path);
+    debug!("[AtomicWrite] Saving {} via {}", path, temp);
+    if let Err(e) = session.write_chunk(&temp, 0, content, true).await {
+        discard(session, &temp).await;
+        return Err(e);
+    }
+
+    if let Some(mode) = existing.as_ref().and_then(|info| info.permissions.as_deref()).and_then(parse_mode) {
+        if let Err(e) = session.chmod(&temp, mode).await {
+            warn!("[AtomicWrite] Cannot keep permissions {:o} on {}: {}", mode, path, e);
+        }
+    }
+
+    if let Err(e) = replace(session, &temp, path, existing.is_some()).await {
+        discard(session, &temp).await;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Rename `temp` over `path`; servers that won't rename onto an existing file
+/// (SFTP v3, some FTP servers) get the original removed first
+async fn replace<S>(session: &S, temp: &str, path: &str, exists: bool) -> Result<(), ConnectionError>
+where
+    S: FileTransferSession + ?Sized,
+{
+    match session.rename(temp, path).await {
+        Ok(()) => Ok(()),
+        Err(e) if exists => {
+            debug!("[AtomicWrite] Rename over {} refused ({}), removing it first", path, e);
+            session.delete(path, false).await?;
+            session.rename(temp, path).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn discard<S>(session: &S, temp: &str)
+where
+    S: FileTransferSession + ?Sized,
+{
+    if let Err(e) = session.delete(temp, false).await {
+        debug!("[AtomicWrite] Cannot remove temp file {}: {}", temp, e);
+    }
+}
+
+/// Hidden sibling of `path`: same directory, so the final rename never crosses filesystems
+fn temp_path(path: &str) -> String {
+    let dir_end = path.rfind('/').map(|i| i + 1).unwrap_or(0);
+    format!("{}{}{}", &path[..dir_end], TEMP_PREFIX, uuid::Uuid::new_v4().simple())
+}
+
+/// Permission bits from a listing: octal (SFTP, may include file type bits) or "-rwxr-xr-x" (FTP)
+fn parse_mode(permissions: &str) -> Option<u32> {
+    if let Ok(mode) = u32::from_str_radix(permissions, 8) {
+        return Some(mode & 0o777);
+    }
+    let bits = permissions.get(permissions.len().checked_sub(9)?..)?;
+    bits.chars().zip((0..9).rev()).try_fold(0, |mode, (c, bit)| match c {
+        '-' => Some(mode),
+        'r' | 'w' | 'x' | 's' | 't' => Some(mode | (1 << bit)),
+        'S' | 'T' => Some(mode),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::fake_session::FakeSession;
+
+    #[tokio::test]
+    async fn test_failed_write_leaves_original_untouched() {
+        let remote = FakeSession::new().file_with_mode("/etc/app.conf", b"original", 0o600).with_dropping_writes();
+
+        let result = write_via_temp(&remote, "/etc/app.conf", b"half-written replacement").await;
+        assert!(result.is_err());
+        assert_eq!(remote.content("/etc/app.conf"), Some(b"original".to_vec()));
+        assert_eq!(remote.mode("/etc/app.conf"), Some(0o600));
+        assert_eq!(remote.paths(), ["/etc", "/etc/app.conf"], "temp file not cleaned up");
+    }
+
+    #[tokio::test]
+    async fn test_replaces_file_and_keeps_permissions() {
+        // Renames refuse existing targets like SFTP v3, so the original is removed first
+        let remote = FakeSession::new().file_with_mode("/etc/app.conf", b"original", 0o600);
+        write_via_temp(&remote, "/etc/app.conf", b"updated").await.unwrap();
+        assert_eq!(remote.content("/etc/app.conf"), Some(b"updated".to_vec()));
+        assert_eq!(remote.mode("/etc/app.conf"), Some(0o600));
+        assert_eq!(remote.paths(), ["/etc", "/etc/app.conf"]);
+
+        // A new file is simply renamed into place
+        write_via_temp(&remote, "/etc/new.conf", b"fresh").await.unwrap();
+        assert_eq!(remote.content("/etc/new.conf"), Some(b"fresh".to_vec()));
+    }
+
+    #[test]
+    fn test_temp_path_and_parse_mode() {
+        assert!(temp_path("/home/u/notes.txt").starts_with("/home/u/.rermius-tmp-"));
+        assert!(temp_path("notes.txt").starts_with(".rermius-tmp-"));
+
+        assert_eq!(parse_mode("100644"), Some(0o644));
+        assert_eq!(parse_mode("-rwxr-x---"), Some(0o750));
+        assert_eq!(parse_mode("-rwsr-xr-T"), Some(0o754));
+        assert_eq!(parse_mode("unknown"), None);
+    }
+}
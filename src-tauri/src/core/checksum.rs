@@ -0,0 +1,113 @@
+//! Checksums for verifying downloads against the server's copy
+
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Hash algorithms a remote checksum command can provide
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Sha256,
+    Md5,
+}
+
+impl ChecksumAlgorithm {
+    /// Tried in order; MD5 only for servers without sha256sum
+    pub const PREFERRED: [ChecksumAlgorithm; 2] = [Self::Sha256, Self::Md5];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "SHA-256",
+            Self::Md5 => "MD5",
+        }
+    }
+
+    /// Shell command printing "<hex digest>  <path>" (coreutils)
+    pub fn command(&self, path: &str) -> String {
+        let program = match self {
+            Self::Sha256 => "sha256sum",
+            Self::Md5 => "md5sum",
+        };
+        format!("{} -b -- '{}'", program, path.replace('\'', "'\\''"))
+    }
+
+    fn hex_len(&self) -> usize {
+        match self {
+            Self::Sha256 => 64,
+            Self::Md5 => 32,
+        }
+    }
+}
+
+/// The lowercase digest from checksum command output, if it holds one
+pub fn parse_checksum_output(output: &str, algorithm: ChecksumAlgorithm) -> Option<String> {
+    // A leading backslash marks a file name with escaped characters
+    let digest = output.split_whitespace().next()?.trim_start_matches('\\');
+    (digest.len() == algorithm.hex_len() && digest.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| digest.to_ascii_lowercase())
+}
+
+/// Lowercase hex digest of a local file (blocking)
+pub fn hash_local_file(path: &Path, algorithm: ChecksumAlgorithm) -> std::io::Result<String> {
+    match algorithm {
+        ChecksumAlgorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            read_chunks(path, |chunk| hasher.update(chunk))?;
+            Ok(format!("{:x}", hasher.finalize()))
+        }
+        ChecksumAlgorithm::Md5 => {
+            let mut context = md5::Context::new();
+            read_chunks(path, |chunk| context.consume(chunk))?;
+            Ok(format!("{:x}", context.compute()))
+        }
+    }
+}
+
+fn read_chunks(path: &Path, mut consume: impl FnMut(&[u8])) -> std::io::Result<()> {
+    let mut file = File::open(path)?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(());
+        }
+        consume(&buffer[..n]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_local_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), b"abc").unwrap();
+        assert_eq!(
+            hash_local_file(file.path(), ChecksumAlgorithm::Sha256).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hash_local_file(file.path(), ChecksumAlgorithm::Md5).unwrap(),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+    }
+
+    #[test]
+    fn test_parse_checksum_output() {
+        let md5 = "900150983cd24fb0d6963f7d28e17f72";
+        assert_eq!(parse_checksum_output(&format!("{} *./abc.txt\n", md5), ChecksumAlgorithm::Md5).as_deref(), Some(md5));
+        assert_eq!(parse_checksum_output(&format!("\\{} *a\\nb", md5.to_uppercase()), ChecksumAlgorithm::Md5).as_deref(), Some(md5));
+        // Wrong length for the algorithm, or an error message instead of a digest
+        assert_eq!(parse_checksum_output(&format!("{} *abc", md5), ChecksumAlgorithm::Sha256), None);
+        assert_eq!(parse_checksum_output("sha256sum: abc: No such file or directory", ChecksumAlgorithm::Sha256), None);
+        assert_eq!(parse_checksum_output("", ChecksumAlgorithm::Md5), None);
+    }
+
+    #[test]
+    fn test_command_quotes_path() {
+        assert_eq!(ChecksumAlgorithm::Sha256.command("/srv/it's here.txt"), "sha256sum -b -- '/srv/it'\\''s here.txt'");
+        assert_eq!(ChecksumAlgorithm::Md5.command("-rf"), "md5sum -b -- '-rf'");
+    }
+}
@@ -1,3 +1,5 @@
+use serde::Serialize;
+use std::fmt::Display;
 use thiserror::Error;
 
 /// Terminal session errors
@@ -25,6 +27,29 @@ pub enum SessionError {
     UnsupportedOperation(String),
 }
 
+impl SessionError {
+    /// Stable category for the frontend to branch on
+    pub fn code(&self) -> ErrorCode {
+        use crate::telnet::TelnetError;
+        match self {
+            SessionError::IoError(e) => e.error_code().unwrap_or(ErrorCode::IoError),
+            SessionError::SshError(e) => e.code(),
+            SessionError::TelnetError(e) => match e {
+                TelnetError::Timeout => ErrorCode::Timeout,
+                TelnetError::ConnectionClosed => ErrorCode::ConnectionLost,
+                TelnetError::IoError(e) => e.error_code().unwrap_or(ErrorCode::ConnectionFailed),
+                TelnetError::Connection(_) | TelnetError::ProtocolError(_) | TelnetError::ChannelError(_) => {
+                    ErrorCode::ConnectionFailed
+                }
+            },
+            SessionError::PtyError(_) => ErrorCode::IoError,
+            SessionError::SessionNotFound => ErrorCode::NotFound,
+            SessionError::InvalidConfig(_) => ErrorCode::InvalidConfig,
+            SessionError::UnsupportedOperation(_) => ErrorCode::Unsupported,
+        }
+    }
+}
+
 /// File transfer connection errors
 #[derive(Debug, Error)]
 pub enum ConnectionError {
@@ -45,8 +70,264 @@ pub enum ConnectionError {
     
     #[error("Unsupported connection type: {0}")]
     UnsupportedType(String),
+
+    #[error("Invalid path: {0}")]
+    InvalidPath(String),
+
+    #[error("Transfer cancelled")]
+    Cancelled,
+
+    /// File is bigger than the caller allowed reading into memory
+    #[error("File too large: {0}")]
+    FileTooLarge(String),
+
+    /// Downloaded file doesn't hash to the server's checksum
+    #[error("Checksum mismatch: {0}")]
+    ChecksumMismatch(String),
+    
+    /// Failure whose cause is known (see `ErrorStatus`); `message` is the full text
+    #[error("{message}")]
+    Categorized { code: ErrorCode, message: String },
     
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
 
+impl ConnectionError {
+    /// SFTP failure during `context`, categorized from the server's status code
+    pub fn sftp<E: ErrorStatus + Display>(context: impl Display, error: &E) -> Self {
+        let message = format!("{}: {}", context, error);
+        match error.error_code() {
+            Some(code) => ConnectionError::Categorized { code, message: format!("SFTP error: {}", message) },
+            None => ConnectionError::SftpError(message),
+        }
+    }
+
+    /// FTP failure during `context`, categorized from the server's reply
+    pub fn ftp<E: ErrorStatus + Display>(context: impl Display, error: &E) -> Self {
+        let message = format!("{}: {}", context, error);
+        match error.error_code() {
+            Some(code) => ConnectionError::Categorized { code, message: format!("FTP error: {}", message) },
+            None => ConnectionError::FtpError(message),
+        }
+    }
+
+    /// Local I/O failure during `context`, categorized from its kind
+    pub fn io(context: impl Display, error: &std::io::Error) -> Self {
+        let message = format!("{}: {}", context, error);
+        match error.error_code() {
+            Some(code) => ConnectionError::Categorized { code, message: format!("I/O error: {}", message) },
+            None => ConnectionError::IoError(message),
+        }
+    }
+
+    /// The session user lacks permission for what was asked
+    pub fn permission_denied(message: impl Into<String>) -> Self {
+        ConnectionError::Categorized { code: ErrorCode::PermissionDenied, message: message.into() }
+    }
+
+    /// Stable category for the frontend to branch on
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ConnectionError::ConnectionFailed(_) => ErrorCode::ConnectionFailed,
+            ConnectionError::AuthenticationFailed(_) => ErrorCode::AuthFailed,
+            ConnectionError::IoError(_) => ErrorCode::IoError,
+            ConnectionError::SftpError(_) | ConnectionError::FtpError(_) => ErrorCode::RemoteError,
+            ConnectionError::UnsupportedType(_) => ErrorCode::Unsupported,
+            ConnectionError::InvalidPath(_) => ErrorCode::InvalidPath,
+            ConnectionError::Cancelled => ErrorCode::Cancelled,
+            ConnectionError::FileTooLarge(_) => ErrorCode::FileTooLarge,
+            ConnectionError::ChecksumMismatch(_) => ErrorCode::ChecksumMismatch,
+            ConnectionError::Categorized { code, .. } => *code,
+            ConnectionError::Unknown(_) => ErrorCode::Unknown,
+        }
+    }
+}
+
+/// Machine-readable error category, serialized as e.g. "PERMISSION_DENIED"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    PermissionDenied,
+    NotFound,
+    AlreadyExists,
+    NoSpace,
+    AuthFailed,
+    /// The SSH key is encrypted and no passphrase was given (prompt for one)
+    PassphraseRequired,
+    PassphraseIncorrect,
+    Timeout,
+    ConnectionFailed,
+    ConnectionLost,
+    Unsupported,
+    InvalidPath,
+    /// Connection settings that can't be used (unknown auth method, bad terminal type, ...)
+    InvalidConfig,
+    Cancelled,
+    FileTooLarge,
+    ChecksumMismatch,
+    /// The server refused without a recognizable reason
+    RemoteError,
+    IoError,
+    Unknown,
+}
+
+/// Errors from the protocol libraries that carry a recognizable cause
+/// All status code / reply code / error kind mappings live here
+pub trait ErrorStatus {
+    /// None when the error doesn't say why (e.g. SFTP's generic "Failure")
+    fn error_code(&self) -> Option<ErrorCode>;
+}
+
+impl ErrorStatus for russh_sftp::client::error::Error {
+    fn error_code(&self) -> Option<ErrorCode> {
+        use russh_sftp::client::error::Error;
+        use russh_sftp::protocol::StatusCode;
+        match self {
+            Error::Status(status) => match status.status_code {
+                StatusCode::NoSuchFile => Some(ErrorCode::NotFound),
+                StatusCode::PermissionDenied => Some(ErrorCode::PermissionDenied),
+                StatusCode::NoConnection | StatusCode::ConnectionLost => Some(ErrorCode::ConnectionLost),
+                StatusCode::OpUnsupported => Some(ErrorCode::Unsupported),
+                _ => None,
+            },
+            Error::Timeout => Some(ErrorCode::Timeout),
+            _ => None,
+        }
+    }
+}
+
+impl ErrorStatus for suppaftp::FtpError {
+    fn error_code(&self) -> Option<ErrorCode> {
+        use suppaftp::Status;
+        let response = match self {
+            suppaftp::FtpError::UnexpectedResponse(response) => response,
+            suppaftp::FtpError::ConnectionError(e) => return e.error_code().or(Some(ErrorCode::ConnectionLost)),
+            _ => return None,
+        };
+        let text = String::from_utf8_lossy(&response.body).to_lowercase();
+        match response.status {
+            Status::NotLoggedIn | Status::InvalidCredentials => Some(ErrorCode::AuthFailed),
+            Status::NotAvailable | Status::TransferAborted => Some(ErrorCode::ConnectionLost),
+            Status::RequestedActionNotTaken | Status::ExceededStorage => Some(ErrorCode::NoSpace),
+            Status::BadFilename => Some(ErrorCode::InvalidPath),
+            Status::NotImplemented | Status::NotImplementedParameter => Some(ErrorCode::Unsupported),
+            // 550 covers every "can't" - the text says which
+            Status::FileUnavailable | Status::RequestFileActionIgnored => {
+                if text.contains("exists") {
+                    Some(ErrorCode::AlreadyExists)
+                } else if text.contains("denied") || text.contains("permission") {
+                    Some(ErrorCode::PermissionDenied)
+                } else if ["no such", "not found", "not exist", "n't exist"].iter().any(|t| text.contains(t)) {
+                    Some(ErrorCode::NotFound)
+                } else {
+                    None
+                }
+            }
+            // 521 (directory already exists) has no Status variant
+            Status::Unknown if text.starts_with("521") => Some(ErrorCode::AlreadyExists),
+            _ => None,
+        }
+    }
+}
+
+impl ErrorStatus for std::io::Error {
+    fn error_code(&self) -> Option<ErrorCode> {
+        use std::io::ErrorKind;
+        match self.kind() {
+            ErrorKind::PermissionDenied => Some(ErrorCode::PermissionDenied),
+            ErrorKind::NotFound => Some(ErrorCode::NotFound),
+            ErrorKind::AlreadyExists => Some(ErrorCode::AlreadyExists),
+            ErrorKind::StorageFull | ErrorKind::QuotaExceeded => Some(ErrorCode::NoSpace),
+            ErrorKind::TimedOut => Some(ErrorCode::Timeout),
+            ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::BrokenPipe => {
+                Some(ErrorCode::ConnectionLost)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Error returned by commands as `{ code, message }`, so the frontend can branch on `code`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl CommandError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<SessionError> for CommandError {
+    fn from(error: SessionError) -> Self {
+        Self { code: error.code(), message: error.to_string() }
+    }
+}
+
+impl From<ConnectionError> for CommandError {
+    fn from(error: ConnectionError) -> Self {
+        Self { code: error.code(), message: error.to_string() }
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self { code: ErrorCode::Unknown, message }
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_from_sftp_ftp_and_io_errors() {
+        use russh_sftp::protocol::{Status as SftpStatus, StatusCode};
+        let sftp_status = |status_code| {
+            russh_sftp::client::error::Error::Status(SftpStatus {
+                id: 1,
+                status_code,
+                error_message: "reason".to_string(),
+                language_tag: "en-US".to_string(),
+            })
+        };
+        let missing = ConnectionError::sftp("Failed to stat /srv/a", &sftp_status(StatusCode::NoSuchFile));
+        assert_eq!(missing.code(), ErrorCode::NotFound);
+        assert_eq!(missing.to_string(), "SFTP error: Failed to stat /srv/a: No such file: reason");
+        assert_eq!(ConnectionError::sftp("x", &sftp_status(StatusCode::PermissionDenied)).code(), ErrorCode::PermissionDenied);
+        assert_eq!(ConnectionError::sftp("x", &sftp_status(StatusCode::Failure)).code(), ErrorCode::RemoteError);
+
+        let reply = |status, body: &str| {
+            suppaftp::FtpError::UnexpectedResponse(suppaftp::types::Response::new(status, body.as_bytes().to_vec()))
+        };
+        use suppaftp::Status;
+        assert_eq!(reply(Status::FileUnavailable, "550 a.txt: No such file or directory\r\n").error_code(), Some(ErrorCode::NotFound));
+        assert_eq!(reply(Status::FileUnavailable, "550 Permission denied\r\n").error_code(), Some(ErrorCode::PermissionDenied));
+        assert_eq!(reply(Status::ExceededStorage, "552 Quota exceeded\r\n").error_code(), Some(ErrorCode::NoSpace));
+        assert_eq!(reply(Status::NotLoggedIn, "530 Login incorrect\r\n").error_code(), Some(ErrorCode::AuthFailed));
+        assert_eq!(reply(Status::FileUnavailable, "550 Create directory operation failed\r\n").error_code(), None);
+
+        let full = std::io::Error::from(std::io::ErrorKind::StorageFull);
+        assert_eq!(ConnectionError::io("Failed to write /tmp/a", &full).code(), ErrorCode::NoSpace);
+        assert_eq!(ConnectionError::Cancelled.code(), ErrorCode::Cancelled);
+
+        let json = serde_json::to_value(CommandError::from(missing)).unwrap();
+        assert_eq!(json["code"], "NOT_FOUND");
+    }
+}
+
@@ -0,0 +1,450 @@
+//! In-memory `FileTransferSession` for tests: a real tree of directories, files and symlinks
+//! Behaves like an SFTP v3 server; tests switch on the quirks they exercise
+
+use crate::core::error::{ConnectionError, ErrorCode};
+use crate::core::file_content::FileRange;
+use crate::core::session::{DirectoryListing, FileInfo, FileTransferSession, ListingWarning};
+use crate::ssh::config::ConnectionType;
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Symlinks followed before giving up (a loop never resolves)
+const MAX_LINKS: usize = 8;
+
+#[derive(Debug, Clone)]
+struct Node {
+    is_directory: bool,
+    content: Vec<u8>,
+    mode: u32,
+    symlink_target: Option<String>,
+}
+
+impl Node {
+    fn directory(mode: u32) -> Self {
+        Self { is_directory: true, content: Vec::new(), mode, symlink_target: None }
+    }
+
+    fn file(content: Vec<u8>, mode: u32) -> Self {
+        Self { is_directory: false, content, mode, symlink_target: None }
+    }
+}
+
+/// Remote tree keyed by absolute path; "/" always exists
+/// Builders add missing parent directories, the session methods don't
+#[derive(Default)]
+pub struct FakeSession {
+    nodes: Mutex<BTreeMap<String, Node>>,
+    operations: Mutex<Vec<String>>,
+    listing_fallback: Option<String>,
+    failing_renames: bool,
+    failing_read: Option<String>,
+    unreadable_entries: Vec<String>,
+    dropping_writes: bool,
+    upload_delay: Option<Duration>,
+    filesystems: Vec<(String, u64)>,
+    uploads_in_flight: AtomicUsize,
+    max_uploads_in_flight: AtomicUsize,
+}
+
+impl FakeSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn dir(self, path: &str) -> Self {
+        self.dir_with_mode(path, 0o755)
+    }
+
+    /// Directory with permission bits; without owner read it can't be listed
+    pub fn dir_with_mode(self, path: &str, mode: u32) -> Self {
+        self.insert(path, Node::directory(mode))
+    }
+
+    pub fn file(self, path: &str, content: &[u8]) -> Self {
+        self.file_with_mode(path, content, 0o644)
+    }
+
+    pub fn file_with_mode(self, path: &str, content: &[u8], mode: u32) -> Self {
+        self.insert(path, Node::file(content.to_vec(), mode))
+    }
+
+    /// Symlink to `target`, which need not exist
+    pub fn symlink(self, path: &str, target: &str) -> Self {
+        self.insert(path, Node { symlink_target: Some(target.to_string()), ..Node::file(Vec::new(), 0o777) })
+    }
+
+    /// Directories that can't be opened list `dir` instead, like FTP after a failed CWD
+    pub fn with_listing_fallback(mut self, dir: &str) -> Self {
+        self.listing_fallback = Some(dir.to_string());
+        self
+    }
+
+    /// Every rename fails with SFTP's generic failure status
+    pub fn with_failing_renames(mut self) -> Self {
+        self.failing_renames = true;
+        self
+    }
+
+    /// Downloading or reading `path` fails
+    pub fn with_failing_read(mut self, path: &str) -> Self {
+        self.failing_read = Some(path.to_string());
+        self
+    }
+
+    /// Listing the parent of `path` reports it as a warning instead of an entry
+    pub fn with_unreadable_entry(mut self, path: &str) -> Self {
+        self.unreadable_entries.push(path.to_string());
+        self
+    }
+
+    /// Writes stop halfway and fail, as when the connection drops
+    pub fn with_dropping_writes(mut self) -> Self {
+        self.dropping_writes = true;
+        self
+    }
+
+    /// Each upload takes `delay`; see `max_uploads_in_flight`
+    pub fn with_slow_uploads(mut self, delay: Duration) -> Self {
+        self.upload_delay = Some(delay);
+        self
+    }
+
+    /// Paths under `prefix` are on filesystem `id`, the rest on 0
+    /// Without any, filesystem_id can't tell (like FTP)
+    pub fn with_filesystem(mut self, prefix: &str, id: u64) -> Self {
+        self.filesystems.push((prefix.to_string(), id));
+        self
+    }
+
+    pub fn paths(&self) -> Vec<String> {
+        self.nodes.lock().unwrap().keys().cloned().collect()
+    }
+
+    pub fn content(&self, path: &str) -> Option<Vec<u8>> {
+        self.nodes.lock().unwrap().get(path).map(|node| node.content.clone())
+    }
+
+    pub fn mode(&self, path: &str) -> Option<u32> {
+        self.nodes.lock().unwrap().get(path).map(|node| node.mode)
+    }
+
+    /// Changes made through the session, in order (e.g. "mkdir /srv/www")
+    pub fn operations(&self) -> Vec<String> {
+        self.operations.lock().unwrap().clone()
+    }
+
+    /// Most uploads that ran at the same time
+    pub fn max_uploads_in_flight(&self) -> usize {
+        self.max_uploads_in_flight.load(Ordering::SeqCst)
+    }
+
+    fn insert(self, path: &str, node: Node) -> Self {
+        {
+            let mut nodes = self.nodes.lock().unwrap();
+            let mut parent = parent_of(path);
+            while parent != "/" {
+                nodes.entry(parent.to_string()).or_insert_with(|| Node::directory(0o755));
+                parent = parent_of(parent);
+            }
+            nodes.insert(path.to_string(), node);
+        }
+        self
+    }
+
+    fn record(&self, operation: String) {
+        self.operations.lock().unwrap().push(operation);
+    }
+
+    fn check_read(&self, path: &str) -> Result<(), ConnectionError> {
+        match &self.failing_read {
+            Some(failing) if failing == path => Err(ConnectionError::SftpError(format!("Failed to read {}", path))),
+            _ => Ok(()),
+        }
+    }
+
+    fn read(&self, path: &str) -> Result<Vec<u8>, ConnectionError> {
+        self.check_read(path)?;
+        match resolve(&self.nodes.lock().unwrap(), &key(path)) {
+            Some((_, node)) if !node.is_directory => Ok(node.content),
+            Some(_) => Err(ConnectionError::SftpError(format!("Is a directory: {}", path))),
+            None => Err(not_found(path)),
+        }
+    }
+
+    /// Write `data` at `offset` (0 creates or truncates the file)
+    fn store(&self, path: &str, offset: u64, data: &[u8]) -> Result<(), ConnectionError> {
+        let path = key(path);
+        let written = if self.dropping_writes { &data[..data.len() / 2] } else { data };
+        {
+            let mut nodes = self.nodes.lock().unwrap();
+            if !is_directory(&nodes, parent_of(&path)) {
+                return Err(not_found(parent_of(&path)));
+            }
+            let node = nodes.entry(path.clone()).or_insert_with(|| Node::file(Vec::new(), 0o644));
+            if node.is_directory {
+                return Err(ConnectionError::SftpError(format!("Is a directory: {}", path)));
+            }
+            if offset == 0 {
+                node.content.clear();
+            } else if offset != node.content.len() as u64 {
+                return Err(ConnectionError::SftpError(format!("Write out of order at {} in {}", offset, path)));
+            }
+            node.content.extend_from_slice(written);
+        }
+        self.record(format!("write {}", path));
+        if self.dropping_writes {
+            return Err(ConnectionError::SftpError("Connection lost".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FileTransferSession for FakeSession {
+    fn id(&self) -> &str {
+        "fake"
+    }
+
+    fn connection_type(&self) -> ConnectionType {
+        ConnectionType::Sftp
+    }
+
+    async fn list_directory(&self, path: &str) -> Result<DirectoryListing, ConnectionError> {
+        let nodes = self.nodes.lock().unwrap();
+        let dir = key(path);
+        let (resolved, readable) = match resolve(&nodes, &dir) {
+            Some((resolved, node)) if node.is_directory => (resolved, node.mode & 0o400 != 0),
+            _ => (dir.clone(), false),
+        };
+        if readable {
+            let (unreadable, entries): (Vec<_>, Vec<_>) = entries(&nodes, &resolved, &dir)
+                .into_iter()
+                .partition(|entry| self.unreadable_entries.contains(&entry.path));
+            let warnings = unreadable
+                .into_iter()
+                .map(|entry| ListingWarning { path: entry.path, message: "Permission denied".to_string() })
+                .collect();
+            return Ok(DirectoryListing { entries, warnings });
+        }
+        match &self.listing_fallback {
+            Some(fallback) => Ok(DirectoryListing { entries: entries(&nodes, fallback, fallback), warnings: Vec::new() }),
+            None if nodes.contains_key(&dir) => Err(ConnectionError::permission_denied(format!("Cannot open {}", dir))),
+            None => Err(not_found(&dir)),
+        }
+    }
+
+    async fn download_file(&self, remote_path: &str, local_path: &str) -> Result<(), ConnectionError> {
+        let content = self.read(remote_path)?;
+        tokio::fs::write(local_path, content)
+            .await
+            .map_err(|e| ConnectionError::io("Failed to write local file", &e))
+    }
+
+    async fn upload_file(&self, local_path: &str, remote_path: &str) -> Result<(), ConnectionError> {
+        if let Some(delay) = self.upload_delay {
+            let now = self.uploads_in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_uploads_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(delay).await;
+            self.uploads_in_flight.fetch_sub(1, Ordering::SeqCst);
+        }
+        let content = tokio::fs::read(local_path)
+            .await
+            .map_err(|e| ConnectionError::io("Failed to read local file", &e))?;
+        self.store(remote_path, 0, &content)
+    }
+
+    async fn filesystem_id(&self, path: &str) -> Result<Option<u64>, ConnectionError> {
+        if self.filesystems.is_empty() {
+            return Ok(None);
+        }
+        let path = key(path);
+        let mount = self
+            .filesystems
+            .iter()
+            .filter(|(prefix, _)| path == *prefix || path.starts_with(&format!("{}/", prefix)))
+            .max_by_key(|(prefix, _)| prefix.len());
+        Ok(Some(mount.map_or(0, |(_, id)| *id)))
+    }
+
+    async fn create_directory(&self, path: &str) -> Result<(), ConnectionError> {
+        let path = key(path);
+        {
+            let mut nodes = self.nodes.lock().unwrap();
+            if path == "/" || nodes.contains_key(&path) {
+                return Err(ConnectionError::SftpError(format!("Failure: {} exists", path)));
+            }
+            if !is_directory(&nodes, parent_of(&path)) {
+                return Err(not_found(parent_of(&path)));
+            }
+            nodes.insert(path.clone(), Node::directory(0o755));
+        }
+        self.record(format!("mkdir {}", path));
+        Ok(())
+    }
+
+    async fn delete(&self, path: &str, _is_directory: bool) -> Result<(), ConnectionError> {
+        let path = key(path);
+        {
+            let mut nodes = self.nodes.lock().unwrap();
+            let children = format!("{}/", path);
+            if nodes.keys().any(|p| p.starts_with(&children)) {
+                return Err(ConnectionError::SftpError(format!("Directory not empty: {}", path)));
+            }
+            nodes.remove(&path).ok_or_else(|| not_found(&path))?;
+        }
+        self.record(format!("delete {}", path));
+        Ok(())
+    }
+
+    async fn rename(&self, old_path: &str, new_path: &str) -> Result<(), ConnectionError> {
+        let (old_path, new_path) = (key(old_path), key(new_path));
+        if self.failing_renames {
+            return Err(ConnectionError::SftpError("Failure".to_string()));
+        }
+        {
+            let mut nodes = self.nodes.lock().unwrap();
+            if !nodes.contains_key(&old_path) {
+                return Err(not_found(&old_path));
+            }
+            // SFTP v3 won't rename onto an existing path
+            if nodes.contains_key(&new_path) {
+                return Err(ConnectionError::SftpError("Failure".to_string()));
+            }
+            if !is_directory(&nodes, parent_of(&new_path)) {
+                return Err(not_found(parent_of(&new_path)));
+            }
+            let children = format!("{}/", old_path);
+            let moved: Vec<String> = nodes
+                .keys()
+                .filter(|p| **p == old_path || p.starts_with(&children))
+                .cloned()
+                .collect();
+            for path in moved {
+                let node = nodes.remove(&path).unwrap();
+                nodes.insert(format!("{}{}", new_path, &path[old_path.len()..]), node);
+            }
+        }
+        self.record(format!("rename {} -> {}", old_path, new_path));
+        Ok(())
+    }
+
+    async fn chmod(&self, path: &str, mode: u32) -> Result<(), ConnectionError> {
+        let mut nodes = self.nodes.lock().unwrap();
+        let node = nodes.get_mut(&key(path)).ok_or_else(|| not_found(path))?;
+        node.mode = mode;
+        Ok(())
+    }
+
+    async fn create_symlink(&self, target: &str, link_path: &str) -> Result<(), ConnectionError> {
+        let link_path = key(link_path);
+        let mut nodes = self.nodes.lock().unwrap();
+        if nodes.contains_key(&link_path) {
+            return Err(ConnectionError::SftpError(format!("Failure: {} exists", link_path)));
+        }
+        let link = Node { symlink_target: Some(target.to_string()), ..Node::file(Vec::new(), 0o777) };
+        nodes.insert(link_path, link);
+        Ok(())
+    }
+
+    /// Follows symlinks, like SFTP stat
+    async fn stat(&self, path: &str) -> Result<FileInfo, ConnectionError> {
+        let (_, node) = resolve(&self.nodes.lock().unwrap(), &key(path)).ok_or_else(|| not_found(path))?;
+        Ok(info(&key(path), &node))
+    }
+
+    async fn read_file_range(&self, path: &str, offset: u64, length: u64) -> Result<FileRange, ConnectionError> {
+        let content = self.read(path)?;
+        let start = (offset as usize).min(content.len());
+        let end = offset.saturating_add(length).min(content.len() as u64) as usize;
+        Ok(FileRange { eof: end == content.len(), data: content[start..end].to_vec() })
+    }
+
+    async fn write_file(&self, path: &str, content: &[u8]) -> Result<(), ConnectionError> {
+        self.store(path, 0, content)
+    }
+
+    async fn write_chunk(&self, path: &str, offset: u64, data: &[u8], _is_last: bool) -> Result<(), ConnectionError> {
+        self.store(path, offset, data)
+    }
+
+    async fn close(&mut self) -> Result<(), ConnectionError> {
+        Ok(())
+    }
+}
+
+fn key(path: &str) -> String {
+    match path.trim_end_matches('/') {
+        "" => "/".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+fn parent_of(path: &str) -> &str {
+    match path.rsplit_once('/') {
+        Some(("", _)) | None => "/",
+        Some((parent, _)) => parent,
+    }
+}
+
+fn not_found(path: &str) -> ConnectionError {
+    ConnectionError::Categorized { code: ErrorCode::NotFound, message: format!("No such file: {}", path) }
+}
+
+/// Path and node that `path` ends up at after following symlinks; None when missing or looping
+fn resolve(nodes: &BTreeMap<String, Node>, path: &str) -> Option<(String, Node)> {
+    let mut path = path.to_string();
+    for _ in 0..MAX_LINKS {
+        if path == "/" {
+            return Some((path, Node::directory(0o755)));
+        }
+        let node = nodes.get(&path)?;
+        match &node.symlink_target {
+            Some(target) => path = key(target),
+            None => return Some((path, node.clone())),
+        }
+    }
+    None
+}
+
+fn is_directory(nodes: &BTreeMap<String, Node>, path: &str) -> bool {
+    resolve(nodes, path).is_some_and(|(_, node)| node.is_directory)
+}
+
+/// Children of `dir`, with paths under `shown_as` (the path the caller listed)
+fn entries(nodes: &BTreeMap<String, Node>, dir: &str, shown_as: &str) -> Vec<FileInfo> {
+    let prefix = if dir == "/" { "/".to_string() } else { format!("{}/", dir) };
+    let shown_prefix = if shown_as == "/" { "/".to_string() } else { format!("{}/", shown_as) };
+    nodes
+        .iter()
+        .filter_map(|(path, node)| {
+            let name = path.strip_prefix(&prefix).filter(|rest| !rest.contains('/'))?;
+            let mut entry = info(&format!("{}{}", shown_prefix, name), node);
+            // Listings report what a link points at, so links to directories can be told apart
+            entry.is_directory = node.is_directory || (node.symlink_target.is_some() && is_directory(nodes, path));
+            Some(entry)
+        })
+        .collect()
+}
+
+fn info(path: &str, node: &Node) -> FileInfo {
+    let file_type = match (node.is_directory, &node.symlink_target) {
+        (_, Some(_)) => 0o120000,
+        (true, None) => 0o040000,
+        (false, None) => 0o100000,
+    };
+    FileInfo {
+        name: path.rsplit('/').next().unwrap_or_default().to_string(),
+        path: path.to_string(),
+        size: node.content.len() as u64,
+        is_directory: node.is_directory,
+        is_symlink: node.symlink_target.is_some(),
+        symlink_target: node.symlink_target.clone(),
+        permissions: Some(format!("{:o}", file_type | node.mode)),
+        modified: None,
+        owner: None,
+        group: None,
+    }
+}
@@ -0,0 +1,127 @@
+//! Text/binary detection for files opened in the editor
+
+use crate::core::error::ConnectionError;
+use serde::Serialize;
+
+/// How much of the file to scan for NUL bytes
+const SAMPLE_SIZE: usize = 8192;
+/// Above this share of undecodable characters the file is treated as binary
+const MAX_INVALID_PERCENT: usize = 5;
+
+/// File content as returned to the editor
+/// Text serializes as a plain string; binary as `{ binary: true, size }`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum FileContent {
+    Text(String),
+    Binary { binary: bool, size: u64 },
+}
+
+impl FileContent {
+    /// Decode bytes as text, or report them as binary
+    ///
+    /// NUL bytes near the start mean binary. Otherwise invalid UTF-8 is
+    /// tolerated (replaced) as long as it is a small fraction of the text,
+    /// so e.g. a Latin-1 config with a few accents still opens.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        let size = bytes.len() as u64;
+        let sample = &bytes[..bytes.len().min(SAMPLE_SIZE)];
+        if sample.contains(&0) {
+            return Self::Binary { binary: true, size };
+        }
+
+        match String::from_utf8(bytes) {
+            Ok(text) => Self::Text(text),
+            Err(e) => {
+                let text = String::from_utf8_lossy(e.as_bytes()).into_owned();
+                let total = text.chars().count().max(1);
+                let invalid = text.chars().filter(|c| *c == char::REPLACEMENT_CHARACTER).count();
+                if invalid * 100 / total > MAX_INVALID_PERCENT {
+                    Self::Binary { binary: true, size }
+                } else {
+                    Self::Text(text)
+                }
+            }
+        }
+    }
+}
+
+/// Fail with FileTooLarge when `size` is over `max_bytes`
+pub fn check_size(size: u64, max_bytes: Option<u64>) -> Result<(), ConnectionError> {
+    match max_bytes {
+        Some(limit) if size > limit => Err(ConnectionError::FileTooLarge(format!(
+            "{} bytes exceeds the {}-byte limit",
+            size, limit
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// How much to read so that exceeding `max_bytes` is noticed (one byte past the limit)
+pub fn read_limit(max_bytes: Option<u64>) -> u64 {
+    max_bytes.map_or(u64::MAX, |limit| limit.saturating_add(1))
+}
+
+/// Largest window read_file_range returns at once
+pub const MAX_RANGE_LENGTH: u64 = 16 * 1024 * 1024;
+
+/// A window of a file, for paging through files too big to load whole
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileRange {
+    pub data: Vec<u8>,
+    /// Nothing follows this window
+    pub eof: bool,
+}
+
+impl FileRange {
+    /// From up to `length + 1` bytes read at the window's offset; the extra byte only tells that more follows
+    pub fn from_read(mut data: Vec<u8>, length: u64) -> Self {
+        let eof = data.len() as u64 <= length;
+        if !eof {
+            data.truncate(length as usize);
+        }
+        Self { data, eof }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_and_binary_detection() {
+        assert_eq!(FileContent::from_bytes(b"hello\n".to_vec()), FileContent::Text("hello\n".into()));
+
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        assert_eq!(FileContent::from_bytes(png), FileContent::Binary { binary: true, size: 16 });
+
+        let noise: Vec<u8> = (0..200u8).map(|i| 0x80 | i).collect();
+        assert!(matches!(FileContent::from_bytes(noise), FileContent::Binary { .. }));
+    }
+
+    #[test]
+    fn test_size_limit() {
+        assert!(check_size(10, None).is_ok());
+        assert!(check_size(10, Some(10)).is_ok());
+        assert!(matches!(check_size(11, Some(10)), Err(ConnectionError::FileTooLarge(_))));
+        assert_eq!(read_limit(Some(10)), 11);
+        assert_eq!(read_limit(Some(u64::MAX)), u64::MAX);
+    }
+
+    #[test]
+    fn test_range_eof_from_extra_byte() {
+        assert_eq!(FileRange::from_read(b"abcd".to_vec(), 3), FileRange { data: b"abc".to_vec(), eof: false });
+        assert_eq!(FileRange::from_read(b"abc".to_vec(), 3), FileRange { data: b"abc".to_vec(), eof: true });
+        assert_eq!(FileRange::from_read(Vec::new(), 3), FileRange { data: Vec::new(), eof: true });
+    }
+
+    #[test]
+    fn test_mostly_text_with_stray_bytes_is_text() {
+        let mut latin1 = "name = Jos".repeat(10).into_bytes();
+        latin1.push(0xE9);
+        match FileContent::from_bytes(latin1) {
+            FileContent::Text(text) => assert!(text.ends_with('\u{FFFD}')),
+            other => panic!("expected text, got {:?}", other),
+        }
+    }
+}
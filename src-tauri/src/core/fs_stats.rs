@@ -0,0 +1,63 @@
+//! Free space on a remote filesystem, so uploads that won't fit can be refused up front
+
+use serde::Serialize;
+
+/// Sizes of the filesystem holding a path, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilesystemStats {
+    pub total_bytes: u64,
+    /// Free blocks, including those reserved for root
+    pub free_bytes: u64,
+    /// Free space an unprivileged user can write to
+    pub available_bytes: u64,
+}
+
+impl FilesystemStats {
+    /// From statvfs block counts (counted in fragments)
+    pub fn from_blocks(fragment_size: u64, blocks: u64, blocks_free: u64, blocks_avail: u64) -> Self {
+        Self {
+            total_bytes: blocks.saturating_mul(fragment_size),
+            free_bytes: blocks_free.saturating_mul(fragment_size),
+            available_bytes: blocks_avail.saturating_mul(fragment_size),
+        }
+    }
+}
+
+/// Fallback for servers without statvfs@openssh.com: POSIX output keeps each filesystem on one line
+pub fn df_command(path: &str) -> String {
+    format!("df -Pk -- '{}'", path.replace('\'', "'\\''"))
+}
+
+/// Sizes from `df -Pk` output (1K blocks; the numbers are the three before the capacity column)
+pub fn parse_df_output(output: &str) -> Option<FilesystemStats> {
+    let fields: Vec<&str> = output.lines().rev().find(|l| !l.trim().is_empty())?.split_whitespace().collect();
+    let capacity = fields.iter().position(|f| f.ends_with('%') && f[..f.len() - 1].chars().all(|c| c.is_ascii_digit()))?;
+    let [total, used, available] = fields.get(capacity.checked_sub(3)?..capacity)? else {
+        return None;
+    };
+    let kib = |field: &str| field.parse::<u64>().ok().map(|n| n.saturating_mul(1024));
+    let total_bytes = kib(total)?;
+    Some(FilesystemStats {
+        total_bytes,
+        free_bytes: total_bytes.saturating_sub(kib(used)?),
+        available_bytes: kib(available)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_df_output() {
+        let output = "Filesystem     1024-blocks    Used Available Capacity Mounted on\n\
+                      /dev/mapper/vg-home 10255636 5000000 4700000 52% /home/my files\n";
+        assert_eq!(
+            parse_df_output(output),
+            Some(FilesystemStats { total_bytes: 10255636 * 1024, free_bytes: 5255636 * 1024, available_bytes: 4700000 * 1024 })
+        );
+        assert_eq!(parse_df_output("df: /nope: No such file or directory\n"), None);
+        assert_eq!(df_command("/srv/it's"), "df -Pk -- '/srv/it'\\''s'");
+    }
+}
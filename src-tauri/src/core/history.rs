@@ -1,11 +1,24 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Prefix of a command entry in fish_history (YAML-like)
+const FISH_CMD_PREFIX: &str = "- cmd: ";
 
 /// Parse history output from either shell commands or history files
 /// Supports:
 /// - "  123  command" (bash `history` style)
 /// - "command" (plain line)
 /// - ": timestamp:0;command" (zsh extended history)
+/// - "- cmd: command" (fish_history; its when:/paths: lines are skipped)
 pub fn parse_history_output(output: &str) -> Vec<String> {
+    if output.lines().any(|line| line.starts_with(FISH_CMD_PREFIX)) {
+        return output
+            .lines()
+            .filter_map(|line| line.strip_prefix(FISH_CMD_PREFIX))
+            .map(unescape_fish)
+            .filter(|cmd| !cmd.trim().is_empty())
+            .collect();
+    }
+
     output
         .lines()
         .filter_map(|line| {
@@ -35,6 +48,24 @@ pub fn parse_history_output(output: &str) -> Vec<String> {
         .collect()
 }
 
+/// Undo fish_history escaping ("\\n" for newlines, "\\\\" for backslashes)
+fn unescape_fish(cmd: &str) -> String {
+    let mut out = String::with_capacity(cmd.len());
+    let mut chars = cmd.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
 fn get_home_dir() -> Option<PathBuf> {
     #[cfg(target_os = "windows")]
     {
@@ -54,35 +85,66 @@ fn get_home_dir() -> Option<PathBuf> {
 }
 
 fn detect_local_history_files(shell: Option<&str>) -> Vec<PathBuf> {
-    let mut candidates: Vec<PathBuf> = Vec::new();
+    match get_home_dir() {
+        Some(home) => history_file_candidates(shell, &home, |key| std::env::var(key).ok()),
+        None => Vec::new(),
+    }
+}
 
-    let home = match get_home_dir() {
-        Some(h) => h,
-        None => return candidates,
-    };
+/// History files to try, most specific first (`env` looks up environment variables)
+///
+/// $HISTFILE wins, then the current shell's files, then the other shell's,
+/// each with its default location before the XDG ones.
+fn history_file_candidates(
+    shell: Option<&str>,
+    home: &Path,
+    env: impl Fn(&str) -> Option<String>,
+) -> Vec<PathBuf> {
+    let non_empty = |key: &str| env(key).filter(|v| !v.trim().is_empty());
+    let data_home = non_empty("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".local/share"));
+    let state_home = non_empty("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".local/state"));
 
-    let bash_hist = home.join(".bash_history");
-    let zsh_hist = home.join(".zsh_history");
+    let bash_files = vec![home.join(".bash_history"), state_home.join("bash/history")];
+    let zsh_files = vec![
+        home.join(".zsh_history"),
+        home.join(".histfile"),
+        data_home.join("zsh/history"),
+        state_home.join("zsh/history"),
+    ];
 
-    if let Some(shell) = shell {
-        let shell_lower = shell.to_lowercase();
-        if shell_lower.contains("bash") {
-            candidates.push(bash_hist.clone());
-        } else if shell_lower.contains("zsh") {
-            candidates.push(zsh_hist.clone());
-        }
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if let Some(histfile) = non_empty("HISTFILE") {
+        candidates.push(expand_home(&histfile, home));
     }
 
-    if !candidates.iter().any(|p| p.ends_with(".bash_history")) {
-        candidates.push(bash_hist);
+    let shell = shell.map(str::to_lowercase).unwrap_or_default();
+    if shell.contains("fish") {
+        candidates.push(data_home.join("fish/fish_history"));
     }
-    if !candidates.iter().any(|p| p.ends_with(".zsh_history")) {
-        candidates.push(zsh_hist);
+
+    let prefers_zsh = shell.contains("zsh");
+    let (first, second) = if prefers_zsh { (zsh_files, bash_files) } else { (bash_files, zsh_files) };
+    for path in first.into_iter().chain(second) {
+        if !candidates.contains(&path) {
+            candidates.push(path);
+        }
     }
 
     candidates
 }
 
+/// Expand a leading `~/` (HISTFILE is often set unexpanded in rc files)
+fn expand_home(path: &str, home: &Path) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
 fn read_local_history_from_file(path: &PathBuf, limit: u32) -> Result<Vec<String>, String> {
     use std::fs;
     use std::io::{BufRead, BufReader};
@@ -127,3 +189,159 @@ pub fn read_local_shell_history(shell: Option<String>, limit: u32) -> Result<Vec
     Ok(Vec::new())
 }
 
+/// Command that prints the remote login shell (one round-trip before fetching history)
+/// POSIX shells and fish print $SHELL; PowerShell prints $PSHOME; cmd.exe echoes it verbatim
+pub const SHELL_DETECT_COMMAND: &str = "echo \"$SHELL\" $PSHOME";
+
+/// Remote shell family, as far as finding its history goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteShell {
+    Bash,
+    Zsh,
+    Fish,
+    /// Windows host (PowerShell or cmd.exe); history comes from PSReadLine
+    PowerShell,
+    Unknown,
+}
+
+impl RemoteShell {
+    /// Parse SHELL_DETECT_COMMAND output ("/usr/bin/zsh" -> Zsh)
+    pub fn detect(output: &str) -> Self {
+        let output = output.trim();
+        // cmd.exe leaves the variable unexpanded; PowerShell prints a Windows path
+        if output.contains("$SHELL") || output.contains('\\') || output.to_lowercase().contains("powershell") {
+            return Self::PowerShell;
+        }
+
+        let shell = output.split_whitespace().next().unwrap_or("");
+        let name = shell.rsplit('/').next().unwrap_or("").to_lowercase();
+        match name.as_str() {
+            "bash" => Self::Bash,
+            "zsh" => Self::Zsh,
+            "fish" => Self::Fish,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Shell commands that print recent history on a remote host, in the order to try them
+///
+/// For POSIX shells: $HISTFILE first (set when the login shell exports it),
+/// then the shell's default and XDG locations. An unknown shell gets every
+/// POSIX attempt, ending with asking the shell itself.
+pub fn remote_history_commands(shell: RemoteShell, limit: u32) -> Vec<String> {
+    match shell {
+        RemoteShell::Fish => {
+            return vec![format!(
+                "grep '^{}' ~/.local/share/fish/fish_history 2>/dev/null | tail -n {}",
+                FISH_CMD_PREFIX, limit
+            )];
+        }
+        RemoteShell::PowerShell => {
+            return vec![
+                // Running under PowerShell: ask PSReadLine where it saves history
+                format!("Get-Content -Tail {} (Get-PSReadLineOption).HistorySavePath", limit),
+                // Running under cmd.exe (or PSReadLine not loaded): default location
+                format!(
+                    "powershell -NoProfile -Command \"Get-Content -Tail {} $env:APPDATA\\Microsoft\\Windows\\PowerShell\\PSReadLine\\ConsoleHost_history.txt\"",
+                    limit
+                ),
+            ];
+        }
+        _ => {}
+    }
+
+    let histfile = format!(
+        "f=\"$HISTFILE\"; case \"$f\" in \"~/\"*) f=\"$HOME/${{f#\\~/}}\";; esac; [ -n \"$f\" ] && tail -n {} \"$f\" 2>/dev/null",
+        limit
+    );
+    let bash = vec![format!("tail -n {} ~/.bash_history 2>/dev/null", limit)];
+    let zsh = vec![
+        format!("tail -n {} ~/.zsh_history 2>/dev/null | cut -d ';' -f 2-", limit),
+        format!(
+            "tail -n {} \"${{XDG_DATA_HOME:-$HOME/.local/share}}/zsh/history\" 2>/dev/null | cut -d ';' -f 2-",
+            limit
+        ),
+    ];
+
+    let mut commands = vec![histfile];
+    match shell {
+        RemoteShell::Bash => commands.extend(bash),
+        RemoteShell::Zsh => commands.extend(zsh),
+        _ => {
+            commands.extend(bash);
+            commands.extend(zsh);
+            commands.push(format!(
+                "HISTFILE=~/.bash_history bash -c 'set -o history; history -r; history {}' 2>/dev/null",
+                limit
+            ));
+            commands.push(format!("fc -l -{} 2>/dev/null", limit));
+        }
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histfile_first_then_shell_defaults() {
+        let home = Path::new("/home/dev");
+        let env = |key: &str| match key {
+            "HISTFILE" => Some("~/.config/zsh/.zhistory".to_string()),
+            _ => None,
+        };
+        let candidates = history_file_candidates(Some("/usr/bin/zsh"), home, env);
+
+        assert_eq!(candidates[0], PathBuf::from("/home/dev/.config/zsh/.zhistory"));
+        assert_eq!(candidates[1], PathBuf::from("/home/dev/.zsh_history"));
+        assert!(candidates.contains(&PathBuf::from("/home/dev/.local/share/zsh/history")));
+        assert!(candidates.contains(&PathBuf::from("/home/dev/.bash_history")));
+    }
+
+    #[test]
+    fn test_xdg_locations_and_no_duplicates() {
+        let home = Path::new("/home/dev");
+        let env = |key: &str| match key {
+            "HISTFILE" => Some("/home/dev/.bash_history".to_string()),
+            "XDG_DATA_HOME" => Some("/data".to_string()),
+            _ => None,
+        };
+        let candidates = history_file_candidates(Some("bash"), home, env);
+
+        assert_eq!(candidates[0], PathBuf::from("/home/dev/.bash_history"));
+        assert_eq!(candidates.iter().filter(|p| p.ends_with(".bash_history")).count(), 1);
+        assert!(candidates.contains(&PathBuf::from("/data/zsh/history")));
+    }
+
+    #[test]
+    fn test_detected_shell_narrows_remote_commands() {
+        assert_eq!(RemoteShell::detect("/usr/bin/zsh\n"), RemoteShell::Zsh);
+        assert_eq!(RemoteShell::detect("/bin/bash"), RemoteShell::Bash);
+        assert_eq!(RemoteShell::detect(""), RemoteShell::Unknown);
+
+        let zsh = remote_history_commands(RemoteShell::Zsh, 50);
+        assert!(zsh[0].contains("HISTFILE"));
+        assert!(zsh.iter().all(|c| !c.contains("bash_history")));
+        assert!(remote_history_commands(RemoteShell::Unknown, 50).len() > zsh.len());
+    }
+
+    #[test]
+    fn test_fish_and_powershell_detection() {
+        assert_eq!(RemoteShell::detect("/usr/local/bin/fish\n"), RemoteShell::Fish);
+        assert_eq!(RemoteShell::detect("\r\nC:\\Program Files\\PowerShell\\7\r\n"), RemoteShell::PowerShell);
+        assert_eq!(RemoteShell::detect("\"$SHELL\" $PSHOME\r\n"), RemoteShell::PowerShell);
+
+        let fish = remote_history_commands(RemoteShell::Fish, 20);
+        assert_eq!(fish.len(), 1);
+        assert!(fish[0].contains("fish_history"));
+        assert!(remote_history_commands(RemoteShell::PowerShell, 20)[0].contains("PSReadLine"));
+    }
+
+    #[test]
+    fn test_parse_fish_history() {
+        let output = "- cmd: git status\n  when: 1700000000\n- cmd: echo a\\\\nb\n  when: 1700000001\n  paths:\n    - src\n";
+        assert_eq!(parse_history_output(output), vec!["git status", "echo a\\nb"]);
+    }
+}
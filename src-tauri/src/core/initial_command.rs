@@ -0,0 +1,182 @@
+//! A command typed into a new session once its shell is ready (e.g. "tmux attach || tmux")
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// How often the I/O tasks check readiness while a command is pending
+pub const CHECK_INTERVAL: Duration = Duration::from_millis(100);
+/// Output ending in something prompt-like, then quiet this long, means the shell waits for input
+const PROMPT_SETTLE: Duration = Duration::from_millis(200);
+/// Output quiet this long counts as ready without a recognizable prompt
+const QUIET_PERIOD: Duration = Duration::from_millis(1500);
+/// Send anyway this long after the session came up (unless a prompt is required)
+const MAX_WAIT: Duration = Duration::from_secs(10);
+/// Characters of recent output kept to look for a prompt
+const TAIL_CHARS: usize = 256;
+
+/// Command to send after a session connects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitialCommand {
+    /// Typed followed by Enter
+    pub command: String,
+    /// Wait at least this long once the session is up, in ms (the prompt is still waited for)
+    #[serde(default)]
+    pub delay_ms: Option<u64>,
+}
+
+/// Decides when to type the initial command, from the output seen so far
+pub struct PendingCommand {
+    command: Option<String>,
+    delay: Duration,
+    not_before: Instant,
+    give_up_waiting: Instant,
+    last_output: Option<Instant>,
+    tail: String,
+}
+
+impl PendingCommand {
+    /// Nothing is pending without a (non-blank) command
+    pub fn new(initial: Option<&InitialCommand>) -> Self {
+        let delay = Duration::from_millis(initial.and_then(|i| i.delay_ms).unwrap_or(0));
+        let now = Instant::now();
+        Self {
+            command: initial.map(|i| i.command.clone()).filter(|c| !c.trim().is_empty()),
+            delay,
+            not_before: now + delay,
+            give_up_waiting: now + MAX_WAIT,
+            last_output: None,
+            tail: String::new(),
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.command.is_some()
+    }
+
+    /// The session isn't ready yet (streaming not started, login running): restart the wait from `now`
+    pub fn hold(&mut self, now: Instant) {
+        self.not_before = now + self.delay;
+        self.give_up_waiting = now + MAX_WAIT;
+    }
+
+    /// Note output received from the session
+    pub fn observe(&mut self, output: &str, now: Instant) {
+        if self.command.is_none() || output.is_empty() {
+            return;
+        }
+        self.last_output = Some(now);
+        self.tail.push_str(output);
+        if let Some((cut, _)) = self.tail.char_indices().rev().nth(TAIL_CHARS) {
+            self.tail.drain(..cut);
+        }
+    }
+
+    /// Bytes to write if the command should be sent at `now` (returned only once)
+    /// With `require_prompt`, only a prompt-looking output end counts (no quiet or time fallback)
+    pub fn poll(&mut self, now: Instant, require_prompt: bool) -> Option<Vec<u8>> {
+        if self.command.is_none() || now < self.not_before {
+            return None;
+        }
+        let quiet = self.last_output.map(|t| now.saturating_duration_since(t));
+        let ready = if looks_like_prompt(&self.tail) {
+            quiet.is_some_and(|q| q >= PROMPT_SETTLE)
+        } else if require_prompt {
+            false
+        } else {
+            quiet.is_some_and(|q| q >= QUIET_PERIOD) || now >= self.give_up_waiting
+        };
+        if !ready {
+            return None;
+        }
+        self.command.take().map(|command| format!("{}\r", command).into_bytes())
+    }
+}
+
+/// Whether the last output line ends like a shell prompt ("$ ", "# ", "> ", "% ", "❯ ")
+fn looks_like_prompt(output: &str) -> bool {
+    let text = strip_escapes(output);
+    let line = text.rsplit(['\n', '\r']).find(|l| !l.trim().is_empty()).unwrap_or("");
+    matches!(line.trim_end().chars().last(), Some('$' | '#' | '>' | '%' | '❯' | '»'))
+}
+
+/// Drop CSI and OSC sequences (colors, bracketed paste mode, window titles)
+fn strip_escapes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(command: &str, delay_ms: Option<u64>) -> PendingCommand {
+        PendingCommand::new(Some(&InitialCommand { command: command.to_string(), delay_ms }))
+    }
+
+    #[test]
+    fn test_waits_for_the_prompt_to_settle() {
+        let start = Instant::now();
+        let mut command = pending("tmux attach", None);
+        command.hold(start);
+        command.observe("Welcome to Ubuntu\r\n", start);
+        assert_eq!(command.poll(start + Duration::from_millis(500), false), None);
+
+        let prompt_at = start + Duration::from_millis(600);
+        command.observe("\x1b[?2004h\x1b]0;me@web: ~\x07\x1b[01;32mme@web\x1b[00m:~$ ", prompt_at);
+        assert_eq!(command.poll(prompt_at + Duration::from_millis(50), false), None);
+        assert_eq!(command.poll(prompt_at + PROMPT_SETTLE, false).as_deref(), Some(&b"tmux attach\r"[..]));
+        // Sent only once
+        assert!(!command.is_pending());
+        assert_eq!(command.poll(prompt_at + MAX_WAIT, false), None);
+    }
+
+    #[test]
+    fn test_fallbacks_and_required_prompt() {
+        let start = Instant::now();
+        let mut command = pending("uptime", Some(1000));
+        command.hold(start);
+        command.observe("login: ", start);
+        // A login prompt is not a shell prompt; quiet output only counts without require_prompt
+        assert_eq!(command.poll(start + Duration::from_secs(5), true), None);
+        assert!(command.poll(start + Duration::from_secs(5), false).is_some());
+
+        // The delay applies even when the prompt is already there
+        let mut command = pending("uptime", Some(1000));
+        command.hold(start);
+        command.observe("# ", start);
+        assert_eq!(command.poll(start + Duration::from_millis(500), false), None);
+        assert!(command.poll(start + Duration::from_millis(1000), false).is_some());
+
+        let mut silent = pending("uptime", None);
+        silent.hold(start);
+        assert_eq!(silent.poll(start + Duration::from_secs(9), false), None);
+        assert!(silent.poll(start + MAX_WAIT, false).is_some());
+
+        assert!(!pending("  ", None).is_pending());
+    }
+}
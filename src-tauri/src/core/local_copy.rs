@@ -0,0 +1,294 @@
+//! Local filesystem copy that preserves permissions and symlinks
+
+use serde::Serialize;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Chunk size for file copies (progress is reported per chunk)
+const COPY_CHUNK_SIZE: usize = 256 * 1024;
+/// Minimum time between progress reports within a file
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Progress of a local copy
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyProgress {
+    pub files_done: u64,
+    pub files_total: u64,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+/// Copy a file, directory tree or symlink, reporting progress as files and chunks complete
+///
+/// - Symlinks are recreated as symlinks (their targets are not followed)
+/// - Directories are copied recursively, keeping structure
+/// - Permission bits of files and directories are carried over
+///
+/// Walks the source first so totals are known up front
+pub async fn copy_path_with_progress(
+    source: &Path,
+    dest: &Path,
+    on_progress: &mut (dyn FnMut(&CopyProgress) + Send),
+) -> io::Result<CopyProgress> {
+    let (files_total, bytes_total) = measure(source).await?;
+    let totals = CopyProgress {
+        files_total,
+        bytes_total,
+        ..Default::default()
+    };
+
+    let mut copier = Copier::new(totals, on_progress);
+    copier.copy(source, dest).await?;
+    Ok(copier.progress)
+}
+
+/// Check a copy/move before anything is written
+///
+/// Rejects a destination that resolves to the source or, for directories,
+/// anywhere inside it. An existing destination is an error unless `overwrite`,
+/// in which case it is removed so the result matches the source exactly.
+pub async fn prepare_destination(source: &Path, dest: &Path, overwrite: bool) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(source).await?;
+    let source_resolved = fs::canonicalize(source).await?;
+    if let Some(dest_resolved) = resolve_destination(dest).await {
+        let inside = metadata.is_dir() && dest_resolved.starts_with(&source_resolved);
+        if inside || dest_resolved == source_resolved {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cannot copy {} into itself", source.display()),
+            ));
+        }
+    }
+
+    match fs::symlink_metadata(dest).await {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+        Ok(_) if !overwrite => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Destination already exists: {}", dest.display()),
+        )),
+        Ok(existing) if existing.is_dir() => fs::remove_dir_all(dest).await,
+        Ok(_) => fs::remove_file(dest).await,
+    }
+}
+
+/// Where `dest` will end up once created: its canonical parent plus its name
+async fn resolve_destination(dest: &Path) -> Option<std::path::PathBuf> {
+    let name = dest.file_name()?;
+    let parent = match dest.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    Some(fs::canonicalize(parent).await.ok()?.join(name))
+}
+
+/// Count files (including symlinks) and bytes under a path
+async fn measure(source: &Path) -> io::Result<(u64, u64)> {
+    let metadata = fs::symlink_metadata(source).await?;
+    if !metadata.is_dir() {
+        let bytes = if metadata.file_type().is_symlink() { 0 } else { metadata.len() };
+        return Ok((1, bytes));
+    }
+
+    let (mut files, mut bytes) = (0, 0);
+    let mut entries = fs::read_dir(source).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let (f, b) = Box::pin(measure(&entry.path())).await?;
+        files += f;
+        bytes += b;
+    }
+    Ok((files, bytes))
+}
+
+/// Recursive copy state
+struct Copier<'a> {
+    progress: CopyProgress,
+    on_progress: &'a mut (dyn FnMut(&CopyProgress) + Send),
+    last_report: Instant,
+}
+
+impl<'a> Copier<'a> {
+    fn new(progress: CopyProgress, on_progress: &'a mut (dyn FnMut(&CopyProgress) + Send)) -> Self {
+        Self {
+            progress,
+            on_progress,
+            last_report: Instant::now(),
+        }
+    }
+
+    async fn copy(&mut self, source: &Path, dest: &Path) -> io::Result<()> {
+        let metadata = fs::symlink_metadata(source).await?;
+
+        if metadata.file_type().is_symlink() {
+            copy_symlink(source, dest).await?;
+        } else if metadata.is_dir() {
+            return self.copy_dir(source, dest).await;
+        } else {
+            self.copy_file(source, dest).await?;
+            fs::set_permissions(dest, metadata.permissions()).await?;
+        }
+
+        self.progress.files_done += 1;
+        self.report();
+        Ok(())
+    }
+
+    /// Copy a directory tree, applying the source mode once its contents are in place
+    /// (a read-only source dir would otherwise block writing its children)
+    async fn copy_dir(&mut self, source: &Path, dest: &Path) -> io::Result<()> {
+        fs::create_dir_all(dest).await?;
+
+        let mut entries = fs::read_dir(source).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let dest_path = dest.join(entry.file_name());
+            Box::pin(self.copy(&entry.path(), &dest_path)).await?;
+        }
+
+        let metadata = fs::metadata(source).await?;
+        fs::set_permissions(dest, metadata.permissions()).await
+    }
+
+    /// Copy file contents in chunks so large files report byte progress
+    async fn copy_file(&mut self, source: &Path, dest: &Path) -> io::Result<()> {
+        let mut reader = fs::File::open(source).await?;
+        let mut writer = fs::File::create(dest).await?;
+        let mut buffer = vec![0u8; COPY_CHUNK_SIZE];
+
+        loop {
+            let n = reader.read(&mut buffer).await?;
+            if n == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..n]).await?;
+            self.progress.bytes_done += n as u64;
+
+            if self.last_report.elapsed() >= PROGRESS_INTERVAL {
+                self.report();
+            }
+        }
+
+        writer.flush().await
+    }
+
+    fn report(&mut self) {
+        (self.on_progress)(&self.progress);
+        self.last_report = Instant::now();
+    }
+}
+
+/// Recreate a symlink pointing at the same target
+async fn copy_symlink(source: &Path, dest: &Path) -> io::Result<()> {
+    let target = fs::read_link(source).await?;
+
+    #[cfg(unix)]
+    {
+        fs::symlink(&target, dest).await
+    }
+
+    #[cfg(windows)]
+    {
+        // Windows distinguishes file and directory links
+        let target_is_dir = fs::metadata(source).await.map(|m| m.is_dir()).unwrap_or(false);
+        if target_is_dir {
+            fs::symlink_dir(&target, dest).await
+        } else {
+            fs::symlink_file(&target, dest).await
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    async fn copy_path(source: &Path, dest: &Path) -> io::Result<CopyProgress> {
+        copy_path_with_progress(source, dest, &mut |_| {}).await
+    }
+
+    #[tokio::test]
+    async fn test_executable_stays_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("run.sh");
+        std::fs::write(&script, "#!/bin/sh\necho hi\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let copy = dir.path().join("run-copy.sh");
+        copy_path(&script, &copy).await.unwrap();
+
+        let mode = std::fs::metadata(&copy).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[tokio::test]
+    async fn test_tree_copy_keeps_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(src.join("nested")).unwrap();
+        std::fs::write(src.join("nested/config.toml"), "a = 1").unwrap();
+        std::os::unix::fs::symlink("nested/config.toml", src.join("link.toml")).unwrap();
+
+        let dest = dir.path().join("dest");
+        copy_path(&src, &dest).await.unwrap();
+
+        let link = dest.join("link.toml");
+        assert!(std::fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(std::fs::read_link(&link).unwrap(), Path::new("nested/config.toml"));
+        assert_eq!(std::fs::read_to_string(dest.join("nested/config.toml")).unwrap(), "a = 1");
+    }
+
+    #[tokio::test]
+    async fn test_prepare_destination_rejects_self_and_existing() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(src.join("nested")).unwrap();
+        std::fs::write(src.join("nested/a.txt"), "a").unwrap();
+        let file = src.join("nested/a.txt");
+
+        // Into itself, however the path is spelled
+        for dest in [src.join("nested/copy"), src.join("copy"), dir.path().join("src/../src/x")] {
+            let err = prepare_destination(&src, &dest, true).await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", dest.display());
+        }
+        let err = prepare_destination(&file, &src.join("./nested/a.txt"), true).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        // A sibling whose name starts with the source name is fine
+        prepare_destination(&src, &dir.path().join("src-copy"), false).await.unwrap();
+
+        let existing = dir.path().join("existing");
+        std::fs::create_dir_all(existing.join("old")).unwrap();
+        let err = prepare_destination(&src, &existing, false).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        prepare_destination(&src, &existing, true).await.unwrap();
+        assert!(!existing.exists());
+
+        copy_path(&src, &existing).await.unwrap();
+        assert_eq!(std::fs::read_to_string(existing.join("nested/a.txt")).unwrap(), "a");
+    }
+
+    #[tokio::test]
+    async fn test_progress_reports_totals() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(src.join("sub")).unwrap();
+        std::fs::write(src.join("a.bin"), vec![0u8; 600 * 1024]).unwrap();
+        std::fs::write(src.join("sub/b.txt"), "hello").unwrap();
+
+        let mut reports = Vec::new();
+        let mut on_progress = |p: &CopyProgress| reports.push(*p);
+        let done = copy_path_with_progress(&src, &dir.path().join("dest"), &mut on_progress)
+            .await
+            .unwrap();
+
+        assert_eq!(done.files_total, 2);
+        assert_eq!(done.files_done, 2);
+        assert_eq!(done.bytes_total, 600 * 1024 + 5);
+        assert_eq!(done.bytes_done, done.bytes_total);
+        assert_eq!(reports.last().unwrap().files_done, 2);
+    }
+}
@@ -2,12 +2,29 @@
 pub mod session;
 pub mod error;
 pub mod path_utils;
+pub mod local_copy;
+pub mod file_content;
+pub mod app_associations;
 pub mod terminal_events;
 pub mod history;
+pub mod paste;
+pub mod output;
+pub mod sync;
+pub mod upload_tree;
+pub mod rate_limit;
+pub mod checksum;
+pub mod atomic_write;
+pub mod scrollback;
+pub mod recording;
+pub mod initial_command;
+pub mod fs_stats;
+pub mod permissions;
+#[cfg(test)]
+pub mod fake_session;
 
-pub use session::{TerminalSession, FileTransferSession, FileInfo};
+pub use session::{TerminalSession, FileTransferSession, FileInfo, SessionInfo};
 pub use error::{SessionError, ConnectionError};
-pub use path_utils::normalize_remote_path;
+pub use path_utils::{generate_unique_filename, normalize_remote_path, validate_upload_path};
 pub use terminal_events::TerminalExitEvent;
 pub use history::{parse_history_output, read_local_shell_history};
 
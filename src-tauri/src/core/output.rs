@@ -0,0 +1,90 @@
+//! Terminal output decoding shared by PTY, SSH and Telnet sessions
+
+use base64::Engine;
+
+/// Decodes a byte stream to UTF-8 text across read boundaries
+/// A multi-byte character split between two reads is held back until it is complete;
+/// only genuinely invalid bytes become U+FFFD
+#[derive(Debug, Default)]
+pub struct Utf8StreamDecoder {
+    /// Start of a character whose remaining bytes haven't arrived yet (at most 3 bytes)
+    tail: Vec<u8>,
+}
+
+impl Utf8StreamDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode the next chunk, keeping an incomplete trailing character for the next call
+    pub fn decode(&mut self, data: &[u8]) -> String {
+        let mut bytes = std::mem::take(&mut self.tail);
+        bytes.extend_from_slice(data);
+
+        let mut output = String::with_capacity(bytes.len());
+        let mut rest = &bytes[..];
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(text) => {
+                    output.push_str(text);
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    // Safe: from_utf8 validated this prefix
+                    output.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match e.error_len() {
+                        Some(len) => {
+                            output.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        None => {
+                            // Incomplete character at the end: wait for more bytes
+                            self.tail = after.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        output
+    }
+
+    /// Flush whatever is held back (stream ended mid-character)
+    pub fn finish(&mut self) -> String {
+        let tail = std::mem::take(&mut self.tail);
+        String::from_utf8_lossy(&tail).to_string()
+    }
+}
+
+/// Encode raw output for the "terminal-output-bytes" event
+pub fn encode_output_bytes(data: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_character_split_across_reads() {
+        let text = "héllo → 世界 🎉";
+        let bytes = text.as_bytes();
+        // Split inside every multi-byte character in turn
+        for split in 1..bytes.len() {
+            let mut decoder = Utf8StreamDecoder::new();
+            let mut output = decoder.decode(&bytes[..split]);
+            output.push_str(&decoder.decode(&bytes[split..]));
+            assert!(!output.contains(char::REPLACEMENT_CHARACTER), "split at {}", split);
+            assert_eq!(output, text);
+        }
+    }
+
+    #[test]
+    fn test_invalid_bytes_replaced_and_tail_flushed() {
+        let mut decoder = Utf8StreamDecoder::new();
+        assert_eq!(decoder.decode(b"a\xffb\xe4\xb8"), "a\u{FFFD}b");
+        assert_eq!(decoder.finish(), "\u{FFFD}");
+        assert_eq!(decoder.decode(b"ok"), "ok");
+    }
+}
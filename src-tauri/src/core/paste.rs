@@ -0,0 +1,131 @@
+//! Paste safety helpers
+//!
+//! Tracks whether the remote application enabled bracketed paste mode
+//! (DECSET 2004) and builds paste payloads accordingly.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Start of a bracketed paste
+pub const PASTE_START: &[u8] = b"\x1b[200~";
+/// End of a bracketed paste
+pub const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// Pastes larger than this are sent in chunks
+pub const PASTE_CHUNK_SIZE: usize = 4096;
+/// Delay between paste chunks so the remote can keep up
+pub const PASTE_CHUNK_DELAY_MS: u64 = 10;
+
+const MODE_ON: &[u8] = b"\x1b[?2004h";
+const MODE_OFF: &[u8] = b"\x1b[?2004l";
+
+/// Watches terminal output for bracketed paste mode changes
+/// Owned by the session's read loop; the flag is shared with the session
+pub struct BracketedPasteTracker {
+    enabled: Arc<AtomicBool>,
+    /// Trailing bytes of the previous chunk (sequence may be split across reads)
+    tail: Vec<u8>,
+}
+
+impl BracketedPasteTracker {
+    pub fn new() -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(false)),
+            tail: Vec::new(),
+        }
+    }
+
+    /// Shared flag, true while the remote has bracketed paste enabled
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        self.enabled.clone()
+    }
+
+    /// Scan an output chunk for mode set/reset sequences
+    pub fn observe(&mut self, data: &[u8]) {
+        let mut window = std::mem::take(&mut self.tail);
+        window.extend_from_slice(data);
+
+        // Last occurrence wins
+        let mut last: Option<(usize, bool)> = None;
+        for (i, w) in window.windows(MODE_ON.len()).enumerate() {
+            if w == MODE_ON {
+                last = Some((i, true));
+            } else if w == MODE_OFF {
+                last = Some((i, false));
+            }
+        }
+        if let Some((_, on)) = last {
+            self.enabled.store(on, Ordering::SeqCst);
+        }
+
+        let keep = window.len().min(MODE_ON.len() - 1);
+        self.tail = window[window.len() - keep..].to_vec();
+    }
+}
+
+impl Default for BracketedPasteTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Build the bytes to send for a paste
+/// When bracketed, embedded end markers are removed so the paste can't break out early
+pub fn build_paste_payload(data: &[u8], bracketed: bool) -> Vec<u8> {
+    if !bracketed {
+        return data.to_vec();
+    }
+
+    let mut payload = Vec::with_capacity(data.len() + PASTE_START.len() + PASTE_END.len());
+    payload.extend_from_slice(PASTE_START);
+    let mut i = 0;
+    while i < data.len() {
+        if data[i..].starts_with(PASTE_END) {
+            i += PASTE_END.len();
+        } else {
+            payload.push(data[i]);
+            i += 1;
+        }
+    }
+    payload.extend_from_slice(PASTE_END);
+    payload
+}
+
+/// Drop control characters a paste could use to inject keystrokes (escape sequences, ^C, ...)
+/// Newlines (CR, LF) and tabs are kept
+pub fn sanitize_paste(text: &str) -> String {
+    text.chars()
+        .filter(|&c| matches!(c, '\n' | '\r' | '\t') || !c.is_control())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_detects_mode_split_across_reads() {
+        let mut tracker = BracketedPasteTracker::new();
+        let flag = tracker.flag();
+
+        tracker.observe(b"prompt$ \x1b[?20");
+        assert!(!flag.load(Ordering::SeqCst));
+        tracker.observe(b"04h");
+        assert!(flag.load(Ordering::SeqCst));
+
+        tracker.observe(b"\x1b[?2004l\r\n");
+        assert!(!flag.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_payload_wrapped_and_end_marker_stripped() {
+        let payload = build_paste_payload(b"ls\x1b[201~rm -rf /\n", true);
+        assert_eq!(payload, b"\x1b[200~lsrm -rf /\n\x1b[201~".to_vec());
+        assert_eq!(build_paste_payload(b"ls\n", false), b"ls\n".to_vec());
+    }
+
+    #[test]
+    fn test_sanitize_keeps_only_newlines_and_tabs() {
+        assert_eq!(sanitize_paste("echo hi\x1b[201~\x03\x7f\u{9b}\r\n\tdone é"), "echo hi[201~\r\n\tdone é");
+    }
+}
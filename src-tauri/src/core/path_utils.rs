@@ -3,15 +3,28 @@
 /// Normalize a path for remote file transfer (SFTP/FTP)
 /// 
 /// - Replaces backslashes with forward slashes
-/// - Removes double slashes
+/// - Strips a Windows drive prefix (`C:\Users\x` -> `/Users/x`)
+/// - Removes double slashes and a leading `./`
 /// - Removes trailing slash except for root path
+///
+/// UNC paths (`\\server\share`) have no remote meaning and are returned
+/// unchanged; check them with [`is_unc_path`] before use
 pub fn normalize_remote_path(path: &str) -> String {
-    let mut normalized = path.replace('\\', "/");
+    if is_unc_path(path) {
+        return path.to_string();
+    }
+
+    let mut normalized = strip_drive_prefix(path).replace('\\', "/");
     
     // Remove double slashes (keep single leading slash for absolute paths)
     while normalized.contains("//") {
         normalized = normalized.replace("//", "/");
     }
+
+    // "./a/b" -> "a/b", "./" -> "."
+    while let Some(rest) = normalized.strip_prefix("./") {
+        normalized = if rest.is_empty() { ".".to_string() } else { rest.to_string() };
+    }
     
     // Remove trailing slash except for root
     if normalized.len() > 1 && normalized.ends_with('/') {
@@ -21,3 +34,189 @@ pub fn normalize_remote_path(path: &str) -> String {
     normalized
 }
 
+/// Whether a path is a Windows UNC path (`\\server\share`, `\\?\C:\`)
+pub fn is_unc_path(path: &str) -> bool {
+    path.starts_with("\\\\")
+}
+
+/// Drop a `C:\` / `C:/` drive prefix, keeping the separator so the path stays absolute
+fn strip_drive_prefix(path: &str) -> &str {
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'\\' | b'/') {
+        &path[2..]
+    } else {
+        path
+    }
+}
+
+/// Resolve `.` and `..` segments of a remote path
+///
+/// `..` never climbs above the root (absolute) or the start (relative)
+pub fn resolve_remote_path(path: &str) -> String {
+    let normalized = normalize_remote_path(path);
+    let absolute = normalized.starts_with('/');
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in normalized.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    let joined = segments.join("/");
+    if absolute {
+        format!("/{}", joined)
+    } else {
+        joined
+    }
+}
+
+/// Every directory from the top of `path` down to `path` itself ("/a/b" -> ["/a", "/a/b"])
+pub fn directory_chain(path: &str) -> Vec<String> {
+    let resolved = resolve_remote_path(path);
+    let mut chain = Vec::new();
+    let mut current = if resolved.starts_with('/') { "/".to_string() } else { String::new() };
+    for segment in resolved.split('/').filter(|s| !s.is_empty()) {
+        if !current.is_empty() && !current.ends_with('/') {
+            current.push('/');
+        }
+        current.push_str(segment);
+        chain.push(current.clone());
+    }
+    chain
+}
+
+/// Whether a path contains `..` segments
+pub fn has_parent_segments(path: &str) -> bool {
+    path.split(['/', '\\']).any(|segment| segment == "..")
+}
+
+/// Whether `path` resolves to `dir` itself or something beneath it
+pub fn is_within_directory(path: &str, dir: &str) -> bool {
+    let path = resolve_remote_path(path);
+    let dir = resolve_remote_path(dir);
+
+    if dir == "/" {
+        return path.starts_with('/');
+    }
+    path == dir || path.starts_with(&format!("{}/", dir))
+}
+
+/// Validate the destination path of an upload
+///
+/// Rejects UNC paths and `..` segments outright, and when the target directory is known,
+/// checks that the path stays inside it
+pub fn validate_upload_path(remote_path: &str, remote_dir: Option<&str>) -> Result<String, String> {
+    if is_unc_path(remote_path) {
+        return Err(format!("UNC paths are not valid remote paths: {}", remote_path));
+    }
+    if has_parent_segments(remote_path) {
+        return Err(format!("Path must not contain '..': {}", remote_path));
+    }
+
+    let normalized = normalize_remote_path(remote_path);
+    if let Some(dir) = remote_dir {
+        if !is_within_directory(&normalized, dir) || resolve_remote_path(&normalized) == resolve_remote_path(dir) {
+            return Err(format!("Path {} is outside target directory {}", remote_path, dir));
+        }
+    }
+
+    Ok(normalized)
+}
+
+/// Extensions that span two dots and must stay together
+const COMPOUND_EXTENSIONS: &[&str] = &[".tar.gz", ".tar.bz2", ".tar.xz", ".tar.zst"];
+
+/// Split a file name into stem and extension (extension includes the dot)
+///
+/// Dotfiles like `.bashrc` have no extension, a trailing dot is not an
+/// extension, and known compound extensions (`.tar.gz`) are kept whole
+fn split_extension(file_name: &str) -> (&str, &str) {
+    let lower = file_name.to_ascii_lowercase();
+    for ext in COMPOUND_EXTENSIONS {
+        if lower.len() > ext.len() && lower.ends_with(ext) {
+            let idx = file_name.len() - ext.len();
+            return (&file_name[..idx], &file_name[idx..]);
+        }
+    }
+
+    match file_name.rfind('.') {
+        Some(idx) if idx > 0 && idx < file_name.len() - 1 => (&file_name[..idx], &file_name[idx..]),
+        _ => (file_name, ""),
+    }
+}
+
+/// Name for the Nth duplicate of a file: `name (N).ext`
+pub fn generate_unique_filename(file_name: &str, counter: u32) -> String {
+    let (stem, ext) = split_extension(file_name);
+    format!("{} ({}){}", stem.trim_end(), counter, ext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_remote_path_windows_inputs() {
+        assert_eq!(normalize_remote_path("C:\\Users\\x"), "/Users/x");
+        assert_eq!(normalize_remote_path("d:/data/"), "/data");
+        assert_eq!(normalize_remote_path("C:"), "C:");
+        assert_eq!(normalize_remote_path("//double//slash//"), "/double/slash");
+        assert_eq!(normalize_remote_path("./logs/./today/"), "logs/./today");
+        assert_eq!(normalize_remote_path("./"), ".");
+
+        // UNC paths are flagged and left alone instead of becoming /server/share/file
+        assert!(is_unc_path("\\\\server\\share\\file"));
+        assert_eq!(normalize_remote_path("\\\\server\\share\\file"), "\\\\server\\share\\file");
+        assert!(validate_upload_path("\\\\server\\share\\file", None).is_err());
+        assert!(!is_unc_path("//double//slash//"));
+    }
+
+    #[test]
+    fn test_resolve_remote_path() {
+        assert_eq!(resolve_remote_path("/home/user/../../etc/passwd"), "/etc/passwd");
+        assert_eq!(resolve_remote_path("/../../x"), "/x");
+        assert_eq!(resolve_remote_path("a/./b/../c"), "a/c");
+    }
+
+    #[test]
+    fn test_directory_chain() {
+        assert_eq!(directory_chain("/srv//www/app/"), ["/srv", "/srv/www", "/srv/www/app"]);
+        assert_eq!(directory_chain("logs/2024"), ["logs", "logs/2024"]);
+        assert!(directory_chain("/").is_empty());
+    }
+
+    #[test]
+    fn test_upload_path_must_stay_in_target_dir() {
+        assert_eq!(
+            validate_upload_path("/home/user\\docs\\a.txt", Some("/home/user/docs")),
+            Ok("/home/user/docs/a.txt".to_string())
+        );
+        assert!(validate_upload_path("/home/user/docs/../../../etc/cron.d/x", Some("/home/user/docs")).is_err());
+        assert!(validate_upload_path("/home/user/docs\\..\\.bashrc", None).is_err());
+        assert!(validate_upload_path("/home/user/docs-other/a.txt", Some("/home/user/docs")).is_err());
+        assert!(validate_upload_path("/home/user/docs", Some("/home/user/docs")).is_err());
+    }
+
+    #[test]
+    fn test_unique_filename() {
+        assert_eq!(generate_unique_filename("report.txt", 1), "report (1).txt");
+        assert_eq!(generate_unique_filename("Makefile", 2), "Makefile (2)");
+        assert_eq!(generate_unique_filename("notes .md", 1), "notes (1).md");
+        assert_eq!(generate_unique_filename("trailing ", 1), "trailing (1)");
+    }
+
+    #[test]
+    fn test_unique_filename_dotfiles_and_multi_dot() {
+        assert_eq!(generate_unique_filename(".bashrc", 1), ".bashrc (1)");
+        assert_eq!(generate_unique_filename(".config.json", 1), ".config (1).json");
+        assert_eq!(generate_unique_filename("archive.tar.gz", 3), "archive (3).tar.gz");
+        assert_eq!(generate_unique_filename("Backup.TAR.XZ", 1), "Backup (1).TAR.XZ");
+        assert_eq!(generate_unique_filename("v1.2.3.zip", 1), "v1.2.3 (1).zip");
+        assert_eq!(generate_unique_filename("odd.", 1), "odd. (1)");
+    }
+}
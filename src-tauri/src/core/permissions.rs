@@ -0,0 +1,98 @@
+//! Write-permission checks against the remote user, so a save into a read-only
+//! file fails up front with PERMISSION_DENIED instead of a generic server error
+
+/// Prints the session user's uid, then its group ids
+pub const ID_COMMAND: &str = "id -u && id -G";
+
+/// The user a session is logged in as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteUser {
+    pub uid: u32,
+    pub groups: Vec<u32>,
+}
+
+impl RemoteUser {
+    /// Parse the output of ID_COMMAND ("1000\n1000 27 100\n")
+    pub fn parse_id_output(output: &str) -> Option<Self> {
+        let mut lines = output.lines();
+        let uid = lines.next()?.trim().parse().ok()?;
+        let groups = lines
+            .next()
+            .unwrap_or("")
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        Some(Self { uid, groups })
+    }
+
+    /// Whether the user may modify a file with this mode and owner
+    pub fn may_write(&self, mode: u32, uid: u32, gid: u32) -> bool {
+        self.allows(mode, uid, gid, 0o2)
+    }
+
+    /// Whether the user may create files in a directory with this mode and owner (write + search)
+    pub fn may_create_in(&self, mode: u32, uid: u32, gid: u32) -> bool {
+        self.allows(mode, uid, gid, 0o3)
+    }
+
+    /// False only when the mode bits clearly deny access (ACLs aren't visible over SFTP)
+    fn allows(&self, mode: u32, uid: u32, gid: u32, wanted: u32) -> bool {
+        let grants = |shift: u32| (mode >> shift) & wanted == wanted;
+        if self.uid == 0 {
+            return true;
+        }
+        if uid == self.uid {
+            return grants(6);
+        }
+        if self.groups.contains(&gid) {
+            return grants(3);
+        }
+        // With ACLs the group bits are the mask for named entries, which may include this user
+        grants(0) || grants(3)
+    }
+}
+
+/// Directory `path` lives in ("/srv/a.txt" -> "/srv", "a.txt" -> ".")
+pub fn parent_directory(path: &str) -> &str {
+    match path.trim_end_matches('/').rsplit_once('/') {
+        Some(("", _)) => "/",
+        Some((parent, _)) => parent,
+        None => ".",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_checks_follow_owner_group_other() {
+        let user = RemoteUser::parse_id_output("1000\n1000 27\n").unwrap();
+        assert_eq!(user, RemoteUser { uid: 1000, groups: vec![1000, 27] });
+
+        // Owner bits decide for the owner, even if others may write
+        assert!(user.may_write(0o644, 1000, 1000));
+        assert!(!user.may_write(0o466, 1000, 1000));
+        // Group member
+        assert!(user.may_write(0o664, 0, 27));
+        assert!(!user.may_write(0o644, 0, 27));
+        // Neither: other bits, unless the group class could come from an ACL
+        assert!(!user.may_write(0o644, 0, 0));
+        assert!(user.may_write(0o664, 0, 0));
+        assert!(user.may_write(0o646, 0, 0));
+
+        // Creating needs write and search on the directory
+        assert!(user.may_create_in(0o755, 1000, 1000));
+        assert!(!user.may_create_in(0o755, 0, 0));
+        assert!(!user.may_create_in(0o633, 1000, 1000));
+
+        let root = RemoteUser::parse_id_output("0\n0\n").unwrap();
+        assert!(root.may_write(0o444, 1000, 1000));
+        assert_eq!(RemoteUser::parse_id_output("uid=1000\n"), None);
+
+        assert_eq!(parent_directory("/srv/www/index.html"), "/srv/www");
+        assert_eq!(parent_directory("/notes.txt"), "/");
+        assert_eq!(parent_directory("notes.txt"), ".");
+    }
+}
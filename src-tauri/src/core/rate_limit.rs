@@ -0,0 +1,89 @@
+//! Bandwidth limiting for file transfer loops
+
+use std::time::{Duration, Instant};
+
+/// Shortest burst the bucket holds, so small caps still pass a whole chunk
+const MIN_BURST_BYTES: f64 = 64.0 * 1024.0;
+
+/// Token bucket: bytes accrue at the cap rate up to a quarter second's worth,
+/// so short bursts go through and the running average stays under the cap
+pub struct RateLimiter {
+    bytes_per_sec: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// None (and 0) mean unlimited, so callers can skip throttling entirely
+    pub fn new(max_bytes_per_sec: Option<u64>) -> Option<Self> {
+        let bytes_per_sec = max_bytes_per_sec.filter(|&rate| rate > 0)? as f64;
+        let capacity = (bytes_per_sec / 4.0).max(MIN_BURST_BYTES);
+        Some(Self {
+            bytes_per_sec,
+            capacity,
+            tokens: capacity,
+            last_refill: Instant::now(),
+        })
+    }
+
+    /// Account for `bytes` just transferred, sleeping if they exceed the budget
+    pub async fn throttle(&mut self, bytes: usize) {
+        let delay = self.delay_after(bytes, Instant::now());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// How long to wait before continuing; a deficit is repaid by later refills
+    fn delay_after(&mut self, bytes: usize, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed * self.bytes_per_sec).min(self.capacity) - bytes as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.bytes_per_sec)
+        }
+    }
+}
+
+/// Per-transfer cap: `Some(0)` lifts the session default, `None` keeps it
+pub fn effective_limit(session_default: Option<u64>, transfer_override: Option<u64>) -> Option<u64> {
+    transfer_override.or(session_default).filter(|&rate| rate > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_has_no_limiter() {
+        assert!(RateLimiter::new(None).is_none());
+        assert!(RateLimiter::new(Some(0)).is_none());
+    }
+
+    #[test]
+    fn test_burst_then_steady_rate() {
+        let mut limiter = RateLimiter::new(Some(1_000_000)).unwrap();
+        let start = limiter.last_refill;
+        // A quarter second's worth passes at once
+        assert_eq!(limiter.delay_after(250_000, start), Duration::ZERO);
+        // Beyond the burst, wait for the bytes to be earned
+        assert_eq!(limiter.delay_after(100_000, start), Duration::from_millis(100));
+        // After waiting, the same amount again costs the same delay: 1 MB/s on average
+        let later = start + Duration::from_millis(100);
+        assert_eq!(limiter.delay_after(100_000, later), Duration::from_millis(100));
+        // An idle period refills only up to the burst size
+        let idle = later + Duration::from_secs(10);
+        assert_eq!(limiter.delay_after(350_000, idle), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_effective_limit() {
+        assert_eq!(effective_limit(Some(500), None), Some(500));
+        assert_eq!(effective_limit(Some(500), Some(100)), Some(100));
+        assert_eq!(effective_limit(Some(500), Some(0)), None);
+        assert_eq!(effective_limit(None, None), None);
+    }
+}
@@ -0,0 +1,198 @@
+//! Session recording in asciinema v2 format (`.cast`: a JSON header line, then one JSON event per line)
+
+use crate::core::output::Utf8StreamDecoder;
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Recording switch shared between a session, its I/O task and the manager
+/// While no recording runs, teeing output costs one atomic load
+#[derive(Clone)]
+pub struct Recording {
+    inner: Arc<RecordingState>,
+}
+
+struct RecordingState {
+    active: AtomicBool,
+    /// Current terminal size, written to the header when a recording starts
+    size: Mutex<(u16, u16)>,
+    /// Terminal type the remote side was told, written as TERM in the header
+    term: String,
+    cast: Mutex<Option<CastWriter>>,
+}
+
+impl Recording {
+    /// A session starting at `cols` x `rows` with terminal type `term`
+    pub fn new(cols: u16, rows: u16, term: impl Into<String>) -> Self {
+        Self {
+            inner: Arc::new(RecordingState {
+                active: AtomicBool::new(false),
+                size: Mutex::new((cols, rows)),
+                term: term.into(),
+                cast: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Start writing a new .cast file at `path` (replaced if it exists)
+    /// With `record_input`, typed input is recorded too (passwords included)
+    pub fn start(&self, path: &str, record_input: bool) -> Result<(), String> {
+        let mut cast = self.inner.cast.lock().unwrap();
+        if cast.is_some() {
+            return Err("Session is already being recorded".to_string());
+        }
+        let file = File::create(path).map_err(|e| format!("Cannot create {}: {}", path, e))?;
+        let size = *self.inner.size.lock().unwrap();
+        let writer = CastWriter::start(Box::new(BufWriter::new(file)), size, &self.inner.term, record_input)
+            .map_err(|e| format!("Cannot write {}: {}", path, e))?;
+        *cast = Some(writer);
+        self.inner.active.store(true, Ordering::SeqCst);
+        log::info!("Recording session to {}", path);
+        Ok(())
+    }
+
+    /// Finish the recording, flushing it to disk
+    pub fn stop(&self) -> Result<(), String> {
+        self.inner.active.store(false, Ordering::SeqCst);
+        let cast = self.inner.cast.lock().unwrap().take();
+        match cast {
+            Some(mut cast) => cast.out.flush().map_err(|e| format!("Cannot write recording: {}", e)),
+            None => Err("Session is not being recorded".to_string()),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.inner.active.load(Ordering::Relaxed)
+    }
+
+    /// Record output received from the session
+    pub fn output(&self, data: &[u8]) {
+        if self.is_active() {
+            self.record(|cast| cast.output(data));
+        }
+    }
+
+    /// Record input sent to the session (only when the recording includes input)
+    pub fn input(&self, data: &[u8]) {
+        if self.is_active() {
+            self.record(|cast| cast.input(data));
+        }
+    }
+
+    /// Track the terminal size, recording the change while a recording runs
+    pub fn resize(&self, cols: u16, rows: u16) {
+        *self.inner.size.lock().unwrap() = (cols, rows);
+        if self.is_active() {
+            self.record(|cast| cast.resize(cols, rows));
+        }
+    }
+
+    /// A write error ends the recording rather than the session
+    fn record(&self, write: impl FnOnce(&mut CastWriter) -> std::io::Result<()>) {
+        let mut cast = self.inner.cast.lock().unwrap();
+        let Some(writer) = cast.as_mut() else {
+            return;
+        };
+        if let Err(e) = write(writer) {
+            log::warn!("Recording stopped, cannot write: {}", e);
+            *cast = None;
+            self.inner.active.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Writes the header and timestamped events of one recording
+struct CastWriter {
+    out: Box<dyn Write + Send>,
+    started: Instant,
+    record_input: bool,
+    /// Event data must be text; hold back characters split across reads
+    output_decoder: Utf8StreamDecoder,
+}
+
+impl CastWriter {
+    fn start(
+        mut out: Box<dyn Write + Send>,
+        (cols, rows): (u16, u16),
+        term: &str,
+        record_input: bool,
+    ) -> std::io::Result<Self> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let header = json!({
+            "version": 2,
+            "width": cols,
+            "height": rows,
+            "timestamp": timestamp,
+            "env": { "TERM": term },
+        });
+        writeln!(out, "{}", header)?;
+        Ok(Self { out, started: Instant::now(), record_input, output_decoder: Utf8StreamDecoder::new() })
+    }
+
+    fn output(&mut self, data: &[u8]) -> std::io::Result<()> {
+        let text = self.output_decoder.decode(data);
+        self.event("o", &text)
+    }
+
+    fn input(&mut self, data: &[u8]) -> std::io::Result<()> {
+        if !self.record_input {
+            return Ok(());
+        }
+        self.event("i", &String::from_utf8_lossy(data))
+    }
+
+    fn resize(&mut self, cols: u16, rows: u16) -> std::io::Result<()> {
+        self.event("r", &format!("{}x{}", cols, rows))
+    }
+
+    /// `[seconds since start, code, data]`
+    fn event(&mut self, code: &str, data: &str) -> std::io::Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let elapsed = self.started.elapsed().as_secs_f64();
+        writeln!(self.out, "{}", json!([(elapsed * 1e6).round() / 1e6, code, data]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writes_asciinema_v2_cast() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.cast");
+        let path = path.to_str().unwrap();
+
+        let recording = Recording::new(80, 24, "vt100");
+        recording.output(b"before start\r\n");
+        recording.resize(120, 40);
+        recording.start(path, false).unwrap();
+        assert!(recording.start(path, false).is_err());
+
+        recording.output(b"$ echo h\xC3");
+        recording.output(b"\xA9\r\n");
+        recording.input(b"secret\r");
+        recording.resize(100, 30);
+        recording.stop().unwrap();
+        recording.output(b"after stop\r\n");
+        assert!(recording.stop().is_err());
+
+        let cast = std::fs::read_to_string(path).unwrap();
+        let lines: Vec<serde_json::Value> = cast.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!((lines[0]["version"].as_u64(), lines[0]["width"].as_u64(), lines[0]["height"].as_u64()), (Some(2), Some(120), Some(40)));
+        assert_eq!(lines[0]["env"]["TERM"], "vt100");
+
+        let events: Vec<(f64, &str, &str)> = lines[1..]
+            .iter()
+            .map(|e| (e[0].as_f64().unwrap(), e[1].as_str().unwrap(), e[2].as_str().unwrap()))
+            .collect();
+        let codes: Vec<_> = events.iter().map(|(_, code, data)| (*code, *data)).collect();
+        assert_eq!(codes, [("o", "$ echo h"), ("o", "é\r\n"), ("r", "100x30")]);
+        assert!(events.windows(2).all(|w| w[0].0 <= w[1].0));
+    }
+}
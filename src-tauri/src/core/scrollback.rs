@@ -0,0 +1,82 @@
+//! Recent terminal output kept per session, so a reloaded frontend can repaint
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Scrollback kept when a session doesn't set a limit
+pub const DEFAULT_SCROLLBACK_BYTES: usize = 256 * 1024;
+
+/// The latest output bytes of a session, shared with its I/O task
+/// A limit of 0 disables it: nothing is allocated and push is a no-op
+#[derive(Debug, Clone, Default)]
+pub struct Scrollback {
+    buffer: Option<Arc<Mutex<VecDeque<u8>>>>,
+    limit: usize,
+}
+
+impl Scrollback {
+    /// Keep up to `limit` bytes (None = DEFAULT_SCROLLBACK_BYTES)
+    pub fn new(limit: Option<usize>) -> Self {
+        let limit = limit.unwrap_or(DEFAULT_SCROLLBACK_BYTES);
+        Self {
+            buffer: (limit > 0).then(|| Arc::new(Mutex::new(VecDeque::new()))),
+            limit,
+        }
+    }
+
+    /// Append output, evicting the oldest bytes beyond the limit
+    pub fn push(&self, data: &[u8]) {
+        let Some(buffer) = &self.buffer else {
+            return;
+        };
+        let data = &data[data.len().saturating_sub(self.limit)..];
+        let mut buffer = buffer.lock().unwrap();
+        let overflow = (buffer.len() + data.len()).saturating_sub(self.limit);
+        buffer.drain(..overflow);
+        buffer.extend(data);
+    }
+
+    /// Buffered bytes, oldest first (eviction may have cut the first character or escape sequence)
+    pub fn bytes(&self) -> Vec<u8> {
+        match &self.buffer {
+            Some(buffer) => buffer.lock().unwrap().iter().copied().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Buffered output as text, skipping a character whose first bytes were evicted
+    pub fn text(&self) -> String {
+        let bytes = self.bytes();
+        let start = bytes.iter().take(3).take_while(|&&b| b & 0xC0 == 0x80).count();
+        String::from_utf8_lossy(&bytes[start..]).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_oldest_bytes_at_the_limit() {
+        let scrollback = Scrollback::new(Some(8));
+        scrollback.push(b"$ ls\r\n");
+        scrollback.push(b"a.txt\r\n");
+        assert_eq!(scrollback.bytes(), b"\na.txt\r\n");
+
+        // A chunk larger than the limit keeps only its end
+        scrollback.push(b"0123456789");
+        assert_eq!(scrollback.bytes(), b"23456789");
+
+        let scrollback = Scrollback::new(Some(4));
+        scrollback.push("aé".as_bytes());
+        scrollback.push(b"bc");
+        assert_eq!(scrollback.text(), "ébc");
+        // "é" lost its first byte to eviction
+        scrollback.push(b"d");
+        assert_eq!(scrollback.text(), "bcd");
+
+        let disabled = Scrollback::new(Some(0));
+        disabled.push(b"output");
+        assert!(disabled.bytes().is_empty());
+    }
+}
@@ -1,9 +1,29 @@
 use async_trait::async_trait;
+use russh::client::{Handle, Msg};
+use russh::Channel;
+use serde::Serialize;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use crate::core::error::{SessionError, ConnectionError};
+use crate::core::checksum::ChecksumAlgorithm;
+use crate::core::file_content::{self, FileRange};
+use crate::core::fs_stats::FilesystemStats;
+use crate::core::path_utils::directory_chain;
+use crate::core::recording::Recording;
+use crate::ssh::client::{AgentAuthInfo, SshClient};
 use crate::ssh::config::ConnectionType;
 use crate::terminal::session::SessionType;
 
+/// How long a remote session waits for its I/O loop to send close/shutdown
+pub const GRACEFUL_CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long a liveness probe waits for the remote end
+pub const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Transfer progress callback (bytes_transferred, total_bytes)
+/// Returning false cancels the transfer before the next chunk
+pub type ProgressCallback = Arc<dyn Fn(u64, u64) -> bool + Send + Sync>;
+
 /// Terminal session trait (Strategy Pattern)
 /// Implemented by PTY and SSH terminal sessions
 #[async_trait]
@@ -13,19 +33,153 @@ pub trait TerminalSession: Send + Sync {
 
     async fn write(&self, data: &[u8]) -> Result<(), SessionError>;
     async fn resize(&self, cols: u16, rows: u16) -> Result<(), SessionError>;
-    async fn close(&mut self) -> Result<(), SessionError>;
+    /// Shared by the manager with in-flight operations (e.g. a chunked paste), which fail once closed
+    async fn close(&self) -> Result<(), SessionError>;
+
+    /// Probe whether the connection (or local shell) is actually alive, not just registered
+    /// Default implementation returns true
+    async fn ping(&self) -> bool {
+        true
+    }
 
     /// Start streaming output to frontend (for SSH sessions)
     /// Default implementation does nothing (local sessions auto-stream)
     fn start_streaming(&self) {}
 
-    /// Execute a command and return output (SSH sessions only)
-    /// Default implementation returns error (not supported for local PTY)
+    /// Execute a command outside the interactive shell and return its output
+    /// Default implementation returns error (not supported, e.g. Telnet)
     async fn execute_command(&self, _command: &str) -> Result<String, SessionError> {
         Err(SessionError::UnsupportedOperation(
             "Command execution not supported for this session type".to_string()
         ))
     }
+
+    /// Execute a command, failing if it hasn't finished within `timeout`
+    /// Default implementation wraps execute_command in a timer
+    async fn execute_command_with_timeout(&self, command: &str, timeout: Duration) -> Result<String, SessionError> {
+        tokio::time::timeout(timeout, self.execute_command(command))
+            .await
+            .unwrap_or_else(|_| Err(SessionError::IoError(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "Command timed out",
+            ))))
+    }
+
+    /// Whether the remote application has enabled bracketed paste mode
+    /// Default implementation returns false (paste is sent as-is)
+    fn bracketed_paste_enabled(&self) -> bool {
+        false
+    }
+
+    /// Recent output, for repainting the terminal after the frontend reloads
+    /// Default implementation keeps none
+    fn scrollback(&self) -> String {
+        String::new()
+    }
+
+    /// Recorder the session's output is teed into (see start_recording)
+    /// Default implementation returns None (session can't be recorded)
+    fn recording(&self) -> Option<Recording> {
+        None
+    }
+
+    /// Describe the session for the UI (type and negotiated parameters)
+    fn session_info(&self) -> SessionInfo {
+        SessionInfo::new(self.id(), self.session_type())
+    }
+
+    /// Operations the session supports
+    /// Default implementation supports none (plain byte stream)
+    fn capabilities(&self) -> SessionCapabilities {
+        SessionCapabilities::default()
+    }
+
+    /// SSH connection handle, for opening extra channels (port forwarding)
+    /// Default implementation returns None (not an SSH session)
+    fn ssh_handle(&self) -> Option<Arc<Handle<SshClient>>> {
+        None
+    }
+}
+
+/// Operations a session supports, so the UI can hide the ones that would only fail
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCapabilities {
+    /// Change permissions
+    pub chmod: bool,
+    /// Create symlinks
+    pub symlink: bool,
+    /// Copy between remote paths without staging files on the local disk
+    pub server_side_copy: bool,
+    /// Run shell commands and read their output
+    pub exec: bool,
+    /// Delete a directory along with its contents
+    pub recursive_delete: bool,
+}
+
+/// An open terminal or file session, for listing what is connected
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSession {
+    pub id: String,
+    /// "local", "ssh", "telnet", "sftp", "ftp" or "ftps"
+    #[serde(rename = "type")]
+    pub session_type: String,
+    /// Remote hostname (None for local terminals)
+    pub host: Option<String>,
+    /// Unix time the session was opened, in seconds
+    pub opened_at: u64,
+}
+
+impl ActiveSession {
+    /// Describe a session opened now
+    pub fn new(id: &str, session_type: &str, host: Option<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            session_type: session_type.to_string(),
+            host,
+            opened_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        }
+    }
+}
+
+/// Terminal session information returned to frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub session_type: SessionType,
+    /// Charset negotiated with the server (Telnet CHARSET), if any
+    pub charset: Option<String>,
+    /// Server identification string (SSH banner)
+    pub server_version: Option<String>,
+    /// Negotiated SSH algorithms
+    pub kex: Option<String>,
+    pub cipher: Option<String>,
+    pub mac: Option<String>,
+    pub host_key_algorithm: Option<String>,
+    /// Whether the server granted a PTY (None = not requested or no reply yet)
+    pub pty_allocated: Option<bool>,
+    /// Agent key used to authenticate (SSH agent auth only)
+    pub agent_auth: Option<AgentAuthInfo>,
+}
+
+impl SessionInfo {
+    /// Create session info with only id and type set
+    pub fn new(session_id: &str, session_type: SessionType) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            session_type,
+            charset: None,
+            server_version: None,
+            kex: None,
+            cipher: None,
+            mac: None,
+            host_key_algorithm: None,
+            pty_allocated: None,
+            agent_auth: None,
+        }
+    }
 }
 
 /// File information for directory listings
@@ -43,6 +197,23 @@ pub struct FileInfo {
     pub group: Option<String>,
 }
 
+/// Directory contents plus problems with individual entries
+/// A warning does not invalidate the rest of the listing
+#[derive(Debug, Clone, Default)]
+pub struct DirectoryListing {
+    pub entries: Vec<FileInfo>,
+    pub warnings: Vec<ListingWarning>,
+}
+
+/// Problem reading one directory entry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingWarning {
+    /// Entry path (or the raw LIST line when it could not be parsed)
+    pub path: String,
+    pub message: String,
+}
+
 /// File transfer session trait
 /// Implemented by SFTP, FTP, FTPS connections
 #[async_trait]
@@ -52,9 +223,16 @@ pub trait FileTransferSession: Send + Sync {
     
     /// Get connection type
     fn connection_type(&self) -> ConnectionType;
+
+    /// Operations the session supports
+    /// Default implementation: recursive delete only (the manager walks the tree with plain deletes)
+    fn capabilities(&self) -> SessionCapabilities {
+        SessionCapabilities { recursive_delete: true, ..Default::default() }
+    }
     
     /// List directory contents
-    async fn list_directory(&self, path: &str) -> Result<Vec<FileInfo>, ConnectionError>;
+    /// Entries that cannot be fully read are reported as warnings instead of failing the listing
+    async fn list_directory(&self, path: &str) -> Result<DirectoryListing, ConnectionError>;
     
     /// Download file from remote to local
     async fn download_file(&self, remote_path: &str, local_path: &str) -> Result<(), ConnectionError>;
@@ -63,29 +241,143 @@ pub trait FileTransferSession: Send + Sync {
     async fn upload_file(&self, local_path: &str, remote_path: &str) -> Result<(), ConnectionError>;
 
     /// Download file with optional progress callback (bytes_transferred, total_bytes)
-    /// Default implementation falls back to download_file without progress.
+    /// and an optional bandwidth cap.
+    /// Default implementation falls back to download_file without progress or cap.
     async fn download_file_with_progress(
         &self,
         remote_path: &str,
         local_path: &str,
-        _progress: Option<Arc<dyn Fn(u64, u64) + Send + Sync>>,
+        _progress: Option<ProgressCallback>,
+        _max_bytes_per_sec: Option<u64>,
     ) -> Result<(), ConnectionError> {
         self.download_file(remote_path, local_path).await
     }
     
+    /// Download file, continuing a partial local file when the remote file is unchanged
+    /// Default implementation can't seek and downloads from the start.
+    async fn resume_download_with_progress(
+        &self,
+        remote_path: &str,
+        local_path: &str,
+        progress: Option<ProgressCallback>,
+        max_bytes_per_sec: Option<u64>,
+    ) -> Result<(), ConnectionError> {
+        self.download_file_with_progress(remote_path, local_path, progress, max_bytes_per_sec).await
+    }
+
     /// Upload file with optional progress callback (bytes_transferred, total_bytes)
-    /// Default implementation falls back to upload_file without progress.
+    /// and an optional bandwidth cap.
+    /// Default implementation falls back to upload_file without progress or cap.
     async fn upload_file_with_progress(
         &self,
         local_path: &str,
         remote_path: &str,
-        _progress: Option<Arc<dyn Fn(u64, u64) + Send + Sync>>,
+        _progress: Option<ProgressCallback>,
+        _max_bytes_per_sec: Option<u64>,
     ) -> Result<(), ConnectionError> {
         self.upload_file(local_path, remote_path).await
     }
+
+    /// Upload file, then give the remote copy the local file's permissions and mtime/atime
+    /// Default implementation uploads without attributes.
+    async fn upload_file_preserving_attributes(
+        &self,
+        local_path: &str,
+        remote_path: &str,
+        progress: Option<ProgressCallback>,
+        max_bytes_per_sec: Option<u64>,
+    ) -> Result<(), ConnectionError> {
+        self.upload_file_with_progress(local_path, remote_path, progress, max_bytes_per_sec).await
+    }
     
+    /// Hex digest of a remote file computed on the server
+    /// Ok(None) when the server can't compute it. Default implementation has no way to.
+    async fn remote_checksum(&self, _path: &str, _algorithm: ChecksumAlgorithm) -> Result<Option<String>, ConnectionError> {
+        Ok(None)
+    }
+
+    /// Run a shell command on the server, returning its stdout and exit code
+    /// Default implementation: the protocol has no way to run commands (only SFTP, via SSH exec)
+    async fn run_command(&self, _command: &str) -> Result<(String, i32), ConnectionError> {
+        Err(ConnectionError::UnsupportedType("Running commands requires an SFTP session".to_string()))
+    }
+
+    /// Start a shell command on the server, returning its channel to stream the output from
+    /// Default implementation: the protocol has no way to run commands (only SFTP, via SSH exec)
+    async fn exec_channel(&self, _command: &str) -> Result<Channel<Msg>, ConnectionError> {
+        Err(ConnectionError::UnsupportedType("Running commands requires an SFTP session".to_string()))
+    }
+
+    /// Size and free space of the filesystem holding `path`
+    /// Default implementation: the protocol has no way to tell (only SFTP can)
+    async fn statfs(&self, _path: &str) -> Result<FilesystemStats, ConnectionError> {
+        Err(ConnectionError::UnsupportedType("Free space can only be queried over SFTP".to_string()))
+    }
+
+    /// Id of the filesystem holding `path`, to tell whether a rename could cross filesystems
+    /// Default implementation: None, the protocol has no way to tell (only SFTP's statvfs can)
+    async fn filesystem_id(&self, _path: &str) -> Result<Option<u64>, ConnectionError> {
+        Ok(None)
+    }
+
+    /// Fail with PERMISSION_DENIED when the session user clearly can't write `path`
+    /// (or, if it doesn't exist, create files in its directory)
+    /// Default implementation can't tell and leaves it to the write itself.
+    async fn check_writable(&self, _path: &str) -> Result<(), ConnectionError> {
+        Ok(())
+    }
+
     /// Create directory on remote
     async fn create_directory(&self, path: &str) -> Result<(), ConnectionError>;
+
+    /// Create a directory and any missing parents (mkdir -p); existing directories are fine
+    /// Default implementation stats each level and creates the ones that are missing.
+    async fn create_directory_recursive(&self, path: &str) -> Result<(), ConnectionError> {
+        for dir in directory_chain(path) {
+            match self.stat(&dir).await {
+                Ok(info) if info.is_directory => continue,
+                Ok(_) => return Err(ConnectionError::InvalidPath(format!("Not a directory: {}", dir))),
+                Err(_) => {}
+            }
+            if let Err(e) = self.create_directory(&dir).await {
+                // Someone else may have created it in the meantime
+                if !self.stat(&dir).await.is_ok_and(|info| info.is_directory) {
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy a file, or a directory recursively, to another path on the same remote
+    /// Entries that can't be read are skipped and returned, the rest is still copied.
+    /// Default implementation stages each file through a local temp file.
+    async fn copy(&self, source_path: &str, dest_path: &str) -> Result<Vec<ListingWarning>, ConnectionError> {
+        if !self.stat(source_path).await?.is_directory {
+            let temp_file = tempfile::NamedTempFile::new()
+                .map_err(|e| ConnectionError::io("Failed to create temp file", &e))?;
+            let temp_path = temp_file.path().to_string_lossy().to_string();
+            self.download_file_with_progress(source_path, &temp_path, None, None).await?;
+            self.upload_file_with_progress(&temp_path, dest_path, None, None).await?;
+            return Ok(Vec::new());
+        }
+
+        self.create_directory(dest_path).await?;
+        let listing = self.list_directory(source_path).await?;
+        let mut skipped = listing.warnings;
+        for warning in &skipped {
+            log::warn!("[Copy] Skipping {}: {}", warning.path, warning.message);
+        }
+        for entry in listing.entries {
+            if entry.name == "." || entry.name == ".." {
+                continue;
+            }
+            let source_item = format!("{}/{}", source_path.trim_end_matches('/'), entry.name);
+            let dest_item = format!("{}/{}", dest_path.trim_end_matches('/'), entry.name);
+            skipped.extend(self.copy(&source_item, &dest_item).await?);
+        }
+        Ok(skipped)
+    }
     
     /// Delete file or directory on remote
     async fn delete(&self, path: &str, is_directory: bool) -> Result<(), ConnectionError>;
@@ -93,19 +385,55 @@ pub trait FileTransferSession: Send + Sync {
     /// Rename/move file or directory
     async fn rename(&self, old_path: &str, new_path: &str) -> Result<(), ConnectionError>;
     
-    /// Change file permissions (SFTP only, returns error for FTP)
+    /// Change file permissions (FTP uses SITE CHMOD and errors if the server lacks it)
     async fn chmod(&self, path: &str, mode: u32) -> Result<(), ConnectionError>;
+
+    /// Create a symlink at `link_path` pointing to `target` (SFTP only, returns error for FTP)
+    async fn create_symlink(&self, target: &str, link_path: &str) -> Result<(), ConnectionError>;
     
     /// Get file info/metadata
     async fn stat(&self, path: &str) -> Result<FileInfo, ConnectionError>;
     
     /// Read file content (for small files)
-    async fn read_file(&self, path: &str) -> Result<Vec<u8>, ConnectionError>;
+    /// Fails with FileTooLarge rather than reading more than `max_bytes`
+    /// Default implementation reads the range from the start up to one byte past the limit.
+    async fn read_file(&self, path: &str, max_bytes: Option<u64>) -> Result<Vec<u8>, ConnectionError> {
+        let range = self.read_file_range(path, 0, file_content::read_limit(max_bytes)).await?;
+        file_content::check_size(range.data.len() as u64, max_bytes)?;
+        Ok(range.data)
+    }
+
+    /// Read up to `length` bytes starting at `offset` (past the end gives an empty, eof window)
+    async fn read_file_range(&self, path: &str, offset: u64, length: u64) -> Result<FileRange, ConnectionError>;
     
     /// Write file content
     async fn write_file(&self, path: &str, content: &[u8]) -> Result<(), ConnectionError>;
+
+    /// Write one chunk of a streamed upload at `offset`
+    /// Offset 0 creates (or truncates) the file; later chunks must follow in order.
+    /// The file may stay open between chunks; it is closed after the `is_last` one
+    async fn write_chunk(&self, path: &str, offset: u64, data: &[u8], is_last: bool) -> Result<(), ConnectionError>;
     
     /// Close the connection
     async fn close(&mut self) -> Result<(), ConnectionError>;
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::fake_session::FakeSession;
+
+    #[tokio::test]
+    async fn test_create_directory_recursive_creates_missing_levels() {
+        // mkdir fails without the parent, like SFTP
+        let tree = FakeSession::new().dir("/srv");
+
+        tree.create_directory_recursive("/srv/www/app").await.unwrap();
+        assert_eq!(tree.operations(), ["mkdir /srv/www", "mkdir /srv/www/app"]);
+
+        // Already there: nothing to do, no error
+        tree.create_directory_recursive("/srv/www/app/").await.unwrap();
+        assert_eq!(tree.operations().len(), 2);
+    }
+}
@@ -0,0 +1,300 @@
+//! One-way directory sync planning (local -> remote, rsync-lite)
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use tokio::fs;
+
+/// Options for sync_directory
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncOptions {
+    /// Delete remote files and directories that don't exist locally
+    #[serde(default)]
+    pub delete: bool,
+    /// Only return the planned actions, change nothing
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// A file or directory in one side of the sync, keyed by its relative path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncEntry {
+    pub is_directory: bool,
+    pub size: u64,
+    /// Modification time in Unix seconds, when known
+    pub mtime: Option<u64>,
+    /// Why the entry is left alone (e.g. it couldn't be read); it is never uploaded or deleted
+    pub skipped: Option<String>,
+}
+
+impl SyncEntry {
+    /// An entry seen while scanning but not synced
+    pub fn skipped(reason: impl Into<String>) -> Self {
+        Self { is_directory: false, size: 0, mtime: None, skipped: Some(reason.into()) }
+    }
+}
+
+/// Tree snapshot: relative path ("a/b.txt") -> entry
+pub type SyncTree = BTreeMap<String, SyncEntry>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncActionKind {
+    /// Create a missing remote directory
+    Mkdir,
+    Upload,
+    Skip,
+    Delete,
+}
+
+/// One planned step of a sync
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncAction {
+    pub action: SyncActionKind,
+    /// Path relative to the synced directories, '/'-separated
+    pub path: String,
+    pub is_directory: bool,
+    /// Bytes to upload (0 for everything but uploads)
+    pub size: u64,
+    pub reason: String,
+}
+
+impl SyncAction {
+    fn new(action: SyncActionKind, path: &str, entry: &SyncEntry, reason: &str) -> Self {
+        Self {
+            action,
+            path: path.to_string(),
+            is_directory: entry.is_directory,
+            size: if action == SyncActionKind::Upload { entry.size } else { 0 },
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Compare trees and decide what to do with each path
+///
+/// A file is uploaded when it is missing remotely, its size differs, or the
+/// local copy was modified after the remote one (mtime is ignored when the
+/// server doesn't report it in a comparable form). Directories come before
+/// their contents; deletions (only with `delete`) come last, deepest first.
+/// Skipped entries on either side are reported as skips and left alone, and
+/// nothing under a locally skipped path (e.g. a symlinked directory) is deleted.
+pub fn plan_sync(local: &SyncTree, remote: &SyncTree, delete: bool) -> Vec<SyncAction> {
+    let mut actions = Vec::new();
+
+    for (path, entry) in local {
+        if let Some(reason) = &entry.skipped {
+            actions.push(SyncAction::new(SyncActionKind::Skip, path, entry, reason));
+            continue;
+        }
+        let action = match remote.get(path) {
+            Some(SyncEntry { skipped: Some(reason), .. }) => {
+                SyncAction::new(SyncActionKind::Skip, path, entry, &format!("remote: {}", reason))
+            }
+            Some(existing) if existing.is_directory != entry.is_directory => SyncAction::new(
+                SyncActionKind::Skip,
+                path,
+                entry,
+                "remote is a different type",
+            ),
+            Some(_) if entry.is_directory => SyncAction::new(SyncActionKind::Skip, path, entry, "exists"),
+            None if entry.is_directory => SyncAction::new(SyncActionKind::Mkdir, path, entry, "new"),
+            None => SyncAction::new(SyncActionKind::Upload, path, entry, "new"),
+            Some(existing) if existing.size != entry.size => {
+                SyncAction::new(SyncActionKind::Upload, path, entry, "size changed")
+            }
+            Some(existing) => match (entry.mtime, existing.mtime) {
+                (Some(local_mtime), Some(remote_mtime)) if local_mtime > remote_mtime => {
+                    SyncAction::new(SyncActionKind::Upload, path, entry, "modified")
+                }
+                _ => SyncAction::new(SyncActionKind::Skip, path, entry, "unchanged"),
+            },
+        };
+        actions.push(action);
+    }
+
+    for (path, entry) in remote {
+        if let (Some(reason), false) = (&entry.skipped, local.contains_key(path)) {
+            actions.push(SyncAction::new(SyncActionKind::Skip, path, entry, &format!("remote: {}", reason)));
+        }
+    }
+
+    if delete {
+        // Reverse order puts "dir/file" before "dir", so directories are empty when removed
+        for (path, entry) in remote.iter().rev() {
+            if entry.skipped.is_none() && !local.contains_key(path) && !under_skipped(local, path) {
+                actions.push(SyncAction::new(SyncActionKind::Delete, path, entry, "not present locally"));
+            }
+        }
+    }
+
+    actions
+}
+
+/// Whether an ancestor directory of `path` was skipped in `tree`
+fn under_skipped(tree: &SyncTree, path: &str) -> bool {
+    path.match_indices('/')
+        .any(|(idx, _)| tree.get(&path[..idx]).is_some_and(|entry| entry.skipped.is_some()))
+}
+
+/// Snapshot a local directory tree (symlinks are skipped, like rsync without -l)
+/// Skipped symlinks stay in the tree so the remote side of them isn't deleted
+pub async fn scan_local_tree(root: &Path) -> io::Result<SyncTree> {
+    let mut tree = SyncTree::new();
+    scan_local_dir(root, "", &mut tree).await?;
+    Ok(tree)
+}
+
+async fn scan_local_dir(dir: &Path, prefix: &str, tree: &mut SyncTree) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let metadata = fs::symlink_metadata(entry.path()).await?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let relative = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+
+        if metadata.file_type().is_symlink() {
+            log::info!("[Sync] Skipping symlink {}", relative);
+            tree.insert(relative, SyncEntry::skipped("symlink"));
+            continue;
+        }
+
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        tree.insert(
+            relative.clone(),
+            SyncEntry {
+                is_directory: metadata.is_dir(),
+                size: if metadata.is_dir() { 0 } else { metadata.len() },
+                mtime,
+                skipped: None,
+            },
+        );
+
+        if metadata.is_dir() {
+            Box::pin(scan_local_dir(&entry.path(), &relative, tree)).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(size: u64, mtime: u64) -> SyncEntry {
+        SyncEntry { is_directory: false, size, mtime: Some(mtime), skipped: None }
+    }
+
+    fn dir() -> SyncEntry {
+        SyncEntry { is_directory: true, size: 0, mtime: None, skipped: None }
+    }
+
+    #[test]
+    fn test_plan_uploads_changed_and_deletes_extra() {
+        let local = SyncTree::from([
+            ("assets".to_string(), dir()),
+            ("assets/app.js".to_string(), file(10, 100)),
+            ("index.html".to_string(), file(5, 200)),
+            ("same.txt".to_string(), file(3, 50)),
+        ]);
+        let remote = SyncTree::from([
+            ("index.html".to_string(), file(5, 150)),
+            ("same.txt".to_string(), file(3, 60)),
+            ("old".to_string(), dir()),
+            ("old/stale.js".to_string(), file(1, 10)),
+        ]);
+
+        let plan: Vec<_> = plan_sync(&local, &remote, true)
+            .into_iter()
+            .map(|a| (a.action, a.path))
+            .collect();
+        assert_eq!(
+            plan,
+            vec![
+                (SyncActionKind::Mkdir, "assets".to_string()),
+                (SyncActionKind::Upload, "assets/app.js".to_string()),
+                (SyncActionKind::Upload, "index.html".to_string()),
+                (SyncActionKind::Skip, "same.txt".to_string()),
+                (SyncActionKind::Delete, "old/stale.js".to_string()),
+                (SyncActionKind::Delete, "old".to_string()),
+            ]
+        );
+
+        assert!(plan_sync(&local, &remote, false)
+            .iter()
+            .all(|a| a.action != SyncActionKind::Delete));
+    }
+
+    #[test]
+    fn test_plan_leaves_unreadable_remote_entries_alone() {
+        let local = SyncTree::from([("locked.txt".to_string(), file(3, 100))]);
+        let remote = SyncTree::from([
+            ("locked.txt".to_string(), SyncEntry::skipped("Permission denied")),
+            ("other".to_string(), SyncEntry::skipped("Permission denied")),
+        ]);
+
+        let plan: Vec<_> = plan_sync(&local, &remote, true)
+            .into_iter()
+            .map(|a| (a.action, a.path))
+            .collect();
+        assert_eq!(
+            plan,
+            vec![
+                (SyncActionKind::Skip, "locked.txt".to_string()),
+                (SyncActionKind::Skip, "other".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_keeps_remote_side_of_local_symlinks() {
+        let local = SyncTree::from([
+            ("linked".to_string(), SyncEntry::skipped("symlink")),
+            ("gone.txt".to_string(), file(1, 10)),
+        ]);
+        let remote = SyncTree::from([
+            ("linked".to_string(), dir()),
+            ("linked/data.bin".to_string(), file(9, 10)),
+            ("linkedness.txt".to_string(), file(2, 10)),
+        ]);
+
+        let plan: Vec<_> = plan_sync(&local, &remote, true)
+            .into_iter()
+            .map(|a| (a.action, a.path))
+            .collect();
+        assert_eq!(
+            plan,
+            vec![
+                (SyncActionKind::Upload, "gone.txt".to_string()),
+                (SyncActionKind::Skip, "linked".to_string()),
+                (SyncActionKind::Delete, "linkedness.txt".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_local_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/a.txt"), b"hello").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path().join("sub"), dir.path().join("link")).unwrap();
+
+        let tree = scan_local_tree(dir.path()).await.unwrap();
+        assert!(tree["sub"].is_directory);
+        assert_eq!(tree["sub/a.txt"].size, 5);
+        assert!(tree["sub/a.txt"].mtime.is_some());
+        #[cfg(unix)]
+        {
+            assert_eq!(tree["link"].skipped.as_deref(), Some("symlink"));
+            assert!(!tree.contains_key("link/a.txt"));
+        }
+    }
+}
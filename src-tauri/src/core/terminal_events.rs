@@ -7,44 +7,65 @@ pub struct TerminalExitEvent {
     pub exit_code: i32,
     /// Reason for exit
     pub reason: Option<String>,
+    /// The connection dropped (not a logout or user close), so reconnect_session can restore it
+    #[serde(default)]
+    pub reconnectable: bool,
 }
 
 impl TerminalExitEvent {
     /// Create a new terminal exit event
     pub fn new(exit_code: i32, reason: Option<String>) -> Self {
-        Self { exit_code, reason }
+        Self { exit_code, reason, reconnectable: false }
+    }
+
+    /// Exit caused by the transport failing rather than the session ending
+    fn lost(reason: String) -> Self {
+        Self { exit_code: 1, reason: Some(reason), reconnectable: true }
     }
 
     /// Create exit event for user-closed connection
     pub fn user_closed() -> Self {
-        Self {
-            exit_code: 0,
-            reason: Some("user-closed".to_string()),
-        }
+        Self::new(0, Some("user-closed".to_string()))
+    }
+
+    /// Create exit event for a remote process that exited on its own (e.g. `exit 3`)
+    pub fn process_exited(exit_code: i32) -> Self {
+        Self::new(exit_code, Some("process-exited".to_string()))
+    }
+
+    /// Create exit event for a remote process killed by a signal ("process-exited:signal:TERM")
+    /// Exit code follows the shell convention of 128 + signal number
+    pub fn process_signaled(signal: &str, exit_code: i32) -> Self {
+        Self::new(exit_code, Some(format!("process-exited:signal:{}", signal)))
     }
 
     /// Create exit event for connection lost
     pub fn connection_lost() -> Self {
-        Self {
-            exit_code: 1,
-            reason: Some("connection-lost".to_string()),
-        }
+        Self::lost("connection-lost".to_string())
     }
 
     /// Create exit event for server disconnect
     pub fn server_disconnect(message: Option<String>) -> Self {
-        Self {
-            exit_code: 1,
-            reason: Some(format!("server-disconnect:{}", message.unwrap_or_default())),
-        }
+        Self::lost(format!("server-disconnect:{}", message.unwrap_or_default()))
     }
 
     /// Create exit event for connection error
     pub fn connection_error(message: String) -> Self {
-        Self {
-            exit_code: 1,
-            reason: Some(format!("connection-error:{}", message)),
-        }
+        Self::lost(format!("connection-error:{}", message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_dropped_connections_are_reconnectable() {
+        assert!(TerminalExitEvent::connection_lost().reconnectable);
+        assert!(TerminalExitEvent::connection_error("reset".to_string()).reconnectable);
+        assert!(!TerminalExitEvent::user_closed().reconnectable);
+        assert!(!TerminalExitEvent::process_exited(0).reconnectable);
+        assert!(!TerminalExitEvent::process_signaled("TERM", 143).reconnectable);
     }
 }
 
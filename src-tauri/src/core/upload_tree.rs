@@ -0,0 +1,135 @@
+//! Local directory walk for recursive uploads
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// What a directory upload does with symlinks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymlinkPolicy {
+    /// Leave symlinks out of the upload
+    #[default]
+    Skip,
+    /// Upload what the link points to (loops are reported, not followed)
+    Follow,
+}
+
+/// A file or directory to recreate remotely
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadEntry {
+    pub local_path: PathBuf,
+    /// Path relative to the uploaded directory, '/'-separated
+    pub relative: String,
+    pub is_directory: bool,
+    pub size: u64,
+}
+
+/// Everything under a directory, parents before their contents
+#[derive(Debug, Clone, Default)]
+pub struct UploadTree {
+    pub entries: Vec<UploadEntry>,
+    /// Symlinks left out under SymlinkPolicy::Skip (relative paths)
+    pub skipped_symlinks: Vec<String>,
+    /// Entries that couldn't be read: (path, error)
+    pub errors: Vec<(String, String)>,
+}
+
+impl UploadTree {
+    pub fn file_count(&self) -> usize {
+        self.entries.iter().filter(|e| !e.is_directory).count()
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.entries.iter().map(|e| e.size).sum()
+    }
+}
+
+/// Walk `root` (blocking - run it on a blocking thread for large trees)
+pub fn walk_upload_tree(root: &Path, symlinks: SymlinkPolicy) -> UploadTree {
+    let mut tree = UploadTree::default();
+    let walker = WalkDir::new(root)
+        .min_depth(1)
+        .follow_links(symlinks == SymlinkPolicy::Follow)
+        .sort_by_file_name();
+
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let path = e
+                    .path()
+                    .map(|p| relative_path(root, p))
+                    .unwrap_or_default();
+                tree.errors.push((path, e.to_string()));
+                continue;
+            }
+        };
+
+        let relative = relative_path(root, entry.path());
+        // Without follow_links, a symlink is reported as itself
+        if entry.path_is_symlink() && symlinks == SymlinkPolicy::Skip {
+            tree.skipped_symlinks.push(relative);
+            continue;
+        }
+
+        let is_directory = entry.file_type().is_dir();
+        let size = if is_directory {
+            0
+        } else {
+            match entry.metadata() {
+                Ok(metadata) => metadata.len(),
+                Err(e) => {
+                    tree.errors.push((relative, e.to_string()));
+                    continue;
+                }
+            }
+        };
+        tree.entries.push(UploadEntry {
+            local_path: entry.path().to_path_buf(),
+            relative,
+            is_directory,
+            size,
+        });
+    }
+
+    tree
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walk_orders_parents_first_and_skips_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        std::fs::write(dir.path().join("src/nested/a.txt"), b"abc").unwrap();
+        std::fs::write(dir.path().join("readme.md"), b"hello").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path().join("readme.md"), dir.path().join("link.md")).unwrap();
+
+        let tree = walk_upload_tree(dir.path(), SymlinkPolicy::Skip);
+        let paths: Vec<_> = tree.entries.iter().map(|e| e.relative.as_str()).collect();
+        assert_eq!(paths, vec!["readme.md", "src", "src/nested", "src/nested/a.txt"]);
+        assert_eq!(tree.file_count(), 2);
+        assert_eq!(tree.total_bytes(), 8);
+
+        #[cfg(unix)]
+        {
+            assert_eq!(tree.skipped_symlinks, vec!["link.md".to_string()]);
+            let followed = walk_upload_tree(dir.path(), SymlinkPolicy::Follow);
+            let link = followed.entries.iter().find(|e| e.relative == "link.md").unwrap();
+            assert_eq!(link.size, 5);
+        }
+    }
+}
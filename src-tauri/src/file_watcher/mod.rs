@@ -1,72 +1,134 @@
+use notify::event::ModifyKind;
 use notify::{Watcher, RecursiveMode, Result as NotifyResult, Event, EventKind};
-use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
+use serde::Serialize;
+use std::sync::{mpsc, Arc, Mutex};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use std::time::Instant;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 use log::{info, error};
 
-const DEBOUNCE_MS: u128 = 500; // Debounce time in milliseconds
+pub mod remote;
+pub mod tail;
+
+pub use remote::RemoteFileWatcherManager;
+pub use tail::RemoteTailManager;
+
+const DEFAULT_DEBOUNCE_MS: u64 = 500; // Quiet time before a burst of events is reported
+
+/// What happened to a watched file, decided once its events settle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChangeKind {
+    /// Contents (or metadata) changed in place
+    Modified,
+    /// The file was recreated, e.g. an editor renamed a fresh copy over it
+    Created,
+    /// The file is gone
+    Removed,
+}
+
+/// Payload of the "file-changed" event
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChange {
+    pub path: String,
+    pub kind: FileChangeKind,
+}
 
 pub struct FileWatcherManager {
     watchers: Arc<Mutex<HashMap<String, notify::RecommendedWatcher>>>,
-    last_event: Arc<Mutex<HashMap<String, Instant>>>,
+    /// Temp files created by the backend, deleted when their watch ends
+    temp_files: Mutex<HashSet<String>>,
 }
 
 impl FileWatcherManager {
     pub fn new() -> Self {
         Self {
             watchers: Arc::new(Mutex::new(HashMap::new())),
-            last_event: Arc::new(Mutex::new(HashMap::new())),
+            temp_files: Mutex::new(HashSet::new()),
         }
     }
 
-    pub fn watch_file(&self, path: String, app_handle: AppHandle) -> Result<(), String> {
-        let path_buf = PathBuf::from(&path);
-        
-        if !path_buf.exists() {
-            return Err(format!("File does not exist: {}", path));
-        }
+    /// Emit "file-changed" once a file's events have been quiet for `debounce_ms` (default 500)
+    pub fn watch_file(&self, path: String, debounce_ms: Option<u64>, app_handle: AppHandle) -> Result<(), String> {
+        let debounce = Duration::from_millis(debounce_ms.unwrap_or(DEFAULT_DEBOUNCE_MS));
+        self.watch_with(path, debounce, move |change| {
+            if let Err(e) = app_handle.emit("file-changed", change) {
+                error!("[FileWatcher] Failed to emit event: {}", e);
+            }
+        })
+    }
 
-        let app_handle_clone = app_handle.clone();
-        let path_clone = path.clone();
-        let last_event = Arc::clone(&self.last_event);
+    /// Watch a temp file owned by the backend; it is deleted when unwatched
+    pub fn watch_temp_file<F>(&self, path: String, on_change: F) -> Result<(), String>
+    where
+        F: Fn(&FileChange) + Send + 'static,
+    {
+        self.watch_with(path.clone(), Duration::from_millis(DEFAULT_DEBOUNCE_MS), on_change)?;
+        self.temp_files.lock().unwrap().insert(path);
+        Ok(())
+    }
 
+    /// Watch a file, calling `on_change` once its events have been quiet for `debounce`
+    /// The parent directory is watched so the watch survives the file being replaced by rename
+    fn watch_with<F>(&self, path: String, debounce: Duration, on_change: F) -> Result<(), String>
+    where
+        F: Fn(&FileChange) + Send + 'static,
+    {
+        let target = PathBuf::from(&path)
+            .canonicalize()
+            .map_err(|_| format!("File does not exist: {}", path))?;
+        let dir = target
+            .parent()
+            .ok_or_else(|| format!("Cannot watch: {}", path))?
+            .to_path_buf();
+
+        // Match on the file name: events in the watched directory may not carry canonical paths
+        let name = target
+            .file_name()
+            .ok_or_else(|| format!("Cannot watch: {}", path))?
+            .to_os_string();
+
+        // `true` when the event replaced or removed the file rather than changing it in place
+        let (tx, rx) = mpsc::channel::<bool>();
         let mut watcher = notify::recommended_watcher(move |res: NotifyResult<Event>| {
             match res {
-                Ok(event) => {
-                    // Only emit on modify events (save)
-                    if matches!(event.kind, EventKind::Modify(_)) {
-                        // Debounce: check if we recently emitted for this file
-                        let mut last_events = last_event.lock().unwrap();
-                        let now = Instant::now();
-                        
-                        if let Some(last) = last_events.get(&path_clone) {
-                            if now.duration_since(*last).as_millis() < DEBOUNCE_MS {
-                                // Skip - too soon after last event
-                                return;
-                            }
-                        }
-                        
-                        // Update last event time
-                        last_events.insert(path_clone.clone(), now);
-                        drop(last_events); // Release lock before emit
-                        
-                        info!("[FileWatcher] File modified: {:?}", path_clone);
-                        if let Err(e) = app_handle_clone.emit("file-changed", &path_clone) {
-                            error!("[FileWatcher] Failed to emit event: {}", e);
-                        }
-                    }
+                Ok(event) if event.paths.iter().any(|p| p.file_name() == Some(name.as_os_str())) => {
+                    let replaced = match event.kind {
+                        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_)) => true,
+                        EventKind::Modify(_) | EventKind::Any => false,
+                        EventKind::Access(_) | EventKind::Other => return,
+                    };
+                    let _ = tx.send(replaced);
                 }
+                Ok(_) => {}
                 Err(e) => error!("[FileWatcher] Watch error: {:?}", e),
             }
         })
         .map_err(|e| format!("Failed to create watcher: {}", e))?;
 
         watcher
-            .watch(&path_buf, RecursiveMode::NonRecursive)
+            .watch(&dir, RecursiveMode::NonRecursive)
             .map_err(|e| format!("Failed to watch file: {}", e))?;
 
+        // Ends when the watcher (and with it the sender) is dropped
+        let watched_path = path.clone();
+        std::thread::spawn(move || {
+            while let Ok(mut replaced) = rx.recv() {
+                // Wait for the burst to end (an editor save can be remove + create + modify)
+                loop {
+                    match rx.recv_timeout(debounce) {
+                        Ok(r) => replaced |= r,
+                        Err(mpsc::RecvTimeoutError::Timeout) => break,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                let kind = settled_kind(replaced, target.exists());
+                info!("[FileWatcher] File {:?}: {:?}", kind, watched_path);
+                on_change(&FileChange { path: watched_path.clone(), kind });
+            }
+        });
+
         let mut watchers = self.watchers.lock().unwrap();
         watchers.insert(path.clone(), watcher);
 
@@ -76,13 +138,15 @@ impl FileWatcherManager {
 
     pub fn unwatch_file(&self, path: &str) -> Result<(), String> {
         let mut watchers = self.watchers.lock().unwrap();
-        
-        if let Some(mut watcher) = watchers.remove(path) {
-            let path_buf = PathBuf::from(path);
-            watcher
-                .unwatch(&path_buf)
-                .map_err(|e| format!("Failed to unwatch file: {}", e))?;
-            
+
+        if let Some(watcher) = watchers.remove(path) {
+            // Stop the watch before deleting a temp file so its removal isn't reported
+            drop(watcher);
+
+            if self.temp_files.lock().unwrap().remove(path) {
+                Self::remove_temp_file(path);
+            }
+
             info!("[FileWatcher] Stopped watching: {}", path);
             Ok(())
         } else {
@@ -93,7 +157,82 @@ impl FileWatcherManager {
     pub fn unwatch_all(&self) {
         let mut watchers = self.watchers.lock().unwrap();
         watchers.clear();
+        for path in self.temp_files.lock().unwrap().drain() {
+            Self::remove_temp_file(&path);
+        }
         info!("[FileWatcher] Stopped watching all files");
     }
+
+    /// Delete a backend-owned temp file and its (per-file) temp directory
+    fn remove_temp_file(path: &str) {
+        let path_buf = PathBuf::from(path);
+        if let Err(e) = std::fs::remove_file(&path_buf) {
+            error!("[FileWatcher] Failed to remove temp file {}: {}", path, e);
+        }
+        if let Some(dir) = path_buf.parent() {
+            let _ = std::fs::remove_dir(dir);
+        }
+        info!("[FileWatcher] Removed temp file: {}", path);
+    }
+}
+
+/// Classify a settled burst by where the file ended up
+fn settled_kind(replaced: bool, exists: bool) -> FileChangeKind {
+    match (exists, replaced) {
+        (false, _) => FileChangeKind::Removed,
+        (true, true) => FileChangeKind::Created,
+        (true, false) => FileChangeKind::Modified,
+    }
+}
+
+impl Drop for FileWatcherManager {
+    fn drop(&mut self) {
+        // Don't leave downloaded copies of remote files behind on exit
+        for path in self.temp_files.lock().unwrap().drain() {
+            Self::remove_temp_file(&path);
+        }
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next_change(rx: &mpsc::Receiver<FileChange>) -> FileChangeKind {
+        rx.recv_timeout(Duration::from_secs(5)).expect("no file-changed event").kind
+    }
+
+    #[test]
+    fn test_reports_modify_replace_and_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "one").unwrap();
+
+        let manager = FileWatcherManager::new();
+        let (tx, rx) = mpsc::channel();
+        let path = file.to_string_lossy().to_string();
+        manager
+            .watch_with(path.clone(), Duration::from_millis(100), move |change| {
+                let _ = tx.send(change.clone());
+            })
+            .unwrap();
+
+        std::fs::write(&file, "two").unwrap();
+        assert_eq!(next_change(&rx), FileChangeKind::Modified);
+
+        // Atomic save: write a temp file and rename it over the original
+        let temp = dir.path().join("notes.txt.swp");
+        std::fs::write(&temp, "three").unwrap();
+        std::fs::rename(&temp, &file).unwrap();
+        assert_eq!(next_change(&rx), FileChangeKind::Created);
+
+        // The watch survived the replacement
+        std::fs::write(&file, "four").unwrap();
+        assert_eq!(next_change(&rx), FileChangeKind::Modified);
+
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(next_change(&rx), FileChangeKind::Removed);
+
+        manager.unwatch_file(&path).unwrap();
+    }
+}
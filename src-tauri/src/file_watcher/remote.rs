@@ -0,0 +1,183 @@
+//! Change detection for remote files by polling stat (SFTP/FTP have no notifications)
+
+use crate::managers::FileTransferManager;
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+
+const DEFAULT_INTERVAL_SECS: u64 = 5;
+/// Longest wait between polls while stat keeps failing
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Payload of the "remote-file-changed" event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteFileChange {
+    pub session_id: String,
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<String>,
+}
+
+/// What a poll compares against the previous one
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Snapshot {
+    size: u64,
+    modified: Option<String>,
+}
+
+struct RemoteWatch {
+    task: JoinHandle<()>,
+    /// Set after our own save so the next poll adopts the new state without reporting it
+    rebaseline: Arc<AtomicBool>,
+}
+
+/// Polls watched remote files, keyed by (session_id, path)
+pub struct RemoteFileWatcherManager {
+    watches: Mutex<HashMap<(String, String), RemoteWatch>>,
+}
+
+impl RemoteFileWatcherManager {
+    pub fn new() -> Self {
+        Self {
+            watches: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Emit "remote-file-changed" when the file's size or mtime changes (polled every
+    /// `interval_secs`, default 5); watching the same file again replaces the old watch
+    pub fn watch_remote_file(
+        &self,
+        session_id: String,
+        path: String,
+        interval_secs: Option<u64>,
+        app_handle: AppHandle,
+    ) {
+        let interval = Duration::from_secs(interval_secs.unwrap_or(DEFAULT_INTERVAL_SECS).max(1));
+        let rebaseline = Arc::new(AtomicBool::new(false));
+        let task = tauri::async_runtime::spawn(poll_remote_file(
+            session_id.clone(),
+            path.clone(),
+            interval,
+            rebaseline.clone(),
+            app_handle,
+        ));
+
+        info!("[RemoteWatcher] Started watching {} on {} every {:?}", path, session_id, interval);
+        let previous = self
+            .watches
+            .lock()
+            .unwrap()
+            .insert((session_id, path), RemoteWatch { task, rebaseline });
+        if let Some(previous) = previous {
+            previous.task.abort();
+        }
+    }
+
+    pub fn unwatch_remote_file(&self, session_id: &str, path: &str) -> Result<(), String> {
+        let key = (session_id.to_string(), path.to_string());
+        match self.watches.lock().unwrap().remove(&key) {
+            Some(watch) => {
+                watch.task.abort();
+                info!("[RemoteWatcher] Stopped watching {} on {}", path, session_id);
+                Ok(())
+            }
+            None => Err(format!("No remote watcher found for path: {}", path)),
+        }
+    }
+
+    /// Stop every watch on a session (it is closing)
+    pub fn unwatch_session(&self, session_id: &str) {
+        self.watches.lock().unwrap().retain(|(id, _), watch| {
+            if id == session_id {
+                watch.task.abort();
+            }
+            id != session_id
+        });
+    }
+
+    /// We just wrote the file ourselves; don't report the change that causes
+    pub fn mark_saved(&self, session_id: &str, path: &str) {
+        let key = (session_id.to_string(), path.to_string());
+        if let Some(watch) = self.watches.lock().unwrap().get(&key) {
+            watch.rebaseline.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+async fn poll_remote_file(
+    session_id: String,
+    path: String,
+    interval: Duration,
+    rebaseline: Arc<AtomicBool>,
+    app_handle: AppHandle,
+) {
+    let mut baseline: Option<Snapshot> = None;
+    let mut failures = 0u32;
+
+    loop {
+        let manager = app_handle.state::<FileTransferManager>();
+        match manager.stat(&session_id, &path).await {
+            Ok(info) => {
+                if failures > 0 {
+                    info!("[RemoteWatcher] Stat of {} works again after {} failure(s)", path, failures);
+                    failures = 0;
+                }
+                let current = Snapshot { size: info.size, modified: info.modified };
+                let saved_by_us = rebaseline.swap(false, Ordering::SeqCst);
+                if matches!(&baseline, Some(previous) if *previous != current) && !saved_by_us {
+                    info!("[RemoteWatcher] Remote file changed: {}", path);
+                    let change = RemoteFileChange {
+                        session_id: session_id.clone(),
+                        path: path.clone(),
+                        size: current.size,
+                        modified: current.modified.clone(),
+                    };
+                    if let Err(e) = app_handle.emit("remote-file-changed", &change) {
+                        warn!("[RemoteWatcher] Failed to emit event: {}", e);
+                    }
+                }
+                baseline = Some(current);
+            }
+            Err(e) => {
+                // Log the first failure of a streak; the rest only at debug level
+                if failures == 0 {
+                    warn!("[RemoteWatcher] Stat of {} failed, backing off: {}", path, e);
+                } else {
+                    debug!("[RemoteWatcher] Stat of {} failed again: {}", path, e);
+                }
+                failures = failures.saturating_add(1);
+            }
+        }
+
+        tokio::time::sleep(poll_delay(interval, failures)).await;
+    }
+}
+
+/// Wait before the next poll: the interval, doubled per consecutive failure up to MAX_BACKOFF
+fn poll_delay(interval: Duration, failures: u32) -> Duration {
+    interval
+        .saturating_mul(1 << failures.min(16))
+        .min(MAX_BACKOFF.max(interval))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_delay_backs_off() {
+        let interval = Duration::from_secs(5);
+        assert_eq!(poll_delay(interval, 0), interval);
+        assert_eq!(poll_delay(interval, 1), Duration::from_secs(10));
+        assert_eq!(poll_delay(interval, 3), Duration::from_secs(40));
+        assert_eq!(poll_delay(interval, 40), MAX_BACKOFF);
+        // An interval longer than the cap is never shortened
+        assert_eq!(poll_delay(Duration::from_secs(600), 2), Duration::from_secs(600));
+    }
+}
@@ -0,0 +1,231 @@
+//! Following remote files as they grow (`tail -F` over an SSH exec channel)
+//! Unlike the polling watcher this delivers the new content itself
+
+use crate::managers::FileTransferManager;
+use log::{debug, info, warn};
+use russh::client::Msg;
+use russh::{Channel, ChannelMsg};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Lines of existing content sent when a follow starts
+const INITIAL_LINES: u32 = 10;
+/// Wait before starting tail again after it exited
+const RESTART_DELAY: Duration = Duration::from_secs(2);
+/// A line longer than this is sent without waiting for its newline
+const MAX_PENDING: usize = 64 * 1024;
+
+/// Payload of the "remote-file-tail-end:{id}" event
+#[derive(Debug, Clone, Serialize)]
+pub struct TailEnd {
+    /// Why following stopped (None when tail simply finished)
+    pub error: Option<String>,
+}
+
+struct Follow {
+    session_id: String,
+    task: JoinHandle<()>,
+}
+
+/// Running follows, keyed by follow id
+pub struct RemoteTailManager {
+    follows: Mutex<HashMap<String, Follow>>,
+}
+
+impl RemoteTailManager {
+    pub fn new() -> Self {
+        Self {
+            follows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Stream lines appended to `path` as "remote-file-tail:{id}" events (text of complete lines)
+    /// With `restart`, tail is started again when it exits (e.g. killed by log rotation tooling)
+    /// Returns the follow id; "remote-file-tail-end:{id}" is emitted when following stops on its own
+    pub fn follow(&self, session_id: String, path: String, restart: bool, app_handle: AppHandle) -> String {
+        let follow_id = uuid::Uuid::new_v4().to_string();
+        let task = tauri::async_runtime::spawn(run_follow(
+            follow_id.clone(),
+            session_id.clone(),
+            path.clone(),
+            restart,
+            app_handle,
+        ));
+
+        info!("[RemoteTail] Following {} on {} ({})", path, session_id, follow_id);
+        self.follows
+            .lock()
+            .unwrap()
+            .insert(follow_id.clone(), Follow { session_id, task });
+        follow_id
+    }
+
+    pub fn stop_follow(&self, follow_id: &str) -> Result<(), String> {
+        match self.follows.lock().unwrap().remove(follow_id) {
+            Some(follow) => {
+                // Dropping the channel closes it, which ends tail on the server
+                follow.task.abort();
+                info!("[RemoteTail] Stopped following ({})", follow_id);
+                Ok(())
+            }
+            None => Err(format!("No follow found: {}", follow_id)),
+        }
+    }
+
+    /// Stop every follow on a session (it is closing)
+    pub fn stop_session(&self, session_id: &str) {
+        self.follows.lock().unwrap().retain(|_, follow| {
+            if follow.session_id == session_id {
+                follow.task.abort();
+            }
+            follow.session_id != session_id
+        });
+    }
+}
+
+async fn run_follow(follow_id: String, session_id: String, path: String, restart: bool, app_handle: AppHandle) {
+    let data_event = format!("remote-file-tail:{}", follow_id);
+    let mut lines = INITIAL_LINES;
+
+    let error = loop {
+        let command = tail_command(&path, lines);
+        let channel = match app_handle.state::<FileTransferManager>().exec_channel(&session_id, &command).await {
+            Ok(channel) => channel,
+            Err(e) => break Some(e.to_string()),
+        };
+
+        let run = stream_tail(channel, &data_event, &app_handle).await;
+        // A tail that fails straight away (missing `tail`, no permission) would only fail again
+        if !restart || (!run.produced_output && run.exit_status != Some(0)) {
+            break run.error();
+        }
+
+        info!("[RemoteTail] tail of {} exited ({:?}), restarting", path, run.exit_status);
+        // Lines written meanwhile are sent by the restarted tail; older ones were already delivered
+        lines = 0;
+        tokio::time::sleep(RESTART_DELAY).await;
+    };
+
+    match &error {
+        Some(e) => warn!("[RemoteTail] Stopped following {}: {}", path, e),
+        None => info!("[RemoteTail] Stopped following {}", path),
+    }
+    app_handle.state::<RemoteTailManager>().follows.lock().unwrap().remove(&follow_id);
+    if let Err(e) = app_handle.emit(&format!("remote-file-tail-end:{}", follow_id), TailEnd { error }) {
+        warn!("[RemoteTail] Failed to emit end event: {}", e);
+    }
+}
+
+/// How one tail process ended
+struct TailRun {
+    produced_output: bool,
+    exit_status: Option<u32>,
+    /// Last line tail wrote to stderr
+    stderr: Option<String>,
+}
+
+impl TailRun {
+    fn error(&self) -> Option<String> {
+        match (self.exit_status, &self.stderr) {
+            (Some(0), _) => None,
+            (_, Some(line)) => Some(line.clone()),
+            (Some(status), None) => Some(format!("tail exited with status {}", status)),
+            (None, None) => Some("Connection closed".to_string()),
+        }
+    }
+}
+
+/// Emit tail's output line by line until its channel closes
+async fn stream_tail(mut channel: Channel<Msg>, event: &str, app_handle: &AppHandle) -> TailRun {
+    let mut buffer = LineBuffer::default();
+    let mut run = TailRun { produced_output: false, exit_status: None, stderr: None };
+    let emit = |text: String| {
+        if let Err(e) = app_handle.emit(event, text) {
+            warn!("[RemoteTail] Failed to emit output: {}", e);
+        }
+    };
+
+    while let Some(msg) = channel.wait().await {
+        match msg {
+            ChannelMsg::Data { data } => {
+                run.produced_output = true;
+                if let Some(text) = buffer.push(&data) {
+                    emit(text);
+                }
+            }
+            ChannelMsg::ExtendedData { data, ext: 1 } => {
+                let text = String::from_utf8_lossy(&data);
+                if let Some(line) = text.lines().map(str::trim).rev().find(|l| !l.is_empty()) {
+                    debug!("[RemoteTail] tail: {}", line);
+                    run.stderr = Some(line.to_string());
+                }
+            }
+            ChannelMsg::ExitStatus { exit_status } => run.exit_status = Some(exit_status),
+            _ => {}
+        }
+    }
+    if let Some(text) = buffer.take_rest() {
+        emit(text);
+    }
+    run
+}
+
+/// `tail -F` keeps following across rotation (reopens the file by name)
+fn tail_command(path: &str, lines: u32) -> String {
+    format!("tail -n {} -F -- '{}'", lines, path.replace('\'', "'\\''"))
+}
+
+/// Holds back a partial last line until its newline arrives
+#[derive(Default)]
+struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Complete lines received so far (with their newlines), None while only a partial line is buffered
+    fn push(&mut self, data: &[u8]) -> Option<String> {
+        self.pending.extend_from_slice(data);
+        if self.pending.len() > MAX_PENDING {
+            return self.take_rest();
+        }
+        let end = self.pending.iter().rposition(|&b| b == b'\n')? + 1;
+        let lines: Vec<u8> = self.pending.drain(..end).collect();
+        Some(String::from_utf8_lossy(&lines).into_owned())
+    }
+
+    fn take_rest(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let rest = std::mem::take(&mut self.pending);
+        Some(String::from_utf8_lossy(&rest).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_buffer_waits_for_newlines() {
+        let mut buffer = LineBuffer::default();
+        assert_eq!(buffer.push(b"GET /index"), None);
+        assert_eq!(buffer.push(b".html 200\nGET /fav").as_deref(), Some("GET /index.html 200\n"));
+        assert_eq!(buffer.push(b"icon.ico 404\n\xC3").as_deref(), Some("GET /favicon.ico 404\n"));
+        // A UTF-8 character split across reads is decoded once complete
+        assert_eq!(buffer.push(b"\xA9t\xC3\xA9\n").as_deref(), Some("été\n"));
+        assert_eq!(buffer.take_rest(), None);
+
+        assert_eq!(buffer.push(b"no newline yet"), None);
+        assert_eq!(buffer.take_rest().as_deref(), Some("no newline yet"));
+    }
+
+    #[test]
+    fn test_tail_command_quotes_path() {
+        assert_eq!(tail_command("/var/log/app's.log", 10), "tail -n 10 -F -- '/var/log/app'\\''s.log'");
+    }
+}
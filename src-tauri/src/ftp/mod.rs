@@ -2,5 +2,6 @@
 /// Provides file operations using suppaftp
 
 pub mod session;
+pub mod tls;
 
 pub use session::FtpSession;
@@ -1,13 +1,18 @@
 use async_trait::async_trait;
-use futures_lite::io::AsyncReadExt;
+use futures_lite::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use suppaftp::{AsyncFtpStream, AsyncRustlsFtpStream, AsyncRustlsConnector};
-use suppaftp::types::FileType;
-use std::sync::Arc;
+use suppaftp::types::{FileType, Mode};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, OnceLock};
 use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
 use tokio::sync::Mutex;
 
-use crate::core::error::ConnectionError;
-use crate::core::session::{FileInfo, FileTransferSession};
+use crate::core::error::{ConnectionError, ErrorCode, ErrorStatus};
+use crate::core::file_content::{self, FileRange};
+use crate::core::rate_limit::RateLimiter;
+use crate::core::session::{DirectoryListing, SessionCapabilities, FileInfo, FileTransferSession, ListingWarning, ProgressCallback};
+use crate::ftp::tls::{self, FtpTlsConfig};
 use crate::ssh::config::ConnectionType;
 
 /// FTP/FTPS session using suppaftp
@@ -21,8 +26,89 @@ pub struct FtpSession {
     is_ftps: bool,
     home_directory: Option<String>,
     home_resolved_for_root: AtomicBool,
+    /// Whether the server answers MLSD (learned from the first listing)
+    mlsd_supported: OnceLock<bool>,
 }
 
+/// How data connections (listings and transfers) are opened
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DataConnectionMode {
+    /// PASV: the client connects to the server; works from behind NAT
+    #[default]
+    Passive,
+    /// PORT: the server connects back to the client
+    ///
+    /// The advertised address is the local address of the control connection, so
+    /// behind NAT it is a private address the server can't reach unless the router
+    /// rewrites PORT commands (FTP ALG). `bind_address` picks the local interface
+    /// the control connection is opened from (and so what gets advertised); it must
+    /// be an address of this machine, not a public NAT address. IPv4 only.
+    Active { bind_address: Option<IpAddr> },
+}
+
+impl DataConnectionMode {
+    /// Build from the session config's `passive` flag and optional bind-address hint
+    pub fn from_options(passive: bool, bind_address: Option<&str>) -> Result<Self, ConnectionError> {
+        let bind_address = bind_address.map(str::trim).filter(|a| !a.is_empty());
+        if passive {
+            if bind_address.is_some() {
+                log::warn!("[FTP] Ignoring active-mode bind address in passive mode");
+            }
+            return Ok(Self::Passive);
+        }
+        let bind_address = bind_address
+            .map(|addr| addr.parse::<IpAddr>()
+                .map_err(|_| ConnectionError::FtpError(format!("Invalid active-mode bind address: {}", addr))))
+            .transpose()?;
+        if bind_address.is_some_and(|ip| ip.is_ipv6()) {
+            return Err(ConnectionError::FtpError(
+                "Active mode needs an IPv4 bind address (PORT)".to_string(),
+            ));
+        }
+        Ok(Self::Active { bind_address })
+    }
+
+    fn mode(&self) -> Mode {
+        match self {
+            Self::Passive => Mode::Passive,
+            Self::Active { .. } => Mode::Active,
+        }
+    }
+
+    fn bind_address(&self) -> Option<IpAddr> {
+        match self {
+            Self::Passive => None,
+            Self::Active { bind_address } => *bind_address,
+        }
+    }
+}
+
+/// Open the control connection from a specific local address
+async fn connect_from(hostname: &str, port: u16, local: IpAddr) -> Result<std::net::TcpStream, ConnectionError> {
+    let remote = tokio::net::lookup_host((hostname, port))
+        .await
+        .map_err(|e| ConnectionError::ConnectionFailed(format!("Failed to resolve {}: {}", hostname, e)))?
+        .find(|addr| addr.is_ipv4() == local.is_ipv4())
+        .ok_or_else(|| ConnectionError::ConnectionFailed(format!("{} has no address reachable from {}", hostname, local)))?;
+    let socket = if local.is_ipv4() {
+        tokio::net::TcpSocket::new_v4()
+    } else {
+        tokio::net::TcpSocket::new_v6()
+    }
+    .map_err(|e| ConnectionError::IoError(e.to_string()))?;
+    socket
+        .bind(SocketAddr::new(local, 0))
+        .map_err(|e| ConnectionError::ConnectionFailed(format!("Cannot bind to {}: {}", local, e)))?;
+    let stream = socket
+        .connect(remote)
+        .await
+        .map_err(|e| ConnectionError::ConnectionFailed(format!("Failed to connect FTP: {}", e)))?;
+    stream.into_std().map_err(|e| ConnectionError::IoError(e.to_string()))
+}
+
+/// Parses one listing line relative to the listed directory
+type LineParser = fn(&str, &str) -> Result<Option<FileInfo>, String>;
+
 /// Macro to execute an operation on either plain or secure FTP stream
 macro_rules! ftp_op {
     ($self:expr, $method:ident $(, $arg:expr)*) => {{
@@ -41,29 +127,36 @@ macro_rules! ftp_op {
 impl FtpSession {
     /// Create new FTP or FTPS session
     ///
-    /// For FTPS, uses explicit TLS (AUTH TLS) - connects plain then upgrades to TLS.
+    /// For FTPS (`tls` set), uses explicit TLS (AUTH TLS) - connects plain then upgrades to TLS.
+    /// The certificate is checked according to the TLS config's security policy.
+    /// `data_mode` is applied before any data connection is opened.
     pub async fn new(
         id: String,
         hostname: &str,
         port: u16,
         username: &str,
         password: &str,
-        use_tls: bool,
+        tls: Option<FtpTlsConfig>,
+        data_mode: DataConnectionMode,
     ) -> Result<Self, ConnectionError> {
         let addr = format!("{}:{}", hostname, port);
+        let use_tls = tls.is_some();
 
-        let (ftp_plain, ftp_secure, home_directory) = if use_tls {
+        let (ftp_plain, ftp_secure, home_directory) = if let Some(tls) = tls {
             // FTPS: Connect and upgrade to TLS using explicit FTPS (AUTH TLS)
             log::info!("[FTPS] Connecting to {} with TLS...", addr);
 
             // Connect with the secure stream type (AsyncRustlsFtpStream) so into_secure works
             // The type allows into_secure to accept AsyncRustlsConnector
-            let ftp = AsyncRustlsFtpStream::connect(&addr)
-                .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to connect FTPS: {}", e)))?;
+            let mut ftp = match data_mode.bind_address() {
+                Some(local) => AsyncRustlsFtpStream::connect_with_stream(connect_from(hostname, port, local).await?.into()).await,
+                None => AsyncRustlsFtpStream::connect(&addr).await,
+            }
+            .map_err(|e| ConnectionError::ftp("Failed to connect FTPS", &e))?;
+            ftp.set_mode(data_mode.mode());
 
             // Create TLS connector chain: ClientConfig -> futures_rustls::TlsConnector -> AsyncRustlsConnector
-            let tls_config = Self::create_tls_config();
+            let tls_config = tls::client_config(&tls, hostname, port)?;
             let rustls_connector = futures_rustls::TlsConnector::from(Arc::new(tls_config));
             let tls_connector = AsyncRustlsConnector::from(rustls_connector);
 
@@ -71,7 +164,7 @@ impl FtpSession {
             let mut secure_ftp = ftp
                 .into_secure(tls_connector, hostname)
                 .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to establish TLS: {}", e)))?;
+                .map_err(|e| ConnectionError::ftp("Failed to establish TLS", &e))?;
 
             log::info!("[FTPS] TLS connection established");
 
@@ -83,7 +176,7 @@ impl FtpSession {
             // Set binary mode
             secure_ftp.transfer_type(FileType::Binary)
                 .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to set binary mode: {}", e)))?;
+                .map_err(|e| ConnectionError::ftp("Failed to set binary mode", &e))?;
 
             // Get home directory
             let home = match secure_ftp.pwd().await {
@@ -102,9 +195,12 @@ impl FtpSession {
         } else {
             // Plain FTP connection
             log::info!("[FTP] Connecting to {}...", addr);
-            let mut ftp = AsyncFtpStream::connect(&addr)
-                .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to connect FTP: {}", e)))?;
+            let mut ftp = match data_mode.bind_address() {
+                Some(local) => AsyncFtpStream::connect_with_stream(connect_from(hostname, port, local).await?.into()).await,
+                None => AsyncFtpStream::connect(&addr).await,
+            }
+            .map_err(|e| ConnectionError::ftp("Failed to connect FTP", &e))?;
+            ftp.set_mode(data_mode.mode());
 
             // Login
             ftp.login(username, password)
@@ -114,7 +210,7 @@ impl FtpSession {
             // Set binary mode
             ftp.transfer_type(FileType::Binary)
                 .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to set binary mode: {}", e)))?;
+                .map_err(|e| ConnectionError::ftp("Failed to set binary mode", &e))?;
 
             // Get home directory
             let home = match ftp.pwd().await {
@@ -139,72 +235,27 @@ impl FtpSession {
             is_ftps: use_tls,
             home_directory,
             home_resolved_for_root: AtomicBool::new(false),
+            mlsd_supported: OnceLock::new(),
         })
     }
 
-    /// Create TLS configuration for FTPS
-    ///
-    /// This config accepts all certificates including self-signed ones,
-    /// which is common for FTP servers.
-    fn create_tls_config() -> rustls::ClientConfig {
-        use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
-        use rustls_pki_types::{CertificateDer, ServerName, UnixTime};
-        use rustls::DigitallySignedStruct;
-
-        /// A certificate verifier that accepts all certificates
-        #[derive(Debug)]
-        struct AcceptAllCertVerifier;
-
-        impl ServerCertVerifier for AcceptAllCertVerifier {
-            fn verify_server_cert(
-                &self,
-                _end_entity: &CertificateDer<'_>,
-                _intermediates: &[CertificateDer<'_>],
-                _server_name: &ServerName<'_>,
-                _ocsp_response: &[u8],
-                _now: UnixTime,
-            ) -> Result<ServerCertVerified, rustls::Error> {
-                Ok(ServerCertVerified::assertion())
-            }
-
-            fn verify_tls12_signature(
-                &self,
-                _message: &[u8],
-                _cert: &CertificateDer<'_>,
-                _dss: &DigitallySignedStruct,
-            ) -> Result<HandshakeSignatureValid, rustls::Error> {
-                Ok(HandshakeSignatureValid::assertion())
-            }
-
-            fn verify_tls13_signature(
-                &self,
-                _message: &[u8],
-                _cert: &CertificateDer<'_>,
-                _dss: &DigitallySignedStruct,
-            ) -> Result<HandshakeSignatureValid, rustls::Error> {
-                Ok(HandshakeSignatureValid::assertion())
-            }
-
-            fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
-                vec![
-                    rustls::SignatureScheme::RSA_PKCS1_SHA256,
-                    rustls::SignatureScheme::RSA_PKCS1_SHA384,
-                    rustls::SignatureScheme::RSA_PKCS1_SHA512,
-                    rustls::SignatureScheme::ECDSA_NISTP256_SHA256,
-                    rustls::SignatureScheme::ECDSA_NISTP384_SHA384,
-                    rustls::SignatureScheme::ECDSA_NISTP521_SHA512,
-                    rustls::SignatureScheme::RSA_PSS_SHA256,
-                    rustls::SignatureScheme::RSA_PSS_SHA384,
-                    rustls::SignatureScheme::RSA_PSS_SHA512,
-                    rustls::SignatureScheme::ED25519,
-                ]
+    /// Raw listing of the current directory and the parser for its lines
+    /// Prefers MLSD (RFC 3659): typed entries and UTC timestamps instead of locale-dependent LIST text
+    async fn list_working_directory(&self) -> Result<(Vec<String>, LineParser), suppaftp::FtpError> {
+        if self.mlsd_supported.get() != Some(&false) {
+            match ftp_op!(self, mlsd, None) {
+                Ok(entries) => {
+                    let _ = self.mlsd_supported.set(true);
+                    return Ok((entries, parse_mlsd_line));
+                }
+                Err(e) if is_unsupported_command(&e) => {
+                    log::info!("[FTP] Server does not support MLSD, using LIST: {}", e);
+                    let _ = self.mlsd_supported.set(false);
+                }
+                Err(e) => return Err(e),
             }
         }
-
-        rustls::ClientConfig::builder()
-            .dangerous()
-            .with_custom_certificate_verifier(Arc::new(AcceptAllCertVerifier))
-            .with_no_client_auth()
+        Ok((ftp_op!(self, list, None)?, parse_ftp_list_line))
     }
 }
 
@@ -222,7 +273,12 @@ impl FileTransferSession for FtpSession {
         }
     }
 
-    async fn list_directory(&self, path: &str) -> Result<Vec<FileInfo>, ConnectionError> {
+    /// chmod goes through SITE CHMOD, which most (not all) servers accept
+    fn capabilities(&self) -> SessionCapabilities {
+        SessionCapabilities { chmod: true, recursive_delete: true, ..Default::default() }
+    }
+
+    async fn list_directory(&self, path: &str) -> Result<DirectoryListing, ConnectionError> {
         // Normalize path: remove trailing slash except for root
         let normalized_path = if path == "/" {
             "/"
@@ -257,129 +313,170 @@ impl FileTransferSession for FtpSession {
         };
 
         // List files
-        let entries = ftp_op!(self, list, None)
+        let (entries, parse_line) = self.list_working_directory()
+            .await
             .map_err(|e| {
                 log::error!("[FTP] Failed to list directory {}: {}", actual_path, e);
-                ConnectionError::FtpError(format!("Failed to list directory: {}", e))
+                ConnectionError::ftp("Failed to list directory", &e)
             })?;
 
         let base_path = if actual_path.is_empty() { "/" } else { &actual_path };
 
-        let files: Vec<FileInfo> = entries
-            .into_iter()
-            .filter_map(|line| parse_ftp_list_line(&line, base_path))
-            .collect();
+        let mut listing = DirectoryListing::default();
+        for line in entries {
+            match parse_line(&line, base_path) {
+                Ok(Some(file)) => listing.entries.push(file),
+                Ok(None) => {}
+                Err(message) => {
+                    log::debug!("[FTP] Unparsed LIST line ({}): {}", message, line);
+                    listing.warnings.push(ListingWarning {
+                        path: line.trim().to_string(),
+                        message,
+                    });
+                }
+            }
+        }
 
-        Ok(files)
+        Ok(listing)
     }
 
     async fn download_file(&self, remote_path: &str, local_path: &str) -> Result<(), ConnectionError> {
-        self.download_file_with_progress(remote_path, local_path, None).await
+        self.download_file_with_progress(remote_path, local_path, None, None).await
     }
 
     async fn download_file_with_progress(
         &self,
         remote_path: &str,
         local_path: &str,
-        progress: Option<Arc<dyn Fn(u64, u64) + Send + Sync>>,
+        progress: Option<ProgressCallback>,
+        max_bytes_per_sec: Option<u64>,
     ) -> Result<(), ConnectionError> {
         // Get file size first
         let total_bytes = ftp_op!(self, size, remote_path).unwrap_or(0) as u64;
 
-        // Download using retr_as_stream and read all data
-        let data = if let Some(ref ftp) = self.ftp_secure {
+        let mut local_file = tokio::fs::File::create(local_path)
+            .await
+            .map_err(|e| ConnectionError::io("Failed to create local file", &e))?;
+
+        // Stream in chunks so progress is live and a cancel stops within one chunk
+        if let Some(ref ftp) = self.ftp_secure {
             let mut ftp = ftp.lock().await;
             let mut stream = ftp.retr_as_stream(remote_path)
                 .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to start download: {}", e)))?;
-
-            let mut buffer = Vec::new();
-            stream.read_to_end(&mut buffer)
-                .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to read file data: {}", e)))?;
+                .map_err(|e| ConnectionError::ftp("Failed to start download", &e))?;
 
+            if let Err(e) = stream_to_file(&mut stream, &mut local_file, total_bytes, &progress, max_bytes_per_sec).await {
+                if matches!(e, ConnectionError::Cancelled) {
+                    let _ = ftp.abort(stream).await;
+                }
+                return Err(e);
+            }
             ftp.finalize_retr_stream(stream)
                 .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to finalize download: {}", e)))?;
-
-            buffer
+                .map_err(|e| ConnectionError::ftp("Failed to finalize download", &e))?;
         } else if let Some(ref ftp) = self.ftp_plain {
             let mut ftp = ftp.lock().await;
             let mut stream = ftp.retr_as_stream(remote_path)
                 .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to start download: {}", e)))?;
-
-            let mut buffer = Vec::new();
-            stream.read_to_end(&mut buffer)
-                .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to read file data: {}", e)))?;
+                .map_err(|e| ConnectionError::ftp("Failed to start download", &e))?;
 
+            if let Err(e) = stream_to_file(&mut stream, &mut local_file, total_bytes, &progress, max_bytes_per_sec).await {
+                if matches!(e, ConnectionError::Cancelled) {
+                    let _ = ftp.abort(stream).await;
+                }
+                return Err(e);
+            }
             ftp.finalize_retr_stream(stream)
                 .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to finalize download: {}", e)))?;
-
-            buffer
+                .map_err(|e| ConnectionError::ftp("Failed to finalize download", &e))?;
         } else {
             return Err(ConnectionError::FtpError("No FTP connection".to_string()));
-        };
+        }
 
-        // Write to local file
-        tokio::fs::write(local_path, &data)
+        local_file
+            .flush()
             .await
-            .map_err(|e| ConnectionError::IoError(format!("Failed to write local file: {}", e)))?;
-
-        // Report final progress
-        if let Some(cb) = &progress {
-            cb(data.len() as u64, total_bytes.max(data.len() as u64));
-        }
+            .map_err(|e| ConnectionError::io("Failed to write local file", &e))?;
 
         Ok(())
     }
 
     async fn upload_file(&self, local_path: &str, remote_path: &str) -> Result<(), ConnectionError> {
-        self.upload_file_with_progress(local_path, remote_path, None).await
+        self.upload_file_with_progress(local_path, remote_path, None, None).await
     }
 
     async fn upload_file_with_progress(
         &self,
         local_path: &str,
         remote_path: &str,
-        progress: Option<Arc<dyn Fn(u64, u64) + Send + Sync>>,
+        progress: Option<ProgressCallback>,
+        max_bytes_per_sec: Option<u64>,
     ) -> Result<(), ConnectionError> {
-        // Read local file
-        let data = tokio::fs::read(local_path)
+        let total_bytes = tokio::fs::metadata(local_path)
             .await
-            .map_err(|e| ConnectionError::IoError(format!("Failed to read local file: {}", e)))?;
-
-        let total_bytes = data.len() as u64;
+            .map_err(|e| ConnectionError::io("Failed to stat local file", &e))?
+            .len();
+        let mut local_file = tokio::fs::File::open(local_path)
+            .await
+            .map_err(|e| ConnectionError::io("Failed to open local file", &e))?;
 
-        // Upload
-        let mut reader: &[u8] = &data;
+        // Upload in chunks; a cancelled upload aborts the STOR and leaves a partial remote file
         if let Some(ref ftp) = self.ftp_secure {
             let mut ftp = ftp.lock().await;
-            ftp.put_file(remote_path, &mut reader)
+            let mut stream = ftp.put_with_stream(remote_path)
+                .await
+                .map_err(|e| ConnectionError::ftp("Failed to start upload", &e))?;
+
+            if let Err(e) = file_to_stream(&mut local_file, &mut stream, total_bytes, &progress, max_bytes_per_sec).await {
+                if matches!(e, ConnectionError::Cancelled) {
+                    let _ = ftp.abort(stream).await;
+                }
+                return Err(e);
+            }
+            ftp.finalize_put_stream(stream)
                 .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to upload file: {}", e)))?;
+                .map_err(|e| ConnectionError::ftp("Failed to upload file", &e))?;
         } else if let Some(ref ftp) = self.ftp_plain {
             let mut ftp = ftp.lock().await;
-            ftp.put_file(remote_path, &mut reader)
+            let mut stream = ftp.put_with_stream(remote_path)
+                .await
+                .map_err(|e| ConnectionError::ftp("Failed to start upload", &e))?;
+
+            if let Err(e) = file_to_stream(&mut local_file, &mut stream, total_bytes, &progress, max_bytes_per_sec).await {
+                if matches!(e, ConnectionError::Cancelled) {
+                    let _ = ftp.abort(stream).await;
+                }
+                return Err(e);
+            }
+            ftp.finalize_put_stream(stream)
                 .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to upload file: {}", e)))?;
+                .map_err(|e| ConnectionError::ftp("Failed to upload file", &e))?;
         } else {
             return Err(ConnectionError::FtpError("No FTP connection".to_string()));
         }
 
-        // Report final progress
-        if let Some(cb) = &progress {
-            cb(total_bytes, total_bytes);
-        }
-
         Ok(())
     }
 
     async fn create_directory(&self, path: &str) -> Result<(), ConnectionError> {
         ftp_op!(self, mkdir, path)
-            .map_err(|e| ConnectionError::FtpError(format!("Failed to create directory: {}", e)))?;
+            .map_err(|e| ConnectionError::ftp("Failed to create directory", &e))?;
+        Ok(())
+    }
+
+    async fn create_directory_recursive(&self, path: &str) -> Result<(), ConnectionError> {
+        for dir in crate::core::path_utils::directory_chain(path) {
+            match ftp_op!(self, mkdir, &dir) {
+                Ok(()) => {}
+                Err(e) if is_already_exists(&e) => {}
+                // Many servers answer a plain 550 for an existing directory too
+                Err(e) => {
+                    if !self.stat(&dir).await.is_ok_and(|info| info.is_directory) {
+                        return Err(ConnectionError::ftp(format!("Failed to create directory {}", dir), &e));
+                    }
+                }
+            }
+        }
         Ok(())
     }
 
@@ -389,6 +486,12 @@ impl FileTransferSession for FtpSession {
             path);
 
         let result = if is_directory {
+            // list_directory leaves the connection inside the directory it listed, and
+            // some servers refuse to remove their working directory, so step out first
+            if let Some((parent, _)) = path.trim_end_matches('/').rsplit_once('/') {
+                let parent = if parent.is_empty() { "/" } else { parent };
+                let _ = ftp_op!(self, cwd, parent);
+            }
             ftp_op!(self, rmdir, path)
         } else {
             ftp_op!(self, rm, path)
@@ -425,13 +528,26 @@ impl FileTransferSession for FtpSession {
         ftp_op!(self, rename, &normalized_old, &normalized_new)
             .map_err(|e| {
                 log::error!("[FTP] Failed to rename {} to {}: {}", normalized_old, normalized_new, e);
-                ConnectionError::FtpError(format!("Failed to rename {} to {}: {}", normalized_old, normalized_new, e))
+                ConnectionError::ftp(format!("Failed to rename {} to {}", normalized_old, normalized_new), &e)
             })?;
         Ok(())
     }
 
-    async fn chmod(&self, _path: &str, _mode: u32) -> Result<(), ConnectionError> {
-        Err(ConnectionError::FtpError("FTP does not support chmod".to_string()))
+    async fn chmod(&self, path: &str, mode: u32) -> Result<(), ConnectionError> {
+        // Not part of RFC 959, but vsftpd, ProFTPD, Pure-FTPd and FileZilla Server accept it
+        let command = site_chmod_command(path, mode);
+        match ftp_op!(self, site, &command) {
+            Ok(_) => Ok(()),
+            Err(e) if is_unsupported_command(&e) => {
+                log::info!("[FTP] Server rejected SITE CHMOD: {}", e);
+                Err(ConnectionError::FtpError("FTP does not support chmod".to_string()))
+            }
+            Err(e) => Err(ConnectionError::ftp("Failed to change permissions", &e)),
+        }
+    }
+
+    async fn create_symlink(&self, _target: &str, _link_path: &str) -> Result<(), ConnectionError> {
+        Err(ConnectionError::FtpError("FTP does not support symlinks".to_string()))
     }
 
     async fn stat(&self, path: &str) -> Result<FileInfo, ConnectionError> {
@@ -445,14 +561,15 @@ impl FileTransferSession for FtpSession {
 
         // Change to directory
         ftp_op!(self, cwd, dir)
-            .map_err(|e| ConnectionError::FtpError(format!("Failed to change directory: {}", e)))?;
+            .map_err(|e| ConnectionError::ftp("Failed to change directory", &e))?;
 
         // List and find file
-        let entries = ftp_op!(self, list, None)
-            .map_err(|e| ConnectionError::FtpError(format!("Failed to list directory: {}", e)))?;
+        let (entries, parse_line) = self.list_working_directory()
+            .await
+            .map_err(|e| ConnectionError::ftp("Failed to list directory", &e))?;
 
         for line in entries {
-            if let Some(file_info) = parse_ftp_list_line(&line, dir) {
+            if let Ok(Some(file_info)) = parse_line(&line, dir) {
                 if file_info.name == name {
                     return Ok(file_info);
                 }
@@ -462,62 +579,75 @@ impl FileTransferSession for FtpSession {
         Err(ConnectionError::FtpError(format!("File not found: {}", path)))
     }
 
-    async fn read_file(&self, path: &str) -> Result<Vec<u8>, ConnectionError> {
-        let data = if let Some(ref ftp) = self.ftp_secure {
-            let mut ftp = ftp.lock().await;
-            let mut stream = ftp.retr_as_stream(path)
-                .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to start read: {}", e)))?;
+    /// SIZE rejects an oversized file up front; servers without it are stopped once the limit is passed
+    async fn read_file(&self, path: &str, max_bytes: Option<u64>) -> Result<Vec<u8>, ConnectionError> {
+        if max_bytes.is_some() {
+            if let Ok(size) = ftp_op!(self, size, path) {
+                file_content::check_size(size as u64, max_bytes)?;
+            }
+        }
 
-            let mut buffer = Vec::new();
-            stream.read_to_end(&mut buffer)
-                .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to read file: {}", e)))?;
+        let range = self.read_file_range(path, 0, file_content::read_limit(max_bytes)).await?;
+        file_content::check_size(range.data.len() as u64, max_bytes)?;
+        Ok(range.data)
+    }
 
-            ftp.finalize_retr_stream(stream)
-                .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to finalize read: {}", e)))?;
+    /// REST + RETR; the transfer is aborted once the window (plus one byte) has arrived
+    async fn read_file_range(&self, path: &str, offset: u64, length: u64) -> Result<FileRange, ConnectionError> {
+        macro_rules! retr_range {
+            ($ftp:expr) => {{
+                let mut ftp = $ftp.lock().await;
+                if offset > 0 {
+                    ftp.resume_transfer(offset as usize)
+                        .await
+                        .map_err(|e| ConnectionError::ftp("Server cannot resume at an offset", &e))?;
+                }
+                let mut stream = ftp.retr_as_stream(path)
+                    .await
+                    .map_err(|e| ConnectionError::ftp("Failed to start read", &e))?;
+
+                let mut data = Vec::new();
+                (&mut stream).take(length.saturating_add(1))
+                    .read_to_end(&mut data)
+                    .await
+                    .map_err(|e| ConnectionError::ftp("Failed to read file", &e))?;
+                let range = FileRange::from_read(data, length);
+
+                if range.eof {
+                    ftp.finalize_retr_stream(stream)
+                        .await
+                        .map_err(|e| ConnectionError::ftp("Failed to finalize read", &e))?;
+                } else if let Err(e) = ftp.abort(stream).await {
+                    log::debug!("[FTP] Aborting read of {} after {} bytes: {}", path, length, e);
+                }
+                Ok(range)
+            }};
+        }
 
-            buffer
+        if let Some(ref ftp) = self.ftp_secure {
+            retr_range!(ftp)
         } else if let Some(ref ftp) = self.ftp_plain {
-            let mut ftp = ftp.lock().await;
-            let mut stream = ftp.retr_as_stream(path)
-                .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to start read: {}", e)))?;
-
-            let mut buffer = Vec::new();
-            stream.read_to_end(&mut buffer)
-                .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to read file: {}", e)))?;
-
-            ftp.finalize_retr_stream(stream)
-                .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to finalize read: {}", e)))?;
-
-            buffer
+            retr_range!(ftp)
         } else {
-            return Err(ConnectionError::FtpError("No FTP connection".to_string()));
-        };
-
-        Ok(data)
+            Err(ConnectionError::FtpError("No FTP connection".to_string()))
+        }
     }
 
+    /// Best-effort atomic save: upload to a temp file, then RNFR/RNTO it over the original
     async fn write_file(&self, path: &str, content: &[u8]) -> Result<(), ConnectionError> {
-        let mut reader: &[u8] = content;
+        crate::core::atomic_write::write_via_temp(self, path, content).await
+    }
 
-        if let Some(ref ftp) = self.ftp_secure {
-            let mut ftp = ftp.lock().await;
-            ftp.put_file(path, &mut reader)
-                .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to write file: {}", e)))?;
-        } else if let Some(ref ftp) = self.ftp_plain {
-            let mut ftp = ftp.lock().await;
-            ftp.put_file(path, &mut reader)
-                .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to write file: {}", e)))?;
+    /// FTP can only append, so chunks are expected back to back (offset = current size)
+    async fn write_chunk(&self, path: &str, offset: u64, data: &[u8], _is_last: bool) -> Result<(), ConnectionError> {
+        let mut reader: &[u8] = data;
+
+        let result = if offset == 0 {
+            ftp_op!(self, put_file, path, &mut reader)
         } else {
-            return Err(ConnectionError::FtpError("No FTP connection".to_string()));
-        }
+            ftp_op!(self, append_file, path, &mut reader)
+        };
+        result.map_err(|e| ConnectionError::ftp("Failed to write chunk", &e))?;
 
         Ok(())
     }
@@ -527,42 +657,122 @@ impl FileTransferSession for FtpSession {
             let mut ftp = ftp.lock().await;
             ftp.quit()
                 .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to close FTPS: {}", e)))?;
+                .map_err(|e| ConnectionError::ftp("Failed to close FTPS", &e))?;
         } else if let Some(ref ftp) = self.ftp_plain {
             let mut ftp = ftp.lock().await;
             ftp.quit()
                 .await
-                .map_err(|e| ConnectionError::FtpError(format!("Failed to close FTP: {}", e)))?;
+                .map_err(|e| ConnectionError::ftp("Failed to close FTP", &e))?;
         }
         Ok(())
     }
 }
 
+/// Size of each read/write in a streamed transfer
+const TRANSFER_CHUNK_SIZE: usize = 32768;
+
+/// Copy an FTP data stream into a local file, reporting progress and throttling after each chunk
+async fn stream_to_file<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    local_file: &mut tokio::fs::File,
+    total_bytes: u64,
+    progress: &Option<ProgressCallback>,
+    max_bytes_per_sec: Option<u64>,
+) -> Result<u64, ConnectionError> {
+    let mut buffer = vec![0u8; TRANSFER_CHUNK_SIZE];
+    let mut limiter = RateLimiter::new(max_bytes_per_sec);
+    let mut transferred: u64 = 0;
+    loop {
+        let n = stream
+            .read(&mut buffer)
+            .await
+            .map_err(|e| ConnectionError::ftp("Failed to read file data", &e))?;
+        if n == 0 {
+            return Ok(transferred);
+        }
+
+        local_file
+            .write_all(&buffer[..n])
+            .await
+            .map_err(|e| ConnectionError::io("Failed to write local file", &e))?;
+
+        transferred += n as u64;
+        if let Some(cb) = progress {
+            if !cb(transferred, total_bytes.max(transferred)) {
+                return Err(ConnectionError::Cancelled);
+            }
+        }
+        if let Some(limiter) = &mut limiter {
+            limiter.throttle(n).await;
+        }
+    }
+}
+
+/// Copy a local file into an FTP data stream, reporting progress and throttling after each chunk
+async fn file_to_stream<S: AsyncWrite + Unpin>(
+    local_file: &mut tokio::fs::File,
+    stream: &mut S,
+    total_bytes: u64,
+    progress: &Option<ProgressCallback>,
+    max_bytes_per_sec: Option<u64>,
+) -> Result<u64, ConnectionError> {
+    let mut buffer = vec![0u8; TRANSFER_CHUNK_SIZE];
+    let mut limiter = RateLimiter::new(max_bytes_per_sec);
+    let mut transferred: u64 = 0;
+    loop {
+        let n = local_file
+            .read(&mut buffer)
+            .await
+            .map_err(|e| ConnectionError::io("Failed to read local file", &e))?;
+        if n == 0 {
+            return Ok(transferred);
+        }
+
+        stream
+            .write_all(&buffer[..n])
+            .await
+            .map_err(|e| ConnectionError::ftp("Failed to upload file", &e))?;
+
+        transferred += n as u64;
+        if let Some(cb) = progress {
+            if !cb(transferred, total_bytes) {
+                return Err(ConnectionError::Cancelled);
+            }
+        }
+        if let Some(limiter) = &mut limiter {
+            limiter.throttle(n).await;
+        }
+    }
+}
+
 /// Parse FTP LIST output line (Unix-style)
-fn parse_ftp_list_line(line: &str, base_path: &str) -> Option<FileInfo> {
+/// Ok(None) for lines that carry no entry (blank, "total N", "." and ".."),
+/// Err with the reason for lines that could not be parsed
+fn parse_ftp_list_line(line: &str, base_path: &str) -> Result<Option<FileInfo>, String> {
     let trimmed = line.trim();
-    if trimmed.is_empty() {
-        return None;
+    if trimmed.is_empty() || trimmed.starts_with("total ") {
+        return Ok(None);
+    }
+
+    if trimmed.split_whitespace().next().is_some_and(is_windows_list_date) {
+        return parse_windows_list_line(trimmed, base_path);
     }
 
     let parts: Vec<&str> = trimmed.split_whitespace().collect();
 
     if parts.len() < 9 {
-        log::debug!("[FTP] LIST line has < 9 parts ({}): {}", parts.len(), trimmed);
-        return None;
+        return Err(format!("Unrecognized listing format ({} fields)", parts.len()));
     }
 
     let permissions = parts[0];
 
     if permissions.len() < 10 {
-        log::debug!("[FTP] Permissions too short ({} chars): {}", permissions.len(), permissions);
-        return None;
+        return Err(format!("Invalid permissions field: {}", permissions));
     }
 
     let first_char = permissions.chars().next();
     if !matches!(first_char, Some('d' | '-' | 'l' | 'c' | 'b' | 'p' | 's')) {
-        log::debug!("[FTP] Invalid permissions first char: {:?}", first_char);
-        return None;
+        return Err(format!("Unknown entry type: {}", permissions));
     }
 
     let is_directory = permissions.starts_with('d');
@@ -582,24 +792,10 @@ fn parse_ftp_list_line(line: &str, base_path: &str) -> Option<FileInfo> {
     };
 
     if name.is_empty() || name == "." || name == ".." {
-        return None;
+        return Ok(None);
     }
 
-    let file_path = if name.contains('\\') || name.contains('/') {
-        if name.starts_with('/') || name.starts_with('\\') {
-            name.clone()
-        } else if base_path == "/" {
-            format!("/{}", name)
-        } else {
-            format!("{}/{}", base_path.trim_end_matches('/'), name)
-        }
-    } else {
-        if base_path == "/" {
-            format!("/{}", name)
-        } else {
-            format!("{}/{}", base_path.trim_end_matches('/'), name)
-        }
-    };
+    let file_path = entry_path(base_path, &name);
 
     let modified = if parts.len() > 7 {
         Some(format!("{} {} {}", parts[5], parts[6], parts[7]))
@@ -610,7 +806,7 @@ fn parse_ftp_list_line(line: &str, base_path: &str) -> Option<FileInfo> {
     let owner = parts.get(2).map(|s| s.to_string());
     let group = parts.get(3).map(|s| s.to_string());
 
-    Some(FileInfo {
+    Ok(Some(FileInfo {
         name: name.clone(),
         path: file_path,
         size,
@@ -621,5 +817,272 @@ fn parse_ftp_list_line(line: &str, base_path: &str) -> Option<FileInfo> {
         modified,
         owner,
         group,
-    })
+    }))
+}
+
+/// Parse a DOS-style line from IIS and other Windows servers
+/// `02-11-24  03:05PM       <DIR>          folder` / `02-11-24  03:05PM   1234 file name.txt`
+fn parse_windows_list_line(line: &str, base_path: &str) -> Result<Option<FileInfo>, String> {
+    let mut rest = line;
+    let mut fields = [""; 3];
+    for field in fields.iter_mut() {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        *field = &rest[..end];
+        rest = &rest[end..];
+    }
+    let [date, time, size_or_dir] = fields;
+    // The name is the rest of the line, inner spaces included
+    let name = rest.trim_start().to_string();
+    if name.is_empty() {
+        return Err(format!("Unrecognized listing format: {}", line));
+    }
+    if name == "." || name == ".." {
+        return Ok(None);
+    }
+
+    let is_directory = matches!(size_or_dir.to_ascii_uppercase().as_str(), "<DIR>" | "<JUNCTION>");
+    let size = if is_directory {
+        0
+    } else {
+        size_or_dir
+            .replace(',', "")
+            .parse()
+            .map_err(|_| format!("Invalid size field: {}", size_or_dir))?
+    };
+
+    Ok(Some(FileInfo {
+        path: entry_path(base_path, &name),
+        name,
+        size,
+        is_directory,
+        is_symlink: false,
+        symlink_target: None,
+        permissions: None,
+        modified: Some(format!("{} {}", date, time)),
+        owner: None,
+        group: None,
+    }))
+}
+
+/// Parse one MLSD line: `type=file;size=1024;modify=20240303101500;UNIX.mode=0644; index.html`
+/// Fact names are case-insensitive; `cdir`/`pdir` entries carry no file
+fn parse_mlsd_line(line: &str, base_path: &str) -> Result<Option<FileInfo>, String> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    let (facts, name) = line
+        .split_once(' ')
+        .ok_or_else(|| format!("Missing file name: {}", line))?;
+    if name.is_empty() || name == "." || name == ".." {
+        return Ok(None);
+    }
+
+    let mut info = FileInfo {
+        name: name.to_string(),
+        path: entry_path(base_path, name),
+        size: 0,
+        is_directory: false,
+        is_symlink: false,
+        symlink_target: None,
+        permissions: None,
+        modified: None,
+        owner: None,
+        group: None,
+    };
+    let mut has_type = false;
+
+    for fact in facts.split(';').filter(|f| !f.is_empty()) {
+        let Some((key, value)) = fact.split_once('=') else {
+            continue;
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "type" => {
+                has_type = true;
+                let kind = value.to_ascii_lowercase();
+                match kind.as_str() {
+                    "cdir" | "pdir" => return Ok(None),
+                    "dir" => info.is_directory = true,
+                    _ if kind.starts_with("os.unix=slink") || kind == "os.unix=symlink" => {
+                        info.is_symlink = true;
+                        // Keep the target's original case: `OS.unix=slink:/path`
+                        info.symlink_target = value.split_once(':').map(|(_, target)| target.to_string());
+                    }
+                    _ => {}
+                }
+            }
+            "size" | "sizd" => info.size = value.parse().unwrap_or(0),
+            "modify" => info.modified = parse_mlsd_time(value).map(|t| t.to_string()),
+            "unix.mode" => {
+                info.permissions = u32::from_str_radix(value, 8).ok().map(|mode| format!("{:o}", mode));
+            }
+            "unix.owner" | "unix.uid" if info.owner.is_none() => info.owner = Some(value.to_string()),
+            "unix.group" | "unix.gid" if info.group.is_none() => info.group = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    if !has_type {
+        return Err(format!("Missing type fact: {}", line));
+    }
+    Ok(Some(info))
+}
+
+/// MLSD `modify` fact (`YYYYMMDDHHMMSS[.sss]`, UTC) as seconds since the epoch
+fn parse_mlsd_time(value: &str) -> Option<u64> {
+    let digits = value.split('.').next()?;
+    if digits.len() != 14 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let field = |range: std::ops::Range<usize>| digits[range].parse::<i64>().ok();
+    let (year, month, day) = (field(0..4)?, field(4..6)?, field(6..8)?);
+    let (hour, minute, second) = (field(8..10)?, field(10..12)?, field(12..14)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar (Howard Hinnant's days_from_civil)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    u64::try_from(days * 86_400 + hour * 3_600 + minute * 60 + second).ok()
+}
+
+/// `CHMOD 755 /path` argument for SITE: permission bits only, plain octal
+fn site_chmod_command(path: &str, mode: u32) -> String {
+    format!("CHMOD {:03o} {}", mode & 0o777, crate::core::normalize_remote_path(path))
+}
+
+/// Whether the server rejected a command as unknown/unimplemented (5xx syntax/not implemented)
+fn is_unsupported_command(error: &suppaftp::FtpError) -> bool {
+    use suppaftp::Status;
+    matches!(
+        error,
+        suppaftp::FtpError::UnexpectedResponse(response)
+            if matches!(
+                response.status,
+                Status::BadCommand | Status::BadArguments | Status::NotImplemented | Status::NotImplementedParameter
+            )
+    )
+}
+
+/// Whether MKD failed because the directory exists (521, or a 550 saying so)
+fn is_already_exists(error: &suppaftp::FtpError) -> bool {
+    error.error_code() == Some(ErrorCode::AlreadyExists)
+}
+
+/// `MM-DD-YY` or `MM-DD-YYYY`, the first column of a Windows listing
+fn is_windows_list_date(field: &str) -> bool {
+    let parts: Vec<&str> = field.split('-').collect();
+    matches!(parts.as_slice(), [month, day, year]
+        if month.len() == 2 && day.len() == 2 && (year.len() == 2 || year.len() == 4)
+            && parts.iter().all(|p| p.bytes().all(|b| b.is_ascii_digit())))
+}
+
+/// Full path of a listed entry (names with a separator are used as given)
+fn entry_path(base_path: &str, name: &str) -> String {
+    if name.starts_with('/') || name.starts_with('\\') {
+        name.to_string()
+    } else if base_path == "/" {
+        format!("/{}", name)
+    } else {
+        format!("{}/{}", base_path.trim_end_matches('/'), name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_line_parse_and_report() {
+        let file = parse_ftp_list_line("-rw-r--r--   1 www  www   1024 Mar 03 10:15 index.html", "/www")
+            .unwrap()
+            .unwrap();
+        assert_eq!(file.path, "/www/index.html");
+        assert_eq!(file.size, 1024);
+
+        assert!(matches!(parse_ftp_list_line("total 48", "/www"), Ok(None)));
+        assert!(parse_ftp_list_line("drwxr-xr-x 2 www www 4096 Mar 03 10:15 ..", "/www").unwrap().is_none());
+        assert!(parse_ftp_list_line("Directory listing follows", "/www").is_err());
+    }
+
+    #[test]
+    fn test_mlsd_lines() {
+        let file = parse_mlsd_line("type=file;size=1024;modify=20240303101500;UNIX.mode=0644; my file.txt", "/www")
+            .unwrap()
+            .unwrap();
+        assert_eq!(file.path, "/www/my file.txt");
+        assert_eq!(file.size, 1024);
+        assert!(!file.is_directory);
+        assert_eq!(file.permissions.as_deref(), Some("644"));
+        assert_eq!(file.modified.as_deref(), Some("1709460900"));
+
+        let dir = parse_mlsd_line("Type=dir;Modify=19700101000001.5; logs", "/").unwrap().unwrap();
+        assert!(dir.is_directory);
+        assert_eq!(dir.modified.as_deref(), Some("1"));
+
+        let link = parse_mlsd_line("type=OS.unix=slink:/var/Data;size=9; data", "/").unwrap().unwrap();
+        assert!(link.is_symlink);
+        assert_eq!(link.symlink_target.as_deref(), Some("/var/Data"));
+
+        assert!(parse_mlsd_line("type=cdir;modify=20240303101500; /www", "/www").unwrap().is_none());
+        assert!(parse_mlsd_line("size=10; no-type", "/www").is_err());
+    }
+
+    #[test]
+    fn test_mkdir_exists_replies() {
+        use suppaftp::{FtpError, Status};
+        let reply = |status, body: &str| FtpError::UnexpectedResponse(suppaftp::types::Response::new(status, body.as_bytes().to_vec()));
+        assert!(is_already_exists(&reply(Status::Unknown, "521 \"/www\" directory already exists\r\n")));
+        assert!(is_already_exists(&reply(Status::FileUnavailable, "550 Can't create directory: File exists\r\n")));
+        assert!(!is_already_exists(&reply(Status::FileUnavailable, "550 Permission denied\r\n")));
+    }
+
+    #[test]
+    fn test_site_chmod_command() {
+        assert_eq!(site_chmod_command("/www/run.sh", 0o755), "CHMOD 755 /www/run.sh");
+        // File type bits from a stat'ed mode are dropped; small modes keep three digits
+        assert_eq!(site_chmod_command("/www/a b.txt", 0o100644), "CHMOD 644 /www/a b.txt");
+        assert_eq!(site_chmod_command("/www/secret/", 0o7), "CHMOD 007 /www/secret");
+    }
+
+    #[test]
+    fn test_data_connection_mode_from_options() {
+        assert_eq!(DataConnectionMode::from_options(true, Some("10.0.0.5")).unwrap(), DataConnectionMode::Passive);
+        assert_eq!(
+            DataConnectionMode::from_options(false, Some(" 10.0.0.5 ")).unwrap(),
+            DataConnectionMode::Active { bind_address: Some("10.0.0.5".parse().unwrap()) }
+        );
+        assert_eq!(DataConnectionMode::from_options(false, Some("")).unwrap(), DataConnectionMode::Active { bind_address: None });
+        assert!(DataConnectionMode::from_options(false, Some("not-an-ip")).is_err());
+        assert!(DataConnectionMode::from_options(false, Some("::1")).is_err());
+    }
+
+    #[test]
+    fn test_windows_list_lines() {
+        let dir = parse_ftp_list_line("02-11-24  03:05PM       <DIR>          folder", "/")
+            .unwrap()
+            .unwrap();
+        assert_eq!(dir.path, "/folder");
+        assert!(dir.is_directory);
+        assert_eq!(dir.modified.as_deref(), Some("02-11-24 03:05PM"));
+
+        let file = parse_ftp_list_line("02-11-2024  15:05             1,234 annual report.txt", "/docs")
+            .unwrap()
+            .unwrap();
+        assert_eq!(file.name, "annual report.txt");
+        assert_eq!(file.path, "/docs/annual report.txt");
+        assert_eq!(file.size, 1234);
+        assert!(!file.is_directory);
+
+        assert!(parse_ftp_list_line("02-11-24  03:05PM  <DIR>  ..", "/docs").unwrap().is_none());
+        assert!(parse_ftp_list_line("02-11-24  03:05PM  12k  broken", "/docs").is_err());
+    }
 }
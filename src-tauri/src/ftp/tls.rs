@@ -0,0 +1,232 @@
+//! FTPS certificate verification driven by SecurityPolicy
+
+use base64::Engine;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::CryptoProvider;
+use rustls::{DigitallySignedStruct, RootCertStore};
+use rustls_pki_types::{CertificateDer, ServerName, UnixTime};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::core::error::ConnectionError;
+use crate::ssh::config::{HostKeyStatus, HostKeyVerdict, SecurityPolicy};
+
+/// File name inside the app config dir
+pub const KNOWN_CERTS_FILE: &str = "known_certs.json";
+
+/// TLS settings for an FTPS session
+#[derive(Debug, Clone, Default)]
+pub struct FtpTlsConfig {
+    pub policy: SecurityPolicy,
+    /// Where certificate pins are kept (None = remember for this session only)
+    pub known_certs_path: Option<PathBuf>,
+}
+
+/// SHA-256 fingerprint of a DER certificate, formatted like OpenSSH ("SHA256:...")
+pub fn fingerprint(cert: &[u8]) -> String {
+    let digest = Sha256::digest(cert);
+    format!("SHA256:{}", base64::engine::general_purpose::STANDARD_NO_PAD.encode(digest))
+}
+
+/// Trust-on-first-use certificate pins: "host:port" -> fingerprint
+#[derive(Debug)]
+struct KnownCerts {
+    path: Option<PathBuf>,
+    pins: Mutex<BTreeMap<String, String>>,
+}
+
+impl KnownCerts {
+    fn load(path: Option<PathBuf>) -> Self {
+        let pins = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            pins: Mutex::new(pins),
+        }
+    }
+
+    fn status(&self, host_id: &str, fingerprint: &str) -> HostKeyStatus {
+        let pins = self.pins.lock().unwrap_or_else(|e| e.into_inner());
+        match pins.get(host_id) {
+            Some(pinned) if pinned == fingerprint => HostKeyStatus::Known,
+            Some(_) => HostKeyStatus::Changed,
+            None => HostKeyStatus::Unknown,
+        }
+    }
+
+    fn remember(&self, host_id: &str, fingerprint: &str) {
+        let mut pins = self.pins.lock().unwrap_or_else(|e| e.into_inner());
+        pins.insert(host_id.to_string(), fingerprint.to_string());
+
+        let Some(path) = &self.path else { return };
+        let saved = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                let content = serde_json::to_string_pretty(&*pins).map_err(std::io::Error::other)?;
+                std::fs::write(path, content)
+            });
+        if let Err(e) = saved {
+            log::warn!("[FTPS] Failed to save certificate pin for {}: {}", host_id, e);
+        }
+    }
+}
+
+/// Certificate verifier applying a SecurityPolicy
+///
+/// - Strict: the chain must validate against the public CA roots, or match a pin
+/// - AcceptNew: pin the first certificate seen, reject a different one later
+/// - AcceptAll: any certificate
+///
+/// Handshake signatures are always checked, so a pinned certificate
+/// cannot be replayed without its private key.
+#[derive(Debug)]
+struct PolicyCertVerifier {
+    policy: SecurityPolicy,
+    host_id: String,
+    known: KnownCerts,
+    provider: Arc<CryptoProvider>,
+    /// CA verifier (Strict only)
+    webpki: Option<Arc<WebPkiServerVerifier>>,
+}
+
+impl ServerCertVerifier for PolicyCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if self.policy == SecurityPolicy::AcceptAll {
+            return Ok(ServerCertVerified::assertion());
+        }
+
+        let fingerprint = fingerprint(end_entity);
+        let status = self.known.status(&self.host_id, &fingerprint);
+
+        if let Some(webpki) = &self.webpki {
+            if status != HostKeyStatus::Changed
+                && webpki
+                    .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+                    .is_ok()
+            {
+                return Ok(ServerCertVerified::assertion());
+            }
+        }
+
+        match self.policy.verdict(status) {
+            HostKeyVerdict::Accept => Ok(ServerCertVerified::assertion()),
+            HostKeyVerdict::Remember => {
+                log::info!("[FTPS] Trusting new certificate for {} ({})", self.host_id, fingerprint);
+                self.known.remember(&self.host_id, &fingerprint);
+                Ok(ServerCertVerified::assertion())
+            }
+            HostKeyVerdict::Reject => {
+                let reason = if status == HostKeyStatus::Changed {
+                    "changed since it was first trusted"
+                } else {
+                    "is not trusted"
+                };
+                log::warn!("[FTPS] Certificate for {} {} ({})", self.host_id, reason, fingerprint);
+                Err(rustls::Error::General(format!(
+                    "Certificate for {} {} ({})",
+                    self.host_id, reason, fingerprint
+                )))
+            }
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Build the rustls client config for an FTPS connection to `host:port`
+pub fn client_config(tls: &FtpTlsConfig, host: &str, port: u16) -> Result<rustls::ClientConfig, ConnectionError> {
+    let builder = rustls::ClientConfig::builder();
+    let provider = builder.crypto_provider().clone();
+
+    let webpki = if tls.policy == SecurityPolicy::Strict {
+        let roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        let verifier = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+            .build()
+            .map_err(|e| ConnectionError::FtpError(format!("Failed to load CA roots: {}", e)))?;
+        Some(verifier)
+    } else {
+        None
+    };
+
+    let verifier = PolicyCertVerifier {
+        policy: tls.policy,
+        host_id: format!("{}:{}", host, port),
+        known: KnownCerts::load(tls.known_certs_path.clone()),
+        provider,
+        webpki,
+    };
+
+    Ok(builder
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_certs_pin_and_detect_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(KNOWN_CERTS_FILE);
+        let first = fingerprint(b"certificate one");
+        let second = fingerprint(b"certificate two");
+        assert!(first.starts_with("SHA256:"));
+
+        let known = KnownCerts::load(Some(path.clone()));
+        assert_eq!(known.status("ftp.example.com:21", &first), HostKeyStatus::Unknown);
+        known.remember("ftp.example.com:21", &first);
+
+        let reloaded = KnownCerts::load(Some(path));
+        assert_eq!(reloaded.status("ftp.example.com:21", &first), HostKeyStatus::Known);
+        assert_eq!(reloaded.status("ftp.example.com:21", &second), HostKeyStatus::Changed);
+        assert_eq!(reloaded.status("ftp.example.com:990", &second), HostKeyStatus::Unknown);
+    }
+
+    #[test]
+    fn test_policy_verdicts() {
+        use HostKeyStatus::*;
+        assert_eq!(SecurityPolicy::AcceptNew.verdict(Unknown), HostKeyVerdict::Remember);
+        assert_eq!(SecurityPolicy::AcceptNew.verdict(Changed), HostKeyVerdict::Reject);
+        assert_eq!(SecurityPolicy::Strict.verdict(Unknown), HostKeyVerdict::Reject);
+        assert_eq!(SecurityPolicy::Strict.verdict(Known), HostKeyVerdict::Accept);
+        assert_eq!(SecurityPolicy::AcceptAll.verdict(Changed), HostKeyVerdict::Accept);
+    }
+}
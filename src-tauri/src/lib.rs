@@ -11,8 +11,10 @@ mod commands;
 
 use tauri::{AppHandle, Manager};
 use tauri::menu::{Menu, MenuItem, Submenu};
-use managers::{TerminalManager, FileTransferManager};
-use file_watcher::FileWatcherManager;
+use managers::{TerminalManager, FileTransferManager, ConnectionAttempts};
+use file_watcher::{FileWatcherManager, RemoteFileWatcherManager, RemoteTailManager};
+use ssh::host_key::HostKeyPrompts;
+use ssh::interactive::AuthPrompts;
 use pty::shell::detect_available_shells;
 use commands::window::spawn_new_instance_for_menu;
 
@@ -58,23 +60,48 @@ pub fn run() {
         .manage(TerminalManager::new())
         .manage(FileTransferManager::new())
         .manage(FileWatcherManager::new())
+        .manage(RemoteFileWatcherManager::new())
+        .manage(RemoteTailManager::new())
+        .manage(ConnectionAttempts::new())
+        .manage(AuthPrompts::new())
+        .manage(HostKeyPrompts::new())
         .invoke_handler(tauri::generate_handler![
             greet,
             // Terminal commands
             commands::terminal::create_terminal,
             commands::terminal::write_terminal,
+            commands::terminal::write_terminal_bytes,
+            commands::terminal::paste_terminal,
+            commands::terminal::write_terminal_paste,
             commands::terminal::resize_terminal,
             commands::terminal::close_terminal,
+            commands::terminal::close_all_terminals,
+            commands::terminal::reconnect_session,
             commands::terminal::start_terminal_streaming,
             commands::terminal::ping_terminal,
             commands::terminal::execute_terminal_command,
+            commands::terminal::get_terminal_session_info,
+            commands::terminal::get_scrollback,
+            commands::terminal::start_recording,
+            commands::terminal::stop_recording,
             commands::terminal::fetch_command_history,
             commands::terminal::fetch_local_shell_history,
             // SSH commands
             commands::ssh::create_ssh_session,
             commands::ssh::create_chained_ssh_session,
+            commands::ssh::resolve_ssh_host,
+            commands::ssh::respond_auth_prompt,
+            commands::ssh::respond_host_key_prompt,
+            commands::ssh::start_dynamic_forward,
+            commands::ssh::stop_dynamic_forward,
+            commands::ssh::add_local_forward,
+            commands::ssh::remove_local_forward,
             // Telnet commands
             commands::telnet::create_telnet_session,
+            // Connection attempt commands
+            commands::connection::cancel_connection,
+            commands::connection::get_session_capabilities,
+            commands::connection::list_active_sessions,
             // Shell detection
             detect_available_shells,
             // File transfer commands
@@ -82,13 +109,27 @@ pub fn run() {
             commands::file_transfer::list_directory,
             commands::file_transfer::download_file,
             commands::file_transfer::upload_file,
+            commands::file_transfer::upload_directory,
+            commands::file_transfer::upload_files,
+            commands::file_transfer::download_directory,
+            commands::file_transfer::cancel_transfer,
+            commands::file_transfer::upload_bytes,
+            commands::file_transfer::sync_directory,
+            commands::file_transfer::calculate_directory_size,
             commands::file_transfer::test_file_transfer_event,
             commands::file_transfer::create_remote_directory,
+            commands::file_transfer::statfs,
             commands::file_transfer::delete_remote_path,
+            commands::file_transfer::delete_remote_paths,
+            commands::file_transfer::delete_remote_recursive,
             commands::file_transfer::rename_remote_path,
             commands::file_transfer::rename_local_path,
             commands::file_transfer::close_file_session,
+            commands::file_transfer::close_all_transfers,
             commands::file_transfer::chmod_remote,
+            commands::file_transfer::chmod_remote_batch,
+            commands::file_transfer::create_remote_symlink,
+            commands::file_transfer::run_remote_command,
             commands::file_transfer::copy_local_path,
             commands::file_transfer::move_local_path,
             commands::file_transfer::copy_remote_path,
@@ -100,13 +141,24 @@ pub fn run() {
             commands::file_operations::list_windows_drives,
             commands::file_operations::open_file_with_system,
             commands::file_operations::open_file_with_app,
+            commands::file_operations::open_remote_with_app,
+            commands::file_operations::close_remote_edit,
+            commands::file_operations::set_default_app,
+            commands::file_operations::get_default_app,
+            commands::file_operations::list_default_apps,
             commands::file_operations::show_open_with_dialog,
             commands::file_operations::show_in_file_manager,
             commands::file_operations::read_file_content,
+            commands::file_operations::read_file_base64,
+            commands::file_operations::read_file_range,
             commands::file_operations::write_file_content,
             // File watcher
             commands::file_watcher::watch_file,
             commands::file_watcher::unwatch_file,
+            commands::file_watcher::watch_remote_file,
+            commands::file_watcher::unwatch_remote_file,
+            commands::file_watcher::follow_remote_file,
+            commands::file_watcher::stop_follow,
             // Window management
             commands::window::create_new_window
         ])
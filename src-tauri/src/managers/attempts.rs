@@ -0,0 +1,145 @@
+use crate::core::error::{CommandError, ErrorCode};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+type Attempts = Arc<Mutex<HashMap<String, Arc<Notify>>>>;
+
+/// Tracks in-flight connection attempts so the frontend can cancel them
+/// Keyed by a client-supplied attempt ID
+pub struct ConnectionAttempts {
+    attempts: Attempts,
+}
+
+impl ConnectionAttempts {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            attempts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Run a connect future, aborting it if `cancel` is called for the same attempt ID
+    /// Dropping the future closes any half-open sockets it owns; a cancelled attempt fails with CANCELLED
+    /// An attempt ID that is already in flight is refused with ALREADY_EXISTS
+    pub async fn run<T, F>(&self, attempt_id: Option<String>, fut: F) -> Result<T, CommandError>
+    where
+        F: Future<Output = Result<T, CommandError>>,
+    {
+        let Some(attempt_id) = attempt_id else {
+            return fut.await;
+        };
+
+        let notify = Arc::new(Notify::new());
+        {
+            let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+            if attempts.contains_key(&attempt_id) {
+                return Err(CommandError::new(
+                    ErrorCode::AlreadyExists,
+                    format!("Connection attempt {} is already in progress", attempt_id),
+                ));
+            }
+            attempts.insert(attempt_id.clone(), notify.clone());
+        }
+        let _registered = RegisteredAttempt {
+            attempts: self.attempts.clone(),
+            attempt_id: attempt_id.clone(),
+        };
+
+        tokio::select! {
+            result = fut => result,
+            _ = notify.notified() => {
+                log::info!("[ConnectionAttempts] Attempt cancelled: {}", attempt_id);
+                Err(CommandError::new(ErrorCode::Cancelled, "Connection cancelled"))
+            }
+        }
+    }
+
+    /// Cancel an in-flight attempt, returns false if it already finished
+    pub async fn cancel(&self, attempt_id: &str) -> bool {
+        match self.attempts.lock().unwrap_or_else(|e| e.into_inner()).get(attempt_id) {
+            Some(notify) => {
+                notify.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Unregisters an attempt when its run ends (finished, cancelled or dropped)
+struct RegisteredAttempt {
+    attempts: Attempts,
+    attempt_id: String,
+}
+
+impl Drop for RegisteredAttempt {
+    fn drop(&mut self) {
+        self.attempts.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.attempt_id);
+    }
+}
+
+impl Default for ConnectionAttempts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_aborts_attempt() {
+        let attempts = Arc::new(ConnectionAttempts::new());
+        let runner = attempts.clone();
+        let handle = tokio::spawn(async move {
+            runner
+                .run(Some("a1".to_string()), async {
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                    Ok::<_, CommandError>(())
+                })
+                .await
+        });
+
+        // Wait until the attempt is registered
+        while !attempts.cancel("a1").await {
+            tokio::task::yield_now().await;
+        }
+
+        assert_eq!(handle.await.unwrap(), Err(CommandError::new(ErrorCode::Cancelled, "Connection cancelled")));
+        assert!(!attempts.cancel("a1").await);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_attempt_id_is_refused() {
+        let attempts = Arc::new(ConnectionAttempts::new());
+        let runner = attempts.clone();
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let first = tokio::spawn(async move {
+            runner
+                .run(Some("a1".to_string()), async {
+                    let _ = started_tx.send(());
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                    Ok::<_, CommandError>(())
+                })
+                .await
+        });
+        started_rx.await.unwrap();
+
+        let second = attempts.run(Some("a1".to_string()), async { Ok::<_, CommandError>(()) }).await;
+        assert_eq!(second.unwrap_err().code, ErrorCode::AlreadyExists);
+
+        // The refused run must not unregister the first one
+        assert!(attempts.cancel("a1").await);
+        assert_eq!(first.await.unwrap().unwrap_err().code, ErrorCode::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_run_without_attempt_id() {
+        let attempts = ConnectionAttempts::new();
+        assert_eq!(attempts.run(None, async { Ok::<_, CommandError>(7) }).await, Ok(7));
+    }
+}
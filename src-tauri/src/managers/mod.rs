@@ -2,7 +2,9 @@
 
 pub mod terminal;
 pub mod transfer;
+pub mod attempts;
 
 pub use terminal::TerminalManager;
-pub use transfer::{FileTransferManager, FileSessionConfig, FileInfoDto};
+pub use transfer::{FileTransferManager, FileSessionConfig, DirectoryListingDto, CommandOutput, ConflictPolicy, UploadResult, UploadItem, DeleteItem, ChmodItem, BatchResult, SyncResult, DirectoryUploadResult, DirectoryDownloadResult, DirectorySize};
+pub use attempts::ConnectionAttempts;
 
@@ -1,21 +1,40 @@
-use crate::core::error::SessionError;
-use crate::core::session::TerminalSession;
+use crate::core::error::{CommandError, ErrorCode, SessionError};
+use crate::core::initial_command::InitialCommand;
+use crate::core::paste::{build_paste_payload, PASTE_CHUNK_DELAY_MS, PASTE_CHUNK_SIZE};
+use crate::core::recording::Recording;
+use crate::core::session::{ActiveSession, SessionCapabilities, SessionInfo, TerminalSession};
 use crate::core::terminal_events::TerminalExitEvent;
 use crate::pty::session::LocalPtySession;
+use crate::ssh::client::SshClient;
+use crate::ssh::forward::{ForwardKind, PortForward};
 use crate::ssh::terminal::SshTerminalSession;
-use crate::ssh::config::{SshAuth, SshConfig, HostConfig};
+use crate::ssh::config::{validate_term, SshConfig, SshSessionInput, HostConfig};
 use crate::ssh::error::SshError;
-use crate::telnet::TelnetConfig;
-use crate::terminal::factory::SessionFactory;
-use std::collections::HashMap;
+use crate::telnet::TelnetSessionInput;
+use crate::terminal::factory::{SessionConfig, SessionFactory};
+use russh::client::Handle;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
+use tokio::time::Duration;
+
+/// A live terminal session and what it is connected to
+struct OpenSession {
+    session: Arc<dyn TerminalSession>,
+    info: ActiveSession,
+}
 
 /// Terminal manager (Singleton Pattern via Tauri's .manage())
 /// Manages all active terminal sessions
 pub struct TerminalManager {
-    sessions: Arc<RwLock<HashMap<String, Box<dyn TerminalSession>>>>,
+    sessions: Arc<RwLock<HashMap<String, OpenSession>>>,
+    /// Port forwards per session (dropped, and so stopped, when the session closes)
+    forwards: Arc<RwLock<HashMap<String, Vec<PortForward>>>>,
+    /// SSH/Telnet configs by session, kept so reconnect_session can re-establish them
+    configs: Arc<RwLock<HashMap<String, SessionConfig>>>,
+    /// Sessions with a reconnect in flight (close_session clears the entry to cancel it)
+    reconnecting: Arc<Mutex<HashSet<String>>>,
 }
 
 impl TerminalManager {
@@ -23,6 +42,9 @@ impl TerminalManager {
     pub fn new() -> Self {
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            forwards: Arc::new(RwLock::new(HashMap::new())),
+            configs: Arc::new(RwLock::new(HashMap::new())),
+            reconnecting: Arc::new(Mutex::new(HashSet::new())),
         }
     }
 
@@ -30,75 +52,30 @@ impl TerminalManager {
     pub async fn create_local_session(
         &self,
         shell: Option<String>,
+        args: Vec<String>,
         cols: u16,
         rows: u16,
+        env: HashMap<String, String>,
+        cwd: Option<String>,
+        binary_output: bool,
+        scrollback_bytes: Option<usize>,
+        initial_command: Option<InitialCommand>,
         app_handle: AppHandle,
-    ) -> Result<String, String> {
-        let session = SessionFactory::local(shell, cols, rows, app_handle)
-            .map_err(|e| e.to_string())?;
+    ) -> Result<String, CommandError> {
+        let session = SessionFactory::local(shell, args, cols, rows, env, cwd, binary_output, scrollback_bytes, initial_command, app_handle)?;
         let session_id = session.id().to_string();
+        let info = ActiveSession::new(&session_id, "local", None);
 
         let mut sessions = self.sessions.write().await;
-        sessions.insert(session_id.clone(), session);
+        sessions.insert(session_id.clone(), OpenSession { session: session.into(), info });
 
         Ok(session_id)
     }
 
     /// Create a new SSH terminal session
-    pub async fn create_ssh_session(
-        &self,
-        hostname: String,
-        port: u16,
-        username: String,
-        auth_method: String,
-        key_path: Option<String>,
-        password: Option<String>,
-        cols: u16,
-        rows: u16,
-        app_handle: AppHandle,
-    ) -> Result<String, String> {
-        // Convert auth method string to SshAuth
-        let auth = match auth_method.as_str() {
-            "password" => {
-                let pwd = password.ok_or_else(|| "Password required".to_string())?;
-                SshAuth::Password(pwd)
-            }
-            "key" => {
-                let path = key_path.ok_or_else(|| "Key path required".to_string())?;
-                SshAuth::Key {
-                    path,
-                    passphrase: None,
-                }
-            }
-            "agent" => SshAuth::Agent,
-            _ => return Err(format!("Unknown auth method: {}", auth_method)),
-        };
-
-        let config = SshConfig {
-            target: HostConfig {
-                hostname,
-                port,
-                username,
-                auth,
-                connection_type: crate::ssh::config::ConnectionType::Ssh,
-            },
-            jumps: Vec::new(),
-            terminal: crate::ssh::config::TerminalConfig { cols, rows },
-        };
-
-        let session = SessionFactory::create(
-            crate::terminal::factory::SessionConfig::Ssh(config),
-            app_handle,
-        )
-        .await
-        .map_err(|e| e.to_string())?;
-
-        let session_id = session.id().to_string();
-
-        let mut sessions = self.sessions.write().await;
-        sessions.insert(session_id.clone(), session);
-
-        Ok(session_id)
+    pub async fn create_ssh_session(&self, input: SshSessionInput, app_handle: AppHandle) -> Result<String, CommandError> {
+        let config = input.into_ssh_config().map_err(invalid_config)?;
+        self.connect_remote(SessionConfig::Ssh(config), app_handle).await
     }
 
     /// Create a chained SSH session through jump hosts (ProxyJump)
@@ -107,10 +84,18 @@ impl TerminalManager {
         chain: Vec<HostConfig>,
         cols: u16,
         rows: u16,
+        keepalive_secs: Option<u64>,
+        scrollback_bytes: Option<usize>,
+        term: Option<String>,
+        initial_command: Option<InitialCommand>,
         app_handle: AppHandle,
-    ) -> Result<String, String> {
+    ) -> Result<String, CommandError> {
         if chain.is_empty() {
-            return Err("Chain cannot be empty".to_string());
+            return Err(invalid_config("Chain cannot be empty".to_string()));
+        }
+
+        if let Some(term) = &term {
+            validate_term(term).map_err(invalid_config)?;
         }
 
         // Last element is target, rest are jumps
@@ -120,57 +105,77 @@ impl TerminalManager {
         let config = SshConfig {
             target,
             jumps,
-            terminal: crate::ssh::config::TerminalConfig { cols, rows },
+            terminal: crate::ssh::config::TerminalConfig { cols, rows, keepalive_secs, scrollback_bytes, term },
+            initial_command,
         };
 
-        let session = SessionFactory::create(
-            crate::terminal::factory::SessionConfig::Ssh(config),
-            app_handle,
-        )
-        .await
-        .map_err(|e| e.to_string())?;
+        self.connect_remote(SessionConfig::Ssh(config), app_handle).await
+    }
+
+    /// Create a new Telnet terminal session
+    pub async fn create_telnet_session(&self, input: TelnetSessionInput, app_handle: AppHandle) -> Result<String, CommandError> {
+        if let Some(term) = &input.term {
+            validate_term(term).map_err(invalid_config)?;
+        }
+        let config = input.into_telnet_config();
+        self.connect_remote(SessionConfig::Telnet(config), app_handle).await
+    }
 
+    /// Connect a remote session and remember its config for reconnect_session
+    async fn connect_remote(&self, config: SessionConfig, app_handle: AppHandle) -> Result<String, CommandError> {
+        let session = SessionFactory::create(config.clone(), app_handle.clone())
+            .await
+            .map_err(|e| connect_error(e, &app_handle))?;
         let session_id = session.id().to_string();
+        let info = describe_remote(&session_id, &config);
 
-        let mut sessions = self.sessions.write().await;
-        sessions.insert(session_id.clone(), session);
+        self.configs.write().await.insert(session_id.clone(), config);
+        self.sessions.write().await.insert(session_id.clone(), OpenSession { session: session.into(), info });
 
         Ok(session_id)
     }
 
-    /// Create a new Telnet terminal session
-    pub async fn create_telnet_session(
-        &self,
-        hostname: String,
-        port: u16,
-        username: Option<String>,
-        password: Option<String>,
-        cols: u16,
-        rows: u16,
-        app_handle: AppHandle,
-    ) -> Result<String, String> {
-        let config = TelnetConfig {
-            hostname,
-            port,
-            cols,
-            rows,
-            username,
-            password,
-        };
+    /// Re-establish a dropped SSH/Telnet session from its original config
+    /// Keeps the session id so the frontend's listeners stay attached; port forwards are not restored
+    pub async fn reconnect_session(&self, session_id: &str, app_handle: AppHandle) -> Result<String, CommandError> {
+        let config = self
+            .configs
+            .read()
+            .await
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| CommandError::new(ErrorCode::NotFound, format!("Session cannot be reconnected: {}", session_id)))?;
 
-        let session = SessionFactory::create(
-            crate::terminal::factory::SessionConfig::Telnet(config),
-            app_handle,
-        )
-        .await
-        .map_err(|e| e.to_string())?;
+        if !self.reconnecting.lock().await.insert(session_id.to_string()) {
+            return Err(format!("Reconnect already in progress: {}", session_id).into());
+        }
 
-        let session_id = session.id().to_string();
+        // Wait for the old I/O task to finish so it can't emit events under the reused id
+        let old_session = self.sessions.write().await.remove(session_id);
+        self.forwards.write().await.remove(session_id);
+        if let Some(OpenSession { session: old_session, .. }) = old_session {
+            if let Err(e) = old_session.close().await {
+                log::debug!("[TerminalManager] Closing dead session {} failed: {}", session_id, e);
+            }
+        }
 
-        let mut sessions = self.sessions.write().await;
-        sessions.insert(session_id.clone(), session);
+        log::info!("[TerminalManager] Reconnecting session: {}", session_id);
+        let info = describe_remote(session_id, &config);
+        let reconnected = SessionFactory::reconnect(session_id.to_string(), config, app_handle.clone()).await;
 
-        Ok(session_id)
+        let cancelled = !self.reconnecting.lock().await.remove(session_id);
+        let session = reconnected.map_err(|e| connect_error(e, &app_handle))?;
+        if cancelled {
+            log::info!("[TerminalManager] Session {} closed while reconnecting", session_id);
+            let _ = session.close().await;
+            return Err(CommandError::new(ErrorCode::Cancelled, format!("Session closed during reconnect: {}", session_id)));
+        }
+
+        // Listeners are already in place
+        session.start_streaming();
+        self.sessions.write().await.insert(session_id.to_string(), OpenSession { session: session.into(), info });
+
+        Ok(session_id.to_string())
     }
 
     /// Write data to a terminal session
@@ -179,9 +184,52 @@ impl TerminalManager {
 
         let session = sessions
             .get(session_id)
+            .map(|open| &open.session)
             .ok_or_else(|| format!("Session not found: {}", session_id))?;
 
-        session.write(data).await.map_err(|e| e.to_string())
+        session.write(data).await.map_err(|e| e.to_string())?;
+        if let Some(recording) = session.recording() {
+            recording.input(data);
+        }
+        Ok(())
+    }
+
+    /// Paste data into a terminal session
+    /// Wraps in bracketed paste markers when the remote enabled the mode,
+    /// and sends large pastes in chunks so the remote isn't overwhelmed
+    pub async fn paste_to_session(&self, session_id: &str, data: &[u8]) -> Result<(), String> {
+        self.send_paste(session_id, data, false).await
+    }
+
+    /// Paste data wrapped in bracketed paste markers even if the remote never enabled the mode
+    pub async fn bracketed_paste_to_session(&self, session_id: &str, data: &[u8]) -> Result<(), String> {
+        self.send_paste(session_id, data, true).await
+    }
+
+    async fn send_paste(&self, session_id: &str, data: &[u8], always_bracketed: bool) -> Result<(), String> {
+        // Not holding the map lock between chunks, so sessions can open and close meanwhile
+        let session = self
+            .sessions
+            .read()
+            .await
+            .get(session_id)
+            .map(|open| open.session.clone())
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+
+        let bracketed = always_bracketed || session.bracketed_paste_enabled();
+        let payload = build_paste_payload(data, bracketed);
+
+        for (i, chunk) in payload.chunks(PASTE_CHUNK_SIZE).enumerate() {
+            if i > 0 {
+                tokio::time::sleep(tokio::time::Duration::from_millis(PASTE_CHUNK_DELAY_MS)).await;
+            }
+            session.write(chunk).await.map_err(|e| e.to_string())?;
+        }
+
+        if let Some(recording) = session.recording() {
+            recording.input(&payload);
+        }
+        Ok(())
     }
 
     /// Resize a terminal session
@@ -195,9 +243,18 @@ impl TerminalManager {
 
         let session = sessions
             .get(session_id)
+            .map(|open| &open.session)
             .ok_or_else(|| format!("Session not found: {}", session_id))?;
 
-        session.resize(cols, rows).await.map_err(|e| e.to_string())
+        session.resize(cols, rows).await.map_err(|e| e.to_string())?;
+        if let Some(recording) = session.recording() {
+            recording.resize(cols, rows);
+        }
+
+        if let Some(config) = self.configs.write().await.get_mut(session_id) {
+            config.set_size(cols, rows);
+        }
+        Ok(())
     }
 
     /// Start streaming for SSH session (call after FE listener is ready)
@@ -206,6 +263,7 @@ impl TerminalManager {
 
         let session = sessions
             .get(session_id)
+            .map(|open| &open.session)
             .ok_or_else(|| format!("Session not found: {}", session_id))?;
 
         session.start_streaming();
@@ -213,24 +271,33 @@ impl TerminalManager {
     }
 
     /// Ping a terminal session (keepalive check)
-    /// Returns true if session exists and is responsive
+    /// Returns false when the probe fails: SSH keepalive unanswered, Telnet NOP unsendable, shell exited
     pub async fn ping_session(&self, session_id: &str) -> Result<bool, String> {
         let sessions = self.sessions.read().await;
 
-        // Check if session exists
-        if sessions.contains_key(session_id) {
-            Ok(true) // Session exists and is responsive
-        } else {
-            Err(format!("Session not found: {}", session_id))
-        }
+        let session = sessions
+            .get(session_id)
+            .map(|open| &open.session)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+
+        Ok(session.ping().await)
     }
 
     /// Close a terminal session
     pub async fn close_session(&self, session_id: &str, app_handle: &AppHandle) -> Result<(), String> {
         let mut sessions = self.sessions.write().await;
+        self.configs.write().await.remove(session_id);
+        // Cancels a reconnect in flight
+        self.reconnecting.lock().await.remove(session_id);
 
-        if let Some(mut session) = sessions.remove(session_id) {
+        if let Some(OpenSession { session, .. }) = sessions.remove(session_id) {
             log::info!("[TerminalManager] Closing terminal session: {}", session_id);
+            self.forwards.write().await.remove(session_id);
+            if let Some(recording) = session.recording().filter(|r| r.is_active()) {
+                if let Err(e) = recording.stop() {
+                    log::warn!("[TerminalManager] Finishing recording of {} failed: {}", session_id, e);
+                }
+            }
 
             // Emit exit event with user-closed reason before closing
             let exit_event = TerminalExitEvent::user_closed();
@@ -245,12 +312,13 @@ impl TerminalManager {
     }
 
     /// Execute a command on a terminal session and return output
-    /// Works for SSH sessions; returns error for local PTY sessions
+    /// Works for SSH and local sessions; returns error for Telnet
     pub async fn execute_command(&self, session_id: &str, command: &str) -> Result<String, String> {
         let sessions = self.sessions.read().await;
 
         let session = sessions
             .get(session_id)
+            .map(|open| &open.session)
             .ok_or_else(|| format!("Session not found: {}", session_id))?;
 
         session
@@ -259,6 +327,169 @@ impl TerminalManager {
             .map_err(|e| e.to_string())
     }
 
+    /// Execute a command, failing if it hasn't finished within `timeout`
+    pub async fn execute_command_with_timeout(
+        &self,
+        session_id: &str,
+        command: &str,
+        timeout: Duration,
+    ) -> Result<String, String> {
+        let sessions = self.sessions.read().await;
+
+        let session = sessions
+            .get(session_id)
+            .map(|open| &open.session)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+
+        session
+            .execute_command_with_timeout(command, timeout)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Get information about a terminal session (type, charset, SSH banner and algorithms)
+    pub async fn get_session_info(&self, session_id: &str) -> Result<SessionInfo, String> {
+        let sessions = self.sessions.read().await;
+
+        let session = sessions
+            .get(session_id)
+            .map(|open| &open.session)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+
+        Ok(session.session_info())
+    }
+
+    /// Operations a terminal session supports (exec on SSH)
+    pub async fn get_session_capabilities(&self, session_id: &str) -> Result<SessionCapabilities, String> {
+        let sessions = self.sessions.read().await;
+
+        let session = sessions
+            .get(session_id)
+            .map(|open| &open.session)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+
+        Ok(session.capabilities())
+    }
+
+    /// Record a session's output (and with `record_input`, its input) to an asciinema v2 .cast file
+    pub async fn start_recording(&self, session_id: &str, path: &str, record_input: bool) -> Result<(), String> {
+        self.recording(session_id).await?.start(path, record_input)
+    }
+
+    /// Finish a recording started with start_recording
+    pub async fn stop_recording(&self, session_id: &str) -> Result<(), String> {
+        self.recording(session_id).await?.stop()
+    }
+
+    async fn recording(&self, session_id: &str) -> Result<Recording, String> {
+        let sessions = self.sessions.read().await;
+
+        let session = sessions
+            .get(session_id)
+            .map(|open| &open.session)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+
+        session
+            .recording()
+            .ok_or_else(|| format!("Session cannot be recorded: {}", session_id))
+    }
+
+    /// Recent output of a session, to repaint the terminal after the frontend reloads
+    pub async fn get_scrollback(&self, session_id: &str) -> Result<String, String> {
+        let sessions = self.sessions.read().await;
+
+        let session = sessions
+            .get(session_id)
+            .map(|open| &open.session)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+
+        Ok(session.scrollback())
+    }
+
+    /// Start a SOCKS5 proxy on 127.0.0.1:`bind_port` tunneled through an SSH session (ssh -D)
+    /// Returns the bound port (useful when `bind_port` is 0)
+    pub async fn start_dynamic_forward(&self, session_id: &str, bind_port: u16) -> Result<u16, String> {
+        let handle = self.ssh_handle(session_id).await?;
+        let forward = PortForward::dynamic(handle, bind_port)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(self.register_forward(session_id, forward).await)
+    }
+
+    /// Stop the SOCKS5 proxy a session has on `bind_port`
+    pub async fn stop_dynamic_forward(&self, session_id: &str, bind_port: u16) -> Result<(), String> {
+        self.remove_forward(session_id, bind_port, |kind| *kind == ForwardKind::Dynamic)
+            .await
+    }
+
+    /// Forward 127.0.0.1:`local_port` to `remote_host`:`remote_port` through an SSH session (ssh -L)
+    /// Returns the bound port (useful when `local_port` is 0)
+    pub async fn add_local_forward(
+        &self,
+        session_id: &str,
+        local_port: u16,
+        remote_host: String,
+        remote_port: u16,
+    ) -> Result<u16, String> {
+        let handle = self.ssh_handle(session_id).await?;
+        let forward = PortForward::local(handle, local_port, remote_host, remote_port)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(self.register_forward(session_id, forward).await)
+    }
+
+    /// Tear down the local forward a session has on `local_port`
+    pub async fn remove_local_forward(&self, session_id: &str, local_port: u16) -> Result<(), String> {
+        self.remove_forward(session_id, local_port, |kind| matches!(kind, ForwardKind::Local { .. }))
+            .await
+    }
+
+    async fn ssh_handle(&self, session_id: &str) -> Result<Arc<Handle<SshClient>>, String> {
+        let sessions = self.sessions.read().await;
+        let session = sessions
+            .get(session_id)
+            .map(|open| &open.session)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        session
+            .ssh_handle()
+            .ok_or_else(|| "Port forwarding requires an SSH session".to_string())
+    }
+
+    /// Keep a forward alive until it is removed or its session closes; returns its port
+    async fn register_forward(&self, session_id: &str, forward: PortForward) -> u16 {
+        let port = forward.local_addr().port();
+        self.forwards
+            .write()
+            .await
+            .entry(session_id.to_string())
+            .or_default()
+            .push(forward);
+        port
+    }
+
+    async fn remove_forward(
+        &self,
+        session_id: &str,
+        port: u16,
+        kind_matches: impl Fn(&ForwardKind) -> bool,
+    ) -> Result<(), String> {
+        let mut forwards = self.forwards.write().await;
+        let session_forwards = forwards
+            .get_mut(session_id)
+            .ok_or_else(|| format!("No forwards for session {}", session_id))?;
+        let index = session_forwards
+            .iter()
+            .position(|f| f.local_addr().port() == port && kind_matches(f.kind()))
+            .ok_or_else(|| format!("No such forward on port {}", port))?;
+
+        // Dropping the forward stops its listener and connections
+        session_forwards.remove(index);
+        if session_forwards.is_empty() {
+            forwards.remove(session_id);
+        }
+        Ok(())
+    }
+
     /// Get number of active sessions
     #[allow(dead_code)]
     pub async fn session_count(&self) -> usize {
@@ -266,19 +497,53 @@ impl TerminalManager {
         sessions.len()
     }
 
-    /// Close all sessions
-    #[allow(dead_code)]
-    pub async fn close_all_sessions(&self) -> Result<(), String> {
-        let mut sessions = self.sessions.write().await;
+    /// Open sessions, oldest first
+    pub async fn list_sessions(&self) -> Vec<ActiveSession> {
+        let mut list: Vec<ActiveSession> = self.sessions.read().await.values().map(|open| open.info.clone()).collect();
+        list.sort_by_key(|info| info.opened_at);
+        list
+    }
 
-        for (_, mut session) in sessions.drain() {
-            session.close().await.ok(); // Ignore errors when closing
+    /// Close all sessions (each gets its user-closed exit event, recordings are finished)
+    /// Returns how many were closed
+    pub async fn close_all_sessions(&self, app_handle: &AppHandle) -> usize {
+        let ids: Vec<String> = self.sessions.read().await.keys().cloned().collect();
+        for session_id in &ids {
+            // Ignore errors when closing
+            if let Err(e) = self.close_session(session_id, app_handle).await {
+                log::debug!("[TerminalManager] Closing session {} failed: {}", session_id, e);
+            }
         }
+        ids.len()
+    }
+}
 
-        Ok(())
+/// Type and host of a remote session, for list_sessions
+fn describe_remote(session_id: &str, config: &SessionConfig) -> ActiveSession {
+    match config {
+        SessionConfig::Local { .. } => ActiveSession::new(session_id, "local", None),
+        SessionConfig::Ssh(ssh) => ActiveSession::new(session_id, "ssh", Some(ssh.target.hostname.clone())),
+        SessionConfig::Telnet(telnet) => ActiveSession::new(session_id, "telnet", Some(telnet.hostname.clone())),
     }
 }
 
+/// Categorize a failed connect, first emitting "ssh-auth-failed" with the methods the server
+/// still accepts when authentication was the problem (so the UI can e.g. prompt for a password)
+fn connect_error(err: SessionError, app_handle: &AppHandle) -> CommandError {
+    if let SessionError::SshError(ssh_err) = &err {
+        if let Some(failure) = ssh_err.auth_failure() {
+            if let Err(e) = app_handle.emit("ssh-auth-failed", &failure) {
+                log::warn!("[TerminalManager] Failed to emit auth failure: {}", e);
+            }
+        }
+    }
+    err.into()
+}
+
+fn invalid_config(message: String) -> CommandError {
+    CommandError::new(ErrorCode::InvalidConfig, message)
+}
+
 impl Default for TerminalManager {
     fn default() -> Self {
         Self::new()
@@ -1,15 +1,28 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::JoinSet;
 use serde::{Deserialize, Serialize};
 
-use crate::core::error::ConnectionError;
-use crate::core::session::{FileInfo, FileTransferSession};
+use crate::core::error::{ConnectionError, ErrorCode};
+use crate::core::checksum::{hash_local_file, ChecksumAlgorithm};
+use crate::core::file_content::{FileRange, MAX_RANGE_LENGTH};
+use crate::core::fs_stats::FilesystemStats;
+use crate::core::rate_limit::effective_limit;
+use crate::core::session::{ActiveSession, FileInfo, FileTransferSession, ListingWarning, ProgressCallback, SessionCapabilities};
+use crate::core::upload_tree::{walk_upload_tree, SymlinkPolicy};
+use crate::core::sync::{plan_sync, scan_local_tree, SyncAction, SyncActionKind, SyncEntry, SyncOptions, SyncTree};
 use crate::sftp::session::SftpSession;
-use crate::ftp::session::FtpSession;
-use crate::ssh::client::{SshClient, connect_direct, authenticate};
-use crate::ssh::config::{ConnectionType, HostConfig, SshAuth, HostConfigInput};
+use crate::ftp::session::{DataConnectionMode, FtpSession};
+use crate::ftp::tls::{FtpTlsConfig, KNOWN_CERTS_FILE};
+use crate::ssh::client::{SshClient, connect_direct_with, authenticate};
+use crate::ssh::config::{resolve_port, AlgorithmPreferences, ConnectionType, HostConfig, SshAuth, HostConfigInput, SecurityPolicy};
 use crate::ssh::chain::HopHandler;
+use crate::ssh::error::SshError;
+use russh::client::Msg;
+use russh::Channel;
 use tauri::{AppHandle, Emitter, Manager};
 
 /// Configuration for creating a file transfer session
@@ -18,13 +31,44 @@ use tauri::{AppHandle, Emitter, Manager};
 pub struct FileSessionConfig {
     pub connection_type: String,
     pub hostname: String,
+    /// 0 or omitted = protocol default (see default_port)
+    #[serde(default)]
     pub port: u16,
     pub username: String,
     pub password: Option<String>,
     pub key_path: Option<String>,
+    /// Passphrase for an encrypted key file
+    #[serde(default)]
+    pub key_passphrase: Option<String>,
     /// Optional chain of jump hosts for SFTP connections (ProxyJump)
     #[serde(default)]
     pub jumps: Vec<HostConfigInput>,
+    /// Host key (SFTP) / certificate (FTPS) verification, default accept-new
+    #[serde(default)]
+    pub security_policy: Option<SecurityPolicy>,
+    /// FTP/FTPS data connections in passive mode (PASV), default true; false uses active mode (PORT)
+    #[serde(default = "default_passive")]
+    pub passive: bool,
+    /// Local IPv4 address to open the control connection from in active mode, which is
+    /// what PORT advertises. Behind NAT the server still can't reach it without an FTP ALG.
+    #[serde(default)]
+    pub active_bind_address: Option<String>,
+    /// Default bandwidth cap for each transfer in bytes/sec; transfers may override it
+    #[serde(default)]
+    pub max_bytes_per_sec: Option<u64>,
+    /// SFTP: connect through this command's stdio instead of TCP (ProxyCommand); with jumps, set it on the first jump
+    #[serde(default)]
+    pub proxy_command: Option<String>,
+    /// SFTP: prefer zlib compression for the SSH connection
+    #[serde(default)]
+    pub compression: bool,
+    /// SFTP: kex/cipher/MAC/host key preferences for the SSH connection
+    #[serde(default)]
+    pub algorithms: Option<AlgorithmPreferences>,
+}
+
+fn default_passive() -> bool {
+    true
 }
 
 /// File info for serialization to frontend
@@ -43,6 +87,14 @@ pub struct FileInfoDto {
     pub group: Option<String>,
 }
 
+/// Directory listing for serialization to frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryListingDto {
+    pub entries: Vec<FileInfoDto>,
+    pub warnings: Vec<ListingWarning>,
+}
+
 impl From<FileInfo> for FileInfoDto {
     fn from(info: FileInfo) -> Self {
         Self {
@@ -60,9 +112,56 @@ impl From<FileInfo> for FileInfoDto {
     }
 }
 
+/// A live file session and what it is connected to
+struct OpenFileSession {
+    session: Arc<dyn FileTransferSession>,
+    info: ActiveSession,
+}
+
 /// Manager for file transfer sessions
 pub struct FileTransferManager {
-    sessions: Arc<Mutex<HashMap<String, Arc<dyn FileTransferSession>>>>,
+    sessions: Arc<Mutex<HashMap<String, OpenFileSession>>>,
+    /// Streamed uploads in progress, keyed by (session_id, remote_path)
+    chunked_uploads: Arc<Mutex<HashMap<(String, String), ChunkedUpload>>>,
+    /// Cancel flags of running transfers, keyed by transfer_id
+    cancel_flags: CancelFlags,
+    /// Default bandwidth cap (bytes/sec) of sessions that have one
+    rate_limits: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+type CancelFlags = Arc<std::sync::Mutex<HashMap<String, Arc<AtomicBool>>>>;
+
+/// A running transfer's cancel flag; unregisters itself when dropped
+struct CancelRegistration {
+    flags: CancelFlags,
+    transfer_id: String,
+    flag: Arc<AtomicBool>,
+}
+
+impl CancelRegistration {
+    fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for CancelRegistration {
+    fn drop(&mut self) {
+        if let Ok(mut flags) = self.flags.lock() {
+            // A newer transfer may have reused the id
+            if flags.get(&self.transfer_id).is_some_and(|f| Arc::ptr_eq(f, &self.flag)) {
+                flags.remove(&self.transfer_id);
+            }
+        }
+    }
+}
+
+/// State of a streamed (upload_bytes) upload between chunks
+#[derive(Debug, Clone, Copy, Default)]
+struct ChunkedUpload {
+    /// Offset the next chunk must start at
+    next_offset: u64,
+    /// A chunk is being written (a concurrent one would land out of order)
+    in_flight: bool,
 }
 
 impl Default for FileTransferManager {
@@ -84,18 +183,476 @@ struct TransferProgressEvent {
     pub bytes_transferred: u64,
     pub total_bytes: u64,
     pub done: bool,
+    /// Upload skipped because the destination already existed
+    pub skipped: bool,
+}
+
+/// What to do when an upload's destination already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    Overwrite,
+    Skip,
+    /// Upload as `name (N).ext`
+    #[default]
+    Rename,
+}
+
+/// Result of an upload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadResult {
+    /// Path actually written (differs from the request when renamed)
+    pub remote_path: String,
+    pub skipped: bool,
+}
+
+/// Result of run_command
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandOutput {
+    pub stdout: String,
+    /// -1 when the server reported no exit status
+    pub exit_code: i32,
+}
+
+/// Give up looking for a free `name (N).ext` after this many tries
+const MAX_DUPLICATE_COUNTER: u32 = 1000;
+
+/// Upper bound for upload_files concurrency (more only queues on the server)
+const MAX_UPLOAD_CONCURRENCY: usize = 16;
+
+/// Item for a batch delete
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteItem {
+    pub path: String,
+    pub is_directory: bool,
+}
+
+/// File for a batch upload
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadItem {
+    pub local_path: String,
+    pub remote_path: String,
+}
+
+/// Item for a batch chmod
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChmodItem {
+    pub path: String,
+    pub mode: u32,
+}
+
+/// Failed item in a batch operation
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// Outcome of a batch operation (one failure doesn't abort the rest)
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResult {
+    pub succeeded: Vec<String>,
+    pub failed: Vec<BatchFailure>,
+    /// Dry run only: what would be done (succeeded/failed stay empty)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub planned: Option<Vec<PlannedChange>>,
+}
+
+/// One change a dry-run batch would make
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedChange {
+    pub path: String,
+    pub is_directory: bool,
+    /// "delete" | "chmod"
+    pub action: &'static str,
+    /// e.g. "rw-r--r-- -> 755", or what a directory delete would hit
+    pub detail: Option<String>,
+    /// Why the change is expected to fail (missing path, non-empty directory)
+    pub problem: Option<String>,
+}
+
+/// Batch progress event payload (one per item, then a final summary with done = true)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchProgressEvent {
+    pub batch_id: Option<String>,
+    pub session_id: String,
+    pub operation: String, // "delete" | "chmod" | "upload"
+    pub path: Option<String>,
+    pub error: Option<String>,
+    pub completed: usize,
+    pub failed: usize,
+    pub total: usize,
+    pub done: bool,
+}
+
+/// Records per-item outcomes of a batch and emits progress events
+struct BatchTracker<'a> {
+    app_handle: &'a AppHandle,
+    batch_id: Option<String>,
+    session_id: String,
+    operation: &'static str,
+    total: usize,
+    result: BatchResult,
+}
+
+impl<'a> BatchTracker<'a> {
+    fn new(app_handle: &'a AppHandle, batch_id: Option<String>, session_id: &str, operation: &'static str, total: usize) -> Self {
+        Self {
+            app_handle,
+            batch_id,
+            session_id: session_id.to_string(),
+            operation,
+            total,
+            result: BatchResult::default(),
+        }
+    }
+
+    fn record(&mut self, path: &str, outcome: Result<(), ConnectionError>) {
+        let error = match outcome {
+            Ok(()) => {
+                self.result.succeeded.push(path.to_string());
+                None
+            }
+            Err(e) => {
+                log::warn!("[FileTransfer] Batch {} failed for {}: {}", self.operation, path, e);
+                self.result.failed.push(BatchFailure {
+                    path: path.to_string(),
+                    error: e.to_string(),
+                });
+                Some(e.to_string())
+            }
+        };
+        self.emit(Some(path.to_string()), error, false);
+    }
+
+    fn finish(self) -> BatchResult {
+        log::info!(
+            "[FileTransfer] Batch {} finished: {} succeeded, {} failed",
+            self.operation,
+            self.result.succeeded.len(),
+            self.result.failed.len()
+        );
+        self.emit(None, None, true);
+        self.result
+    }
+
+    fn emit(&self, path: Option<String>, error: Option<String>, done: bool) {
+        let event = BatchProgressEvent {
+            batch_id: self.batch_id.clone(),
+            session_id: self.session_id.clone(),
+            operation: self.operation.to_string(),
+            path,
+            error,
+            completed: self.result.succeeded.len() + self.result.failed.len(),
+            failed: self.result.failed.len(),
+            total: self.total,
+            done,
+        };
+        if let Err(e) = self.app_handle.emit("file-batch-progress", &event) {
+            log::error!("[FileTransfer] Failed to emit batch progress event: {}", e);
+        }
+    }
+}
+
+/// Outcome of sync_directory
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncResult {
+    /// Every planned action, including skips
+    pub actions: Vec<SyncAction>,
+    /// Actions that failed (the rest of the sync still ran)
+    pub failed: Vec<BatchFailure>,
+    /// Nothing was changed (planned actions only)
+    pub dry_run: bool,
+}
+
+/// Directory sync progress event payload (directory-sync-progress)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncProgressEvent {
+    pub sync_id: Option<String>,
+    pub session_id: String,
+    /// Action just performed (None on the final summary)
+    pub action: Option<SyncAction>,
+    pub error: Option<String>,
+    pub completed: usize,
+    pub total: usize,
+    pub bytes_transferred: u64,
+    pub total_bytes: u64,
+    pub done: bool,
+}
+
+/// Outcome of upload_directory
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryUploadResult {
+    /// Remote directory actually created (renamed when the name was taken)
+    pub remote_path: String,
+    pub files_uploaded: usize,
+    pub bytes_uploaded: u64,
+    /// Entries that couldn't be read or uploaded (the rest still ran)
+    pub failed: Vec<BatchFailure>,
+    /// Symlinks left out (SymlinkPolicy::Skip), relative to the uploaded directory
+    pub skipped_symlinks: Vec<String>,
+}
+
+/// Outcome of download_directory
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryDownloadResult {
+    /// Local directory actually created (renamed when the name was taken)
+    pub local_path: String,
+    pub files_downloaded: usize,
+    pub bytes_downloaded: u64,
+    /// Entries that couldn't be listed or downloaded (the rest still ran)
+    pub failed: Vec<BatchFailure>,
+    /// Symlinked directories (not followed) and broken symlinks, relative to the downloaded directory
+    pub skipped_symlinks: Vec<String>,
+}
+
+/// Remote directories nested deeper than this are reported as failed instead of walked
+const MAX_DOWNLOAD_DEPTH: usize = 64;
+
+/// Outcome of calculate_directory_size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectorySize {
+    pub total_bytes: u64,
+    /// Files and symlinks (a symlink counts its own size, not its target's)
+    pub file_count: u64,
+    /// Subdirectories below the measured one (symlinked directories aren't followed)
+    pub directory_count: u64,
+    /// Subdirectories that couldn't be listed (permissions, too deep); their contents aren't counted
+    pub inaccessible: u64,
+}
+
+/// directory-size-progress payload (periodic while scanning, then once with done = true)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DirectorySizeEvent {
+    scan_id: Option<String>,
+    session_id: String,
+    path: String,
+    #[serde(flatten)]
+    size: DirectorySize,
+    done: bool,
+}
+
+/// Directories listed at once when measuring over SFTP
+/// FTP lists one at a time: CWD + LIST share the session's single control connection
+const SIZE_SCAN_CONCURRENCY: usize = 8;
+/// Minimum time between directory-size-progress events
+const SIZE_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Remote tree collected before a directory download (parents before their contents)
+#[derive(Debug, Default)]
+struct RemoteDownloadTree {
+    /// (relative path, is_directory, size)
+    entries: Vec<(String, bool, u64)>,
+    skipped_symlinks: Vec<String>,
+    failed: Vec<BatchFailure>,
+}
+
+/// Deletions planned for a recursive delete, contents before their directories
+#[derive(Debug, Default)]
+struct TreeDeletePlan {
+    /// (absolute path, is_directory)
+    entries: Vec<(String, bool)>,
+    /// Subdirectories that couldn't be listed (their parents will fail to delete too)
+    failed: Vec<BatchFailure>,
+}
+
+/// Cumulative directory transfer progress (directory-upload-progress / directory-download-progress)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DirectoryProgressEvent {
+    pub transfer_id: String,
+    pub session_id: String,
+    /// "upload" | "download"
+    pub direction: &'static str,
+    /// File being transferred (None on the final summary)
+    pub current_file: Option<String>,
+    pub files_done: usize,
+    pub total_files: usize,
+    pub bytes_transferred: u64,
+    pub total_bytes: u64,
+    pub done: bool,
+}
+
+/// One file of a directory transfer finished (directory-upload-file / directory-download-file)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DirectoryFileEvent {
+    pub transfer_id: String,
+    pub session_id: String,
+    pub direction: &'static str,
+    pub local_path: String,
+    pub remote_path: String,
+    /// Path relative to the transferred directory
+    pub relative_path: String,
+    pub size: u64,
+    pub error: Option<String>,
+}
+
+/// Connection lifecycle event payload (file-session-status)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileSessionStatusEvent {
+    /// Set once connected
+    pub session_id: Option<String>,
+    pub connection_type: String,
+    pub hostname: String,
+    pub port: u16,
+    pub status: &'static str, // "connecting" | "authenticating" | "connected" | "failed"
+    pub message: String,
+}
+
+/// Emits file-session-status events for one connection attempt
+struct SessionStatusReporter<'a> {
+    app_handle: &'a AppHandle,
+    connection_type: String,
+    hostname: String,
+    port: u16,
+}
+
+impl<'a> SessionStatusReporter<'a> {
+    fn new(app_handle: &'a AppHandle, config: &FileSessionConfig) -> Self {
+        Self {
+            app_handle,
+            connection_type: config.connection_type.clone(),
+            hostname: config.hostname.clone(),
+            port: config.port,
+        }
+    }
+
+    fn emit(&self, session_id: Option<&str>, status: &'static str, message: String) {
+        let event = FileSessionStatusEvent {
+            session_id: session_id.map(str::to_string),
+            connection_type: self.connection_type.clone(),
+            hostname: self.hostname.clone(),
+            port: self.port,
+            status,
+            message,
+        };
+        if let Err(e) = self.app_handle.emit("file-session-status", &event) {
+            log::error!("[FileTransfer] Failed to emit session status event: {}", e);
+        }
+    }
+}
+
+/// SSH failure while opening an SFTP session, wrapped by `wrap`
+/// Key passphrase problems keep their own code so the file browser can ask for one
+fn ssh_failure(error: SshError, wrap: impl FnOnce(String) -> ConnectionError) -> ConnectionError {
+    match error.code() {
+        code @ (ErrorCode::PassphraseRequired | ErrorCode::PassphraseIncorrect) => {
+            ConnectionError::Categorized { code, message: error.to_string() }
+        }
+        _ => wrap(error.to_string()),
+    }
 }
 
 impl FileTransferManager {
     pub fn new() -> Self {
         Self {
             sessions: Arc::new(Mutex::new(HashMap::new())),
+            chunked_uploads: Arc::new(Mutex::new(HashMap::new())),
+            cancel_flags: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            rate_limits: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Ask a running transfer to stop; it ends within one chunk with ConnectionError::Cancelled
+    /// Returns false when no transfer with that id is running
+    pub fn cancel_transfer(&self, transfer_id: &str) -> bool {
+        let flags = self.cancel_flags.lock().unwrap_or_else(|e| e.into_inner());
+        match flags.get(transfer_id) {
+            Some(flag) => {
+                log::info!("[FileTransfer] Cancelling transfer {}", transfer_id);
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn register_cancel(&self, transfer_id: &str) -> CancelRegistration {
+        let flag = Arc::new(AtomicBool::new(false));
+        self.cancel_flags
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(transfer_id.to_string(), flag.clone());
+        CancelRegistration {
+            flags: self.cancel_flags.clone(),
+            transfer_id: transfer_id.to_string(),
+            flag,
+        }
+    }
+
+    /// Remove what a cancelled download left behind
+    async fn discard_partial_download(local_path: &str) {
+        if let Err(e) = tokio::fs::remove_file(local_path).await {
+            log::warn!("[FileTransfer] Cannot remove partial download {}: {}", local_path, e);
         }
     }
 
     /// Create a new file transfer session
-    pub async fn create_session(&self, config: FileSessionConfig, app_handle: AppHandle) -> Result<String, ConnectionError> {
+    /// Emits file-session-status events (connecting/authenticating/connected/failed)
+    pub async fn create_session(&self, mut config: FileSessionConfig, app_handle: AppHandle) -> Result<String, ConnectionError> {
+        if let Some(connection_type) = ConnectionType::from_name(&config.connection_type) {
+            config.port = resolve_port(config.port, &connection_type);
+        }
         let session_id = uuid::Uuid::new_v4().to_string();
+        let status = SessionStatusReporter::new(&app_handle, &config);
+        let max_bytes_per_sec = config.max_bytes_per_sec.filter(|&rate| rate > 0);
+        status.emit(None, "connecting", format!(
+            "Connecting to {}:{} ({})",
+            config.hostname,
+            config.port,
+            config.connection_type.to_uppercase()
+        ));
+
+        let session = match Self::connect(&session_id, config, &app_handle, &status).await {
+            Ok(session) => session,
+            Err(e) => {
+                status.emit(None, "failed", e.to_string());
+                return Err(e);
+            }
+        };
+
+        if let Some(rate) = max_bytes_per_sec {
+            self.rate_limits.lock().await.insert(session_id.clone(), rate);
+        }
+        let info = ActiveSession::new(
+            &session_id,
+            &status.connection_type.to_ascii_lowercase(),
+            Some(status.hostname.clone()),
+        );
+        let mut sessions = self.sessions.lock().await;
+        sessions.insert(session_id.clone(), OpenFileSession { session, info });
+        
+        log::info!("Created file transfer session: {} (total sessions: {})", session_id, sessions.len());
+        status.emit(Some(&session_id), "connected", format!("Connected to {}", status.hostname));
+        Ok(session_id)
+    }
+
+    /// Open the underlying SFTP/FTP/FTPS connection
+    async fn connect(
+        session_id: &str,
+        config: FileSessionConfig,
+        app_handle: &AppHandle,
+        status: &SessionStatusReporter<'_>,
+    ) -> Result<Arc<dyn FileTransferSession>, ConnectionError> {
+        let session_id = session_id.to_string();
+        let security_policy = config.security_policy.unwrap_or_default();
         
         let session: Arc<dyn FileTransferSession> = match config.connection_type.as_str() {
             "sftp" => {
@@ -104,25 +661,38 @@ impl FileTransferManager {
                     hostname: config.hostname.clone(),
                     port: config.port,
                     username: config.username.clone(),
-                    auth: if let Some(key_path) = config.key_path {
-                        SshAuth::Key { path: key_path, passphrase: None }
+                    auth: if let Some(key_path) = config.key_path.clone() {
+                        SshAuth::Key { path: key_path, passphrase: config.key_passphrase.clone() }
                     } else if let Some(password) = config.password.clone() {
                         SshAuth::Password(password)
                     } else {
                         return Err(ConnectionError::AuthenticationFailed("No auth method provided".to_string()));
                     },
+                    // With both a key and a password, fall back to the password if the key is rejected
+                    fallback_auth: match (&config.key_path, &config.password) {
+                        (Some(_), Some(password)) => vec![SshAuth::Password(password.clone())],
+                        _ => Vec::new(),
+                    },
                     connection_type: ConnectionType::Sftp,
+                    algorithms: config.algorithms.clone().unwrap_or_default(),
+                    security_policy,
+                    connect_timeout_secs: None,
+                    proxy_command: config.proxy_command.clone(),
+                    compression: config.compression,
+                    forward_agent: false,
                 };
 
                 // Check if we need to use chain connection
                 let ssh_handle = if config.jumps.is_empty() {
                     // Direct connection
                     log::info!("SFTP direct connection to {}", target_config.hostname);
-                    let mut handle = connect_direct(&target_config).await
+                    let client = SshClient::new(&target_config).with_events(app_handle.clone());
+                    let mut handle = connect_direct_with(&target_config, client).await
                         .map_err(|e| ConnectionError::ConnectionFailed(e.to_string()))?;
                     
-                    authenticate(&mut handle, &target_config).await
-                        .map_err(|e| ConnectionError::AuthenticationFailed(e.to_string()))?;
+                    status.emit(None, "authenticating", format!("Authenticating as {}", target_config.username));
+                    authenticate(&mut handle, &target_config, Some(app_handle)).await
+                        .map_err(|e| ssh_failure(e, ConnectionError::AuthenticationFailed))?;
                     
                     handle
                 } else {
@@ -138,59 +708,85 @@ impl FileTransferManager {
                     
                     // Create chain handler and execute
                     let chain = HopHandler::from_config(&jumps, &target_config);
-                    chain.execute(None, &app_handle).await
-                        .map_err(|e| ConnectionError::ConnectionFailed(format!("Chain connection failed: {}", e)))?
+                    chain.execute(None, app_handle).await
+                        .map_err(|e| ssh_failure(e, |m| ConnectionError::ConnectionFailed(format!("Chain connection failed: {}", m))))?
                 };
 
                 Arc::new(SftpSession::new(session_id.clone(), ssh_handle).await?)
             }
             "ftp" => {
                 let password = config.password.unwrap_or_default();
+                let data_mode = DataConnectionMode::from_options(config.passive, config.active_bind_address.as_deref())?;
                 Arc::new(FtpSession::new(
                     session_id.clone(),
                     &config.hostname,
                     config.port,
                     &config.username,
                     &password,
-                    false,
+                    None,
+                    data_mode,
                 ).await?)
             }
             "ftps" => {
                 let password = config.password.unwrap_or_default();
+                let data_mode = DataConnectionMode::from_options(config.passive, config.active_bind_address.as_deref())?;
+                let tls = FtpTlsConfig {
+                    policy: security_policy,
+                    known_certs_path: app_handle
+                        .path()
+                        .app_config_dir()
+                        .ok()
+                        .map(|dir| dir.join(KNOWN_CERTS_FILE)),
+                };
                 Arc::new(FtpSession::new(
                     session_id.clone(),
                     &config.hostname,
                     config.port,
                     &config.username,
                     &password,
-                    true,
+                    Some(tls),
+                    data_mode,
                 ).await?)
             }
             other => {
                 return Err(ConnectionError::UnsupportedType(other.to_string()));
             }
         };
-
-        let mut sessions = self.sessions.lock().await;
-        sessions.insert(session_id.clone(), session);
-        
-        log::info!("Created file transfer session: {} (total sessions: {})", session_id, sessions.len());
-        Ok(session_id)
+        Ok(session)
     }
 
     /// Helper: Get session Arc and release lock immediately
     async fn get_session_arc(&self, session_id: &str) -> Option<Arc<dyn FileTransferSession>> {
         let sessions = self.sessions.lock().await;
-        sessions.get(session_id).cloned()
+        sessions.get(session_id).map(|open| open.session.clone())
+    }
+
+    /// Open sessions, oldest first
+    pub async fn list_sessions(&self) -> Vec<ActiveSession> {
+        let mut list: Vec<ActiveSession> = self.sessions.lock().await.values().map(|open| open.info.clone()).collect();
+        list.sort_by_key(|info| info.opened_at);
+        list
+    }
+
+    /// Bandwidth cap for one transfer: the override if given (0 = unlimited), else the session default
+    async fn transfer_limit(&self, session_id: &str, max_bytes_per_sec: Option<u64>) -> Option<u64> {
+        let session_default = self.rate_limits.lock().await.get(session_id).copied();
+        effective_limit(session_default, max_bytes_per_sec)
     }
 
-    /// List directory contents
-    pub async fn list_directory(&self, session_id: &str, path: &str) -> Result<Vec<FileInfoDto>, ConnectionError> {
+    /// List directory contents, with warnings for entries that could not be fully read
+    pub async fn list_directory(&self, session_id: &str, path: &str) -> Result<DirectoryListingDto, ConnectionError> {
         let session = self.get_session_arc(session_id).await
             .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
         
-        let files = session.list_directory(path).await?;
-        Ok(files.into_iter().map(FileInfoDto::from).collect())
+        let listing = session.list_directory(path).await?;
+        for warning in &listing.warnings {
+            log::warn!("[FileTransfer] Listing {}: {} ({})", path, warning.path, warning.message);
+        }
+        Ok(DirectoryListingDto {
+            entries: listing.entries.into_iter().map(FileInfoDto::from).collect(),
+            warnings: listing.warnings,
+        })
     }
 
     fn emit_progress(app_handle: &AppHandle, event: &TransferProgressEvent) {
@@ -200,6 +796,11 @@ impl FileTransferManager {
     }
 
     /// Download file
+    /// With `resume`, an existing partial local file is continued when the
+    /// protocol supports it (SFTP) and the remote file hasn't changed.
+    /// `max_bytes_per_sec` overrides the session's bandwidth cap (0 = unlimited).
+    /// With `verify_checksum`, a file that doesn't match the server's checksum is
+    /// deleted and ChecksumMismatch returned
     pub async fn download_file(
         &self,
         app_handle: &AppHandle,
@@ -207,9 +808,13 @@ impl FileTransferManager {
         remote_path: &str,
         local_path: &str,
         transfer_id: &str,
+        resume: bool,
+        max_bytes_per_sec: Option<u64>,
+        verify_checksum: bool,
     ) -> Result<(), ConnectionError> {
         let session = self.get_session_arc(session_id).await
             .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        let max_bytes_per_sec = self.transfer_limit(session_id, max_bytes_per_sec).await;
 
         let file_info = session.stat(remote_path).await?;
         let total_bytes = file_info.size;
@@ -225,6 +830,8 @@ impl FileTransferManager {
         let app = app_handle.clone();
         let total_bytes_captured = total_bytes;
 
+        let cancel = self.register_cancel(transfer_id);
+        let cancelled = cancel.flag.clone();
         let transfer_id_str = transfer_id.to_string();
         let progress_cb = std::sync::Arc::new(move |bytes: u64, total: u64| {
             let event = TransferProgressEvent {
@@ -237,13 +844,33 @@ impl FileTransferManager {
                 bytes_transferred: bytes,
                 total_bytes: if total > 0 { total } else { total_bytes_captured },
                 done: false,
+                skipped: false,
             };
             Self::emit_progress(&app, &event);
+            !cancelled.load(Ordering::Relaxed)
         });
 
-        session
-            .download_file_with_progress(remote_path, local_path, Some(progress_cb))
-            .await?;
+        let outcome = if resume {
+            session.resume_download_with_progress(remote_path, local_path, Some(progress_cb), max_bytes_per_sec).await
+        } else {
+            session.download_file_with_progress(remote_path, local_path, Some(progress_cb), max_bytes_per_sec).await
+        };
+        drop(cancel);
+        if let Err(e) = outcome {
+            if matches!(e, ConnectionError::Cancelled) {
+                log::info!("[FileTransfer] Download cancelled: {}", file_name);
+                Self::discard_partial_download(local_path).await;
+            }
+            return Err(e);
+        }
+        if verify_checksum {
+            if let Err(e) = Self::verify_download(&session, remote_path, local_path).await {
+                if matches!(e, ConnectionError::ChecksumMismatch(_)) {
+                    Self::discard_partial_download(local_path).await;
+                }
+                return Err(e);
+            }
+        }
 
         let final_event = TransferProgressEvent {
             transfer_id: transfer_id.to_string(),
@@ -255,6 +882,7 @@ impl FileTransferManager {
             bytes_transferred: total_bytes,
             total_bytes,
             done: true,
+            skipped: false,
         };
         Self::emit_progress(app_handle, &final_event);
         log::info!("[FileTransfer] Download completed: {}", file_name);
@@ -262,31 +890,86 @@ impl FileTransferManager {
         Ok(())
     }
     
-    /// Upload file (overwrites existing file if present)
+    /// Compare a downloaded file with the server's checksum (SHA-256, else MD5)
+    /// Skipped with a warning when the server can compute neither
+    async fn verify_download(
+        session: &Arc<dyn FileTransferSession>,
+        remote_path: &str,
+        local_path: &str,
+    ) -> Result<(), ConnectionError> {
+        for algorithm in ChecksumAlgorithm::PREFERRED {
+            let Some(remote) = session.remote_checksum(remote_path, algorithm).await? else {
+                continue;
+            };
+            let path = PathBuf::from(local_path);
+            let local = tokio::task::spawn_blocking(move || hash_local_file(&path, algorithm))
+                .await
+                .map_err(|e| ConnectionError::Unknown(format!("Hashing task failed: {}", e)))?
+                .map_err(|e| ConnectionError::io("Failed to hash local file", &e))?;
+            if local != remote {
+                return Err(ConnectionError::ChecksumMismatch(format!(
+                    "{} of {} is {}, server has {}",
+                    algorithm.name(), local_path, local, remote
+                )));
+            }
+            log::info!("[FileTransfer] Verified {} ({})", local_path, algorithm.name());
+            return Ok(());
+        }
+        log::warn!("[FileTransfer] Cannot verify {}: server has no sha256sum or md5sum", remote_path);
+        Ok(())
+    }
+
+    /// Upload file, resolving a name clash according to `on_conflict`
+    /// When `remote_dir` is given, the destination must resolve inside it.
+    /// `max_bytes_per_sec` overrides the session's bandwidth cap (0 = unlimited)
     pub async fn upload_file(
         &self,
         app_handle: &AppHandle,
         session_id: &str,
         local_path: &str,
         remote_path: &str,
+        remote_dir: Option<&str>,
+        on_conflict: ConflictPolicy,
         transfer_id: &str,
-    ) -> Result<(), ConnectionError> {
+        preserve_attributes: bool,
+        max_bytes_per_sec: Option<u64>,
+    ) -> Result<UploadResult, ConnectionError> {
+        // Filenames may come from arbitrary drag-and-drop sources - don't let them escape the target
+        let requested_path = crate::core::validate_upload_path(remote_path, remote_dir)
+            .map_err(ConnectionError::InvalidPath)?;
+
         let session = self.get_session_arc(session_id).await
             .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        let max_bytes_per_sec = self.transfer_limit(session_id, max_bytes_per_sec).await;
+
+        let final_remote_path = match on_conflict {
+            ConflictPolicy::Overwrite => requested_path,
+            _ if session.stat(&requested_path).await.is_err() => requested_path,
+            ConflictPolicy::Skip => {
+                log::info!("[FileTransfer] Upload skipped, destination exists: {}", requested_path);
+                let event = TransferProgressEvent {
+                    transfer_id: transfer_id.to_string(),
+                    session_id: session_id.to_string(),
+                    direction: "upload".to_string(),
+                    local_path: local_path.to_string(),
+                    remote_path: requested_path.clone(),
+                    file_name: Self::file_name_of(&requested_path),
+                    bytes_transferred: 0,
+                    total_bytes: 0,
+                    done: true,
+                    skipped: true,
+                };
+                Self::emit_progress(app_handle, &event);
+                return Ok(UploadResult { remote_path: requested_path, skipped: true });
+            }
+            ConflictPolicy::Rename => Self::unique_remote_path(&session, &requested_path).await?,
+        };
 
         let meta = tokio::fs::metadata(local_path)
             .await
-            .map_err(|e| ConnectionError::IoError(format!("Failed to stat local file: {}", e)))?;
+            .map_err(|e| ConnectionError::io("Failed to stat local file", &e))?;
         let total_bytes = meta.len();
-        let file_name = std::path::Path::new(remote_path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or(remote_path)
-            .to_string();
-
-        // Normalize path to use forward slashes (Unix-style) for remote paths
-        use crate::core::normalize_remote_path;
-        let final_remote_path = normalize_remote_path(remote_path);
+        let file_name = Self::file_name_of(&final_remote_path);
 
         let file_name_for_final = file_name.clone();
         let file_name_for_cb = file_name.clone();
@@ -299,6 +982,8 @@ impl FileTransferManager {
         let app = app_handle.clone();
         let total_bytes_captured = total_bytes;
 
+        let cancel = self.register_cancel(transfer_id);
+        let cancelled = cancel.flag.clone();
         let transfer_id_str = transfer_id.to_string();
         let progress_cb = std::sync::Arc::new(move |bytes: u64, total: u64| {
             let event = TransferProgressEvent {
@@ -311,13 +996,24 @@ impl FileTransferManager {
                 bytes_transferred: bytes,
                 total_bytes: if total > 0 { total } else { total_bytes_captured },
                 done: false,
+                skipped: false,
             };
             Self::emit_progress(&app, &event);
+            !cancelled.load(Ordering::Relaxed)
         });
 
-        session
-            .upload_file_with_progress(local_path, &final_remote_path, Some(progress_cb))
-            .await?;
+        let outcome = if preserve_attributes {
+            session.upload_file_preserving_attributes(local_path, &final_remote_path, Some(progress_cb), max_bytes_per_sec).await
+        } else {
+            session.upload_file_with_progress(local_path, &final_remote_path, Some(progress_cb), max_bytes_per_sec).await
+        };
+        drop(cancel);
+        if let Err(e) = outcome {
+            if matches!(e, ConnectionError::Cancelled) {
+                log::info!("[FileTransfer] Upload cancelled: {}", file_name);
+            }
+            return Err(e);
+        }
 
         let final_event = TransferProgressEvent {
             transfer_id: transfer_id.to_string(),
@@ -329,26 +1025,339 @@ impl FileTransferManager {
             bytes_transferred: total_bytes,
             total_bytes,
             done: true,
+            skipped: false,
         };
         Self::emit_progress(app_handle, &final_event);
         log::info!("[FileTransfer] Upload completed: {}", file_name_for_final);
 
-        Ok(())
+        Ok(UploadResult { remote_path: final_remote_path, skipped: false })
     }
 
-    /// Create directory
-    pub async fn create_directory(&self, session_id: &str, path: &str) -> Result<(), ConnectionError> {
+    /// Write one chunk of a file streamed from the frontend (no local path)
+    ///
+    /// Chunks must arrive in order: `offset` 0 starts (and truncates) the file,
+    /// each later chunk starts where the previous one ended. `is_last` finishes
+    /// the upload. Returns the number of bytes written so far.
+    pub async fn upload_chunk(
+        &self,
+        app_handle: &AppHandle,
+        session_id: &str,
+        remote_path: &str,
+        data: &[u8],
+        offset: u64,
+        is_last: bool,
+        total_bytes: Option<u64>,
+        transfer_id: &str,
+    ) -> Result<u64, ConnectionError> {
+        let remote_path = crate::core::validate_upload_path(remote_path, None)
+            .map_err(ConnectionError::InvalidPath)?;
         let session = self.get_session_arc(session_id).await
             .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
-        session.create_directory(path).await
-    }
+        let key = (session_id.to_string(), remote_path.clone());
 
-    /// Delete file or directory
-    pub async fn delete(&self, session_id: &str, path: &str, is_directory: bool) -> Result<(), ConnectionError> {
-        let session = self.get_session_arc(session_id).await
-            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
-        session.delete(path, is_directory).await
-    }
+        {
+            let mut uploads = self.chunked_uploads.lock().await;
+            let state = uploads.entry(key.clone()).or_default();
+            if offset == 0 && !state.in_flight {
+                // (Re)start the file
+                *state = ChunkedUpload::default();
+            }
+            if state.in_flight || state.next_offset != offset {
+                let expected = state.next_offset;
+                return Err(ConnectionError::IoError(format!(
+                    "Out-of-order chunk for {}: expected offset {}, got {}",
+                    remote_path, expected, offset
+                )));
+            }
+            state.in_flight = true;
+        }
+
+        if let Err(e) = session.write_chunk(&remote_path, offset, data, is_last).await {
+            self.chunked_uploads.lock().await.remove(&key);
+            return Err(e);
+        }
+
+        let written = offset + data.len() as u64;
+        {
+            let mut uploads = self.chunked_uploads.lock().await;
+            if is_last {
+                uploads.remove(&key);
+            } else if let Some(state) = uploads.get_mut(&key) {
+                *state = ChunkedUpload { next_offset: written, in_flight: false };
+            }
+        }
+
+        let event = TransferProgressEvent {
+            transfer_id: transfer_id.to_string(),
+            session_id: session_id.to_string(),
+            direction: "upload".to_string(),
+            local_path: String::new(),
+            remote_path: remote_path.clone(),
+            file_name: Self::file_name_of(&remote_path),
+            bytes_transferred: written,
+            total_bytes: if is_last { written } else { total_bytes.unwrap_or(0).max(written) },
+            done: is_last,
+            skipped: false,
+        };
+        Self::emit_progress(app_handle, &event);
+        if is_last {
+            log::info!("[FileTransfer] Streamed upload completed: {} ({} bytes)", remote_path, written);
+        }
+
+        Ok(written)
+    }
+
+    /// Last component of a remote path
+    fn file_name_of(remote_path: &str) -> String {
+        remote_path.rsplit('/').next().unwrap_or(remote_path).to_string()
+    }
+
+    /// First `name (N).ext` next to `remote_path` that doesn't exist yet
+    async fn unique_remote_path(
+        session: &Arc<dyn FileTransferSession>,
+        remote_path: &str,
+    ) -> Result<String, ConnectionError> {
+        let (dir, file_name) = match remote_path.rsplit_once('/') {
+            Some((dir, name)) => (format!("{}/", dir), name),
+            None => (String::new(), remote_path),
+        };
+
+        for counter in 1..=MAX_DUPLICATE_COUNTER {
+            let candidate = format!("{}{}", dir, crate::core::generate_unique_filename(file_name, counter));
+            if session.stat(&candidate).await.is_err() {
+                log::info!("[FileTransfer] Destination exists, uploading as {}", candidate);
+                return Ok(candidate);
+            }
+        }
+
+        Err(ConnectionError::InvalidPath(format!("Too many duplicate files: {}", remote_path)))
+    }
+
+    /// Create directory
+    pub async fn create_directory(&self, session_id: &str, path: &str) -> Result<(), ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        session.create_directory(path).await
+    }
+
+    /// Create directory and any missing parents
+    pub async fn create_directory_recursive(&self, session_id: &str, path: &str) -> Result<(), ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        session.create_directory_recursive(path).await
+    }
+
+    /// Delete file or directory
+    pub async fn delete(&self, session_id: &str, path: &str, is_directory: bool) -> Result<(), ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        session.delete(path, is_directory).await
+    }
+
+    /// Upload many files with at most `concurrency` transfers in flight
+    ///
+    /// Every worker shares the session's single connection, so this doesn't add
+    /// bandwidth; it overlaps the per-file open/close round trips that dominate
+    /// many small files. Destinations are overwritten. Emits file-batch-progress
+    /// (operation "upload") as each file finishes; `batch_id` can be passed to
+    /// cancel_transfer to stop the remaining files.
+    pub async fn upload_files(
+        &self,
+        app_handle: &AppHandle,
+        session_id: &str,
+        items: Vec<UploadItem>,
+        concurrency: usize,
+        batch_id: Option<String>,
+    ) -> Result<BatchResult, ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        let max_bytes_per_sec = self.transfer_limit(session_id, None).await;
+
+        let concurrency = concurrency.clamp(1, MAX_UPLOAD_CONCURRENCY);
+        log::info!("[FileTransfer] Uploading {} files, {} at a time", items.len(), concurrency);
+
+        let cancel = batch_id.as_deref().map(|id| self.register_cancel(id));
+        let cancelled = cancel
+            .as_ref()
+            .map(|c| c.flag.clone())
+            .unwrap_or_default();
+
+        let mut tracker = BatchTracker::new(app_handle, batch_id, session_id, "upload", items.len());
+        let mut uploads = Self::spawn_uploads(session, items, concurrency, max_bytes_per_sec, cancelled);
+        while let Some(joined) = uploads.join_next().await {
+            match joined {
+                Ok((remote_path, outcome)) => tracker.record(&remote_path, outcome),
+                Err(e) => log::error!("[FileTransfer] Upload task failed: {}", e),
+            }
+        }
+        Ok(tracker.finish())
+    }
+
+    /// Start one task per file; a semaphore keeps at most `concurrency` of them transferring
+    /// (each capped at `max_bytes_per_sec`)
+    fn spawn_uploads(
+        session: Arc<dyn FileTransferSession>,
+        items: Vec<UploadItem>,
+        concurrency: usize,
+        max_bytes_per_sec: Option<u64>,
+        cancelled: Arc<AtomicBool>,
+    ) -> JoinSet<(String, Result<(), ConnectionError>)> {
+        let permits = Arc::new(Semaphore::new(concurrency));
+        let mut uploads = JoinSet::new();
+
+        for item in items {
+            let session = session.clone();
+            let permits = permits.clone();
+            let cancelled = cancelled.clone();
+            uploads.spawn(async move {
+                let remote_path = match crate::core::validate_upload_path(&item.remote_path, None) {
+                    Ok(path) => path,
+                    Err(e) => return (item.remote_path, Err(ConnectionError::InvalidPath(e))),
+                };
+                // Held until this file is done; the semaphore is never closed
+                let _permit = permits.acquire_owned().await;
+                if cancelled.load(Ordering::Relaxed) {
+                    return (remote_path, Err(ConnectionError::Cancelled));
+                }
+                let progress: ProgressCallback = Arc::new(move |_, _| !cancelled.load(Ordering::Relaxed));
+                let outcome = session
+                    .upload_file_with_progress(&item.local_path, &remote_path, Some(progress), max_bytes_per_sec)
+                    .await;
+                (remote_path, outcome)
+            });
+        }
+        uploads
+    }
+
+    /// Delete many files/directories, continuing past individual failures
+    /// With `dry_run`, nothing is deleted and the planned deletions are returned
+    pub async fn delete_batch(
+        &self,
+        app_handle: &AppHandle,
+        session_id: &str,
+        items: Vec<DeleteItem>,
+        batch_id: Option<String>,
+        dry_run: bool,
+    ) -> Result<BatchResult, ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+
+        if dry_run {
+            let mut planned = Vec::with_capacity(items.len());
+            for item in &items {
+                planned.push(Self::plan_delete(&session, item).await);
+            }
+            return Ok(BatchResult { planned: Some(planned), ..Default::default() });
+        }
+
+        let mut tracker = BatchTracker::new(app_handle, batch_id, session_id, "delete", items.len());
+        for item in &items {
+            let outcome = session.delete(&item.path, item.is_directory).await;
+            tracker.record(&item.path, outcome);
+        }
+        Ok(tracker.finish())
+    }
+
+    /// Delete a file, or a directory and everything in it, continuing past items that fail
+    ///
+    /// The tree is listed first, then deleted depth-first (symlinks are removed, not
+    /// followed). Emits file-batch-progress (operation "delete") per item; `transfer_id`
+    /// can be passed to cancel_transfer, which stops before the next item with
+    /// ConnectionError::Cancelled.
+    pub async fn delete_recursive(
+        &self,
+        app_handle: &AppHandle,
+        session_id: &str,
+        path: &str,
+        transfer_id: Option<String>,
+    ) -> Result<BatchResult, ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        let root = crate::core::normalize_remote_path(path);
+        if root == "/" {
+            return Err(ConnectionError::InvalidPath("Refusing to delete /".to_string()));
+        }
+
+        let cancel = transfer_id.as_deref().map(|id| self.register_cancel(id));
+        let plan = Self::plan_tree_delete(&session, &root).await?;
+        log::info!("[FileTransfer] Deleting {} ({} items)", root, plan.entries.len());
+
+        let mut tracker = BatchTracker::new(
+            app_handle,
+            transfer_id,
+            session_id,
+            "delete",
+            plan.entries.len() + plan.failed.len(),
+        );
+        tracker.result.failed.extend(plan.failed);
+        for (entry, is_directory) in &plan.entries {
+            if cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+                log::info!("[FileTransfer] Delete of {} cancelled", root);
+                tracker.finish();
+                return Err(ConnectionError::Cancelled);
+            }
+            let outcome = session.delete(entry, *is_directory).await;
+            tracker.record(entry, outcome);
+        }
+        Ok(tracker.finish())
+    }
+
+    /// List what a recursive delete of `root` removes, children before parents
+    async fn plan_tree_delete(
+        session: &Arc<dyn FileTransferSession>,
+        root: &str,
+    ) -> Result<TreeDeletePlan, ConnectionError> {
+        let info = session.stat(root).await?;
+        if !info.is_directory || info.is_symlink {
+            return Ok(TreeDeletePlan { entries: vec![(root.to_string(), false)], failed: Vec::new() });
+        }
+
+        // Refuses a listing of some other directory (FTP after a failed CWD)
+        let tree = Self::scan_remote_download(session, root).await?;
+        let absolute = |relative: &str| format!("{}/{}", root.trim_end_matches('/'), relative);
+
+        // Skipped symlinks (to directories, or broken) are plain entries to delete
+        let mut entries: Vec<(String, bool)> = tree.skipped_symlinks.iter().map(|p| (absolute(p), false)).collect();
+        // The scan lists parents before their contents
+        entries.extend(tree.entries.iter().rev().map(|(p, is_dir, _)| (absolute(p), *is_dir)));
+        entries.push((root.to_string(), true));
+
+        let failed = tree
+            .failed
+            .into_iter()
+            .map(|f| BatchFailure { path: absolute(&f.path), error: f.error })
+            .collect();
+        Ok(TreeDeletePlan { entries, failed })
+    }
+
+    /// What deleting one item would do (delete is not recursive, so a non-empty directory would fail)
+    async fn plan_delete(session: &Arc<dyn FileTransferSession>, item: &DeleteItem) -> PlannedChange {
+        let mut change = PlannedChange {
+            path: item.path.clone(),
+            is_directory: item.is_directory,
+            action: "delete",
+            detail: None,
+            problem: None,
+        };
+
+        if let Err(e) = session.stat(&item.path).await {
+            change.problem = Some(e.to_string());
+            return change;
+        }
+        if item.is_directory {
+            match session.list_directory(&item.path).await {
+                Ok(listing) => {
+                    let count = listing.entries.iter().filter(|e| e.name != "." && e.name != "..").count();
+                    if count > 0 {
+                        change.detail = Some(format!("{} entries", count));
+                        change.problem = Some("Directory is not empty".to_string());
+                    }
+                }
+                Err(e) => change.problem = Some(e.to_string()),
+            }
+        }
+        change
+    }
 
     /// Rename file or directory
     pub async fn rename(&self, session_id: &str, old_path: &str, new_path: &str) -> Result<(), ConnectionError> {
@@ -357,13 +1366,91 @@ impl FileTransferManager {
         session.rename(old_path, new_path).await
     }
 
-    /// Change file permissions (SFTP only)
+    /// Change file permissions (FTP via SITE CHMOD where the server supports it)
     pub async fn chmod(&self, session_id: &str, path: &str, mode: u32) -> Result<(), ConnectionError> {
         let session = self.get_session_arc(session_id).await
             .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
         session.chmod(path, mode).await
     }
 
+    /// Create a symlink at `link_path` pointing to `target` (SFTP only)
+    /// `target` is stored as given, so a relative target resolves from the link's directory
+    pub async fn create_symlink(&self, session_id: &str, target: &str, link_path: &str) -> Result<(), ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        if target.is_empty() {
+            return Err(ConnectionError::InvalidPath("Symlink target is empty".to_string()));
+        }
+        session.create_symlink(target, link_path).await
+    }
+
+    /// Size and free space of the filesystem holding `path` (SFTP only)
+    pub async fn statfs(&self, session_id: &str, path: &str) -> Result<FilesystemStats, ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        session.statfs(path).await
+    }
+
+    /// Operations the session supports (chmod, symlinks, exec, ...)
+    pub async fn capabilities(&self, session_id: &str) -> Result<SessionCapabilities, ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        Ok(session.capabilities())
+    }
+
+    /// Run a shell command on the session's host (SFTP only), e.g. to extract an archive in place
+    pub async fn run_command(&self, session_id: &str, command: &str) -> Result<CommandOutput, ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        log::info!("[FileTransfer] Running command on {}: {}", session_id, command);
+        let (stdout, exit_code) = session.run_command(command).await?;
+        Ok(CommandOutput { stdout, exit_code })
+    }
+
+    /// Start a command on the session's host (SFTP only), returning its channel for streaming output
+    pub async fn exec_channel(&self, session_id: &str, command: &str) -> Result<Channel<Msg>, ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        session.exec_channel(command).await
+    }
+
+    /// Change permissions of many files, continuing past individual failures
+    /// With `dry_run`, nothing is changed and current -> new permissions are returned
+    pub async fn chmod_batch(
+        &self,
+        app_handle: &AppHandle,
+        session_id: &str,
+        items: Vec<ChmodItem>,
+        batch_id: Option<String>,
+        dry_run: bool,
+    ) -> Result<BatchResult, ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+
+        if dry_run {
+            let mut planned = Vec::with_capacity(items.len());
+            for item in &items {
+                let (is_directory, detail, problem) = match session.stat(&item.path).await {
+                    Ok(info) => (
+                        info.is_directory,
+                        Some(format!("{} -> {:o}", info.permissions.as_deref().unwrap_or("?"), item.mode)),
+                        None,
+                    ),
+                    Err(e) => (false, None, Some(e.to_string())),
+                };
+                planned.push(PlannedChange { path: item.path.clone(), is_directory, action: "chmod", detail, problem });
+            }
+            return Ok(BatchResult { planned: Some(planned), ..Default::default() });
+        }
+
+        let mut tracker = BatchTracker::new(app_handle, batch_id, session_id, "chmod", items.len());
+        for item in &items {
+            let outcome = session.chmod(&item.path, item.mode).await;
+            tracker.record(&item.path, outcome);
+        }
+        Ok(tracker.finish())
+    }
+
     /// Get file info/metadata
     pub async fn stat(&self, session_id: &str, path: &str) -> Result<FileInfoDto, ConnectionError> {
         let session = self.get_session_arc(session_id).await
@@ -373,10 +1460,17 @@ impl FileTransferManager {
     }
 
     /// Read file content (for small files)
-    pub async fn read_file(&self, session_id: &str, path: &str) -> Result<Vec<u8>, ConnectionError> {
+    pub async fn read_file(&self, session_id: &str, path: &str, max_bytes: Option<u64>) -> Result<Vec<u8>, ConnectionError> {
         let session = self.get_session_arc(session_id).await
             .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
-        session.read_file(path).await
+        session.read_file(path, max_bytes).await
+    }
+
+    /// Read a window of a file (at most MAX_RANGE_LENGTH bytes), for paging through large files
+    pub async fn read_file_range(&self, session_id: &str, path: &str, offset: u64, length: u64) -> Result<FileRange, ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        session.read_file_range(path, offset, length.min(MAX_RANGE_LENGTH)).await
     }
 
     /// Write file content
@@ -395,93 +1489,1047 @@ impl FileTransferManager {
         } else {
             log::warn!("[FileTransfer] close_session: session not found: {}", session_id);
         }
+        drop(sessions);
+        self.chunked_uploads.lock().await.retain(|(id, _), _| id != session_id);
+        self.rate_limits.lock().await.remove(session_id);
         Ok(())
     }
 
-    /// Copy file/directory on remote (uses download+upload since SFTP/FTP don't have native copy)
-    pub async fn copy_remote(
+    /// Close all sessions, returning how many were closed
+    pub async fn close_all_sessions(&self) -> usize {
+        let closed = self.sessions.lock().await.drain().count();
+        self.chunked_uploads.lock().await.clear();
+        self.rate_limits.lock().await.clear();
+        log::info!("[FileTransfer] Closed all {} file sessions", closed);
+        closed
+    }
+
+    /// Mirror a local directory to a remote one (one-way, rsync-lite)
+    ///
+    /// Uploads new and changed files, creates missing directories and, with
+    /// `options.delete`, removes remote paths absent locally. With
+    /// `options.dry_run` only the plan is returned. Emits directory-sync-progress
+    /// per action; a failed action is recorded and the sync continues.
+    pub async fn sync_directory(
         &self,
+        app_handle: &AppHandle,
         session_id: &str,
-        source_path: &str,
-        dest_path: &str,
-    ) -> Result<(), ConnectionError> {
+        local_dir: &str,
+        remote_dir: &str,
+        options: SyncOptions,
+        sync_id: Option<String>,
+    ) -> Result<SyncResult, ConnectionError> {
         let session = self.get_session_arc(session_id).await
             .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        let max_bytes_per_sec = self.transfer_limit(session_id, None).await;
 
-        // Check if source is a file or directory
-        let source_info = session.stat(source_path).await?;
-
-        if source_info.is_directory {
-            // Recursive directory copy
-            self.copy_remote_directory_recursive(session_id, source_path, dest_path).await
+        let local = scan_local_tree(Path::new(local_dir))
+            .await
+            .map_err(|e| ConnectionError::io(format!("Failed to read {}", local_dir), &e))?;
+        let remote_exists = session.stat(remote_dir).await.is_ok();
+        let remote = if remote_exists {
+            Self::scan_remote_tree(&session, remote_dir).await?
         } else {
-            // Single file copy (download to temp + upload)
-            self.copy_remote_file(session_id, source_path, dest_path).await
+            SyncTree::new()
+        };
+
+        let actions = plan_sync(&local, &remote, options.delete);
+        if options.dry_run {
+            return Ok(SyncResult { actions, failed: Vec::new(), dry_run: true });
         }
+
+        if !remote_exists {
+            session.create_directory(remote_dir).await?;
+        }
+
+        let total_bytes: u64 = actions.iter().map(|a| a.size).sum();
+        let mut bytes_transferred = 0;
+        let mut failed = Vec::new();
+        let mut event = SyncProgressEvent {
+            sync_id,
+            session_id: session_id.to_string(),
+            action: None,
+            error: None,
+            completed: 0,
+            total: actions.len(),
+            bytes_transferred: 0,
+            total_bytes,
+            done: false,
+        };
+
+        for action in &actions {
+            let remote_path = format!("{}/{}", remote_dir.trim_end_matches('/'), action.path);
+            let outcome = match action.action {
+                SyncActionKind::Skip => Ok(()),
+                SyncActionKind::Mkdir => session.create_directory(&remote_path).await,
+                SyncActionKind::Delete => session.delete(&remote_path, action.is_directory).await,
+                SyncActionKind::Upload => {
+                    let local_path = Path::new(local_dir).join(&action.path);
+                    session
+                        .upload_file_with_progress(&local_path.to_string_lossy(), &remote_path, None, max_bytes_per_sec)
+                        .await
+                }
+            };
+
+            event.error = match outcome {
+                Ok(()) => {
+                    bytes_transferred += action.size;
+                    None
+                }
+                Err(e) => {
+                    log::warn!("[Sync] {:?} failed for {}: {}", action.action, remote_path, e);
+                    failed.push(BatchFailure { path: action.path.clone(), error: e.to_string() });
+                    Some(e.to_string())
+                }
+            };
+            event.action = Some(action.clone());
+            event.completed += 1;
+            event.bytes_transferred = bytes_transferred;
+            Self::emit_sync_progress(app_handle, &event);
+        }
+
+        log::info!(
+            "[Sync] {} -> {}: {} actions, {} failed",
+            local_dir,
+            remote_dir,
+            actions.len(),
+            failed.len()
+        );
+        event.action = None;
+        event.error = None;
+        event.done = true;
+        Self::emit_sync_progress(app_handle, &event);
+
+        Ok(SyncResult { actions, failed, dry_run: false })
     }
 
-    /// Copy single remote file (download to temp, upload to destination)
-    async fn copy_remote_file(
+    /// Upload a local directory tree into `remote_dir`
+    ///
+    /// The top-level directory gets a `name (N)` suffix when it already exists
+    /// remotely; nested entries are written as-is inside it. Emits
+    /// directory-upload-progress (files and bytes so far) and one
+    /// directory-upload-file per finished file. A failed entry is recorded and
+    /// the upload continues.
+    pub async fn upload_directory(
         &self,
+        app_handle: &AppHandle,
         session_id: &str,
-        source_path: &str,
-        dest_path: &str,
-    ) -> Result<(), ConnectionError> {
-        use tempfile::NamedTempFile;
+        local_dir: &str,
+        remote_dir: &str,
+        symlinks: SymlinkPolicy,
+        transfer_id: &str,
+    ) -> Result<DirectoryUploadResult, ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        let max_bytes_per_sec = self.transfer_limit(session_id, None).await;
+
+        let local_root = Path::new(local_dir);
+        if !local_root.is_dir() {
+            return Err(ConnectionError::InvalidPath(format!("Not a local directory: {}", local_dir)));
+        }
+        let dir_name = local_root
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| ConnectionError::InvalidPath(format!("Cannot upload {}", local_dir)))?;
+
+        let requested = format!("{}/{}", remote_dir.trim_end_matches('/'), dir_name);
+        let requested = crate::core::validate_upload_path(&requested, Some(remote_dir))
+            .map_err(ConnectionError::InvalidPath)?;
+        let remote_root = if session.stat(&requested).await.is_ok() {
+            Self::unique_remote_path(&session, &requested).await?
+        } else {
+            requested
+        };
+
+        let root = local_root.to_path_buf();
+        let tree = tokio::task::spawn_blocking(move || walk_upload_tree(&root, symlinks))
+            .await
+            .map_err(|e| ConnectionError::IoError(format!("Failed to read {}: {}", local_dir, e)))?;
+
+        log::info!(
+            "[FileTransfer] Uploading directory {} -> {} ({} files, {} bytes)",
+            local_dir,
+            remote_root,
+            tree.file_count(),
+            tree.total_bytes()
+        );
+        session.create_directory(&remote_root).await?;
+
+        let mut failed: Vec<BatchFailure> = tree
+            .errors
+            .iter()
+            .map(|(path, error)| BatchFailure { path: path.clone(), error: error.clone() })
+            .collect();
+        let mut progress = DirectoryProgressEvent {
+            transfer_id: transfer_id.to_string(),
+            session_id: session_id.to_string(),
+            direction: "upload",
+            current_file: None,
+            files_done: 0,
+            total_files: tree.file_count(),
+            bytes_transferred: 0,
+            total_bytes: tree.total_bytes(),
+            done: false,
+        };
+        Self::emit_directory_progress(app_handle, &progress);
+
+        let cancel = self.register_cancel(transfer_id);
+        for entry in &tree.entries {
+            if cancel.is_cancelled() {
+                log::info!("[FileTransfer] Directory upload {} cancelled", remote_root);
+                return Err(ConnectionError::Cancelled);
+            }
+            let remote_path = format!("{}/{}", remote_root, entry.relative);
+
+            if entry.is_directory {
+                if let Err(e) = session.create_directory(&remote_path).await {
+                    log::warn!("[FileTransfer] Cannot create {}: {}", remote_path, e);
+                    failed.push(BatchFailure { path: entry.relative.clone(), error: e.to_string() });
+                }
+                continue;
+            }
+
+            // Bytes within the current file on top of everything already uploaded
+            let app = app_handle.clone();
+            let mut file_progress = progress.clone();
+            file_progress.current_file = Some(entry.relative.clone());
+            let base_bytes = progress.bytes_transferred;
+            let cancelled = cancel.flag.clone();
+            let progress_cb = Arc::new(move |bytes: u64, _total: u64| {
+                let mut event = file_progress.clone();
+                event.bytes_transferred = base_bytes + bytes;
+                Self::emit_directory_progress(&app, &event);
+                !cancelled.load(Ordering::Relaxed)
+            });
+
+            let local_path = entry.local_path.to_string_lossy().to_string();
+            let outcome = session
+                .upload_file_with_progress(&local_path, &remote_path, Some(progress_cb), max_bytes_per_sec)
+                .await;
+            if let Err(ConnectionError::Cancelled) = outcome {
+                log::info!("[FileTransfer] Directory upload {} cancelled", remote_root);
+                return Err(ConnectionError::Cancelled);
+            }
+
+            let error = outcome.err().map(|e| {
+                log::warn!("[FileTransfer] Upload failed for {}: {}", remote_path, e);
+                failed.push(BatchFailure { path: entry.relative.clone(), error: e.to_string() });
+                e.to_string()
+            });
+            if error.is_none() {
+                progress.files_done += 1;
+                progress.bytes_transferred += entry.size;
+            }
+
+            let file_event = DirectoryFileEvent {
+                transfer_id: transfer_id.to_string(),
+                session_id: session_id.to_string(),
+                direction: "upload",
+                local_path,
+                remote_path,
+                relative_path: entry.relative.clone(),
+                size: entry.size,
+                error,
+            };
+            Self::emit_directory_file(app_handle, &file_event);
+            progress.current_file = Some(entry.relative.clone());
+            Self::emit_directory_progress(app_handle, &progress);
+        }
+
+        progress.current_file = None;
+        progress.done = true;
+        Self::emit_directory_progress(app_handle, &progress);
+        log::info!(
+            "[FileTransfer] Directory upload {} finished: {} files, {} failed",
+            remote_root,
+            progress.files_done,
+            failed.len()
+        );
 
-        // Create temp file
-        let temp_file = NamedTempFile::new()
-            .map_err(|e| ConnectionError::IoError(format!("Failed to create temp file: {}", e)))?;
-        let temp_path = temp_file.path().to_str().unwrap();
+        Ok(DirectoryUploadResult {
+            remote_path: remote_root,
+            files_uploaded: progress.files_done,
+            bytes_uploaded: progress.bytes_transferred,
+            failed,
+            skipped_symlinks: tree.skipped_symlinks,
+        })
+    }
 
-        // Get session
+    /// Download a remote directory tree into `local_dir`
+    ///
+    /// The remote tree is listed first (breadth-first, at most MAX_DOWNLOAD_DEPTH
+    /// levels) so progress has an accurate byte total. The top-level directory
+    /// gets a `name (N)` suffix when it already exists locally. Symlinked files
+    /// are downloaded; symlinked directories are not followed (loops) and broken
+    /// symlinks are skipped. Emits directory-download-progress and one
+    /// directory-download-file per finished file.
+    pub async fn download_directory(
+        &self,
+        app_handle: &AppHandle,
+        session_id: &str,
+        remote_dir: &str,
+        local_dir: &str,
+        transfer_id: &str,
+    ) -> Result<DirectoryDownloadResult, ConnectionError> {
         let session = self.get_session_arc(session_id).await
             .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        let max_bytes_per_sec = self.transfer_limit(session_id, None).await;
 
-        // Download source to temp (without progress)
-        session.download_file_with_progress(source_path, temp_path, None).await?;
+        let remote_root = remote_dir.trim_end_matches('/');
+        let dir_name = Self::file_name_of(remote_root);
+        if dir_name.is_empty() || dir_name == "." || dir_name == ".." {
+            return Err(ConnectionError::InvalidPath(format!("Cannot download {}", remote_dir)));
+        }
+        let local_root = Self::unique_local_path(&Path::new(local_dir).join(&dir_name))?;
 
-        // Upload temp to destination (without progress)
-        session.upload_file_with_progress(temp_path, dest_path, None).await?;
+        let tree = Self::scan_remote_download(&session, remote_root).await?;
+        let total_files = tree.entries.iter().filter(|(_, is_dir, _)| !is_dir).count();
+        let total_bytes: u64 = tree.entries.iter().map(|(_, _, size)| size).sum();
+        log::info!(
+            "[FileTransfer] Downloading directory {} -> {} ({} files, {} bytes)",
+            remote_root,
+            local_root.display(),
+            total_files,
+            total_bytes
+        );
 
-        Ok(())
+        tokio::fs::create_dir_all(&local_root)
+            .await
+            .map_err(|e| ConnectionError::IoError(format!("Failed to create {}: {}", local_root.display(), e)))?;
+
+        let mut failed = tree.failed;
+        let mut progress = DirectoryProgressEvent {
+            transfer_id: transfer_id.to_string(),
+            session_id: session_id.to_string(),
+            direction: "download",
+            current_file: None,
+            files_done: 0,
+            total_files,
+            bytes_transferred: 0,
+            total_bytes,
+            done: false,
+        };
+        Self::emit_directory_progress(app_handle, &progress);
+
+        let cancel = self.register_cancel(transfer_id);
+        for (relative, is_directory, size) in &tree.entries {
+            if cancel.is_cancelled() {
+                log::info!("[FileTransfer] Directory download {} cancelled", remote_root);
+                return Err(ConnectionError::Cancelled);
+            }
+            let remote_path = format!("{}/{}", remote_root, relative);
+            let local_path = local_root.join(relative);
+
+            if *is_directory {
+                if let Err(e) = tokio::fs::create_dir_all(&local_path).await {
+                    log::warn!("[FileTransfer] Cannot create {}: {}", local_path.display(), e);
+                    failed.push(BatchFailure { path: relative.clone(), error: e.to_string() });
+                }
+                continue;
+            }
+
+            let app = app_handle.clone();
+            let mut file_progress = progress.clone();
+            file_progress.current_file = Some(relative.clone());
+            let base_bytes = progress.bytes_transferred;
+            let cancelled = cancel.flag.clone();
+            let progress_cb = Arc::new(move |bytes: u64, _total: u64| {
+                let mut event = file_progress.clone();
+                event.bytes_transferred = base_bytes + bytes;
+                Self::emit_directory_progress(&app, &event);
+                !cancelled.load(Ordering::Relaxed)
+            });
+
+            let local_path = local_path.to_string_lossy().to_string();
+            let outcome = session
+                .download_file_with_progress(&remote_path, &local_path, Some(progress_cb), max_bytes_per_sec)
+                .await;
+            if let Err(ConnectionError::Cancelled) = outcome {
+                log::info!("[FileTransfer] Directory download {} cancelled", remote_root);
+                Self::discard_partial_download(&local_path).await;
+                return Err(ConnectionError::Cancelled);
+            }
+
+            let error = outcome.err().map(|e| {
+                log::warn!("[FileTransfer] Download failed for {}: {}", remote_path, e);
+                failed.push(BatchFailure { path: relative.clone(), error: e.to_string() });
+                e.to_string()
+            });
+            if error.is_none() {
+                progress.files_done += 1;
+                progress.bytes_transferred += size;
+            }
+
+            let file_event = DirectoryFileEvent {
+                transfer_id: transfer_id.to_string(),
+                session_id: session_id.to_string(),
+                direction: "download",
+                local_path,
+                remote_path,
+                relative_path: relative.clone(),
+                size: *size,
+                error,
+            };
+            Self::emit_directory_file(app_handle, &file_event);
+            progress.current_file = Some(relative.clone());
+            Self::emit_directory_progress(app_handle, &progress);
+        }
+
+        progress.current_file = None;
+        progress.done = true;
+        Self::emit_directory_progress(app_handle, &progress);
+        log::info!(
+            "[FileTransfer] Directory download {} finished: {} files, {} failed",
+            remote_root,
+            progress.files_done,
+            failed.len()
+        );
+
+        Ok(DirectoryDownloadResult {
+            local_path: local_root.to_string_lossy().to_string(),
+            files_downloaded: progress.files_done,
+            bytes_downloaded: progress.bytes_transferred,
+            failed,
+            skipped_symlinks: tree.skipped_symlinks,
+        })
+    }
+
+    /// List a remote tree for download, one directory at a time (no recursion)
+    ///
+    /// SFTP and FTP listings fall back to another directory when the requested
+    /// one can't be opened (FTP lists the current directory after a failed CWD),
+    /// so a listing whose entries aren't inside the requested directory is
+    /// reported as a failure rather than walked.
+    async fn scan_remote_download(
+        session: &Arc<dyn FileTransferSession>,
+        root: &str,
+    ) -> Result<RemoteDownloadTree, ConnectionError> {
+        let mut tree = RemoteDownloadTree::default();
+        let mut pending = std::collections::VecDeque::from([(String::new(), 0usize)]);
+
+        while let Some((prefix, depth)) = pending.pop_front() {
+            let dir = if prefix.is_empty() { root.to_string() } else { format!("{}/{}", root, prefix) };
+            let listing = match session.list_directory(&dir).await {
+                Ok(listing) => listing,
+                Err(e) if prefix.is_empty() => return Err(e),
+                Err(e) => {
+                    tree.failed.push(BatchFailure { path: prefix, error: e.to_string() });
+                    continue;
+                }
+            };
+            for warning in listing.warnings {
+                tree.failed.push(BatchFailure { path: warning.path, error: warning.message });
+            }
+
+            let expected_parent = format!("{}/", dir);
+            if listing.entries.iter().any(|e| !e.path.starts_with(&expected_parent)) {
+                let error = ConnectionError::InvalidPath(format!("Cannot open directory {}", dir));
+                if prefix.is_empty() {
+                    return Err(error);
+                }
+                tree.failed.push(BatchFailure { path: prefix, error: error.to_string() });
+                continue;
+            }
+
+            for entry in listing.entries {
+                if entry.name.is_empty()
+                    || entry.name == "."
+                    || entry.name == ".."
+                    || entry.name.contains(['/', '\\'])
+                {
+                    continue;
+                }
+                let relative = if prefix.is_empty() {
+                    entry.name.clone()
+                } else {
+                    format!("{}/{}", prefix, entry.name)
+                };
+
+                if entry.is_symlink {
+                    if entry.is_directory {
+                        log::info!("[FileTransfer] Not following symlinked directory {}", entry.path);
+                        tree.skipped_symlinks.push(relative);
+                        continue;
+                    }
+                    // Listings can't tell a broken link from a link to a file; stat follows the link
+                    match session.stat(&entry.path).await {
+                        Ok(target) if !target.is_directory => {
+                            tree.entries.push((relative, false, target.size));
+                        }
+                        _ => {
+                            log::info!("[FileTransfer] Skipping broken symlink {}", entry.path);
+                            tree.skipped_symlinks.push(relative);
+                        }
+                    }
+                    continue;
+                }
+
+                if entry.is_directory {
+                    if depth + 1 >= MAX_DOWNLOAD_DEPTH {
+                        tree.failed.push(BatchFailure {
+                            path: relative,
+                            error: format!("Nested deeper than {} levels", MAX_DOWNLOAD_DEPTH),
+                        });
+                        continue;
+                    }
+                    pending.push_back((relative.clone(), depth + 1));
+                    tree.entries.push((relative, true, 0));
+                } else {
+                    tree.entries.push((relative, false, entry.size));
+                }
+            }
+        }
+
+        Ok(tree)
+    }
+
+    /// First `name (N)` next to `path` that doesn't exist locally yet
+    fn unique_local_path(path: &Path) -> Result<std::path::PathBuf, ConnectionError> {
+        if !path.exists() {
+            return Ok(path.to_path_buf());
+        }
+        let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        for counter in 1..=MAX_DUPLICATE_COUNTER {
+            let candidate = path.with_file_name(crate::core::generate_unique_filename(&file_name, counter));
+            if !candidate.exists() {
+                return Ok(candidate);
+            }
+        }
+        Err(ConnectionError::InvalidPath(format!("Too many duplicate files: {}", path.display())))
+    }
+
+    fn emit_directory_progress(app_handle: &AppHandle, event: &DirectoryProgressEvent) {
+        let name = format!("directory-{}-progress", event.direction);
+        if let Err(e) = app_handle.emit(&name, event) {
+            log::error!("[FileTransfer] Failed to emit {} event: {}", name, e);
+        }
+    }
+
+    fn emit_directory_file(app_handle: &AppHandle, event: &DirectoryFileEvent) {
+        let name = format!("directory-{}-file", event.direction);
+        if let Err(e) = app_handle.emit(&name, event) {
+            log::error!("[FileTransfer] Failed to emit {} event: {}", name, e);
+        }
+    }
+
+    fn emit_directory_size(app_handle: &AppHandle, event: &DirectorySizeEvent) {
+        if let Err(e) = app_handle.emit("directory-size-progress", event) {
+            log::error!("[FileTransfer] Failed to emit directory size event: {}", e);
+        }
+    }
+
+    fn emit_sync_progress(app_handle: &AppHandle, event: &SyncProgressEvent) {
+        if let Err(e) = app_handle.emit("directory-sync-progress", event) {
+            log::error!("[FileTransfer] Failed to emit sync progress event: {}", e);
+        }
     }
 
-    /// Recursive directory copy (list + copy each file/subdir)
-    async fn copy_remote_directory_recursive(
+    /// Snapshot a remote directory tree (mtime only when the server reports Unix seconds)
+    /// Entries the listing couldn't read are kept as skipped so the sync leaves them alone
+    async fn scan_remote_tree(
+        session: &Arc<dyn FileTransferSession>,
+        root: &str,
+    ) -> Result<SyncTree, ConnectionError> {
+        let mut tree = SyncTree::new();
+        let mut pending = vec![String::new()];
+
+        while let Some(prefix) = pending.pop() {
+            let dir = if prefix.is_empty() {
+                root.to_string()
+            } else {
+                format!("{}/{}", root.trim_end_matches('/'), prefix)
+            };
+            let listing = session.list_directory(&dir).await?;
+            let dir_prefix = format!("{}/", dir.trim_end_matches('/'));
+            for warning in listing.warnings {
+                log::warn!("[Sync] Skipping {}: {}", warning.path, warning.message);
+                // An unparsable FTP line has no path; nothing is known about it to upload over or delete
+                if let Some(name) = warning.path.strip_prefix(&dir_prefix) {
+                    let relative = if prefix.is_empty() { name.to_string() } else { format!("{}/{}", prefix, name) };
+                    tree.insert(relative, SyncEntry::skipped(warning.message));
+                }
+            }
+
+            for entry in listing.entries {
+                if entry.name == "." || entry.name == ".." || entry.is_symlink {
+                    continue;
+                }
+                let relative = if prefix.is_empty() {
+                    entry.name.clone()
+                } else {
+                    format!("{}/{}", prefix, entry.name)
+                };
+                if entry.is_directory {
+                    pending.push(relative.clone());
+                }
+                tree.insert(relative, SyncEntry {
+                    is_directory: entry.is_directory,
+                    size: if entry.is_directory { 0 } else { entry.size },
+                    mtime: entry.modified.as_deref().and_then(|m| m.parse().ok()),
+                    skipped: None,
+                });
+            }
+        }
+
+        Ok(tree)
+    }
+
+    /// Move file/directory on remote
+    ///
+    /// Tries a rename first. When that fails, the destination doesn't exist and
+    /// statvfs shows source and destination on different filesystems, the source
+    /// is copied and only deleted once the copy has fully succeeded; a failed copy
+    /// is removed again and the source is left untouched.
+    pub async fn move_remote(
         &self,
         session_id: &str,
-        source_dir: &str,
-        dest_dir: &str,
+        source_path: &str,
+        dest_path: &str,
     ) -> Result<(), ConnectionError> {
         let session = self.get_session_arc(session_id).await
             .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
 
-        // Create destination directory
-        session.create_directory(dest_dir).await?;
+        let source = crate::core::normalize_remote_path(source_path);
+        let dest = crate::core::normalize_remote_path(dest_path);
+        if crate::core::path_utils::is_within_directory(&dest, &source) {
+            return Err(ConnectionError::InvalidPath(format!("Cannot move {} into itself ({})", source, dest)));
+        }
 
-        // List source directory
-        let entries = session.list_directory(source_dir).await?;
+        match session.rename(&source, &dest).await {
+            Ok(()) => return Ok(()),
+            // SFTP reports EXDEV as the generic failure also used for e.g. an existing destination
+            Err(e) if e.code() == ErrorCode::RemoteError && Self::crosses_filesystems(&session, &source, &dest).await => {
+                log::info!("[FileTransfer] Rename {} -> {} crosses filesystems ({}), copying instead", source, dest, e);
+            }
+            Err(e) => return Err(e),
+        }
+
+        // Deleting the source after a copy that skipped entries would lose them
+        let copied = session.copy(&source, &dest).await.and_then(|skipped| {
+            if skipped.is_empty() {
+                return Ok(());
+            }
+            Err(ConnectionError::Unknown(format!(
+                "Cannot move {}: {}",
+                source,
+                skipped.iter().map(|w| format!("{} ({})", w.path, w.message)).collect::<Vec<_>>().join(", ")
+            )))
+        });
+        if let Err(e) = copied {
+            log::warn!("[FileTransfer] Copy {} -> {} failed, removing partial copy: {}", source, dest, e);
+            if let Err(cleanup) = Self::delete_tree(&session, &dest).await {
+                log::warn!("[FileTransfer] Cannot remove partial copy {}: {}", dest, cleanup);
+            }
+            return Err(e);
+        }
+
+        Self::delete_tree(&session, &source).await.map_err(|e| {
+            ConnectionError::Unknown(format!("Copied to {} but could not remove {}: {}", dest, source, e))
+        })
+    }
+
+    /// Whether moving `source` to a not yet existing `dest` changes filesystem
+    /// False when the server can't tell, so an unexplained rename failure is reported as is
+    async fn crosses_filesystems(session: &Arc<dyn FileTransferSession>, source: &str, dest: &str) -> bool {
+        if session.stat(dest).await.is_ok() {
+            return false;
+        }
+        let source_fs = session.filesystem_id(source).await;
+        let dest_fs = session.filesystem_id(crate::core::permissions::parent_directory(dest)).await;
+        matches!((source_fs, dest_fs), (Ok(Some(from)), Ok(Some(to))) if from != to)
+    }
 
-        for entry in entries {
-            // Skip . and ..
+    /// Delete a file, or a directory and everything in it (symlinks are removed, not followed)
+    async fn delete_tree(session: &Arc<dyn FileTransferSession>, path: &str) -> Result<(), ConnectionError> {
+        let info = session.stat(path).await?;
+        if !info.is_directory || info.is_symlink {
+            return session.delete(path, false).await;
+        }
+
+        let listing = session.list_directory(path).await?;
+        // A listing of some other directory (fallback after a failed open) must not be deleted
+        let expected_parent = format!("{}/", path.trim_end_matches('/'));
+        if listing.entries.iter().any(|e| !e.path.starts_with(&expected_parent)) {
+            return Err(ConnectionError::InvalidPath(format!("Cannot open directory {}", path)));
+        }
+        for entry in listing.entries {
             if entry.name == "." || entry.name == ".." {
                 continue;
             }
+            if entry.is_directory && !entry.is_symlink {
+                Box::pin(Self::delete_tree(session, &entry.path)).await?;
+            } else {
+                session.delete(&entry.path, false).await?;
+            }
+        }
+        session.delete(path, true).await
+    }
+
+    /// Total size of a remote directory tree
+    /// Subdirectories that can't be listed are counted in `inaccessible` and skipped;
+    /// only the top-level directory failing is an error. Emits directory-size-progress.
+    pub async fn calculate_directory_size(
+        &self,
+        app_handle: &AppHandle,
+        session_id: &str,
+        path: &str,
+        scan_id: Option<String>,
+    ) -> Result<DirectorySize, ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+        let concurrency = match session.connection_type() {
+            ConnectionType::Sftp => SIZE_SCAN_CONCURRENCY,
+            _ => 1,
+        };
+
+        let path = crate::core::normalize_remote_path(path);
+        let mut event = DirectorySizeEvent {
+            scan_id,
+            session_id: session_id.to_string(),
+            path: path.clone(),
+            size: DirectorySize::default(),
+            done: false,
+        };
+        let app = app_handle.clone();
+        let mut progress_event = event.clone();
+        let mut on_progress = move |size: &DirectorySize| {
+            progress_event.size = *size;
+            Self::emit_directory_size(&app, &progress_event);
+        };
 
-            let source_item = format!("{}/{}", source_dir.trim_end_matches('/'), entry.name);
-            let dest_item = format!("{}/{}", dest_dir.trim_end_matches('/'), entry.name);
+        let size = Self::measure_remote_tree(session, &path, concurrency, &mut on_progress).await?;
+        log::info!(
+            "[FileTransfer] {} holds {} bytes in {} files ({} directories unreadable)",
+            path,
+            size.total_bytes,
+            size.file_count,
+            size.inaccessible
+        );
 
-            if entry.is_directory {
-                // Recursive copy for subdirectories
-                Box::pin(self.copy_remote_directory_recursive(session_id, &source_item, &dest_item)).await?;
-            } else {
-                // Copy file
-                self.copy_remote_file(session_id, &source_item, &dest_item).await?;
+        event.size = size;
+        event.done = true;
+        Self::emit_directory_size(app_handle, &event);
+        Ok(size)
+    }
+
+    /// Sum a remote tree, listing up to `concurrency` directories at a time
+    async fn measure_remote_tree(
+        session: Arc<dyn FileTransferSession>,
+        root: &str,
+        concurrency: usize,
+        on_progress: &mut (dyn FnMut(&DirectorySize) + Send),
+    ) -> Result<DirectorySize, ConnectionError> {
+        let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+        let mut listings = JoinSet::new();
+        let spawn_listing = |listings: &mut JoinSet<_>, dir: String, depth: usize| {
+            let session = session.clone();
+            let permits = permits.clone();
+            listings.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let listing = session.list_directory(&dir).await;
+                (dir, depth, listing)
+            });
+        };
+        spawn_listing(&mut listings, root.to_string(), 0);
+
+        let mut size = DirectorySize::default();
+        let mut last_report = std::time::Instant::now();
+        while let Some(joined) = listings.join_next().await {
+            let (dir, depth, listing) = joined
+                .map_err(|e| ConnectionError::Unknown(format!("Directory scan failed: {}", e)))?;
+
+            // Listings fall back to another directory when this one can't be opened
+            let expected_parent = format!("{}/", dir.trim_end_matches('/'));
+            let listing = match listing {
+                Ok(listing) if listing.entries.iter().all(|e| e.path.starts_with(&expected_parent)) => listing,
+                Err(e) if depth == 0 => return Err(e),
+                Ok(_) if depth == 0 => {
+                    return Err(ConnectionError::InvalidPath(format!("Cannot open directory {}", dir)));
+                }
+                _ => {
+                    log::debug!("[FileTransfer] Size scan: cannot list {}", dir);
+                    size.inaccessible += 1;
+                    continue;
+                }
+            };
+
+            for entry in listing.entries {
+                if entry.name.is_empty() || entry.name == "." || entry.name == ".." {
+                    continue;
+                }
+                if !entry.is_directory {
+                    size.file_count += 1;
+                    size.total_bytes += entry.size;
+                } else if !entry.is_symlink {
+                    size.directory_count += 1;
+                    if depth + 1 >= MAX_DOWNLOAD_DEPTH {
+                        size.inaccessible += 1;
+                    } else {
+                        spawn_listing(&mut listings, entry.path, depth + 1);
+                    }
+                }
+            }
+
+            if last_report.elapsed() >= SIZE_PROGRESS_INTERVAL {
+                on_progress(&size);
+                last_report = std::time::Instant::now();
             }
         }
 
-        Ok(())
+        Ok(size)
+    }
+
+    /// Copy file/directory on remote (the session streams or stages the bytes; neither protocol has a native copy)
+    /// Returns the entries that couldn't be read and were skipped
+    pub async fn copy_remote(
+        &self,
+        session_id: &str,
+        source_path: &str,
+        dest_path: &str,
+    ) -> Result<Vec<ListingWarning>, ConnectionError> {
+        let session = self.get_session_arc(session_id).await
+            .ok_or_else(|| ConnectionError::Unknown(format!("Session not found: {}", session_id)))?;
+
+        let source = crate::core::normalize_remote_path(source_path);
+        let dest = crate::core::normalize_remote_path(dest_path);
+        // Copying a directory into itself would never run out of entries
+        if crate::core::path_utils::is_within_directory(&dest, &source) {
+            return Err(ConnectionError::InvalidPath(format!("Cannot copy {} into itself ({})", source, dest)));
+        }
+
+        log::info!("[FileTransfer] Copying {} -> {}", source, dest);
+        session.copy(&source, &dest).await
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::fake_session::FakeSession;
+
+    async fn add_session(manager: &FileTransferManager, id: &str, session: Arc<dyn FileTransferSession>) {
+        let info = ActiveSession::new(id, "sftp", Some("files.example.com".to_string()));
+        manager.sessions.lock().await.insert(id.to_string(), OpenFileSession { session, info });
+    }
+
+    #[tokio::test]
+    async fn test_list_and_close_all_sessions() {
+        let manager = FileTransferManager::new();
+        add_session(&manager, "a", Arc::new(FakeSession::new())).await;
+        add_session(&manager, "b", Arc::new(FakeSession::new())).await;
+
+        let mut ids: Vec<String> = manager.list_sessions().await.into_iter().map(|s| s.id).collect();
+        ids.sort();
+        assert_eq!(ids, ["a", "b"]);
+
+        assert_eq!(manager.close_all_sessions().await, 2);
+        assert!(manager.list_sessions().await.is_empty());
+        assert!(manager.get_session_arc("a").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_scan_remote_download_skips_links_and_fallback_listings() {
+        let session: Arc<dyn FileTransferSession> = Arc::new(
+            FakeSession::new()
+                .file("/data/a.txt", b"abc")
+                .file("/data/sub/b.txt", b"abcd")
+                .dir_with_mode("/data/locked", 0o000)
+                .symlink("/data/link.txt", "/srv/real.txt")
+                .symlink("/data/broken", "/srv/missing.txt")
+                .symlink("/data/loop", "/data")
+                .file("/srv/real.txt", b"1234567")
+                .file("/home/.bashrc", b"x")
+                .with_listing_fallback("/home"),
+        );
+
+        let tree = FileTransferManager::scan_remote_download(&session, "/data").await.unwrap();
+        assert_eq!(
+            tree.entries,
+            vec![
+                ("a.txt".to_string(), false, 3),
+                ("link.txt".to_string(), false, 7),
+                ("locked".to_string(), true, 0),
+                ("sub".to_string(), true, 0),
+                ("sub/b.txt".to_string(), false, 4),
+            ]
+        );
+        assert_eq!(tree.skipped_symlinks, vec!["broken".to_string(), "loop".to_string()]);
+        // "locked" listed /home instead - reported, not walked
+        assert_eq!(tree.failed.len(), 1);
+        assert_eq!(tree.failed[0].path, "locked");
+    }
+
+    #[test]
+    fn test_cancel_flag_lives_as_long_as_the_transfer() {
+        let manager = FileTransferManager::new();
+        assert!(!manager.cancel_transfer("t1"));
+
+        let first = manager.register_cancel("t1");
+        assert!(manager.cancel_transfer("t1"));
+        assert!(first.is_cancelled());
+
+        // A retry under the same id must not be unregistered by the old transfer
+        let retry = manager.register_cancel("t1");
+        drop(first);
+        assert!(!retry.is_cancelled());
+        assert!(manager.cancel_transfer("t1"));
+        drop(retry);
+        assert!(!manager.cancel_transfer("t1"));
+    }
+
+    #[test]
+    fn test_ssh_failure_keeps_passphrase_codes() {
+        let error = ssh_failure(SshError::PassphraseRequired("id_ed25519".to_string()), ConnectionError::AuthenticationFailed);
+        assert_eq!(error.code(), ErrorCode::PassphraseRequired);
+        let error = ssh_failure(SshError::PassphraseIncorrect("id_ed25519".to_string()), ConnectionError::AuthenticationFailed);
+        assert_eq!(error.code(), ErrorCode::PassphraseIncorrect);
+
+        let error = ssh_failure(SshError::auth_failed(russh::MethodKind::Password, "denied"), ConnectionError::AuthenticationFailed);
+        assert_eq!(error.code(), ErrorCode::AuthFailed);
+    }
+
+    #[tokio::test]
+    async fn test_copy_remote_rejects_copy_into_itself() {
+        let manager = FileTransferManager::new();
+        add_session(&manager, "s", Arc::new(FakeSession::new())).await;
+
+        for dest in ["/data/sub/copy", "/data/", "//data"] {
+            let result = manager.copy_remote("s", "/data", dest).await;
+            assert!(matches!(result, Err(ConnectionError::InvalidPath(_))), "{}", dest);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_measure_remote_tree_skips_unreadable_directories() {
+        let session: Arc<dyn FileTransferSession> = Arc::new(
+            FakeSession::new()
+                .file("/data/a.txt", b"abc")
+                .file("/data/sub/b.txt", b"abcd")
+                .file("/data/sub/deeper/c.txt", b"abcde")
+                .dir_with_mode("/data/locked", 0o000)
+                .symlink("/data/loop", "/data")
+                .file("/home/.bashrc", b"x")
+                .with_listing_fallback("/home"),
+        );
+
+        for concurrency in [1, 4] {
+            let size = FileTransferManager::measure_remote_tree(session.clone(), "/data", concurrency, &mut |_| {})
+                .await
+                .unwrap();
+            // "locked" listed /home instead - counted as inaccessible, /home not summed
+            assert_eq!(size, DirectorySize { total_bytes: 12, file_count: 3, directory_count: 3, inaccessible: 1 });
+        }
+
+        let missing = FileTransferManager::measure_remote_tree(session, "/nope", 1, &mut |_| {}).await;
+        assert!(matches!(missing, Err(ConnectionError::InvalidPath(_))));
+    }
+
+    #[tokio::test]
+    async fn test_move_remote_falls_back_to_copy_and_keeps_source_on_failure() {
+        // /a is its own filesystem and renames fail, as SFTP reports EXDEV
+        let tree = || {
+            FakeSession::new()
+                .file("/a/x", b"x")
+                .file("/a/sub/y", b"y")
+                .dir("/taken")
+                .with_failing_renames()
+                .with_filesystem("/a", 1)
+        };
+        let manager = FileTransferManager::new();
+
+        let fs = Arc::new(tree());
+        add_session(&manager, "ok", fs.clone()).await;
+        manager.move_remote("ok", "/a", "/b").await.unwrap();
+        assert_eq!(fs.paths(), vec!["/b", "/b/sub", "/b/sub/y", "/b/x", "/taken"]);
+        assert_eq!(fs.content("/b/sub/y"), Some(b"y".to_vec()));
+
+        // Copy dies halfway: the partial copy goes, the source stays
+        let fs = Arc::new(tree().with_failing_read("/a/sub/y"));
+        add_session(&manager, "fail", fs.clone()).await;
+        assert!(manager.move_remote("fail", "/a", "/b").await.is_err());
+        assert_eq!(fs.paths(), vec!["/a", "/a/sub", "/a/sub/y", "/a/x", "/taken"]);
+
+        // An existing destination is never copied over
+        assert!(manager.move_remote("fail", "/a", "/taken").await.is_err());
+        assert_eq!(fs.paths(), vec!["/a", "/a/sub", "/a/sub/y", "/a/x", "/taken"]);
+
+        // A failed rename within one filesystem is reported, not turned into a copy
+        let err = manager.move_remote("fail", "/a/x", "/a/z").await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::RemoteError);
+        assert_eq!(fs.paths(), vec!["/a", "/a/sub", "/a/sub/y", "/a/x", "/taken"]);
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_entries_are_skipped_by_copy_and_sync() {
+        let fs = Arc::new(
+            FakeSession::new()
+                .file("/a/x", b"x")
+                .file("/a/sub/locked", b"l")
+                .with_unreadable_entry("/a/sub/locked")
+                .with_failing_renames()
+                .with_filesystem("/a", 1),
+        );
+        let manager = FileTransferManager::new();
+        add_session(&manager, "s", fs.clone()).await;
+
+        let skipped = manager.copy_remote("s", "/a", "/b").await.unwrap();
+        assert_eq!(skipped.iter().map(|w| w.path.as_str()).collect::<Vec<_>>(), vec!["/a/sub/locked"]);
+        assert_eq!(fs.content("/b/x"), Some(b"x".to_vec()));
+
+        // Moving would lose the skipped entry: the partial copy goes, the source stays
+        assert!(manager.move_remote("s", "/a", "/c").await.is_err());
+        assert_eq!(fs.paths(), vec!["/a", "/a/sub", "/a/sub/locked", "/a/x", "/b", "/b/sub", "/b/x"]);
+
+        let session: Arc<dyn FileTransferSession> = fs;
+        let tree = FileTransferManager::scan_remote_tree(&session, "/a").await.unwrap();
+        assert_eq!(tree["sub/locked"].skipped.as_deref(), Some("Permission denied"));
+        assert!(tree["x"].skipped.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_plan_tree_delete_empties_directories_first() {
+        let fs = Arc::new(FakeSession::new().file("/a/x", b"x").file("/a/sub/deep/y", b"y").file("/b", b"b"));
+        let session: Arc<dyn FileTransferSession> = fs.clone();
+
+        let plan = FileTransferManager::plan_tree_delete(&session, "/a").await.unwrap();
+        assert!(plan.failed.is_empty());
+        assert_eq!(plan.entries.last(), Some(&("/a".to_string(), true)));
+        for (path, is_directory) in &plan.entries {
+            session.delete(path, *is_directory).await.unwrap();
+        }
+        assert_eq!(fs.paths(), vec!["/b"]);
+
+        let plan = FileTransferManager::plan_tree_delete(&session, "/b").await.unwrap();
+        assert_eq!(plan.entries, vec![("/b".to_string(), false)]);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_uploads_bounds_in_flight_transfers() {
+        let local = tempfile::tempdir().unwrap();
+        let fake = Arc::new(FakeSession::new().dir("/up").with_slow_uploads(std::time::Duration::from_millis(20)));
+        let session: Arc<dyn FileTransferSession> = fake.clone();
+        let items: Vec<UploadItem> = (0..12)
+            .map(|i| {
+                let local_path = local.path().join(i.to_string());
+                std::fs::write(&local_path, i.to_string()).unwrap();
+                UploadItem { local_path: local_path.to_string_lossy().to_string(), remote_path: format!("/up/{}", i) }
+            })
+            .chain([UploadItem { local_path: "/tmp/x".to_string(), remote_path: "/up/../x".to_string() }])
+            .collect();
+
+        let mut uploads = FileTransferManager::spawn_uploads(session, items, 3, None, Arc::default());
+        let mut ok = 0;
+        let mut rejected = Vec::new();
+        while let Some(joined) = uploads.join_next().await {
+            match joined.unwrap() {
+                (_, Ok(())) => ok += 1,
+                (path, Err(_)) => rejected.push(path),
+            }
+        }
+
+        assert_eq!(ok, 12);
+        assert_eq!(rejected, vec!["/up/../x".to_string()]);
+        assert_eq!(fake.max_uploads_in_flight(), 3);
+        assert_eq!(fake.content("/up/11"), Some(b"11".to_vec()));
+    }
+}
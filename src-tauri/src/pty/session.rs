@@ -1,32 +1,79 @@
 use crate::core::error::SessionError;
+use crate::core::initial_command::{InitialCommand, PendingCommand, CHECK_INTERVAL};
+use crate::core::output::{encode_output_bytes, Utf8StreamDecoder};
+use crate::core::paste::BracketedPasteTracker;
+use crate::core::recording::Recording;
+use crate::core::scrollback::Scrollback;
 use crate::core::session::TerminalSession;
+use crate::core::terminal_events::TerminalExitEvent;
 use crate::terminal::session::SessionType;
 use async_trait::async_trait;
 use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
+use std::collections::HashMap;
 use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+/// Limit for execute_command when the caller gives none
+const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long to wait for the shell to be reapable once its output ends
+const EXIT_STATUS_TIMEOUT: Duration = Duration::from_secs(2);
+const EXIT_STATUS_POLL: Duration = Duration::from_millis(20);
+/// How long close() lets the shell exit after SIGHUP (saving history, running EXIT traps) before killing it
+const CLOSE_GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+type SharedChild = Arc<Mutex<Box<dyn portable_pty::Child + Send + Sync>>>;
+
 /// Local PTY terminal session
 pub struct LocalPtySession {
     id: String,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
     pty_pair: Arc<Mutex<portable_pty::PtyPair>>,
-    child: Arc<Mutex<Box<dyn portable_pty::Child + Send + Sync>>>,
+    child: SharedChild,
+    bracketed_paste: Arc<AtomicBool>,
+    /// Recent output bytes (appended by the reader task)
+    scrollback: Scrollback,
+    /// Emitted as base64 bytes rather than text
+    binary_output: bool,
+    /// Output recording (teed by the reader task)
+    recording: Recording,
+    /// Shell, environment and directory one-shot commands run with (the shell's custom args are not used)
+    shell_path: String,
+    env: HashMap<String, String>,
+    cwd: Option<String>,
 }
 
 impl LocalPtySession {
     /// Create a new local PTY terminal session
+    /// `args` are passed to the shell one by one, unquoted (e.g. ["-l"] or ["-d", "Ubuntu"])
+    /// `env` is added to the inherited environment (and may override TERM); `cwd` must exist.
+    /// With `binary_output`, output is emitted base64-encoded on "terminal-output-bytes:{id}"
+    /// instead of as text on "terminal-output:{id}", so the frontend gets the exact bytes
+    /// `scrollback_bytes` of recent output are kept for get_scrollback (None = 256KB, 0 = none)
+    /// `initial_command` is typed in once the shell prompt is ready
     pub fn new(
         shell: Option<String>,
+        args: Vec<String>,
         cols: u16,
         rows: u16,
+        env: HashMap<String, String>,
+        cwd: Option<String>,
+        binary_output: bool,
+        scrollback_bytes: Option<usize>,
+        initial_command: Option<InitialCommand>,
         app_handle: AppHandle,
     ) -> Result<Self, SessionError> {
         let id = Uuid::new_v4().to_string();
 
+        // Determine shell to use and build the command (fails early on a bad cwd)
+        let shell_path = shell.unwrap_or_else(|| crate::pty::shell::get_default_shell());
+        let cmd = build_shell_command(&shell_path, &args, env.clone(), cwd.clone())?;
+
         // Get PTY system
         let pty_system = NativePtySystem::default();
 
@@ -40,15 +87,6 @@ impl LocalPtySession {
             })
             .map_err(|e| SessionError::PtyError(format!("Failed to open PTY: {}", e)))?;
 
-        // Determine shell to use
-        let shell_path = shell.unwrap_or_else(|| crate::pty::shell::get_default_shell());
-
-        // Create command
-        let mut cmd = CommandBuilder::new(&shell_path);
-
-        // Set environment variables
-        cmd.env("TERM", "xterm-256color");
-
         // Spawn child process
         let child = pty_pair
             .slave
@@ -73,17 +111,37 @@ impl LocalPtySession {
         // Spawn background task to read PTY output and emit events
         let session_id = id.clone();
         let app_handle_clone = app_handle.clone();
+        let mut paste_tracker = BracketedPasteTracker::new();
+        let bracketed_paste = paste_tracker.flag();
+        let exited_child = child.clone();
+        let scrollback = Scrollback::new(scrollback_bytes);
+        let output_scrollback = scrollback.clone();
+        let term = env.get("TERM").map_or("xterm-256color", String::as_str);
+        let recording = Recording::new(cols, rows, term);
+        let output_recording = recording.clone();
+        let initial_command = PendingCommand::new(initial_command.as_ref());
+        let initial_command = initial_command.is_pending().then(|| {
+            let pending = Arc::new(std::sync::Mutex::new(initial_command));
+            tokio::spawn(send_initial_command(id.clone(), pending.clone(), writer.clone()));
+            pending
+        });
 
         tokio::spawn(async move {
             let mut reader = reader;
             let mut buffer = [0u8; 8192];
+            let mut decoder = Utf8StreamDecoder::new();
 
             loop {
                 match reader.read(&mut buffer) {
                     Ok(0) => {
-                        // EOF - process exited
-                        use crate::core::terminal_events::TerminalExitEvent;
-                        let exit_event = TerminalExitEvent::new(0, Some("process-exited".to_string()));
+                        // EOF - process exited; flush a character cut off mid-sequence
+                        let rest = decoder.finish();
+                        if !rest.is_empty() {
+                            app_handle_clone
+                                .emit(&format!("terminal-output:{}", session_id), rest)
+                                .ok();
+                        }
+                        let exit_event = child_exit_event(&exited_child).await;
                         app_handle_clone
                             .emit(&format!("terminal-exit:{}", session_id), exit_event)
                             .ok();
@@ -91,12 +149,30 @@ impl LocalPtySession {
                     }
                     Ok(n) => {
                         // Got data from PTY
-                        let data = String::from_utf8_lossy(&buffer[..n]).to_string();
+                        paste_tracker.observe(&buffer[..n]);
+                        output_scrollback.push(&buffer[..n]);
+                        output_recording.output(&buffer[..n]);
+                        if let Some(pending) = &initial_command {
+                            let text = String::from_utf8_lossy(&buffer[..n]);
+                            pending.lock().unwrap().observe(&text, std::time::Instant::now());
+                        }
 
                         // Emit output event
-                        app_handle_clone
-                            .emit(&format!("terminal-output:{}", session_id), data)
-                            .ok();
+                        if binary_output {
+                            app_handle_clone
+                                .emit(
+                                    &format!("terminal-output-bytes:{}", session_id),
+                                    encode_output_bytes(&buffer[..n]),
+                                )
+                                .ok();
+                        } else {
+                            let data = decoder.decode(&buffer[..n]);
+                            if !data.is_empty() {
+                                app_handle_clone
+                                    .emit(&format!("terminal-output:{}", session_id), data)
+                                    .ok();
+                            }
+                        }
                     }
                     Err(e) => {
                         // Read error
@@ -117,8 +193,204 @@ impl LocalPtySession {
             writer,
             pty_pair,
             child,
+            bracketed_paste,
+            scrollback,
+            binary_output,
+            recording,
+            shell_path,
+            env,
+            cwd,
         })
     }
+
+    /// Run `command` through the session's shell outside the PTY
+    async fn run_command(&self, command: &str, timeout: Duration) -> Result<String, SessionError> {
+        log::info!("PTY[{}] executing command: {}", self.id, command);
+        run_one_shot(&self.shell_path, &self.env, self.cwd.as_deref(), command, timeout).await
+    }
+}
+
+/// Type the initial command into the shell once its prompt settled
+async fn send_initial_command(
+    id: String,
+    pending: Arc<std::sync::Mutex<PendingCommand>>,
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+) {
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+        let command = pending.lock().unwrap().poll(std::time::Instant::now(), false);
+        if let Some(command) = command {
+            log::debug!("PTY[{}] sending initial command", id);
+            let mut writer = writer.lock().await;
+            if let Err(e) = writer.write_all(&command).and_then(|_| writer.flush()) {
+                log::warn!("PTY[{}] failed to send initial command: {}", id, e);
+            }
+            return;
+        }
+    }
+}
+
+/// Run a single shell command, returning stdout then stderr
+/// The process is killed if it outlives `timeout`
+async fn run_one_shot(
+    shell_path: &str,
+    env: &HashMap<String, String>,
+    cwd: Option<&str>,
+    command: &str,
+    timeout: Duration,
+) -> Result<String, SessionError> {
+    let mut cmd = tokio::process::Command::new(shell_path);
+    cmd.args(one_shot_args(shell_path, command))
+        .envs(env)
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true);
+    if let Some(dir) = cwd {
+        cmd.current_dir(dir);
+    }
+    #[cfg(windows)]
+    {
+        // CREATE_NO_WINDOW: don't flash a console window
+        cmd.creation_flags(0x0800_0000);
+    }
+
+    let output = tokio::time::timeout(timeout, cmd.output())
+        .await
+        .map_err(|_| {
+            log::warn!("Local command timed out after {:?}: {}", timeout, command);
+            SessionError::IoError(std::io::Error::new(std::io::ErrorKind::TimedOut, "Command timed out"))
+        })??;
+
+    let mut combined = String::from_utf8_lossy(&output.stdout).into_owned();
+    combined.push_str(&String::from_utf8_lossy(&output.stderr));
+    log::debug!("Local command exited with {}", output.status);
+    Ok(combined)
+}
+
+/// Ask the shell to exit with SIGHUP, killing it if it is still running after `grace`
+/// On Windows there is no gentle signal, so it is killed right away
+async fn terminate_child(child: &SharedChild, grace: Duration) -> Result<(), SessionError> {
+    #[cfg(unix)]
+    {
+        let pid = child.lock().await.process_id();
+        if let Some(pid) = pid {
+            // Bash and zsh save history and forward the hangup to their jobs
+            if unsafe { libc::kill(pid as libc::pid_t, libc::SIGHUP) } != 0 {
+                log::debug!("Failed to send SIGHUP to shell {}: {}", pid, std::io::Error::last_os_error());
+            }
+            let deadline = tokio::time::Instant::now() + grace;
+            while tokio::time::Instant::now() < deadline {
+                if let Ok(Some(_)) = child.lock().await.try_wait() {
+                    return Ok(());
+                }
+                tokio::time::sleep(EXIT_STATUS_POLL).await;
+            }
+            log::warn!("Shell {} did not exit within {:?} of SIGHUP, killing it", pid, grace);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = grace;
+
+    let mut child = child.lock().await;
+    if let Ok(Some(_)) = child.try_wait() {
+        return Ok(());
+    }
+    child.kill().map_err(|e| SessionError::PtyError(format!("Failed to kill process: {}", e)))
+}
+
+/// Exit event carrying the shell's real status
+/// Polls try_wait so the child lock is never held across a blocking wait (close() needs it to kill)
+async fn child_exit_event(child: &SharedChild) -> TerminalExitEvent {
+    let deadline = tokio::time::Instant::now() + EXIT_STATUS_TIMEOUT;
+    loop {
+        match child.lock().await.try_wait() {
+            Ok(Some(status)) => return exit_event_for(&status),
+            Ok(None) if tokio::time::Instant::now() < deadline => {}
+            Ok(None) => {
+                log::warn!("PTY output ended but the shell is still running; exit code unknown");
+                break;
+            }
+            Err(e) => {
+                log::warn!("Failed to get shell exit status: {}", e);
+                break;
+            }
+        }
+        tokio::time::sleep(EXIT_STATUS_POLL).await;
+    }
+    TerminalExitEvent::process_exited(0)
+}
+
+/// Exit event for a reaped shell: its exit code, or the signal that killed it (128 + number)
+fn exit_event_for(status: &portable_pty::ExitStatus) -> TerminalExitEvent {
+    // portable-pty only exposes a signal through Display, as strsignal's description
+    let description = status.to_string();
+    match description.strip_prefix("Terminated by ") {
+        Some(signal) if !status.success() => {
+            let (name, number) = signal_from_description(signal);
+            TerminalExitEvent::process_signaled(&name, 128 + number)
+        }
+        _ => TerminalExitEvent::process_exited(status.exit_code() as i32),
+    }
+}
+
+/// Signal name and number for a strsignal description (glibc and BSD wording)
+fn signal_from_description(description: &str) -> (String, i32) {
+    let (name, number) = match description {
+        "Hangup" => ("HUP", 1),
+        "Interrupt" => ("INT", 2),
+        "Quit" => ("QUIT", 3),
+        "Illegal instruction" => ("ILL", 4),
+        "Aborted" | "Abort trap" => ("ABRT", 6),
+        "Floating point exception" | "Floating-point exception" => ("FPE", 8),
+        "Killed" => ("KILL", 9),
+        "Segmentation fault" => ("SEGV", 11),
+        "Broken pipe" => ("PIPE", 13),
+        "Alarm clock" => ("ALRM", 14),
+        "Terminated" => ("TERM", 15),
+        other => return (other.to_string(), 0),
+    };
+    (name.to_string(), number)
+}
+
+/// Arguments that make `shell_path` run a single command and exit
+fn one_shot_args(shell_path: &str, command: &str) -> Vec<String> {
+    let name = shell_path
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(shell_path)
+        .to_ascii_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    match name {
+        "powershell" | "pwsh" => vec!["-NoProfile".to_string(), "-Command".to_string(), command.to_string()],
+        "cmd" => vec!["/C".to_string(), command.to_string()],
+        _ => vec!["-c".to_string(), command.to_string()],
+    }
+}
+
+/// Shell command with its arguments, TERM set, then the caller's environment (which wins) and working directory
+fn build_shell_command(
+    shell_path: &str,
+    args: &[String],
+    env: HashMap<String, String>,
+    cwd: Option<String>,
+) -> Result<CommandBuilder, SessionError> {
+    let mut cmd = CommandBuilder::new(shell_path);
+    cmd.args(args);
+    cmd.env("TERM", "xterm-256color");
+    for (key, value) in env {
+        cmd.env(key, value);
+    }
+
+    if let Some(dir) = cwd {
+        if !Path::new(&dir).is_dir() {
+            return Err(SessionError::InvalidConfig(format!(
+                "Working directory does not exist: {}",
+                dir
+            )));
+        }
+        cmd.cwd(dir);
+    }
+
+    Ok(cmd)
 }
 
 #[async_trait]
@@ -156,9 +428,147 @@ impl TerminalSession for LocalPtySession {
             .map_err(|e| SessionError::PtyError(format!("Failed to resize PTY: {}", e)))
     }
 
-    async fn close(&mut self) -> Result<(), SessionError> {
-        let mut child = self.child.lock().await;
-        child.kill().map_err(|e| SessionError::PtyError(format!("Failed to kill process: {}", e)))
+    /// Alive while the shell hasn't exited
+    async fn ping(&self) -> bool {
+        matches!(self.child.lock().await.try_wait(), Ok(None))
+    }
+
+    async fn close(&self) -> Result<(), SessionError> {
+        // Dropping the master writer sends EOF, the shell's cue to log out
+        *self.writer.lock().await = Box::new(std::io::sink());
+        terminate_child(&self.child, CLOSE_GRACE_PERIOD).await
+    }
+
+    async fn execute_command(&self, command: &str) -> Result<String, SessionError> {
+        self.run_command(command, DEFAULT_COMMAND_TIMEOUT).await
+    }
+
+    async fn execute_command_with_timeout(&self, command: &str, timeout: Duration) -> Result<String, SessionError> {
+        self.run_command(command, timeout).await
+    }
+
+    fn bracketed_paste_enabled(&self) -> bool {
+        self.bracketed_paste.load(Ordering::SeqCst)
+    }
+
+    /// Base64 of the raw bytes for binary_output sessions, like their output events
+    fn scrollback(&self) -> String {
+        if self.binary_output {
+            encode_output_bytes(&self.scrollback.bytes())
+        } else {
+            self.scrollback.text()
+        }
+    }
+
+    fn recording(&self) -> Option<Recording> {
+        Some(self.recording.clone())
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn test_env_overrides_term_and_cwd_is_validated() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = HashMap::from([
+            ("TERM".to_string(), "dumb".to_string()),
+            ("VIRTUAL_ENV".to_string(), "/opt/venv".to_string()),
+        ]);
+        let cwd = dir.path().to_string_lossy().to_string();
+
+        let cmd = build_shell_command("/bin/sh", &[], env, Some(cwd.clone())).unwrap();
+        assert_eq!(cmd.get_argv(), &["/bin/sh"]);
+        assert_eq!(cmd.get_env("TERM"), Some(OsStr::new("dumb")));
+        assert_eq!(cmd.get_env("VIRTUAL_ENV"), Some(OsStr::new("/opt/venv")));
+        assert_eq!(cmd.get_cwd().map(|c| c.to_string_lossy().to_string()), Some(cwd));
+
+        let missing = dir.path().join("missing").to_string_lossy().to_string();
+        let err = build_shell_command("/bin/sh", &[], HashMap::new(), Some(missing));
+        assert!(matches!(err, Err(SessionError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_shell_args_are_kept_separate() {
+        let args = ["exec".to_string(), "-it".to_string(), "my container".to_string(), "bash".to_string()];
+        let cmd = build_shell_command("docker", &args, HashMap::new(), None).unwrap();
+        assert_eq!(cmd.get_argv(), &["docker", "exec", "-it", "my container", "bash"]);
+    }
+
+    #[test]
+    fn test_one_shot_args_per_shell() {
+        assert_eq!(one_shot_args("/bin/zsh", "echo hi"), ["-c", "echo hi"]);
+        assert_eq!(one_shot_args("fish", "echo hi"), ["-c", "echo hi"]);
+        assert_eq!(one_shot_args("C:\\Windows\\System32\\cmd.exe", "dir"), ["/C", "dir"]);
+        assert_eq!(one_shot_args("PowerShell.exe", "ls"), ["-NoProfile", "-Command", "ls"]);
+        assert_eq!(one_shot_args("/usr/local/bin/pwsh", "ls"), ["-NoProfile", "-Command", "ls"]);
+    }
+
+    #[test]
+    fn test_exit_event_reports_code_and_signal() {
+        let event = exit_event_for(&portable_pty::ExitStatus::with_exit_code(3));
+        assert_eq!((event.exit_code, event.reason.as_deref()), (3, Some("process-exited")));
+
+        let event = exit_event_for(&portable_pty::ExitStatus::with_signal("Killed"));
+        assert_eq!((event.exit_code, event.reason.as_deref()), (137, Some("process-exited:signal:KILL")));
+
+        let event = exit_event_for(&portable_pty::ExitStatus::with_signal("Real-time signal 3"));
+        assert_eq!(event.reason.as_deref(), Some("process-exited:signal:Real-time signal 3"));
+        assert!(!event.reconnectable);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_one_shot_combines_output_and_times_out() {
+        let env = HashMap::from([("GREETING".to_string(), "hi".to_string())]);
+        let output = run_one_shot("/bin/sh", &env, Some("/"), "echo $GREETING; pwd; echo oops >&2", Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(output, "hi\n/\noops\n");
+
+        let err = run_one_shot("/bin/sh", &HashMap::new(), None, "sleep 5", Duration::from_millis(100)).await;
+        assert!(matches!(err, Err(SessionError::IoError(e)) if e.kind() == std::io::ErrorKind::TimedOut));
+    }
+
+    /// Run `script` under sh in a PTY, returning once it has written `ready`
+    #[cfg(unix)]
+    async fn spawn_in_pty(script: &str, ready: &Path) -> (portable_pty::PtyPair, SharedChild) {
+        let pair = NativePtySystem::default().openpty(PtySize::default()).unwrap();
+        let mut cmd = CommandBuilder::new("/bin/sh");
+        cmd.args(["-c", script]);
+        let child = pair.slave.spawn_command(cmd).unwrap();
+        while !ready.exists() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        (pair, Arc::new(Mutex::new(child)))
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_terminate_child_hangs_up_before_killing() {
+        let dir = tempfile::tempdir().unwrap();
+        let ready = dir.path().join("ready");
+        let saved = dir.path().join("saved");
+
+        // A shell that runs its trap gets to finish cleanly
+        let script = format!(
+            "trap 'echo bye > {}; exit 0' HUP; touch {}; while :; do sleep 0.05; done",
+            saved.display(),
+            ready.display()
+        );
+        let (_pair, child) = spawn_in_pty(&script, &ready).await;
+        terminate_child(&child, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&saved).unwrap(), "bye\n");
+        assert_eq!(child_exit_event(&child).await.exit_code, 0);
+
+        // One that ignores the hangup is killed once the grace period is over
+        std::fs::remove_file(&ready).unwrap();
+        let script = format!("trap '' HUP; touch {}; while :; do sleep 0.05; done", ready.display());
+        let (_pair, child) = spawn_in_pty(&script, &ready).await;
+        terminate_child(&child, Duration::from_millis(100)).await.unwrap();
+        let event = child_exit_event(&child).await;
+        assert_eq!(event.reason.as_deref(), Some("process-exited:signal:KILL"));
+    }
+}
@@ -1,14 +1,22 @@
 use async_trait::async_trait;
-use russh::client::Handle;
+use russh::client::{Handle, Msg};
+use russh::Channel;
+use russh_sftp::client::fs::{File, Metadata};
 use russh_sftp::client::SftpSession as RusshSftpSession;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use tokio::sync::Mutex;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{Mutex, OnceCell};
+use russh_sftp::protocol::OpenFlags;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use std::collections::HashMap;
 
+use crate::core::checksum::{parse_checksum_output, ChecksumAlgorithm};
 use crate::core::error::ConnectionError;
-use crate::core::session::{FileInfo, FileTransferSession};
+use crate::core::file_content::{self, FileRange};
+use crate::core::fs_stats::{df_command, parse_df_output, FilesystemStats};
+use crate::core::permissions::{parent_directory, RemoteUser, ID_COMMAND};
+use crate::core::rate_limit::RateLimiter;
+use crate::core::session::{DirectoryListing, SessionCapabilities, FileInfo, FileTransferSession, ListingWarning, ProgressCallback};
 use crate::ssh::client::SshClient;
 use crate::ssh::config::ConnectionType;
 
@@ -22,6 +30,10 @@ pub struct SftpSession {
     gid_cache: Arc<Mutex<HashMap<u32, String>>>,
     /// Whether we've already mapped root/empty path to home for this session
     home_resolved_for_root: AtomicBool,
+    /// Login user's uid and groups, looked up on the first permission check (None = unknown)
+    remote_user: OnceCell<Option<RemoteUser>>,
+    /// Streamed uploads kept open between chunks: path -> (handle, offset of the next chunk)
+    open_uploads: Mutex<HashMap<String, (File, u64)>>,
 }
 
 impl SftpSession {
@@ -45,7 +57,7 @@ impl SftpSession {
         // Create SFTP session
         let sftp = RusshSftpSession::new(channel.into_stream())
             .await
-            .map_err(|e| ConnectionError::SftpError(format!("Failed to create SFTP session: {}", e)))?;
+            .map_err(|e| ConnectionError::sftp("Failed to create SFTP session", &e))?;
 
         Ok(Self {
             id,
@@ -54,6 +66,8 @@ impl SftpSession {
             uid_cache: Arc::new(Mutex::new(HashMap::new())),
             gid_cache: Arc::new(Mutex::new(HashMap::new())),
             home_resolved_for_root: AtomicBool::new(false),
+            remote_user: OnceCell::new(),
+            open_uploads: Mutex::new(HashMap::new()),
         })
     }
 
@@ -203,6 +217,357 @@ impl SftpSession {
 
         None
     }
+
+    /// Run a command over an SSH exec channel; returns its exit status and stdout
+    async fn exec_output(&self, command: &str) -> Result<(Option<u32>, Vec<u8>), ConnectionError> {
+        let mut channel = self.exec_channel(command).await?;
+
+        // The exit status may arrive after EOF, so read until the channel closes
+        let mut output = Vec::new();
+        let mut exit_status = None;
+        while let Some(msg) = channel.wait().await {
+            match msg {
+                russh::ChannelMsg::Data { data } => output.extend_from_slice(&data),
+                russh::ChannelMsg::ExitStatus { exit_status: status } => exit_status = Some(status),
+                _ => {}
+            }
+        }
+        Ok((exit_status, output))
+    }
+
+    /// The login user, via `id` over exec (None when the server has no shell access)
+    async fn remote_user(&self) -> Option<&RemoteUser> {
+        self.remote_user
+            .get_or_init(|| async {
+                match self.exec_output(ID_COMMAND).await {
+                    Ok((Some(0), output)) => RemoteUser::parse_id_output(&String::from_utf8_lossy(&output)),
+                    Ok((status, _)) => {
+                        log::debug!("[SFTP] Cannot look up login user (exit status {:?})", status);
+                        None
+                    }
+                    Err(e) => {
+                        log::debug!("[SFTP] Cannot look up login user: {}", e);
+                        None
+                    }
+                }
+            })
+            .await
+            .as_ref()
+    }
+
+    /// Upload in 32KB chunks; with `preserve`, copy the local mode and times afterwards
+    async fn upload_from_file(
+        &self,
+        local_path: &str,
+        remote_path: &str,
+        progress: Option<ProgressCallback>,
+        max_bytes_per_sec: Option<u64>,
+        preserve: bool,
+    ) -> Result<(), ConnectionError> {
+        // Get file metadata and open local file first (before locking SFTP session)
+        let meta = tokio::fs::metadata(local_path)
+            .await
+            .map_err(|e| ConnectionError::io("Failed to stat local file", &e))?;
+        let total_bytes = meta.len();
+
+        let mut local_file = tokio::fs::File::open(local_path)
+            .await
+            .map_err(|e| ConnectionError::io("Failed to open local file", &e))?;
+
+        // Only lock SFTP session to create remote file handle, then release lock
+        let mut remote_file = {
+            let sftp = self.sftp.lock().await;
+            sftp.create(remote_path)
+                .await
+                .map_err(|e| ConnectionError::sftp("Failed to create remote file", &e))?
+        };
+        // Lock is released here, allowing other transfers to proceed
+
+        // Now transfer data without holding the lock
+        let mut buffer = vec![0u8; 32768]; // 32KB buffer
+        let mut limiter = RateLimiter::new(max_bytes_per_sec);
+        let mut transferred: u64 = 0;
+        loop {
+            let n = local_file
+                .read(&mut buffer)
+                .await
+                .map_err(|e| ConnectionError::io("Failed to read local file", &e))?;
+
+            if n == 0 {
+                break;
+            }
+            
+            remote_file
+                .write_all(&buffer[..n])
+                .await
+                .map_err(|e| ConnectionError::sftp("Failed to write remote file", &e))?;
+
+            transferred += n as u64;
+            if let Some(cb) = &progress {
+                if !cb(transferred, total_bytes) {
+                    return Err(ConnectionError::Cancelled);
+                }
+            }
+            if let Some(limiter) = &mut limiter {
+                limiter.throttle(n).await;
+            }
+        }
+
+        if preserve {
+            // Close first so the final write can't bump the mtime we set
+            remote_file
+                .shutdown()
+                .await
+                .map_err(|e| ConnectionError::sftp("Failed to close remote file", &e))?;
+            if let Err(e) = self.sftp.lock().await.set_metadata(remote_path, local_file_attributes(&meta)).await {
+                log::warn!("[SFTP] upload: cannot preserve attributes on {}: {}", remote_path, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Download in 32KB chunks; with `resume`, append to an existing partial local file
+    async fn download_to_file(
+        &self,
+        remote_path: &str,
+        local_path: &str,
+        progress: Option<ProgressCallback>,
+        max_bytes_per_sec: Option<u64>,
+        resume: bool,
+    ) -> Result<(), ConnectionError> {
+        // Only lock SFTP session to get metadata and open remote file handle, then release lock
+        let (mut remote_file, total_bytes, remote_mtime) = {
+            let sftp = self.sftp.lock().await;
+
+            // Get remote size (progress, resume check) and mtime (resume check), best-effort
+            let (total_bytes, remote_mtime) = match sftp.metadata(remote_path).await {
+                Ok(attrs) => (attrs.size.unwrap_or(0), attrs.mtime),
+                Err(_) => (0, None),
+            };
+
+            let remote_file = sftp
+                .open(remote_path)
+                .await
+                .map_err(|e| ConnectionError::sftp("Failed to open remote file", &e))?;
+
+            (remote_file, total_bytes, remote_mtime)
+        };
+        // Lock is released here, allowing other transfers to proceed
+
+        let offset = if resume {
+            match tokio::fs::metadata(local_path).await {
+                Ok(meta) if meta.len() > 0 => {
+                    let local_mtime = meta
+                        .modified()
+                        .ok()
+                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs());
+                    match resume_offset(meta.len(), local_mtime, total_bytes, remote_mtime) {
+                        Ok(offset) => {
+                            log::info!("[SFTP] Resuming download of {} at {} of {} bytes", remote_path, offset, total_bytes);
+                            offset
+                        }
+                        Err(reason) => {
+                            log::info!("[SFTP] Not resuming {}: {}; downloading from the start", remote_path, reason);
+                            0
+                        }
+                    }
+                }
+                _ => 0,
+            }
+        } else {
+            0
+        };
+
+        let mut local_file = if offset > 0 {
+            remote_file
+                .seek(std::io::SeekFrom::Start(offset))
+                .await
+                .map_err(|e| ConnectionError::sftp("Failed to seek remote file", &e))?;
+            tokio::fs::OpenOptions::new()
+                .append(true)
+                .open(local_path)
+                .await
+                .map_err(|e| ConnectionError::io("Failed to open local file", &e))?
+        } else {
+            tokio::fs::File::create(local_path)
+                .await
+                .map_err(|e| ConnectionError::io("Failed to create local file", &e))?
+        };
+
+        // Now transfer data without holding the lock
+        let mut buffer = vec![0u8; 32768]; // 32KB buffer
+        let mut limiter = RateLimiter::new(max_bytes_per_sec);
+        let mut transferred: u64 = offset;
+        loop {
+            let n = remote_file
+                .read(&mut buffer)
+                .await
+                .map_err(|e| ConnectionError::sftp("Failed to read remote file", &e))?;
+
+            if n == 0 {
+                break;
+            }
+
+            local_file
+                .write_all(&buffer[..n])
+                .await
+                .map_err(|e| ConnectionError::io("Failed to write local file", &e))?;
+
+            transferred += n as u64;
+            if let Some(cb) = &progress {
+                if !cb(transferred, total_bytes) {
+                    return Err(ConnectionError::Cancelled);
+                }
+            }
+            if let Some(limiter) = &mut limiter {
+                limiter.throttle(n).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copy `source` to `dest` on the server, recursing into directories
+    /// File contents pass through this connection in memory, never the local disk.
+    /// Inside a directory, symlinked files are copied as regular files; symlinked
+    /// directories (possible loops) and broken links are skipped and added to `skipped`.
+    async fn copy_tree(
+        &self,
+        source: &str,
+        dest: &str,
+        attrs: Metadata,
+        skipped: &mut Vec<ListingWarning>,
+    ) -> Result<(), ConnectionError> {
+        if attrs.is_dir() {
+            let entries = {
+                let sftp = self.sftp.lock().await;
+                sftp.create_dir(dest)
+                    .await
+                    .map_err(|e| ConnectionError::sftp(format!("Failed to create directory {}", dest), &e))?;
+                sftp.read_dir(source)
+                    .await
+                    .map_err(|e| ConnectionError::sftp(format!("Failed to read directory {}", source), &e))?
+            };
+
+            for entry in entries {
+                let name = entry.file_name();
+                if name == "." || name == ".." {
+                    continue;
+                }
+                let source_item = format!("{}/{}", source.trim_end_matches('/'), name);
+                let dest_item = format!("{}/{}", dest.trim_end_matches('/'), name);
+
+                let item_attrs = if entry.file_type().is_symlink() {
+                    let target = self.sftp.lock().await.metadata(&source_item).await;
+                    match target {
+                        Ok(target) if !target.is_dir() => target,
+                        Ok(_) => {
+                            log::warn!("[SFTP] copy: skipping symlinked directory {}", source_item);
+                            skipped.push(ListingWarning {
+                                path: source_item,
+                                message: "symlinked directory not copied".to_string(),
+                            });
+                            continue;
+                        }
+                        Err(e) => {
+                            log::warn!("[SFTP] copy: skipping broken symlink {}: {}", source_item, e);
+                            skipped.push(ListingWarning {
+                                path: source_item,
+                                message: format!("broken symlink: {}", e),
+                            });
+                            continue;
+                        }
+                    }
+                } else {
+                    entry.metadata()
+                };
+                Box::pin(self.copy_tree(&source_item, &dest_item, item_attrs, skipped)).await?;
+            }
+        } else {
+            // Only lock SFTP session to open both handles, then release lock
+            let (mut source_file, mut dest_file) = {
+                let sftp = self.sftp.lock().await;
+                let source_file = sftp
+                    .open(source)
+                    .await
+                    .map_err(|e| ConnectionError::sftp(format!("Failed to open {}", source), &e))?;
+                let dest_file = sftp
+                    .create(dest)
+                    .await
+                    .map_err(|e| ConnectionError::sftp(format!("Failed to create {}", dest), &e))?;
+                (source_file, dest_file)
+            };
+            tokio::io::copy(&mut source_file, &mut dest_file)
+                .await
+                .map_err(|e| ConnectionError::sftp(format!("Failed to copy {}", source), &e))?;
+            dest_file
+                .shutdown()
+                .await
+                .map_err(|e| ConnectionError::sftp(format!("Failed to close {}", dest), &e))?;
+        }
+
+        // Keep the mode (incl. setuid/sticky bits); ownership and times are left to the server
+        if let Some(permissions) = attrs.permissions {
+            let mut mode = russh_sftp::protocol::FileAttributes::empty();
+            mode.permissions = Some(permissions & 0o7777);
+            if let Err(e) = self.sftp.lock().await.set_metadata(dest, mode).await {
+                log::warn!("[SFTP] copy: cannot set permissions on {}: {}", dest, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Mode and access/modification times of a local file, to apply to its uploaded copy
+fn local_file_attributes(meta: &std::fs::Metadata) -> russh_sftp::protocol::FileAttributes {
+    let unix_secs = |time: std::io::Result<std::time::SystemTime>| {
+        time.ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .and_then(|d| u32::try_from(d.as_secs()).ok())
+    };
+    let mut attrs = russh_sftp::protocol::FileAttributes::empty();
+    attrs.permissions = Some(local_permissions(meta));
+    attrs.atime = unix_secs(meta.accessed());
+    attrs.mtime = unix_secs(meta.modified());
+    attrs
+}
+
+#[cfg(unix)]
+fn local_permissions(meta: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o7777
+}
+
+/// Windows has no mode bits to copy: use the usual defaults
+#[cfg(not(unix))]
+fn local_permissions(meta: &std::fs::Metadata) -> u32 {
+    if meta.is_dir() { 0o755 } else { 0o644 }
+}
+
+/// Where an interrupted download can continue, or why it has to start over
+///
+/// The partial file must be shorter than the remote file, and the remote file
+/// must not have been modified after the partial was last written (mtimes in
+/// seconds since the epoch).
+fn resume_offset(
+    local_len: u64,
+    local_mtime: Option<u64>,
+    remote_size: u64,
+    remote_mtime: Option<u32>,
+) -> Result<u64, &'static str> {
+    if remote_size == 0 {
+        return Err("remote size unknown");
+    }
+    if local_len >= remote_size {
+        return Err("local file is not smaller than the remote file");
+    }
+    match (local_mtime, remote_mtime) {
+        (Some(local), Some(remote)) if u64::from(remote) <= local => Ok(local_len),
+        (Some(_), Some(_)) => Err("remote file changed since the partial download"),
+        _ => Err("modification time unknown"),
+    }
 }
 
 #[async_trait]
@@ -215,7 +580,17 @@ impl FileTransferSession for SftpSession {
         ConnectionType::Sftp
     }
 
-    async fn list_directory(&self, path: &str) -> Result<Vec<FileInfo>, ConnectionError> {
+    fn capabilities(&self) -> SessionCapabilities {
+        SessionCapabilities {
+            chmod: true,
+            symlink: true,
+            server_side_copy: true,
+            exec: true,
+            recursive_delete: true,
+        }
+    }
+
+    async fn list_directory(&self, path: &str) -> Result<DirectoryListing, ConnectionError> {
         // Normalize path: remove trailing slash except for root
         let normalized_path = if path == "/" {
             "/"
@@ -303,6 +678,7 @@ impl FileTransferSession for SftpSession {
             .collect();
 
         // Resolve symlink targets for symlinks
+        let mut warnings = Vec::new();
         for file in &mut files {
             if file.is_symlink {
                 match sftp.read_link(&file.path).await {
@@ -324,129 +700,133 @@ impl FileTransferSession for SftpSession {
                     }
                     Err(e) => {
                         log::warn!("[SFTP] Failed to read symlink target for {}: {}", file.path, e);
+                        warnings.push(ListingWarning {
+                            path: file.path.clone(),
+                            message: format!("Cannot read symlink target: {}", e),
+                        });
                     }
                 }
             }
         }
 
-        Ok(files)
+        Ok(DirectoryListing { entries: files, warnings })
     }
 
     async fn download_file(&self, remote_path: &str, local_path: &str) -> Result<(), ConnectionError> {
-        self.download_file_with_progress(remote_path, local_path, None).await
+        self.download_file_with_progress(remote_path, local_path, None, None).await
     }
 
     async fn download_file_with_progress(
         &self,
         remote_path: &str,
         local_path: &str,
-        progress: Option<Arc<dyn Fn(u64, u64) + Send + Sync>>,
+        progress: Option<ProgressCallback>,
+        max_bytes_per_sec: Option<u64>,
     ) -> Result<(), ConnectionError> {
-        // Create local file first (before locking SFTP session)
-        let mut local_file = tokio::fs::File::create(local_path)
-            .await
-            .map_err(|e| ConnectionError::IoError(format!("Failed to create local file: {}", e)))?;
-
-        // Only lock SFTP session to get metadata and open remote file handle, then release lock
-        let (mut remote_file, total_bytes) = {
-            let mut sftp = self.sftp.lock().await;
-            
-            // Get remote file size for progress (best-effort)
-            let total_bytes = match sftp.metadata(remote_path).await {
-                Ok(attrs) => attrs.size.unwrap_or(0),
-                Err(_) => 0,
-            };
-
-            let remote_file = sftp
-                .open(remote_path)
-                .await
-                .map_err(|e| ConnectionError::SftpError(format!("Failed to open remote file: {}", e)))?;
-            
-            (remote_file, total_bytes)
-        };
-        // Lock is released here, allowing other transfers to proceed
-
-        // Now transfer data without holding the lock
-        let mut buffer = vec![0u8; 32768]; // 32KB buffer
-        let mut transferred: u64 = 0;
-        loop {
-            let n = remote_file
-                .read(&mut buffer)
-                .await
-                .map_err(|e| ConnectionError::SftpError(format!("Failed to read remote file: {}", e)))?;
-
-            if n == 0 {
-                break;
-            }
-            
-            local_file
-                .write_all(&buffer[..n])
-                .await
-                .map_err(|e| ConnectionError::IoError(format!("Failed to write local file: {}", e)))?;
-
-            transferred += n as u64;
-            if let Some(cb) = &progress {
-                cb(transferred, total_bytes);
-            }
-        }
+        self.download_to_file(remote_path, local_path, progress, max_bytes_per_sec, false).await
+    }
 
-        Ok(())
+    async fn resume_download_with_progress(
+        &self,
+        remote_path: &str,
+        local_path: &str,
+        progress: Option<ProgressCallback>,
+        max_bytes_per_sec: Option<u64>,
+    ) -> Result<(), ConnectionError> {
+        self.download_to_file(remote_path, local_path, progress, max_bytes_per_sec, true).await
     }
 
     async fn upload_file(&self, local_path: &str, remote_path: &str) -> Result<(), ConnectionError> {
-        self.upload_file_with_progress(local_path, remote_path, None).await
+        self.upload_file_with_progress(local_path, remote_path, None, None).await
     }
 
     async fn upload_file_with_progress(
         &self,
         local_path: &str,
         remote_path: &str,
-        progress: Option<Arc<dyn Fn(u64, u64) + Send + Sync>>,
+        progress: Option<ProgressCallback>,
+        max_bytes_per_sec: Option<u64>,
     ) -> Result<(), ConnectionError> {
-        // Get file metadata and open local file first (before locking SFTP session)
-        let meta = tokio::fs::metadata(local_path)
-            .await
-            .map_err(|e| ConnectionError::IoError(format!("Failed to stat local file: {}", e)))?;
-        let total_bytes = meta.len();
+        self.upload_from_file(local_path, remote_path, progress, max_bytes_per_sec, false).await
+    }
 
-        let mut local_file = tokio::fs::File::open(local_path)
+    async fn upload_file_preserving_attributes(
+        &self,
+        local_path: &str,
+        remote_path: &str,
+        progress: Option<ProgressCallback>,
+        max_bytes_per_sec: Option<u64>,
+    ) -> Result<(), ConnectionError> {
+        self.upload_from_file(local_path, remote_path, progress, max_bytes_per_sec, true).await
+    }
+
+    async fn copy(&self, source_path: &str, dest_path: &str) -> Result<Vec<ListingWarning>, ConnectionError> {
+        let attrs = self.sftp.lock().await
+            .metadata(source_path)
             .await
-            .map_err(|e| ConnectionError::IoError(format!("Failed to open local file: {}", e)))?;
+            .map_err(|e| ConnectionError::sftp(format!("Failed to stat {}", source_path), &e))?;
+        let mut skipped = Vec::new();
+        self.copy_tree(source_path, dest_path, attrs, &mut skipped).await?;
+        Ok(skipped)
+    }
 
-        // Only lock SFTP session to create remote file handle, then release lock
-        let mut remote_file = {
-            let sftp = self.sftp.lock().await;
-            sftp.create(remote_path)
-                .await
-                .map_err(|e| ConnectionError::SftpError(format!("Failed to create remote file: {}", e)))?
-        };
-        // Lock is released here, allowing other transfers to proceed
+    async fn remote_checksum(&self, path: &str, algorithm: ChecksumAlgorithm) -> Result<Option<String>, ConnectionError> {
+        let (exit_status, output) = self.exec_output(&algorithm.command(path)).await?;
+        if exit_status != Some(0) {
+            // 127: the command isn't installed
+            log::info!("[SFTP] {} of {} unavailable (exit status {:?})", algorithm.name(), path, exit_status);
+            return Ok(None);
+        }
+        Ok(parse_checksum_output(&String::from_utf8_lossy(&output), algorithm))
+    }
 
-        // Now transfer data without holding the lock
-        let mut buffer = vec![0u8; 32768]; // 32KB buffer
-        let mut transferred: u64 = 0;
-        loop {
-            let n = local_file
-                .read(&mut buffer)
-                .await
-                .map_err(|e| ConnectionError::IoError(format!("Failed to read local file: {}", e)))?;
+    /// Runs on the session's SSH connection; exit code is -1 when the server reports none (e.g. killed by a signal)
+    async fn run_command(&self, command: &str) -> Result<(String, i32), ConnectionError> {
+        let (exit_status, output) = self.exec_output(command).await?;
+        Ok((String::from_utf8_lossy(&output).into_owned(), exit_status.map_or(-1, |s| s as i32)))
+    }
 
-            if n == 0 {
-                break;
-            }
-            
-            remote_file
-                .write_all(&buffer[..n])
-                .await
-                .map_err(|e| ConnectionError::SftpError(format!("Failed to write remote file: {}", e)))?;
+    /// statvfs@openssh.com when the server offers it, otherwise `df` over an exec channel
+    async fn statfs(&self, path: &str) -> Result<FilesystemStats, ConnectionError> {
+        let info = self.sftp.lock().await
+            .fs_info(path)
+            .await
+            .map_err(|e| ConnectionError::sftp(format!("Failed to stat filesystem of {}", path), &e))?;
+        if let Some(info) = info {
+            return Ok(FilesystemStats::from_blocks(info.fragment_size, info.blocks, info.blocks_free, info.blocks_avail));
+        }
 
-            transferred += n as u64;
-            if let Some(cb) = &progress {
-                cb(transferred, total_bytes);
-            }
+        log::debug!("[SFTP] No statvfs extension, running df for {}", path);
+        let (exit_status, output) = self.exec_output(&df_command(path)).await?;
+        let output = String::from_utf8_lossy(&output);
+        match parse_df_output(&output) {
+            Some(stats) if exit_status == Some(0) => Ok(stats),
+            _ => Err(ConnectionError::UnsupportedType(format!(
+                "Cannot query free space of {} (no statvfs extension, df exited with {:?})",
+                path, exit_status
+            ))),
         }
+    }
 
-        Ok(())
+    /// None without the statvfs@openssh.com extension (df can't tell filesystems apart reliably)
+    async fn filesystem_id(&self, path: &str) -> Result<Option<u64>, ConnectionError> {
+        let info = self.sftp.lock().await
+            .fs_info(path)
+            .await
+            .map_err(|e| ConnectionError::sftp(format!("Failed to stat filesystem of {}", path), &e))?;
+        Ok(info.map(|info| info.fs_id))
+    }
+
+    async fn exec_channel(&self, command: &str) -> Result<Channel<Msg>, ConnectionError> {
+        let channel = self.ssh_handle.lock().await
+            .channel_open_session()
+            .await
+            .map_err(|e| ConnectionError::SftpError(format!("Failed to open channel: {}", e)))?;
+        channel
+            .exec(true, command.as_bytes())
+            .await
+            .map_err(|e| ConnectionError::SftpError(format!("Failed to run command: {}", e)))?;
+        Ok(channel)
     }
 
     async fn create_directory(&self, path: &str) -> Result<(), ConnectionError> {
@@ -454,7 +834,7 @@ impl FileTransferSession for SftpSession {
         
         sftp.create_dir(path)
             .await
-            .map_err(|e| ConnectionError::SftpError(format!("Failed to create directory: {}", e)))?;
+            .map_err(|e| ConnectionError::sftp("Failed to create directory", &e))?;
 
         Ok(())
     }
@@ -465,11 +845,11 @@ impl FileTransferSession for SftpSession {
         if is_directory {
             sftp.remove_dir(path)
                 .await
-                .map_err(|e| ConnectionError::SftpError(format!("Failed to remove directory: {}", e)))?;
+                .map_err(|e| ConnectionError::sftp("Failed to remove directory", &e))?;
         } else {
             sftp.remove_file(path)
                 .await
-                .map_err(|e| ConnectionError::SftpError(format!("Failed to remove file: {}", e)))?;
+                .map_err(|e| ConnectionError::sftp("Failed to remove file", &e))?;
         }
 
         Ok(())
@@ -486,11 +866,42 @@ impl FileTransferSession for SftpSession {
             .await
             .map_err(|e| {
                 log::error!("[SFTP] Failed to rename {} to {}: {}", normalized_old, normalized_new, e);
-                ConnectionError::SftpError(format!("Failed to rename: {}", e))
+                ConnectionError::sftp("Failed to rename", &e)
             })?;
         Ok(())
     }
 
+    async fn create_symlink(&self, target: &str, link_path: &str) -> Result<(), ConnectionError> {
+        let link_path = crate::core::normalize_remote_path(link_path);
+        let sftp = self.sftp.lock().await;
+
+        // SSH_FXP_SYMLINK argument order: the filexfer draft sends (linkpath, targetpath),
+        // but OpenSSH's sftp-server reads the packet as (targetpath, linkpath) and most
+        // servers copy OpenSSH. russh-sftp's symlink(a, b) puts `a` first on the wire,
+        // so the OpenSSH order means passing the target first. Verified against
+        // OpenSSH 9.x; the read_link check below catches a server that follows the draft.
+        sftp.symlink(target, link_path.as_str())
+            .await
+            .map_err(|e| ConnectionError::sftp("Failed to create symlink", &e))?;
+
+        match sftp.read_link(link_path.as_str()).await {
+            Ok(created) if created == target => Ok(()),
+            Ok(created) => Err(ConnectionError::SftpError(format!(
+                "Symlink {} points to {} instead of {}",
+                link_path, created, target
+            ))),
+            Err(_) => {
+                // A draft-order server made `target` a link to `link_path`: undo it
+                if sftp.read_link(target).await.is_ok_and(|t| t == link_path) {
+                    let _ = sftp.remove_file(target).await;
+                }
+                Err(ConnectionError::SftpError(
+                    "Server uses the reversed SFTP symlink argument order (not supported)".to_string(),
+                ))
+            }
+        }
+    }
+
     async fn chmod(&self, path: &str, mode: u32) -> Result<(), ConnectionError> {
         let sftp = self.sftp.lock().await;
         
@@ -498,7 +909,7 @@ impl FileTransferSession for SftpSession {
         let current_attrs = sftp
             .metadata(path)
             .await
-            .map_err(|e| ConnectionError::SftpError(format!("Failed to get file metadata: {}", e)))?;
+            .map_err(|e| ConnectionError::sftp("Failed to get file metadata", &e))?;
         
         // Extract current permissions (may include file type bits)
         let current_perms = current_attrs.permissions.unwrap_or(0);
@@ -564,7 +975,7 @@ impl FileTransferSession for SftpSession {
         let attrs = sftp
             .metadata(path)
             .await
-            .map_err(|e| ConnectionError::SftpError(format!("Failed to stat file: {}", e)))?;
+            .map_err(|e| ConnectionError::sftp("Failed to stat file", &e))?;
 
         let name = path.split('/').last().unwrap_or(path).to_string();
 
@@ -624,33 +1035,120 @@ impl FileTransferSession for SftpSession {
         })
     }
 
-    async fn read_file(&self, path: &str) -> Result<Vec<u8>, ConnectionError> {
+    /// Refuses an oversized file by its size before reading any of it
+    async fn read_file(&self, path: &str, max_bytes: Option<u64>) -> Result<Vec<u8>, ConnectionError> {
+        if max_bytes.is_some() {
+            let attrs = self.sftp.lock().await
+                .metadata(path)
+                .await
+                .map_err(|e| ConnectionError::sftp("Failed to stat file", &e))?;
+            file_content::check_size(attrs.size.unwrap_or(0), max_bytes)?;
+        }
+
+        // Capped too, in case the file grew after the stat
+        let range = self.read_file_range(path, 0, file_content::read_limit(max_bytes)).await?;
+        file_content::check_size(range.data.len() as u64, max_bytes)?;
+        Ok(range.data)
+    }
+
+    /// Positioned reads from `offset`, so only the window crosses the connection
+    async fn read_file_range(&self, path: &str, offset: u64, length: u64) -> Result<FileRange, ConnectionError> {
         let sftp = self.sftp.lock().await;
-        
         let mut file = sftp
             .open(path)
             .await
-            .map_err(|e| ConnectionError::SftpError(format!("Failed to open file: {}", e)))?;
+            .map_err(|e| ConnectionError::sftp("Failed to open file", &e))?;
+        file.seek(std::io::SeekFrom::Start(offset))
+            .await
+            .map_err(|e| ConnectionError::sftp("Failed to seek file", &e))?;
 
-        let mut content = Vec::new();
-        file.read_to_end(&mut content)
+        let mut data = Vec::new();
+        file.take(length.saturating_add(1))
+            .read_to_end(&mut data)
             .await
-            .map_err(|e| ConnectionError::SftpError(format!("Failed to read file: {}", e)))?;
+            .map_err(|e| ConnectionError::sftp("Failed to read file", &e))?;
+        Ok(FileRange::from_read(data, length))
+    }
+
+    /// Checks the mode bits against the login user's uid and groups (root may write anything)
+    async fn check_writable(&self, path: &str) -> Result<(), ConnectionError> {
+        let Some(user) = self.remote_user().await else {
+            return Ok(());
+        };
+        let existing = self.sftp.lock().await.metadata(path).await;
+        let (attrs, creating) = match existing {
+            Ok(attrs) => (attrs, false),
+            Err(_) => match self.sftp.lock().await.metadata(parent_directory(path)).await {
+                Ok(attrs) => (attrs, true),
+                // Let the write report a missing directory
+                Err(_) => return Ok(()),
+            },
+        };
+        let (Some(mode), Some(uid), Some(gid)) = (attrs.permissions, attrs.uid, attrs.gid) else {
+            return Ok(());
+        };
 
-        Ok(content)
+        if creating && !user.may_create_in(mode, uid, gid) {
+            return Err(ConnectionError::permission_denied(format!(
+                "Permission denied: cannot create files in {}",
+                parent_directory(path)
+            )));
+        }
+        if !creating && !user.may_write(mode, uid, gid) {
+            return Err(ConnectionError::permission_denied(format!("Permission denied: {} is read-only", path)));
+        }
+        Ok(())
     }
 
+    /// Saved via a temp file renamed over the original; a symlink's target is replaced, not the link
     async fn write_file(&self, path: &str, content: &[u8]) -> Result<(), ConnectionError> {
-        let sftp = self.sftp.lock().await;
-        
-        let mut file = sftp
-            .create(path)
-            .await
-            .map_err(|e| ConnectionError::SftpError(format!("Failed to create file: {}", e)))?;
+        let target = {
+            let sftp = self.sftp.lock().await;
+            match sftp.symlink_metadata(path).await {
+                Ok(attrs) if attrs.is_symlink() => sftp
+                    .canonicalize(path)
+                    .await
+                    .map_err(|e| ConnectionError::sftp("Failed to resolve symlink", &e))?,
+                _ => path.to_string(),
+            }
+        };
+        crate::core::atomic_write::write_via_temp(self, &target, content).await
+    }
 
-        file.write_all(content)
+    /// Keeps the file open between chunks; a failed chunk drops (and closes) the handle
+    async fn write_chunk(&self, path: &str, offset: u64, data: &[u8], is_last: bool) -> Result<(), ConnectionError> {
+        let cached = self.open_uploads.lock().await.remove(path);
+        let mut file = match cached {
+            Some((file, next_offset)) if offset > 0 && next_offset == offset => file,
+            _ => {
+                let mut file = {
+                    let sftp = self.sftp.lock().await;
+                    if offset == 0 {
+                        sftp.create(path).await
+                    } else {
+                        sftp.open_with_flags(path, OpenFlags::WRITE).await
+                    }
+                    .map_err(|e| ConnectionError::sftp("Failed to open remote file", &e))?
+                };
+                file.seek(std::io::SeekFrom::Start(offset))
+                    .await
+                    .map_err(|e| ConnectionError::sftp("Failed to seek remote file", &e))?;
+                file
+            }
+        };
+
+        file.write_all(data)
             .await
-            .map_err(|e| ConnectionError::SftpError(format!("Failed to write file: {}", e)))?;
+            .map_err(|e| ConnectionError::sftp("Failed to write remote file", &e))?;
+
+        if is_last {
+            file.shutdown()
+                .await
+                .map_err(|e| ConnectionError::sftp("Failed to close remote file", &e))?;
+        } else {
+            let next_offset = offset + data.len() as u64;
+            self.open_uploads.lock().await.insert(path.to_string(), (file, next_offset));
+        }
 
         Ok(())
     }
@@ -662,3 +1160,35 @@ impl FileTransferSession for SftpSession {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_offset_requires_unchanged_larger_remote() {
+        assert_eq!(resume_offset(100, Some(2_000), 500, Some(1_000)), Ok(100));
+        // Remote written after the partial: its content may differ
+        assert!(resume_offset(100, Some(2_000), 500, Some(3_000)).is_err());
+        // Remote shrank or partial already complete
+        assert!(resume_offset(500, Some(2_000), 500, Some(1_000)).is_err());
+        assert!(resume_offset(100, Some(2_000), 0, Some(1_000)).is_err());
+        assert!(resume_offset(100, None, 500, Some(1_000)).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_local_file_attributes_keep_mode_and_mtime() {
+        use std::os::unix::fs::PermissionsExt;
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::set_permissions(file.path(), std::fs::Permissions::from_mode(0o700)).unwrap();
+        let mtime = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000);
+        file.as_file().set_modified(mtime).unwrap();
+
+        let attrs = local_file_attributes(&std::fs::metadata(file.path()).unwrap());
+        assert_eq!(attrs.permissions, Some(0o700));
+        assert_eq!(attrs.mtime, Some(1_600_000_000));
+        assert!(attrs.atime.is_some());
+        assert_eq!(attrs.size, None);
+    }
+}
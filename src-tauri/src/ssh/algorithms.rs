@@ -0,0 +1,167 @@
+use crate::ssh::config::AlgorithmPreferences;
+use crate::ssh::error::SshError;
+use russh::keys::Algorithm;
+use russh::{cipher, compression, kex, mac, Preferred};
+use std::borrow::Cow;
+use std::str::FromStr;
+
+/// Compression offered when a host enables it; "none" stays last so servers without zlib still connect
+pub const COMPRESSION_PREFERRED: &[compression::Name] = &[compression::ZLIB_LEGACY, compression::ZLIB, compression::NONE];
+
+/// Build russh algorithm preferences from host config
+/// Empty lists keep russh's defaults for that category; a list starting with "+" is added to them
+pub fn build_preferred(prefs: &AlgorithmPreferences) -> Result<Preferred, SshError> {
+    let mut preferred = Preferred::default();
+
+    if !prefs.kex.is_empty() {
+        let mut names = resolve(&prefs.kex, &preferred.kex, |names| {
+            parse_names::<kex::Name>(names, "key exchange", kex::ALL_KEX_ALGORITHMS)
+        })?;
+        // Keep protocol extension markers so ext-info and strict kex still work
+        for marker in [kex::EXTENSION_SUPPORT_AS_CLIENT, kex::EXTENSION_OPENSSH_STRICT_KEX_AS_CLIENT] {
+            if !names.contains(&marker) {
+                names.push(marker);
+            }
+        }
+        preferred.kex = Cow::Owned(names);
+    }
+
+    if !prefs.cipher.is_empty() {
+        let names = resolve(&prefs.cipher, &preferred.cipher, |names| {
+            parse_names::<cipher::Name>(names, "cipher", cipher::ALL_CIPHERS)
+        })?;
+        preferred.cipher = Cow::Owned(names);
+    }
+
+    if !prefs.mac.is_empty() {
+        let names = resolve(&prefs.mac, &preferred.mac, |names| {
+            parse_names::<mac::Name>(names, "MAC", mac::ALL_MAC_ALGORITHMS)
+        })?;
+        preferred.mac = Cow::Owned(names);
+    }
+
+    if !prefs.host_key.is_empty() {
+        preferred.key = Cow::Owned(resolve(&prefs.host_key, &preferred.key, parse_host_key_algorithms)?);
+    }
+
+    Ok(preferred)
+}
+
+/// One category's list: the given names (comma-separated entries allowed), or with a
+/// leading "+" (OpenSSH's `-oKexAlgorithms=+...`) russh's defaults followed by them
+fn resolve<N: Clone + PartialEq>(
+    names: &[String],
+    defaults: &[N],
+    parse: impl FnOnce(&[String]) -> Result<Vec<N>, SshError>,
+) -> Result<Vec<N>, SshError> {
+    let mut names: Vec<String> = names
+        .iter()
+        .flat_map(|n| n.split(','))
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(str::to_string)
+        .collect();
+    let append = names.first().is_some_and(|n| n.starts_with('+'));
+    if !append {
+        return parse(&names);
+    }
+
+    names[0].remove(0);
+    let mut list = defaults.to_vec();
+    for name in parse(&names)? {
+        if !list.contains(&name) {
+            list.push(name);
+        }
+    }
+    Ok(list)
+}
+
+/// Parse algorithm names, rejecting any russh does not implement
+fn parse_names<N>(names: &[String], kind: &str, supported: &[&N]) -> Result<Vec<N>, SshError>
+where
+    N: for<'a> TryFrom<&'a str> + AsRef<str>,
+{
+    names
+        .iter()
+        .map(|name| {
+            N::try_from(name.trim()).map_err(|_| {
+                let supported: Vec<&str> = supported.iter().map(|n| n.as_ref()).collect();
+                SshError::Connection(format!(
+                    "Unsupported {} algorithm '{}'. Supported: {}",
+                    kind,
+                    name,
+                    supported.join(", ")
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Parse host key algorithm names (e.g. ssh-ed25519, rsa-sha2-512)
+fn parse_host_key_algorithms(names: &[String]) -> Result<Vec<Algorithm>, SshError> {
+    names
+        .iter()
+        .map(|name| match Algorithm::from_str(name.trim()) {
+            Ok(Algorithm::Other(_)) | Err(_) => Err(SshError::Connection(format!(
+                "Unsupported host key algorithm '{}'",
+                name
+            ))),
+            Ok(alg) => Ok(alg),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefs(kex: &[&str], cipher: &[&str], mac: &[&str], host_key: &[&str]) -> AlgorithmPreferences {
+        let to_vec = |v: &[&str]| v.iter().map(|s| s.to_string()).collect();
+        AlgorithmPreferences {
+            kex: to_vec(kex),
+            cipher: to_vec(cipher),
+            mac: to_vec(mac),
+            host_key: to_vec(host_key),
+        }
+    }
+
+    #[test]
+    fn test_empty_keeps_defaults() {
+        let preferred = build_preferred(&AlgorithmPreferences::default()).unwrap();
+        assert_eq!(preferred.cipher, Preferred::default().cipher);
+        assert_eq!(preferred.kex, Preferred::default().kex);
+    }
+
+    #[test]
+    fn test_legacy_kex_enabled() {
+        let preferred = build_preferred(&prefs(&["diffie-hellman-group14-sha1"], &[], &[], &[])).unwrap();
+        assert_eq!(preferred.kex[0].as_ref(), "diffie-hellman-group14-sha1");
+        assert!(preferred.kex.contains(&kex::EXTENSION_SUPPORT_AS_CLIENT));
+    }
+
+    #[test]
+    fn test_restricted_cipher_and_host_key() {
+        let preferred = build_preferred(&prefs(&[], &["aes256-gcm@openssh.com"], &["hmac-sha2-256"], &["ssh-ed25519"])).unwrap();
+        assert_eq!(preferred.cipher.len(), 1);
+        assert_eq!(preferred.mac.len(), 1);
+        assert_eq!(preferred.key.as_ref(), &[Algorithm::Ed25519]);
+    }
+
+    #[test]
+    fn test_plus_appends_to_defaults() {
+        let preferred = build_preferred(&prefs(&["+diffie-hellman-group1-sha1,diffie-hellman-group14-sha1"], &["+aes128-cbc"], &[], &[])).unwrap();
+        let defaults = Preferred::default();
+        assert_eq!(&preferred.kex[..defaults.kex.len()], &defaults.kex[..]);
+        assert_eq!(preferred.kex[defaults.kex.len()].as_ref(), "diffie-hellman-group1-sha1");
+        assert_eq!(preferred.cipher.len(), defaults.cipher.len() + 1);
+        assert_eq!(preferred.cipher.last().unwrap().as_ref(), "aes128-cbc");
+    }
+
+    #[test]
+    fn test_unknown_names_rejected() {
+        let err = build_preferred(&prefs(&[], &["rot13"], &[], &[])).unwrap_err();
+        assert!(err.to_string().contains("Unsupported cipher algorithm 'rot13'"));
+        assert!(build_preferred(&prefs(&[], &[], &[], &["ssh-foo"])).is_err());
+        assert!(build_preferred(&prefs(&["dh-bogus"], &[], &[], &[])).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::ssh::client::{self, SshClient};
+use crate::ssh::client::{self, ConnectionInfoSlot, SshClient};
 use crate::ssh::config::{HostConfig, ChainProgress};
 use crate::ssh::error::SshError;
 use log::{debug, info, warn};
@@ -9,19 +9,32 @@ use std::future::Future;
 use tauri::{AppHandle, Emitter};
 use tokio::net::TcpListener;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::task::JoinHandle;
 
 /// Handler for a single hop in SSH chain (Chain of Responsibility pattern)
+///
+/// Each hop is its own SSH connection from this machine, tunneled through the
+/// previous one. A forwarded agent therefore belongs to the target hop's handle:
+/// the agent channel is opened by the target, travels inside the tunnel and is
+/// answered by the target's `SshClient`. Jump hosts never get the agent.
 pub struct HopHandler {
     config: HostConfig,
     next: Option<Box<HopHandler>>,
     hop_index: usize,
     total_hops: usize,
+    /// Records negotiated connection details (set on the target hop only)
+    info: Option<ConnectionInfoSlot>,
 }
 
 impl HopHandler {
     /// Create a chain of handlers from config
+    /// `forward_agent` is dropped from jump hosts, so they can't open agent channels
     pub fn from_config(jumps: &[HostConfig], target: &HostConfig) -> Self {
         let mut handlers: Vec<HostConfig> = jumps.to_vec();
+        for jump in handlers.iter_mut().filter(|jump| jump.forward_agent) {
+            info!("SSH agent forwarding to jump host {} ignored (only the target gets the agent)", jump.hostname);
+            jump.forward_agent = false;
+        }
         handlers.push(target.clone());
         let total = handlers.len();
         
@@ -36,15 +49,36 @@ impl HopHandler {
                 next: None,
                 hop_index: index,
                 total_hops: total,
+                info: None,
             },
             [first, rest @ ..] => HopHandler {
                 config: first.clone(),
                 next: Some(Box::new(Self::build_chain(rest, index + 1, total))),
                 hop_index: index,
                 total_hops: total,
+                info: None,
             },
         }
     }
+
+    /// Record the target hop's negotiated connection details into `info`
+    pub fn with_connection_info(mut self, info: ConnectionInfoSlot) -> Self {
+        let mut hop = &mut self;
+        while let Some(ref mut next) = hop.next {
+            hop = next;
+        }
+        hop.info = Some(info);
+        self
+    }
+
+    /// Handler for this hop, recording connection details when requested
+    fn client(&self, app_handle: &AppHandle) -> SshClient {
+        let client = match &self.info {
+            Some(info) => SshClient::with_info(&self.config, info.clone()),
+            None => SshClient::new(&self.config),
+        };
+        client.with_events(app_handle.clone())
+    }
     
     /// Emit progress event to frontend
     fn emit_progress(&self, app_handle: &AppHandle, status: &str, message: &str) {
@@ -57,6 +91,19 @@ impl HopHandler {
         };
         let _ = app_handle.emit("ssh-chain-progress", progress);
     }
+
+    /// Attribute an error to this hop and report it as a failed progress event
+    fn fail(&self, app_handle: &AppHandle, err: SshError) -> SshError {
+        let err = err.context(&format!(
+            "hop {}/{} ({})",
+            self.hop_index + 1,
+            self.total_hops,
+            self.config.hostname
+        ));
+        warn!("SSH chain failed: {}", err);
+        self.emit_progress(app_handle, "failed", &err.to_string());
+        err
+    }
     
     /// Execute the chain, returning final session handle
     pub fn execute<'a>(
@@ -71,21 +118,27 @@ impl HopHandler {
         self.emit_progress(app_handle, "connecting", 
             &format!("{}: Connecting to {}:{}", hop_label, self.config.hostname, self.config.port));
         
-        let mut handle = match transport {
+        let connected = match transport {
             Some(channel) => {
                 // Connect over existing channel (tunnel)
-                Self::connect_over_channel(channel, &self.config).await?
+                Self::connect_over_channel(channel, &self.config, self.client(app_handle)).await
             }
             None => {
                 // First hop: direct TCP connection
-                client::connect_direct(&self.config).await?
+                client::connect_direct_with(&self.config, self.client(app_handle)).await
             }
         };
+        let mut handle = connected.map_err(|e| self.fail(app_handle, e))?;
         
         // 2. Authenticate
         self.emit_progress(app_handle, "authenticating",
             &format!("{}: Authenticating as {}", hop_label, self.config.username));
-        client::authenticate(&mut handle, &self.config).await?;
+        let agent_auth = client::authenticate(&mut handle, &self.config, Some(app_handle))
+            .await
+            .map_err(|e| self.fail(app_handle, e))?;
+        if let Some(info) = &self.info {
+            client::record_agent_auth(info, agent_auth);
+        }
         
         self.emit_progress(app_handle, "connected",
             &format!("{}: Connected to {}", hop_label, self.config.hostname));
@@ -98,22 +151,26 @@ impl HopHandler {
             // Wait a bit for the session to stabilize
             tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
             
-            let tunnel = match handle
-                .channel_open_direct_tcpip(
-                    &next.config.hostname,
-                    next.config.port as u32,
-                    "127.0.0.1",
-                    22222,
-                )
-                .await
-            {
+            // The jump host connects to the next hop when the channel opens
+            let open_tunnel = async {
+                handle
+                    .channel_open_direct_tcpip(
+                        &next.config.hostname,
+                        next.config.port as u32,
+                        "127.0.0.1",
+                        22222,
+                    )
+                    .await
+                    .map_err(SshError::from)
+            };
+            let tunnel = match client::within_connect_timeout(&next.config, open_tunnel).await {
                 Ok(ch) => ch,
                 Err(e) => {
                     warn!("SSH chain failed to open tunnel: {:?}", e);
-                    return Err(SshError::Connection(format!(
+                    return Err(self.fail(app_handle, SshError::Connection(format!(
                         "Cannot open tunnel to {}:{} - check if TCP forwarding is enabled on jump host and target is reachable. Error: {}",
                         next.config.hostname, next.config.port, e
-                    )));
+                    ))));
                 }
             };
             debug!("SSH chain tunnel opened, channel id: {:?}", tunnel.id());
@@ -132,6 +189,7 @@ impl HopHandler {
     async fn connect_over_channel(
         mut channel: Channel<Msg>,
         config: &HostConfig,
+        client: SshClient,
     ) -> Result<Handle<SshClient>, SshError> {
         // 1. Bind local listener on random port
         let listener = TcpListener::bind("127.0.0.1:0").await
@@ -142,7 +200,7 @@ impl HopHandler {
         debug!("SSH chain local bridge listener on {}", local_addr);
         
         // 2. Spawn bridge task: channel <-> TCP
-        tokio::spawn(async move {
+        let bridge = BridgeTask(Some(tokio::spawn(async move {
             // Accept one connection (the SSH client we'll create)
             let Ok((mut stream, _)) = listener.accept().await else {
                 warn!("SSH chain bridge: failed to accept");
@@ -197,15 +255,41 @@ impl HopHandler {
             }
             
             debug!("SSH chain bridge: I/O loop ended");
-        });
+        })));
         
         // 3. Connect SSH client through local bridge
-        let ssh_config = Arc::new(russh::client::Config::default());
-        let client = SshClient::new();
+        let ssh_config = Arc::new(client::client_config(config)?);
         
         debug!("SSH chain connecting through bridge to {}:{}", config.hostname, config.port);
         
-        russh::client::connect(ssh_config, local_addr, client).await
-            .map_err(|e| SshError::Connection(format!("SSH over tunnel failed: {}", e)))
+        let info = client.info();
+        let connect = async {
+            russh::client::connect(ssh_config, local_addr, client).await
+                .map_err(|e| {
+                    client::connect_failure(&info, e, |e| SshError::Connection(format!("SSH over tunnel failed: {}", e)))
+                })
+        };
+        let handle = client::within_handshake_timeout(config, &info, connect).await?;
+        bridge.keep();
+        Ok(handle)
+    }
+}
+
+/// Bridge task for a hop, aborted if the connection through it fails or is cancelled
+/// (otherwise it would wait in accept() forever and keep the jump channel open)
+struct BridgeTask(Option<JoinHandle<()>>);
+
+impl BridgeTask {
+    /// The connection is up; the bridge lives as long as it does
+    fn keep(mut self) {
+        self.0.take();
+    }
+}
+
+impl Drop for BridgeTask {
+    fn drop(&mut self) {
+        if let Some(task) = self.0.take() {
+            task.abort();
+        }
     }
 }
@@ -1,4 +1,12 @@
+use crate::core::initial_command::InitialCommand;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Connect timeout when a host doesn't set one
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 30;
+
+/// Terminal type (TERM) requested when none is configured
+pub const DEFAULT_TERM: &str = "xterm-256color";
 
 /// Connection type enum
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -17,6 +25,88 @@ impl Default for ConnectionType {
     }
 }
 
+impl ConnectionType {
+    /// Parse the lowercase protocol name used by the frontend ("sftp", "ftps", ...)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "ssh" => Some(ConnectionType::Ssh),
+            "sftp" => Some(ConnectionType::Sftp),
+            "ftp" => Some(ConnectionType::Ftp),
+            "ftps" => Some(ConnectionType::Ftps),
+            "telnet" => Some(ConnectionType::Telnet),
+            _ => None,
+        }
+    }
+}
+
+/// Well-known port for a protocol
+///
+/// - SSH / SFTP: 22
+/// - FTP: 21
+/// - FTPS: 21 (explicit TLS via AUTH TLS; implicit FTPS on 990 is not supported)
+/// - Telnet: 23
+pub fn default_port(connection_type: &ConnectionType) -> u16 {
+    match connection_type {
+        ConnectionType::Ssh | ConnectionType::Sftp => 22,
+        ConnectionType::Ftp | ConnectionType::Ftps => 21,
+        ConnectionType::Telnet => 23,
+    }
+}
+
+/// Port to connect to: the given one, or the protocol default when it is 0 (left blank)
+pub fn resolve_port(port: u16, connection_type: &ConnectionType) -> u16 {
+    if port == 0 {
+        default_port(connection_type)
+    } else {
+        port
+    }
+}
+
+/// How strictly to verify server identities (SSH host keys, FTPS certificates)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecurityPolicy {
+    /// Only connect to servers whose identity is already known
+    Strict,
+    /// Trust on first use: remember new servers, reject changed ones
+    #[default]
+    AcceptNew,
+    /// Skip verification entirely
+    AcceptAll,
+}
+
+/// How a presented server identity compares to what was recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HostKeyStatus {
+    Known,
+    Unknown,
+    Changed,
+}
+
+/// What to do with a presented server identity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HostKeyVerdict {
+    Accept,
+    /// Accept and record it for next time
+    Remember,
+    Reject,
+}
+
+impl SecurityPolicy {
+    /// Decide whether to trust a server identity with the given status
+    pub fn verdict(self, status: HostKeyStatus) -> HostKeyVerdict {
+        match (self, status) {
+            (SecurityPolicy::AcceptAll, _) => HostKeyVerdict::Accept,
+            (_, HostKeyStatus::Known) => HostKeyVerdict::Accept,
+            (_, HostKeyStatus::Changed) => HostKeyVerdict::Reject,
+            (SecurityPolicy::AcceptNew, HostKeyStatus::Unknown) => HostKeyVerdict::Remember,
+            (SecurityPolicy::Strict, HostKeyStatus::Unknown) => HostKeyVerdict::Reject,
+        }
+    }
+}
+
 /// SSH authentication method
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SshAuth {
@@ -25,9 +115,53 @@ pub enum SshAuth {
         path: String,
         passphrase: Option<String>,
     },
+    /// Server-driven prompts (2FA/OTP), answered through "ssh-auth-prompt" events
+    KeyboardInteractive,
     Agent,
 }
 
+impl SshAuth {
+    /// Auth for a frontend method name ("password", "key", "agent", "keyboard-interactive")
+    pub fn from_method(
+        method: &str,
+        key_path: Option<&str>,
+        key_passphrase: Option<&str>,
+        password: Option<&str>,
+    ) -> Result<Self, String> {
+        Ok(match method {
+            "password" => SshAuth::Password(password.ok_or("Password required")?.to_string()),
+            "key" => SshAuth::Key {
+                path: key_path.ok_or("Key path required")?.to_string(),
+                passphrase: key_passphrase.map(str::to_string),
+            },
+            "agent" => SshAuth::Agent,
+            "keyboard-interactive" => SshAuth::KeyboardInteractive,
+            _ => return Err(format!("Unknown auth method: {}", method)),
+        })
+    }
+}
+
+/// Preferred SSH algorithms (names as used by OpenSSH, in priority order)
+/// Empty lists fall back to the library defaults; starting a list with "+" (e.g. "+ssh-rsa")
+/// adds to the defaults instead of replacing them, like OpenSSH's `-oKexAlgorithms=+...`.
+/// Unknown names are rejected before connecting, with the supported names in the error.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlgorithmPreferences {
+    /// e.g. curve25519-sha256, ecdh-sha2-nistp256, diffie-hellman-group14-sha256,
+    /// legacy diffie-hellman-group14-sha1 and diffie-hellman-group1-sha1
+    #[serde(default)]
+    pub kex: Vec<String>,
+    /// e.g. chacha20-poly1305@openssh.com, aes256-gcm@openssh.com, aes128-ctr, legacy aes128-cbc and aes256-cbc
+    #[serde(default)]
+    pub cipher: Vec<String>,
+    /// e.g. hmac-sha2-256-etm@openssh.com, hmac-sha2-512, legacy hmac-sha1
+    #[serde(default)]
+    pub mac: Vec<String>,
+    /// e.g. ssh-ed25519, ecdsa-sha2-nistp256, rsa-sha2-512, legacy ssh-rsa
+    #[serde(default)]
+    pub host_key: Vec<String>,
+}
+
 /// Configuration for a single SSH host (internal use)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HostConfig {
@@ -35,45 +169,204 @@ pub struct HostConfig {
     pub port: u16,
     pub username: String,
     pub auth: SshAuth,
+    /// Tried in order after `auth` is rejected, on the same connection
+    /// (methods the server no longer lists as remaining are skipped)
+    #[serde(default)]
+    pub fallback_auth: Vec<SshAuth>,
     #[serde(default)]
     pub connection_type: ConnectionType,
+    #[serde(default)]
+    pub algorithms: AlgorithmPreferences,
+    #[serde(default)]
+    pub security_policy: SecurityPolicy,
+    /// Give up on TCP connect + handshake after this many seconds (None or 0 = 30s)
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    /// Connect through this command's stdin/stdout instead of TCP (OpenSSH ProxyCommand;
+    /// %h, %p and %r expand to host, port and user). Only used for the first hop of a chain
+    #[serde(default)]
+    pub proxy_command: Option<String>,
+    /// Prefer zlib compression (helps on slow, high-latency links)
+    #[serde(default)]
+    pub compression: bool,
+    /// Forward the local SSH agent to this host (ssh -A)
+    /// In a chain only the target gets it: jump hosts only carry tunnels, so there is
+    /// no session on them to forward into, and agent requests from the target travel
+    /// inside the tunnel without the jump hosts seeing them
+    #[serde(default)]
+    pub forward_agent: bool,
+}
+
+impl HostConfig {
+    /// How long connecting to this host may take
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.connect_timeout_secs
+                .filter(|&secs| secs > 0)
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS),
+        )
+    }
+
+    /// Auth methods in the order they are attempted
+    pub fn auth_methods(&self) -> impl Iterator<Item = &SshAuth> {
+        std::iter::once(&self.auth).chain(&self.fallback_auth)
+    }
 }
 
 /// Host config from frontend (flat structure for JSON)
 #[derive(Debug, Clone, Deserialize)]
 pub struct HostConfigInput {
     pub hostname: String,
+    /// 0 or omitted = protocol default
+    #[serde(default)]
     pub port: u16,
     pub username: String,
     pub auth_method: String,
+    /// Methods to try after `auth_method` is rejected, in order (same names and credentials)
+    #[serde(default)]
+    pub fallback_auth_methods: Vec<String>,
     pub key_path: Option<String>,
+    /// Passphrase for an encrypted key file
+    #[serde(default)]
+    pub key_passphrase: Option<String>,
     pub password: Option<String>,
     #[serde(default)]
     pub connection_type: Option<ConnectionType>,
+    #[serde(default)]
+    pub algorithms: Option<AlgorithmPreferences>,
+    #[serde(default)]
+    pub security_policy: Option<SecurityPolicy>,
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub proxy_command: Option<String>,
+    #[serde(default)]
+    pub compression: bool,
+    #[serde(default)]
+    pub forward_agent: bool,
 }
 
 impl HostConfigInput {
     /// Convert to internal HostConfig
     pub fn into_host_config(self) -> Result<HostConfig, String> {
-        let auth = match self.auth_method.as_str() {
-            "password" => {
-                let pwd = self.password.ok_or("Password required")?;
-                SshAuth::Password(pwd)
-            }
-            "key" => {
-                let path = self.key_path.ok_or("Key path required")?;
-                SshAuth::Key { path, passphrase: None }
-            }
-            "agent" => SshAuth::Agent,
-            _ => return Err(format!("Unknown auth method: {}", self.auth_method)),
-        };
-        
+        let auth = self.auth(&self.auth_method)?;
+        let fallback_auth = self
+            .fallback_auth_methods
+            .iter()
+            .map(|method| self.auth(method))
+            .collect::<Result<_, _>>()?;
+
+        let connection_type = self.connection_type.unwrap_or(ConnectionType::Ssh);
         Ok(HostConfig {
             hostname: self.hostname,
-            port: self.port,
+            port: resolve_port(self.port, &connection_type),
             username: self.username,
             auth,
-            connection_type: self.connection_type.unwrap_or(ConnectionType::Ssh),
+            fallback_auth,
+            connection_type,
+            algorithms: self.algorithms.unwrap_or_default(),
+            security_policy: self.security_policy.unwrap_or_default(),
+            connect_timeout_secs: self.connect_timeout_secs,
+            proxy_command: self.proxy_command,
+            compression: self.compression,
+            forward_agent: self.forward_agent,
+        })
+    }
+
+    /// Auth for a method name, using the credentials given with the host
+    fn auth(&self, method: &str) -> Result<SshAuth, String> {
+        SshAuth::from_method(
+            method,
+            self.key_path.as_deref(),
+            self.key_passphrase.as_deref(),
+            self.password.as_deref(),
+        )
+    }
+}
+
+/// Options of create_ssh_session: one host plus its terminal (camelCase, like the
+/// command's other arguments)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshSessionInput {
+    pub hostname: String,
+    /// 0 or omitted = 22
+    #[serde(default)]
+    pub port: Option<u16>,
+    pub username: String,
+    pub auth_method: String,
+    /// Tried in order on the same connection if `auth_method` is rejected
+    #[serde(default)]
+    pub fallback_auth_methods: Option<Vec<String>>,
+    pub key_path: Option<String>,
+    /// Passphrase for an encrypted key file
+    #[serde(default)]
+    pub key_passphrase: Option<String>,
+    pub password: Option<String>,
+    /// Terminal size (default 80x24)
+    #[serde(default)]
+    pub cols: Option<u16>,
+    #[serde(default)]
+    pub rows: Option<u16>,
+    #[serde(default)]
+    pub keepalive_secs: Option<u64>,
+    #[serde(default)]
+    pub scrollback_bytes: Option<usize>,
+    #[serde(default)]
+    pub term: Option<String>,
+    #[serde(default)]
+    pub agent_forward: Option<bool>,
+    #[serde(default)]
+    pub initial_command: Option<InitialCommand>,
+    #[serde(default)]
+    pub security_policy: Option<SecurityPolicy>,
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub proxy_command: Option<String>,
+    #[serde(default)]
+    pub compression: Option<bool>,
+    #[serde(default)]
+    pub algorithms: Option<AlgorithmPreferences>,
+}
+
+impl SshSessionInput {
+    /// Convert to internal SshConfig (a direct connection, no jumps)
+    pub fn into_ssh_config(self) -> Result<SshConfig, String> {
+        if let Some(term) = &self.term {
+            validate_term(term)?;
+        }
+
+        let target = HostConfigInput {
+            hostname: self.hostname,
+            port: self.port.unwrap_or(0),
+            username: self.username,
+            auth_method: self.auth_method,
+            fallback_auth_methods: self.fallback_auth_methods.unwrap_or_default(),
+            key_path: self.key_path,
+            key_passphrase: self.key_passphrase,
+            password: self.password,
+            connection_type: Some(ConnectionType::Ssh),
+            algorithms: self.algorithms,
+            security_policy: self.security_policy,
+            connect_timeout_secs: self.connect_timeout_secs,
+            proxy_command: self.proxy_command,
+            compression: self.compression.unwrap_or(false),
+            forward_agent: self.agent_forward.unwrap_or(false),
+        }
+        .into_host_config()?;
+
+        Ok(SshConfig {
+            target,
+            jumps: Vec::new(),
+            terminal: TerminalConfig {
+                cols: self.cols.unwrap_or(80),
+                rows: self.rows.unwrap_or(24),
+                keepalive_secs: self.keepalive_secs,
+                scrollback_bytes: self.scrollback_bytes,
+                term: self.term,
+            },
+            initial_command: self.initial_command,
         })
     }
 }
@@ -85,7 +378,14 @@ impl Default for HostConfig {
             port: 22,
             username: String::new(),
             auth: SshAuth::Agent,
+            fallback_auth: Vec::new(),
             connection_type: ConnectionType::Ssh,
+            algorithms: AlgorithmPreferences::default(),
+            security_policy: SecurityPolicy::default(),
+            connect_timeout_secs: None,
+            proxy_command: None,
+            compression: false,
+            forward_agent: false,
         }
     }
 }
@@ -95,12 +395,39 @@ impl Default for HostConfig {
 pub struct TerminalConfig {
     pub cols: u16,
     pub rows: u16,
+    /// Send an SSH keepalive after this many idle seconds (None = disabled, like ServerAliveInterval)
+    #[serde(default)]
+    pub keepalive_secs: Option<u64>,
+    /// Bytes of recent output kept for get_scrollback (None = 256KB, 0 = keep none)
+    #[serde(default)]
+    pub scrollback_bytes: Option<usize>,
+    /// Terminal type requested with the PTY (None = xterm-256color)
+    #[serde(default)]
+    pub term: Option<String>,
 }
 
 impl Default for TerminalConfig {
     fn default() -> Self {
-        Self { cols: 80, rows: 24 }
+        Self { cols: 80, rows: 24, keepalive_secs: None, scrollback_bytes: None, term: None }
+    }
+}
+
+impl TerminalConfig {
+    /// Terminal type to request
+    pub fn term(&self) -> &str {
+        self.term.as_deref().unwrap_or(DEFAULT_TERM)
+    }
+}
+
+/// Check a terminal type before it is sent to a server (SSH pty-req, Telnet TTYPE)
+pub fn validate_term(term: &str) -> Result<(), String> {
+    if term.is_empty() {
+        return Err("Terminal type cannot be empty".to_string());
+    }
+    if term.chars().any(|c| c.is_control()) {
+        return Err(format!("Terminal type contains control characters: {:?}", term));
     }
+    Ok(())
 }
 
 /// Complete SSH connection configuration
@@ -109,6 +436,9 @@ pub struct SshConfig {
     pub target: HostConfig,
     pub jumps: Vec<HostConfig>,
     pub terminal: TerminalConfig,
+    /// Typed into the shell once its prompt is ready
+    #[serde(default)]
+    pub initial_command: Option<InitialCommand>,
 }
 
 /// Progress event for SSH chain connection
@@ -121,3 +451,68 @@ pub struct ChainProgress {
     pub message: String,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blank_port_uses_protocol_default() {
+        assert_eq!(resolve_port(0, &ConnectionType::Sftp), 22);
+        assert_eq!(resolve_port(0, &ConnectionType::Ftps), 21);
+        assert_eq!(resolve_port(0, &ConnectionType::Telnet), 23);
+        assert_eq!(resolve_port(2222, &ConnectionType::Ssh), 2222);
+        assert_eq!(ConnectionType::from_name("FTP"), Some(ConnectionType::Ftp));
+        assert_eq!(ConnectionType::from_name("rdp"), None);
+    }
+
+    #[test]
+    fn test_term_defaults_and_validation() {
+        let mut terminal = TerminalConfig::default();
+        assert_eq!(terminal.term(), "xterm-256color");
+        terminal.term = Some("screen-256color".to_string());
+        assert_eq!(terminal.term(), "screen-256color");
+
+        assert!(validate_term("tmux-256color").is_ok());
+        assert!(validate_term("").is_err());
+        assert!(validate_term("xterm\r\nwhoami").is_err());
+    }
+
+    #[test]
+    fn test_connect_timeout_defaults_to_30s() {
+        let mut config = HostConfig::default();
+        assert_eq!(config.connect_timeout(), Duration::from_secs(30));
+        config.connect_timeout_secs = Some(0);
+        assert_eq!(config.connect_timeout(), Duration::from_secs(30));
+        config.connect_timeout_secs = Some(5);
+        assert_eq!(config.connect_timeout(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_ssh_session_input_fills_defaults() {
+        let input: SshSessionInput = serde_json::from_value(serde_json::json!({
+            "hostname": "example.com",
+            "username": "deploy",
+            "authMethod": "password",
+            "password": "secret",
+            "agentForward": true,
+            "fallbackAuthMethods": null,
+            "compression": null,
+        }))
+        .unwrap();
+        let config = input.into_ssh_config().unwrap();
+        assert_eq!(config.target.port, 22);
+        assert!(matches!(config.target.auth, SshAuth::Password(ref p) if p == "secret"));
+        assert!(config.target.forward_agent);
+        assert!(config.jumps.is_empty());
+        assert_eq!((config.terminal.cols, config.terminal.rows), (80, 24));
+
+        let input: SshSessionInput = serde_json::from_value(serde_json::json!({
+            "hostname": "example.com",
+            "username": "deploy",
+            "authMethod": "agent",
+            "term": "",
+        }))
+        .unwrap();
+        assert!(input.into_ssh_config().is_err());
+    }
+}
@@ -1,3 +1,6 @@
+use crate::core::error::{ErrorCode, ErrorStatus};
+use russh::{MethodKind, MethodSet};
+use serde::Serialize;
 use thiserror::Error;
 
 /// SSH-related errors
@@ -6,12 +9,25 @@ pub enum SshError {
     #[error("Connection error: {0}")]
     Connection(String),
 
-    #[error("Authentication failed: {0}")]
-    AuthFailed(String),
+    #[error("Authentication failed: {reason}")]
+    AuthFailed {
+        /// Method that was tried
+        method: MethodKind,
+        /// Methods the server still accepts (empty when it never got to answer)
+        remaining_methods: Vec<MethodKind>,
+        reason: String,
+    },
 
     #[error("Key error: {0}")]
     KeyError(String),
 
+    /// Key file is encrypted and no passphrase was given (frontend should prompt)
+    #[error("Key passphrase required: {0}")]
+    PassphraseRequired(String),
+
+    #[error("Incorrect key passphrase: {0}")]
+    PassphraseIncorrect(String),
+
     #[error("Channel error: {0}")]
     ChannelError(String),
 
@@ -25,14 +41,198 @@ pub enum SshError {
     ProtocolError(String),
 }
 
+impl SshError {
+    /// Stable category for the frontend to branch on
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            SshError::AuthFailed { .. } | SshError::KeyError(_) => ErrorCode::AuthFailed,
+            SshError::PassphraseRequired(_) => ErrorCode::PassphraseRequired,
+            SshError::PassphraseIncorrect(_) => ErrorCode::PassphraseIncorrect,
+            SshError::IoError(e) => e.error_code().unwrap_or(ErrorCode::ConnectionFailed),
+            SshError::CommandFailed(_) => ErrorCode::RemoteError,
+            SshError::Connection(_) | SshError::ChannelError(_) | SshError::ProtocolError(_) => {
+                ErrorCode::ConnectionFailed
+            }
+        }
+    }
+
+    /// Authentication with `method` failed before the server rejected it
+    pub fn auth_failed(method: MethodKind, reason: impl Into<String>) -> Self {
+        SshError::AuthFailed { method, remaining_methods: Vec::new(), reason: reason.into() }
+    }
+
+    /// The server rejected `method`, leaving `remaining` to try
+    pub fn auth_rejected(method: MethodKind, remaining: &MethodSet, reason: impl Into<String>) -> Self {
+        SshError::AuthFailed { method, remaining_methods: remaining.to_vec(), reason: reason.into() }
+    }
+
+    /// Details of an authentication failure for the frontend (None for other errors)
+    pub fn auth_failure(&self) -> Option<AuthFailure> {
+        match self {
+            SshError::AuthFailed { method, remaining_methods, reason } => Some(AuthFailure {
+                method: method.into(),
+                remaining_methods: remaining_methods.iter().map(String::from).collect(),
+                message: reason.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Prefix the error message with context, keeping the variant where possible
+    pub fn context(self, ctx: &str) -> Self {
+        match self {
+            SshError::Connection(m) => SshError::Connection(format!("{}: {}", ctx, m)),
+            SshError::AuthFailed { method, remaining_methods, reason } => SshError::AuthFailed {
+                method,
+                remaining_methods,
+                reason: format!("{}: {}", ctx, reason),
+            },
+            SshError::KeyError(m) => SshError::KeyError(format!("{}: {}", ctx, m)),
+            SshError::PassphraseRequired(m) => SshError::PassphraseRequired(format!("{}: {}", ctx, m)),
+            SshError::PassphraseIncorrect(m) => SshError::PassphraseIncorrect(format!("{}: {}", ctx, m)),
+            SshError::ChannelError(m) => SshError::ChannelError(format!("{}: {}", ctx, m)),
+            SshError::CommandFailed(m) => SshError::CommandFailed(format!("{}: {}", ctx, m)),
+            SshError::ProtocolError(m) => SshError::ProtocolError(format!("{}: {}", ctx, m)),
+            SshError::IoError(e) => SshError::Connection(format!("{}: {}", ctx, e)),
+        }
+    }
+}
+
+/// Payload of the "ssh-auth-failed" event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthFailure {
+    /// SSH method name that failed: "password", "publickey" or "keyboard-interactive"
+    pub method: String,
+    /// Methods the server still accepts, e.g. ["password"] after a rejected key
+    pub remaining_methods: Vec<String>,
+    pub message: String,
+}
+
+/// Auth methods tried in turn on one connection, merged into a single AuthFailed
+#[derive(Debug, Default)]
+pub struct AuthAttempts {
+    /// "method: reason" for each method that failed or was skipped
+    failures: Vec<String>,
+    last_method: Option<MethodKind>,
+    /// Methods the server still accepts, from its latest rejection
+    remaining_methods: Vec<MethodKind>,
+}
+
+impl AuthAttempts {
+    /// Whether `method` is worth trying (the server listed it as remaining, or hasn't said yet)
+    pub fn should_try(&mut self, method: MethodKind) -> bool {
+        if self.remaining_methods.is_empty() || self.remaining_methods.contains(&method) {
+            return true;
+        }
+        self.failures.push(format!("{}: not accepted by the server", String::from(&method)));
+        false
+    }
+
+    /// Record a failed method; errors unrelated to the method (connection, protocol) are handed back
+    pub fn record(&mut self, method: MethodKind, err: SshError) -> Result<(), SshError> {
+        let reason = match err {
+            SshError::AuthFailed { remaining_methods, reason, .. } => {
+                if !remaining_methods.is_empty() {
+                    self.remaining_methods = remaining_methods;
+                }
+                reason
+            }
+            SshError::KeyError(_) | SshError::PassphraseRequired(_) | SshError::PassphraseIncorrect(_) => {
+                err.to_string()
+            }
+            err => return Err(err),
+        };
+        self.failures.push(format!("{}: {}", String::from(&method), reason));
+        self.last_method = Some(method);
+        Ok(())
+    }
+
+    /// The error once every method failed
+    pub fn into_error(self, username: &str) -> SshError {
+        SshError::AuthFailed {
+            method: self.last_method.unwrap_or(MethodKind::None),
+            remaining_methods: self.remaining_methods,
+            reason: format!("All auth methods failed for {} ({})", username, self.failures.join("; ")),
+        }
+    }
+}
+
 impl From<russh::Error> for SshError {
     fn from(err: russh::Error) -> Self {
         match err {
             russh::Error::IO(e) => SshError::IoError(e),
             russh::Error::Disconnect => SshError::Connection("Disconnected".to_string()),
             russh::Error::ChannelOpenFailure(_) => SshError::ChannelError("Channel open failed".to_string()),
+            russh::Error::NoCommonAlgo { kind, theirs, .. } => SshError::Connection(format!(
+                "Server has no {} algorithm in common with us (it offers: {}); allow one in the host's algorithm preferences, e.g. \"+{}\"",
+                algorithm_kind_name(kind),
+                theirs.join(", "),
+                theirs.first().map(String::as_str).unwrap_or("..."),
+            )),
             _ => SshError::ProtocolError(err.to_string()),
         }
     }
 }
 
+/// Algorithm category as named in settings and error messages
+fn algorithm_kind_name(kind: russh::AlgorithmKind) -> &'static str {
+    match kind {
+        russh::AlgorithmKind::Kex => "key exchange",
+        russh::AlgorithmKind::Key => "host key",
+        russh::AlgorithmKind::Cipher => "cipher",
+        russh::AlgorithmKind::Compression => "compression",
+        russh::AlgorithmKind::Mac => "MAC",
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_failure_keeps_message_and_methods() {
+        let remaining = MethodSet::from(&[MethodKind::Password, MethodKind::KeyboardInteractive][..]);
+        let err = SshError::auth_rejected(MethodKind::PublicKey, &remaining, "Key auth failed for bob")
+            .context("hop 1/2 (bastion)");
+        assert_eq!(err.to_string(), "Authentication failed: hop 1/2 (bastion): Key auth failed for bob");
+
+        let failure = err.auth_failure().unwrap();
+        assert_eq!(failure.method, "publickey");
+        assert_eq!(failure.remaining_methods, ["password", "keyboard-interactive"]);
+        assert_eq!(failure.message, "hop 1/2 (bastion): Key auth failed for bob");
+        assert!(SshError::Connection("refused".to_string()).auth_failure().is_none());
+    }
+
+    #[test]
+    fn test_auth_attempts_follow_remaining_methods() {
+        let mut attempts = AuthAttempts::default();
+        assert!(attempts.should_try(MethodKind::PublicKey));
+        let remaining = MethodSet::from(&[MethodKind::Password][..]);
+        let rejected = SshError::auth_rejected(MethodKind::PublicKey, &remaining, "Key auth failed for bob");
+        attempts.record(MethodKind::PublicKey, rejected).unwrap();
+
+        // The server only wants a password now
+        assert!(!attempts.should_try(MethodKind::KeyboardInteractive));
+        assert!(attempts.should_try(MethodKind::Password));
+        let missing = SshError::PassphraseRequired("id_ed25519".to_string());
+        attempts.record(MethodKind::PublicKey, missing).unwrap();
+        let rejected = SshError::auth_rejected(MethodKind::Password, &MethodSet::empty(), "Password auth failed for bob");
+        attempts.record(MethodKind::Password, rejected).unwrap();
+
+        let failure = attempts.into_error("bob").auth_failure().unwrap();
+        assert_eq!(failure.method, "password");
+        // An empty hint keeps the previous one
+        assert_eq!(failure.remaining_methods, ["password"]);
+        assert_eq!(
+            failure.message,
+            "All auth methods failed for bob (publickey: Key auth failed for bob; \
+             keyboard-interactive: not accepted by the server; \
+             publickey: Key passphrase required: id_ed25519; password: Password auth failed for bob)"
+        );
+
+        let refused = SshError::Connection("reset".to_string());
+        assert!(AuthAttempts::default().record(MethodKind::Password, refused).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! Port forwarding over an established SSH connection
+
+use crate::ssh::client::SshClient;
+use crate::ssh::error::SshError;
+use log::{debug, info, warn};
+use russh::client::Handle;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::{JoinHandle, JoinSet};
+
+const SOCKS_VERSION: u8 = 5;
+const METHOD_NO_AUTH: u8 = 0x00;
+const METHOD_NONE_ACCEPTABLE: u8 = 0xFF;
+const CMD_CONNECT: u8 = 0x01;
+const ATYP_IPV4: u8 = 0x01;
+const ATYP_DOMAIN: u8 = 0x03;
+const ATYP_IPV6: u8 = 0x04;
+
+const REPLY_SUCCEEDED: u8 = 0x00;
+const REPLY_GENERAL_FAILURE: u8 = 0x01;
+const REPLY_CONNECTION_REFUSED: u8 = 0x05;
+const REPLY_COMMAND_NOT_SUPPORTED: u8 = 0x07;
+const REPLY_ADDRESS_NOT_SUPPORTED: u8 = 0x08;
+
+/// What a forward does with each accepted connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForwardKind {
+    /// SOCKS5 proxy: the client picks the destination (ssh -D)
+    Dynamic,
+    /// Fixed destination as seen from the SSH server (ssh -L)
+    Local { remote_host: String, remote_port: u16 },
+}
+
+/// Listener on localhost whose connections are tunneled through SSH
+/// Dropping it stops the listener and every connection it accepted
+pub struct PortForward {
+    local_addr: SocketAddr,
+    kind: ForwardKind,
+    task: JoinHandle<()>,
+}
+
+impl PortForward {
+    /// SOCKS5 proxy on 127.0.0.1:`bind_port` (0 = any free port)
+    pub async fn dynamic(handle: Arc<Handle<SshClient>>, bind_port: u16) -> Result<Self, SshError> {
+        Self::start(handle, bind_port, ForwardKind::Dynamic).await
+    }
+
+    /// Forward 127.0.0.1:`local_port` to `remote_host`:`remote_port` on the server side
+    pub async fn local(
+        handle: Arc<Handle<SshClient>>,
+        local_port: u16,
+        remote_host: String,
+        remote_port: u16,
+    ) -> Result<Self, SshError> {
+        if remote_host.is_empty() || remote_port == 0 {
+            return Err(SshError::Connection("Forward target needs a host and port".to_string()));
+        }
+        Self::start(handle, local_port, ForwardKind::Local { remote_host, remote_port }).await
+    }
+
+    async fn start(handle: Arc<Handle<SshClient>>, bind_port: u16, kind: ForwardKind) -> Result<Self, SshError> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, bind_port))
+            .await
+            .map_err(|e| SshError::Connection(format!("Cannot bind local port {}: {}", bind_port, e)))?;
+        let local_addr = listener.local_addr()?;
+        info!("SSH forward {:?} listening on {}", kind, local_addr);
+
+        let task = tokio::spawn(Self::accept_loop(listener, handle, kind.clone()));
+        Ok(Self { local_addr, kind, task })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn kind(&self) -> &ForwardKind {
+        &self.kind
+    }
+
+    async fn accept_loop(listener: TcpListener, handle: Arc<Handle<SshClient>>, kind: ForwardKind) {
+        // Owned here so aborting the listener also aborts its connections
+        let mut connections = JoinSet::new();
+
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, peer)) => {
+                        debug!("SSH forward: client {} connected", peer);
+                        connections.spawn(Self::serve(stream, peer, handle.clone(), kind.clone()));
+                    }
+                    Err(e) => {
+                        warn!("SSH forward: accept failed: {}", e);
+                        break;
+                    }
+                },
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            }
+        }
+    }
+
+    /// Find the destination for one client, then bridge it to a direct-tcpip channel
+    async fn serve(mut stream: TcpStream, peer: SocketAddr, handle: Arc<Handle<SshClient>>, kind: ForwardKind) {
+        let socks = kind == ForwardKind::Dynamic;
+        let (host, port) = match kind {
+            ForwardKind::Local { remote_host, remote_port } => (remote_host, remote_port),
+            ForwardKind::Dynamic => match socks5_handshake(&mut stream).await {
+                Ok(Some(target)) => target,
+                Ok(None) => return,
+                Err(e) => {
+                    debug!("SSH forward: SOCKS handshake with {} failed: {}", peer, e);
+                    return;
+                }
+            },
+        };
+
+        let channel = match handle
+            .channel_open_direct_tcpip(&host, port as u32, &peer.ip().to_string(), peer.port() as u32)
+            .await
+        {
+            Ok(channel) => channel,
+            Err(e) => {
+                warn!("SSH forward: cannot open channel to {}:{}: {:?}", host, port, e);
+                if socks {
+                    let _ = send_reply(&mut stream, REPLY_CONNECTION_REFUSED).await;
+                }
+                return;
+            }
+        };
+        if socks && send_reply(&mut stream, REPLY_SUCCEEDED).await.is_err() {
+            return;
+        }
+
+        debug!("SSH forward: {} -> {}:{}", peer, host, port);
+        let mut remote = channel.into_stream();
+        if let Err(e) = tokio::io::copy_bidirectional(&mut stream, &mut remote).await {
+            debug!("SSH forward: {}:{} closed: {}", host, port, e);
+        }
+    }
+}
+
+impl Drop for PortForward {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Negotiate a SOCKS5 CONNECT and return the requested target
+/// Only "no authentication" is accepted; a client that insists on username/password
+/// is told no method is acceptable. Returns None once the client has been sent a refusal
+async fn socks5_handshake<S>(stream: &mut S) -> io::Result<Option<(String, u16)>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // Greeting: VER NMETHODS METHODS...
+    let [version, method_count] = read_array(stream).await?;
+    if version != SOCKS_VERSION {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Not SOCKS5 (version {})", version)));
+    }
+    let mut methods = vec![0u8; method_count as usize];
+    stream.read_exact(&mut methods).await?;
+
+    if !methods.contains(&METHOD_NO_AUTH) {
+        debug!("SSH forward: SOCKS client offered no usable auth method ({:?})", methods);
+        stream.write_all(&[SOCKS_VERSION, METHOD_NONE_ACCEPTABLE]).await?;
+        return Ok(None);
+    }
+    stream.write_all(&[SOCKS_VERSION, METHOD_NO_AUTH]).await?;
+
+    // Request: VER CMD RSV ATYP DST.ADDR DST.PORT
+    let [_, command, _, address_type] = read_array(stream).await?;
+    let host = match address_type {
+        ATYP_IPV4 => Ipv4Addr::from(read_array::<_, 4>(stream).await?).to_string(),
+        ATYP_IPV6 => Ipv6Addr::from(read_array::<_, 16>(stream).await?).to_string(),
+        ATYP_DOMAIN => {
+            let [len] = read_array(stream).await?;
+            let mut name = vec![0u8; len as usize];
+            stream.read_exact(&mut name).await?;
+            String::from_utf8_lossy(&name).to_string()
+        }
+        _ => {
+            send_reply(stream, REPLY_ADDRESS_NOT_SUPPORTED).await?;
+            return Ok(None);
+        }
+    };
+    let port = u16::from_be_bytes(read_array(stream).await?);
+
+    if command != CMD_CONNECT {
+        // BIND and UDP ASSOCIATE have no direct-tcpip equivalent
+        send_reply(stream, REPLY_COMMAND_NOT_SUPPORTED).await?;
+        return Ok(None);
+    }
+    if host.is_empty() || port == 0 {
+        send_reply(stream, REPLY_GENERAL_FAILURE).await?;
+        return Ok(None);
+    }
+
+    Ok(Some((host, port)))
+}
+
+/// Send a reply with an unspecified bound address (clients only look at the code)
+async fn send_reply<S: AsyncWrite + Unpin>(stream: &mut S, code: u8) -> io::Result<()> {
+    stream
+        .write_all(&[SOCKS_VERSION, code, 0, ATYP_IPV4, 0, 0, 0, 0, 0, 0])
+        .await
+}
+
+async fn read_array<S: AsyncRead + Unpin, const N: usize>(stream: &mut S) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    stream.read_exact(&mut buf).await?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_socks5_connect_domain() {
+        let (mut client, mut server) = tokio::io::duplex(256);
+        client.write_all(&[5, 2, 0x00, 0x02]).await.unwrap();
+        client
+            .write_all(&[5, CMD_CONNECT, 0, ATYP_DOMAIN, 11])
+            .await
+            .unwrap();
+        client.write_all(b"example.com").await.unwrap();
+        client.write_all(&443u16.to_be_bytes()).await.unwrap();
+
+        let target = socks5_handshake(&mut server).await.unwrap();
+        assert_eq!(target, Some(("example.com".to_string(), 443)));
+
+        let mut method = [0u8; 2];
+        client.read_exact(&mut method).await.unwrap();
+        assert_eq!(method, [5, METHOD_NO_AUTH]);
+    }
+
+    #[tokio::test]
+    async fn test_socks5_rejects_password_only_and_bind() {
+        let (mut client, mut server) = tokio::io::duplex(256);
+        client.write_all(&[5, 1, 0x02]).await.unwrap();
+        assert_eq!(socks5_handshake(&mut server).await.unwrap(), None);
+        let mut reply = [0u8; 2];
+        client.read_exact(&mut reply).await.unwrap();
+        assert_eq!(reply, [5, METHOD_NONE_ACCEPTABLE]);
+
+        let (mut client, mut server) = tokio::io::duplex(256);
+        client.write_all(&[5, 1, 0x00, 5, 0x02, 0, ATYP_IPV4, 10, 0, 0, 1, 0, 80]).await.unwrap();
+        assert_eq!(socks5_handshake(&mut server).await.unwrap(), None);
+        let mut reply = [0u8; 12];
+        client.read_exact(&mut reply).await.unwrap();
+        assert_eq!(reply[3], REPLY_COMMAND_NOT_SUPPORTED);
+    }
+}
@@ -0,0 +1,44 @@
+//! Trust-on-first-use host key prompts (new or changed keys confirmed by the user)
+
+use crate::ssh::config::HostKeyStatus;
+use crate::ssh::interactive::{Prompts, PROMPT_TIMEOUT};
+use log::warn;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Host key prompts; the answer is whether the user trusts the key
+pub type HostKeyPrompts = Prompts<bool>;
+
+/// Prompt payload emitted as "ssh-host-key-prompt"; answer with respond_host_key_prompt
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostKeyPromptEvent {
+    pub prompt_id: String,
+    pub hostname: String,
+    pub port: u16,
+    pub algorithm: String,
+    /// SHA256 fingerprint as printed by ssh-keygen -l ("SHA256:...")
+    pub fingerprint: String,
+    pub status: HostKeyStatus,
+}
+
+/// Ask the user whether to trust a host key, waiting for the answer
+/// Anything but an explicit yes (no listener, timeout, cancelled) is a no
+pub async fn confirm(app_handle: &AppHandle, event: HostKeyPromptEvent) -> bool {
+    let registry = app_handle.state::<HostKeyPrompts>();
+    let (_pending, rx) = registry.register(&event.prompt_id);
+
+    if let Err(e) = app_handle.emit("ssh-host-key-prompt", &event) {
+        warn!("SSH failed to emit host key prompt: {}", e);
+        return false;
+    }
+
+    match tokio::time::timeout(PROMPT_TIMEOUT, rx).await {
+        Ok(Ok(trusted)) => trusted,
+        Ok(Err(_)) => false,
+        Err(_) => {
+            warn!("SSH host key prompt for {}:{} timed out", event.hostname, event.port);
+            false
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Keyboard-interactive authentication (2FA/OTP prompts answered by the user)
+
+use crate::ssh::client::SshClient;
+use crate::ssh::error::SshError;
+use log::{debug, info, warn};
+use russh::client::{Handle, KeyboardInteractiveAuthResponse, Prompt};
+use russh::MethodKind;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::oneshot;
+
+/// Give up after this many challenge rounds (password, OTP, ... rarely exceed a handful)
+const MAX_ROUNDS: usize = 10;
+/// How long the user has to answer a prompt
+pub(crate) const PROMPT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// One field of a prompt
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptField {
+    pub prompt: String,
+    /// Whether the answer may be shown while typing (false for passwords/OTPs)
+    pub echo: bool,
+}
+
+/// Prompt payload emitted as "ssh-auth-prompt"; answer with respond_auth_prompt
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthPromptEvent {
+    pub prompt_id: String,
+    pub hostname: String,
+    pub username: String,
+    pub name: String,
+    pub instructions: String,
+    pub prompts: Vec<PromptField>,
+}
+
+type Pending<T> = Arc<Mutex<HashMap<String, oneshot::Sender<T>>>>;
+
+/// Prompts waiting for the user's answer, keyed by prompt ID
+pub struct Prompts<T> {
+    pending: Pending<T>,
+}
+
+/// Keyboard-interactive prompts; the answer is None when the user cancelled
+pub type AuthPrompts = Prompts<Option<Vec<String>>>;
+
+impl<T> Default for Prompts<T> {
+    fn default() -> Self {
+        Self { pending: Arc::default() }
+    }
+}
+
+impl<T> Prompts<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deliver the user's answer; false if the prompt is gone
+    pub fn respond(&self, prompt_id: &str, answer: T) -> bool {
+        let responder = self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(prompt_id);
+        match responder {
+            Some(tx) => tx.send(answer).is_ok(),
+            None => false,
+        }
+    }
+
+    pub(crate) fn register(&self, prompt_id: &str) -> (PendingPrompt<T>, oneshot::Receiver<T>) {
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(prompt_id.to_string(), tx);
+        let guard = PendingPrompt {
+            pending: self.pending.clone(),
+            prompt_id: prompt_id.to_string(),
+        };
+        (guard, rx)
+    }
+}
+
+/// Removes a prompt from the registry when the wait ends (answered, timed out or cancelled)
+pub(crate) struct PendingPrompt<T> {
+    pending: Pending<T>,
+    prompt_id: String,
+}
+
+impl<T> Drop for PendingPrompt<T> {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.prompt_id);
+    }
+}
+
+/// Where keyboard-interactive answers come from
+pub struct PromptAnswers<'a> {
+    /// Answers a lone hidden "Password:" prompt in the first round (password fallback)
+    pub password: Option<&'a str>,
+    /// Asks the user for everything else (None = non-interactive)
+    pub app_handle: Option<&'a AppHandle>,
+    pub hostname: &'a str,
+}
+
+/// Run keyboard-interactive auth, answering each challenge round until the server decides
+pub async fn authenticate_keyboard_interactive(
+    handle: &mut Handle<SshClient>,
+    username: &str,
+    answers: &PromptAnswers<'_>,
+) -> Result<(), SshError> {
+    let mut response = handle
+        .authenticate_keyboard_interactive_start(username, None::<String>)
+        .await?;
+    let mut password_used = false;
+
+    for round in 0..MAX_ROUNDS {
+        let (name, instructions, prompts) = match response {
+            KeyboardInteractiveAuthResponse::Success => {
+                info!("SSH keyboard-interactive auth success");
+                return Ok(());
+            }
+            KeyboardInteractiveAuthResponse::Failure { remaining_methods, .. } => {
+                return Err(SshError::auth_rejected(
+                    MethodKind::KeyboardInteractive,
+                    &remaining_methods,
+                    format!("Keyboard-interactive auth failed for {}", username),
+                ));
+            }
+            KeyboardInteractiveAuthResponse::InfoRequest { name, instructions, prompts } => {
+                (name, instructions, prompts)
+            }
+        };
+        debug!("SSH keyboard-interactive round {}: {} prompt(s)", round + 1, prompts.len());
+
+        let responses = if prompts.is_empty() {
+            // Servers may send an empty request (e.g. just instructions); an empty reply continues
+            Vec::new()
+        } else if let Some(password) = answers.password.filter(|_| !password_used && is_password_prompt(&prompts)) {
+            password_used = true;
+            vec![password.to_string()]
+        } else {
+            ask_user(answers, username, name, instructions, &prompts).await?
+        };
+
+        response = handle.authenticate_keyboard_interactive_respond(responses).await?;
+    }
+
+    Err(SshError::auth_failed(
+        MethodKind::KeyboardInteractive,
+        format!("Keyboard-interactive auth for {} exceeded {} rounds", username, MAX_ROUNDS),
+    ))
+}
+
+/// A single hidden prompt asking for the account password
+fn is_password_prompt(prompts: &[Prompt]) -> bool {
+    matches!(prompts, [p] if !p.echo && p.prompt.to_lowercase().contains("password"))
+}
+
+/// Emit the prompt to the frontend and wait for its answers
+async fn ask_user(
+    answers: &PromptAnswers<'_>,
+    username: &str,
+    name: String,
+    instructions: String,
+    prompts: &[Prompt],
+) -> Result<Vec<String>, SshError> {
+    let Some(app_handle) = answers.app_handle else {
+        return Err(SshError::auth_failed(
+            MethodKind::KeyboardInteractive,
+            "Server asked for keyboard-interactive input but no prompt is available",
+        ));
+    };
+
+    let prompt_id = uuid::Uuid::new_v4().to_string();
+    let registry = app_handle.state::<AuthPrompts>();
+    let (_pending, rx) = registry.register(&prompt_id);
+
+    let event = AuthPromptEvent {
+        prompt_id,
+        hostname: answers.hostname.to_string(),
+        username: username.to_string(),
+        name,
+        instructions,
+        prompts: prompts
+            .iter()
+            .map(|p| PromptField { prompt: p.prompt.clone(), echo: p.echo })
+            .collect(),
+    };
+    app_handle
+        .emit("ssh-auth-prompt", &event)
+        .map_err(|e| SshError::auth_failed(MethodKind::KeyboardInteractive, format!("Cannot show auth prompt: {}", e)))?;
+
+    match tokio::time::timeout(PROMPT_TIMEOUT, rx).await {
+        Ok(Ok(Some(responses))) if responses.len() == prompts.len() => Ok(responses),
+        Ok(Ok(Some(responses))) => Err(SshError::auth_failed(
+            MethodKind::KeyboardInteractive,
+            format!("Expected {} answers, got {}", prompts.len(), responses.len()),
+        )),
+        Ok(Ok(None)) | Ok(Err(_)) => Err(SshError::auth_failed(MethodKind::KeyboardInteractive, "Authentication cancelled")),
+        Err(_) => {
+            warn!("SSH keyboard-interactive prompt timed out");
+            Err(SshError::auth_failed(MethodKind::KeyboardInteractive, "Authentication prompt timed out"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(text: &str, echo: bool) -> Prompt {
+        Prompt { prompt: text.to_string(), echo }
+    }
+
+    #[test]
+    fn test_password_prompt_detection() {
+        assert!(is_password_prompt(&[prompt("Password: ", false)]));
+        assert!(!is_password_prompt(&[prompt("Verification code: ", false)]));
+        assert!(!is_password_prompt(&[prompt("Password: ", false), prompt("OTP: ", false)]));
+    }
+
+    #[tokio::test]
+    async fn test_responses_reach_waiter_and_registry_is_cleaned() {
+        let prompts = AuthPrompts::new();
+        let (pending, rx) = prompts.register("p1");
+        assert!(prompts.respond("p1", Some(vec!["123456".to_string()])));
+        assert_eq!(rx.await.unwrap(), Some(vec!["123456".to_string()]));
+        drop(pending);
+
+        let (pending, _rx) = prompts.register("p2");
+        drop(pending);
+        assert!(!prompts.respond("p2", None));
+    }
+}
@@ -1,9 +1,15 @@
 pub mod config;
 pub mod error;
 pub mod client;
+pub mod interactive;
+pub mod host_key;
+pub mod algorithms;
 pub mod chain;
+pub mod forward;
+pub mod proxy;
+pub mod ssh_config;
 pub mod terminal;
 
-pub use config::{SshConfig, HostConfig, SshAuth, TerminalConfig, ChainProgress, ConnectionType};
+pub use config::{SshConfig, HostConfig, SshAuth, TerminalConfig, ChainProgress, ConnectionType, AlgorithmPreferences};
 pub use error::SshError;
 pub use terminal::SshTerminalSession;
@@ -0,0 +1,199 @@
+//! ProxyCommand: reach the SSH server through an external program's stdin/stdout
+//! (e.g. `cloudflared access ssh --hostname %h`, `aws ssm start-session ...`)
+
+use crate::ssh::client::{self, SshClient};
+use crate::ssh::config::HostConfig;
+use crate::ssh::error::SshError;
+use log::{info, warn};
+use russh::client::Handle;
+use std::io;
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, BufReader, ReadBuf};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+/// Last line the proxy wrote to stderr, kept to explain a failed connection
+type StderrTail = Arc<Mutex<Option<String>>>;
+
+/// The proxy process's stdout/stdin as one stream; dropping it kills the process
+pub struct ProxyStream {
+    _child: Child,
+    reader: ChildStdout,
+    writer: ChildStdin,
+    stderr: StderrTail,
+}
+
+impl ProxyStream {
+    /// Run `command` through the platform shell with piped stdio
+    pub fn spawn(command: &str) -> Result<Self, SshError> {
+        let mut cmd = shell_command(command);
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| SshError::Connection(format!("Failed to start ProxyCommand: {}", e)))?;
+
+        let missing = || SshError::Connection("ProxyCommand stdio unavailable".to_string());
+        let reader = child.stdout.take().ok_or_else(missing)?;
+        let writer = child.stdin.take().ok_or_else(missing)?;
+
+        let stderr = StderrTail::default();
+        if let Some(pipe) = child.stderr.take() {
+            let tail = stderr.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(pipe).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let line = line.trim();
+                    if !line.is_empty() {
+                        warn!("ProxyCommand: {}", line);
+                        *tail.lock().unwrap() = Some(line.to_string());
+                    }
+                }
+            });
+        }
+
+        Ok(Self { _child: child, reader, writer, stderr })
+    }
+
+    fn stderr_tail(&self) -> StderrTail {
+        self.stderr.clone()
+    }
+}
+
+impl AsyncRead for ProxyStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.reader).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for ProxyStream {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.writer).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.writer).poll_shutdown(cx)
+    }
+}
+
+/// Connect SSH over `command`'s stdio instead of TCP
+/// If the process dies mid-session its stdout ends, which the terminal reports as connection lost
+pub async fn connect_via_proxy(
+    config: &HostConfig,
+    command: &str,
+    client: SshClient,
+) -> Result<Handle<SshClient>, SshError> {
+    let command = expand_proxy_command(command, config);
+    info!("SSH connecting to {}:{} via ProxyCommand: {}", config.hostname, config.port, command);
+
+    let ssh_config = Arc::new(client::client_config(config)?);
+    let info = client.info();
+    let stream = ProxyStream::spawn(&command)?;
+    let stderr = stream.stderr_tail();
+
+    let connect = async {
+        russh::client::connect_stream(ssh_config, stream, client).await.map_err(|e| {
+            warn!("SSH connection via ProxyCommand failed: {:?}", e);
+            client::connect_failure(&info, e, |e| {
+                let message = match stderr.lock().unwrap().as_deref() {
+                    Some(line) => format!("ProxyCommand failed: {} ({})", e, line),
+                    None => format!("ProxyCommand failed: {}", e),
+                };
+                SshError::Connection(message)
+            })
+        })
+    };
+    client::within_handshake_timeout(config, &info, connect).await
+}
+
+/// Substitute OpenSSH's tokens: %h host, %p port, %r user, %% a literal %
+fn expand_proxy_command(template: &str, config: &HostConfig) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('h') => expanded.push_str(&config.hostname),
+            Some('p') => expanded.push_str(&config.port.to_string()),
+            Some('r') => expanded.push_str(&config.username),
+            Some('%') => expanded.push('%'),
+            Some(other) => {
+                expanded.push('%');
+                expanded.push(other);
+            }
+            None => expanded.push('%'),
+        }
+    }
+    expanded
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("/bin/sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    // CREATE_NO_WINDOW: don't flash a console window
+    cmd.creation_flags(0x0800_0000);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_expand_proxy_command_tokens() {
+        let config = HostConfig {
+            hostname: "db.internal".to_string(),
+            port: 2222,
+            username: "deploy".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            expand_proxy_command("cloudflared access ssh --hostname %h:%p --user %r", &config),
+            "cloudflared access ssh --hostname db.internal:2222 --user deploy"
+        );
+        assert_eq!(expand_proxy_command("echo 100%% %x %", &config), "echo 100% %x %");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_proxy_stream_pipes_through_the_process() {
+        let mut stream = ProxyStream::spawn("cat").unwrap();
+        stream.write_all(b"SSH-2.0-test\r\n").await.unwrap();
+        let mut buf = [0u8; 14];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"SSH-2.0-test\r\n");
+
+        // A proxy that dies ends the stream and leaves its complaint behind
+        let mut stream = ProxyStream::spawn("echo 'access denied' >&2; exit 1").unwrap();
+        let tail = stream.stderr_tail();
+        let mut rest = Vec::new();
+        assert_eq!(stream.read_to_end(&mut rest).await.unwrap(), 0);
+        for _ in 0..100 {
+            if tail.lock().unwrap().is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(tail.lock().unwrap().as_deref(), Some("access denied"));
+    }
+}
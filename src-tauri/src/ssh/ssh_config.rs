@@ -0,0 +1,468 @@
+//! OpenSSH client config (~/.ssh/config): turn a host alias into a connection config
+//!
+//! Like ssh, the first value found for a keyword wins, so `Host *` defaults go at the end
+//! of the file. `Match` blocks other than `Match all` are not evaluated and are skipped.
+
+use crate::ssh::config::{AlgorithmPreferences, HostConfig, SecurityPolicy, SshAuth, SshConfig, TerminalConfig};
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Includes nested deeper than this are ignored (same limit as OpenSSH)
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Resolve `alias` through ~/.ssh/config, including its ProxyJump hosts as `jumps`
+/// Without a config file (or a matching Host block) the alias is used as the hostname
+pub fn resolve_host(alias: &str) -> Result<SshConfig, String> {
+    resolve_in(alias, home_dir().as_deref())
+}
+
+fn resolve_in(alias: &str, home: Option<&Path>) -> Result<SshConfig, String> {
+    let alias = alias.trim();
+    if alias.is_empty() {
+        return Err("Host alias is empty".to_string());
+    }
+
+    let settings = HostSettings::collect(alias, home);
+    let mut target = settings.host_config(alias, None, home)?;
+
+    let mut jumps = Vec::new();
+    if let Some(proxy_jump) = settings.get("proxyjump").filter(|v| !v.eq_ignore_ascii_case("none")) {
+        for spec in proxy_jump.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let jump = JumpSpec::parse(spec)?;
+            // A jump host's own ProxyJump is not followed
+            let jump_settings = HostSettings::collect(&jump.host, home);
+            jumps.push(jump_settings.host_config(&jump.host, Some(&jump), home)?);
+        }
+    }
+
+    if !jumps.is_empty() {
+        // Only the first hop of a chain can use a ProxyCommand; later hops go through the previous one
+        target.proxy_command = None;
+        for jump in jumps.iter_mut().skip(1) {
+            jump.proxy_command = None;
+        }
+    }
+
+    let keepalive_secs = settings.number("serveraliveinterval")?.filter(|&secs| secs > 0);
+    Ok(SshConfig {
+        target,
+        jumps,
+        terminal: TerminalConfig { keepalive_secs, ..Default::default() },
+        initial_command: None,
+    })
+}
+
+/// Keyword values that apply to one host alias
+#[derive(Debug, Default)]
+struct HostSettings {
+    /// Lowercased keyword -> rest of the line, first occurrence only
+    values: HashMap<String, String>,
+    /// Every IdentityFile, in order (ssh tries them all; the first is used here)
+    identity_files: Vec<String>,
+}
+
+impl HostSettings {
+    /// Read ~/.ssh/config (and its includes) for the lines that apply to `alias`
+    fn collect(alias: &str, home: Option<&Path>) -> Self {
+        let mut settings = Self::default();
+        if let Some(home) = home {
+            settings.read_file(alias, &home.join(".ssh").join("config"), home, 0);
+        }
+        settings
+    }
+
+    fn read_file(&mut self, alias: &str, path: &Path, home: &Path, depth: usize) {
+        match std::fs::read_to_string(path) {
+            Ok(text) => self.read_text(alias, &text, home, depth),
+            Err(e) => debug!("[SshConfig] Skipping {}: {}", path.display(), e),
+        }
+    }
+
+    fn read_text(&mut self, alias: &str, text: &str, home: &Path, depth: usize) {
+        // Lines before the first Host apply to every host
+        let mut active = true;
+        for line in text.lines() {
+            let Some((keyword, value)) = split_line(line) else {
+                continue;
+            };
+            match keyword.as_str() {
+                "host" => active = host_matches(alias, &split_args(value)),
+                "match" => active = value.eq_ignore_ascii_case("all"),
+                "include" if active => {
+                    if depth >= MAX_INCLUDE_DEPTH {
+                        warn!("[SshConfig] Include nested too deeply, ignoring: {}", value);
+                        continue;
+                    }
+                    for pattern in split_args(value) {
+                        for path in include_paths(&pattern, home) {
+                            self.read_file(alias, &path, home, depth + 1);
+                        }
+                    }
+                }
+                "identityfile" if active => self.identity_files.push(unquote(value)),
+                _ if active => {
+                    self.values.entry(keyword).or_insert_with(|| value.to_string());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn get(&self, keyword: &str) -> Option<&str> {
+        self.values.get(keyword).map(String::as_str)
+    }
+
+    /// yes/no keyword
+    fn flag(&self, keyword: &str) -> Option<bool> {
+        self.get(keyword).map(|v| v.eq_ignore_ascii_case("yes"))
+    }
+
+    fn number<T: std::str::FromStr>(&self, keyword: &str) -> Result<Option<T>, String> {
+        self.get(keyword)
+            .map(|v| v.parse().map_err(|_| format!("Invalid {} in ssh config: {}", keyword, v)))
+            .transpose()
+    }
+
+    /// Comma-separated algorithm list; "+" (add to defaults) is kept, "-" and "^" are not supported
+    fn algorithms(&self, keyword: &str) -> Vec<String> {
+        match self.get(keyword) {
+            Some(v) if v.starts_with('-') || v.starts_with('^') => {
+                warn!("[SshConfig] Ignoring {} {}: only replacing or \"+\" lists are supported", keyword, v);
+                Vec::new()
+            }
+            Some(v) => v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Build the host config; user and port written in a ProxyJump spec win over the config
+    fn host_config(&self, alias: &str, jump: Option<&JumpSpec>, home: Option<&Path>) -> Result<HostConfig, String> {
+        let hostname = match self.get("hostname") {
+            Some(hostname) => expand_tokens(&unquote(hostname), &[('h', alias)]),
+            None => alias.to_string(),
+        };
+        let port = match jump.and_then(|j| j.port) {
+            Some(port) => port,
+            None => self.number("port")?.unwrap_or(22),
+        };
+        let username = jump
+            .and_then(|j| j.user.clone())
+            .or_else(|| self.get("user").map(unquote))
+            .unwrap_or_else(local_user);
+
+        let auth = match self.identity_files.first() {
+            Some(file) => {
+                let home = home.map(|h| h.to_string_lossy().into_owned()).unwrap_or_default();
+                let port = port.to_string();
+                let local = local_user();
+                let tokens = [('h', hostname.as_str()), ('p', &port), ('r', &username), ('d', &home), ('u', &local)];
+                let path = expand_tokens(&expand_home(file, &home), &tokens);
+                SshAuth::Key { path, passphrase: None }
+            }
+            None => SshAuth::Agent,
+        };
+
+        let security_policy = match self.get("stricthostkeychecking").map(str::to_ascii_lowercase).as_deref() {
+            Some("yes") => SecurityPolicy::Strict,
+            Some("no") | Some("off") => SecurityPolicy::AcceptAll,
+            _ => SecurityPolicy::AcceptNew,
+        };
+
+        Ok(HostConfig {
+            hostname,
+            port,
+            username,
+            auth,
+            algorithms: AlgorithmPreferences {
+                kex: self.algorithms("kexalgorithms"),
+                cipher: self.algorithms("ciphers"),
+                mac: self.algorithms("macs"),
+                host_key: self.algorithms("hostkeyalgorithms"),
+            },
+            security_policy,
+            connect_timeout_secs: self.number("connecttimeout")?,
+            proxy_command: self
+                .get("proxycommand")
+                .filter(|v| !v.eq_ignore_ascii_case("none"))
+                .map(str::to_string),
+            compression: self.flag("compression").unwrap_or(false),
+            forward_agent: self.flag("forwardagent").unwrap_or(false),
+            ..Default::default()
+        })
+    }
+}
+
+/// One ProxyJump entry: [user@]host[:port]
+#[derive(Debug, PartialEq)]
+struct JumpSpec {
+    user: Option<String>,
+    host: String,
+    port: Option<u16>,
+}
+
+impl JumpSpec {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.strip_prefix("ssh://").unwrap_or(spec);
+        let (user, host_port) = match spec.rsplit_once('@') {
+            Some((user, rest)) => (Some(user.to_string()), rest),
+            None => (None, spec),
+        };
+        // [::1]:2222 for IPv6 addresses
+        let (host, port) = if let Some(rest) = host_port.strip_prefix('[') {
+            let (host, after) = rest.split_once(']').ok_or_else(|| format!("Invalid ProxyJump host: {}", spec))?;
+            (host, after.strip_prefix(':'))
+        } else {
+            match host_port.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (host_port, None),
+            }
+        };
+        let port = port
+            .map(|p| p.parse().map_err(|_| format!("Invalid ProxyJump port: {}", spec)))
+            .transpose()?;
+        if host.is_empty() {
+            return Err(format!("Invalid ProxyJump host: {}", spec));
+        }
+        Ok(Self { user, host: host.to_string(), port })
+    }
+}
+
+/// Keyword (lowercased) and the rest of the line; keyword and value may be separated by "="
+fn split_line(line: &str) -> Option<(String, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let end = line.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(line.len());
+    let (keyword, rest) = line.split_at(end);
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim();
+    Some((keyword.to_ascii_lowercase(), strip_comment(rest)))
+}
+
+/// Drop a trailing " # comment" (a "#" outside quotes that starts a word)
+fn strip_comment(value: &str) -> &str {
+    let mut quoted = false;
+    let mut word_start = true;
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted && word_start => return value[..i].trim_end(),
+            _ => {}
+        }
+        word_start = c.is_whitespace();
+    }
+    value
+}
+
+/// Whitespace-separated arguments; double quotes group them
+fn split_args(value: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quoted = false;
+    for c in value.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+fn unquote(value: &str) -> String {
+    split_args(value).into_iter().next().unwrap_or_default()
+}
+
+/// `Host` patterns: any positive match, and no negated (`!pattern`) match
+fn host_matches(alias: &str, patterns: &[String]) -> bool {
+    let alias = alias.to_ascii_lowercase();
+    let mut matched = false;
+    for pattern in patterns {
+        let pattern = pattern.to_ascii_lowercase();
+        match pattern.strip_prefix('!') {
+            Some(negated) if wildcard_match(negated, &alias) => return false,
+            Some(_) => {}
+            None => matched |= wildcard_match(&pattern, &alias),
+        }
+    }
+    matched
+}
+
+/// `*` matches any run of characters, `?` exactly one
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    fn matches(pattern: &[char], text: &[char]) -> bool {
+        match pattern.split_first() {
+            None => text.is_empty(),
+            Some(('*', rest)) => (0..=text.len()).any(|skip| matches(rest, &text[skip..])),
+            Some(('?', rest)) => !text.is_empty() && matches(rest, &text[1..]),
+            Some((c, rest)) => text.first() == Some(c) && matches(rest, &text[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    matches(&pattern, &text)
+}
+
+/// Files named by an Include; relative paths are under ~/.ssh and the file name may use wildcards
+fn include_paths(pattern: &str, home: &Path) -> Vec<PathBuf> {
+    let expanded = PathBuf::from(expand_home(pattern, &home.to_string_lossy()));
+    let path = if expanded.is_absolute() { expanded } else { home.join(".ssh").join(expanded) };
+
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    if !name.contains(['*', '?']) {
+        return vec![path];
+    }
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| wildcard_match(&name, &entry.file_name().to_string_lossy()))
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+fn expand_home(path: &str, home: &str) -> String {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("{}{}", home, rest),
+        _ => path.to_string(),
+    }
+}
+
+/// Replace %x tokens listed in `tokens`; %% is a literal %, unknown tokens are kept
+fn expand_tokens(value: &str, tokens: &[(char, &str)]) -> String {
+    let mut expanded = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => expanded.push('%'),
+            Some(token) => match tokens.iter().find(|(t, _)| *t == token) {
+                Some((_, replacement)) => expanded.push_str(replacement),
+                None => {
+                    expanded.push('%');
+                    expanded.push(token);
+                }
+            },
+            None => expanded.push('%'),
+        }
+    }
+    expanded
+}
+
+fn home_dir() -> Option<PathBuf> {
+    ["HOME", "USERPROFILE"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .find(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Local user name, ssh's default remote user
+fn local_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, text: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_alias_with_includes_and_defaults() {
+        let home = tempfile::tempdir().unwrap();
+        let ssh = home.path().join(".ssh");
+        write(
+            &ssh.join("config"),
+            "Include conf.d/*.conf\n\
+             \n\
+             Host db prod-*  !prod-legacy\n\
+             \x20   HostName %h.internal.example.com\n\
+             \x20   User=deploy\n\
+             \x20   IdentityFile \"~/.ssh/id_%r\"\n\
+             \x20   ProxyJump ops@bastion:2222\n\
+             \x20   KexAlgorithms +diffie-hellman-group14-sha1\n\
+             \n\
+             Host *\n\
+             \x20   User nobody\n\
+             \x20   Port 2200   # everywhere\n\
+             \x20   ServerAliveInterval 30\n\
+             \x20   ForwardAgent yes\n",
+        );
+        write(&ssh.join("conf.d/bastion.conf"), "Host bastion\n    HostName 203.0.113.7\n    IdentityFile %d/.ssh/jump\n");
+
+        let config = resolve_in("prod-web", Some(home.path())).unwrap();
+        assert_eq!(config.target.hostname, "prod-web.internal.example.com");
+        assert_eq!(config.target.username, "deploy");
+        assert_eq!(config.target.port, 2200);
+        assert_eq!(config.target.algorithms.kex, ["+diffie-hellman-group14-sha1"]);
+        let home_str = home.path().to_string_lossy();
+        assert!(matches!(&config.target.auth, SshAuth::Key { path, .. } if *path == format!("{}/.ssh/id_deploy", home_str)));
+        assert_eq!(config.terminal.keepalive_secs, Some(30));
+        assert!(config.target.forward_agent);
+
+        assert_eq!(config.jumps.len(), 1);
+        let jump = &config.jumps[0];
+        assert_eq!((jump.hostname.as_str(), jump.port, jump.username.as_str()), ("203.0.113.7", 2222, "ops"));
+        assert!(matches!(&jump.auth, SshAuth::Key { path, .. } if *path == format!("{}/.ssh/jump", home_str)));
+
+        // Negated pattern: only the Host * defaults apply
+        let legacy = resolve_in("prod-legacy", Some(home.path())).unwrap();
+        assert_eq!((legacy.target.hostname.as_str(), legacy.target.username.as_str()), ("prod-legacy", "nobody"));
+        assert!(legacy.jumps.is_empty());
+        assert!(matches!(legacy.target.auth, SshAuth::Agent));
+
+        // No config file at all
+        let empty = tempfile::tempdir().unwrap();
+        let plain = resolve_in("example.org", Some(empty.path())).unwrap();
+        assert_eq!((plain.target.hostname.as_str(), plain.target.port), ("example.org", 22));
+    }
+
+    #[test]
+    fn test_parse_lines_patterns_and_jumps() {
+        assert_eq!(split_line("  Port=2222"), Some(("port".to_string(), "2222")));
+        assert_eq!(split_line("# comment"), None);
+        assert_eq!(split_line("IdentityFile \"~/My Key#1\" # work"), Some(("identityfile".to_string(), "\"~/My Key#1\"")));
+        assert_eq!(split_args("\"My Key\" other"), ["My Key", "other"]);
+        assert!(wildcard_match("web-??.example.*", "web-01.example.com"));
+        assert!(!wildcard_match("web-?", "web-01"));
+
+        assert_eq!(
+            JumpSpec::parse("admin@[2001:db8::1]:2022").unwrap(),
+            JumpSpec { user: Some("admin".to_string()), host: "2001:db8::1".to_string(), port: Some(2022) }
+        );
+        assert!(JumpSpec::parse("bastion:ssh").is_err());
+    }
+}
@@ -1,45 +1,95 @@
-use crate::ssh::client::{self, SshClient};
+use crate::ssh::client::{self, ConnectionInfoSlot, SshClient};
 use crate::ssh::config::SshConfig;
 use crate::ssh::error::SshError;
 use crate::core::error::SessionError;
-use crate::core::session::TerminalSession;
+use crate::core::output::Utf8StreamDecoder;
+use crate::core::initial_command::{PendingCommand, CHECK_INTERVAL};
+use crate::core::paste::BracketedPasteTracker;
+use crate::core::recording::Recording;
+use crate::core::scrollback::Scrollback;
+use crate::core::session::{SessionCapabilities, SessionInfo, TerminalSession, GRACEFUL_CLOSE_TIMEOUT, PING_TIMEOUT};
 use crate::core::terminal_events::TerminalExitEvent;
 use crate::terminal::session::SessionType;
 use async_trait::async_trait;
 use log::{debug, info, warn};
-use russh::{client::{Handle, Msg}, Channel, ChannelMsg};
+use russh::{client::{Handle, Msg}, Channel, ChannelMsg, Sig};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, Interval};
 use uuid::Uuid;
 
+/// How long the server has to answer a keepalive before the connection is considered lost
+const KEEPALIVE_REPLY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// What the channel I/O loop works with besides the channel itself
+struct ChannelIo {
+    handle: Arc<Handle<SshClient>>,
+    keepalive: Option<Duration>,
+    write_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    resize_rx: mpsc::UnboundedReceiver<(u16, u16)>,
+    close_rx: oneshot::Receiver<()>,
+    session_id: String,
+    app_handle: AppHandle,
+    streaming_started: Arc<AtomicBool>,
+    pty_allocated: Arc<std::sync::Mutex<Option<bool>>>,
+    paste_tracker: BracketedPasteTracker,
+    scrollback: Scrollback,
+    recording: Recording,
+    initial_command: PendingCommand,
+}
+
 /// SSH terminal session (Strategy Pattern implementation)
 pub struct SshTerminalSession {
     id: String,
-    handle: Handle<SshClient>,
+    /// Shared with the I/O loop, which sends keepalives through it
+    handle: Arc<Handle<SshClient>>,
     write_tx: mpsc::UnboundedSender<Vec<u8>>,
     resize_tx: mpsc::UnboundedSender<(u16, u16)>,
+    /// Signals the I/O loop to send EOF/close on the channel
+    close_tx: std::sync::Mutex<Option<oneshot::Sender<()>>>,
+    io_task: std::sync::Mutex<Option<JoinHandle<()>>>,
     streaming_started: Arc<AtomicBool>,
+    /// Server banner and negotiated algorithms (filled during key exchange)
+    connection_info: ConnectionInfoSlot,
+    /// PTY request outcome (set by the I/O loop when the server replies)
+    pty_allocated: Arc<std::sync::Mutex<Option<bool>>>,
+    /// Whether the remote enabled bracketed paste (updated by the I/O loop)
+    bracketed_paste: Arc<AtomicBool>,
+    /// Recent output (appended by the I/O loop)
+    scrollback: Scrollback,
+    /// Output recording (teed by the I/O loop)
+    recording: Recording,
 }
 
 impl SshTerminalSession {
     /// Connect to SSH server (supports direct and ProxyJump)
     /// Returns session immediately - call start_streaming() after FE listener is ready
     pub async fn connect(config: SshConfig, app_handle: AppHandle) -> Result<Self, SshError> {
-        let id = Uuid::new_v4().to_string();
+        Self::connect_with_id(Uuid::new_v4().to_string(), config, app_handle).await
+    }
+
+    /// Connect under an existing session id, so events reach the listeners already on it
+    pub async fn connect_with_id(id: String, config: SshConfig, app_handle: AppHandle) -> Result<Self, SshError> {
+        let connection_info = ConnectionInfoSlot::default();
 
         let handle = if config.jumps.is_empty() {
             // Direct connection
             info!("SSH direct connection to {}", config.target.hostname);
-            let mut h = client::connect_direct(&config.target).await?;
-            client::authenticate(&mut h, &config.target).await?;
+            let client = SshClient::with_info(&config.target, connection_info.clone()).with_events(app_handle.clone());
+            let mut h = client::connect_direct_with(&config.target, client).await?;
+            let agent_auth = client::authenticate(&mut h, &config.target, Some(&app_handle)).await?;
+            client::record_agent_auth(&connection_info, agent_auth);
             h
         } else {
             // ProxyJump via chain
             info!("SSH chain connection through {} jumps", config.jumps.len());
             use crate::ssh::chain::HopHandler;
-            let chain = HopHandler::from_config(&config.jumps, &config.target);
+            let chain = HopHandler::from_config(&config.jumps, &config.target)
+                .with_connection_info(connection_info.clone());
             chain.execute(None, &app_handle).await?
         };
         
@@ -51,10 +101,11 @@ impl SshTerminalSession {
         // Request PTY with TTY operation settings
         // TTY_OP_ISPEED and TTY_OP_OSPEED are critical for interactive programs like vi/vim
         // Without these, the remote shell may not properly configure raw mode
-        debug!("SSH requesting PTY {}x{}", config.terminal.cols, config.terminal.rows);
+        debug!("SSH requesting PTY {}x{} ({})", config.terminal.cols, config.terminal.rows, config.terminal.term());
+        // want_reply so the I/O loop can report whether the PTY was granted
         channel.request_pty(
-            false,
-            "xterm-256color",
+            true,
+            config.terminal.term(),
             config.terminal.cols as u32,
             config.terminal.rows as u32,
             0,
@@ -65,6 +116,12 @@ impl SshTerminalSession {
             ],
         ).await?;
 
+        // Request agent forwarding before the shell starts so SSH_AUTH_SOCK is set
+        if config.target.forward_agent {
+            debug!("SSH requesting agent forwarding to {}", config.target.hostname);
+            channel.agent_forward(false).await?;
+        }
+
         // Start shell (false = non-blocking, don't wait for server response)
         // This matches Kerminal's approach and may improve responsiveness
         debug!("SSH requesting shell");
@@ -74,21 +131,44 @@ impl SshTerminalSession {
         // Create channels for write and resize commands
         let (write_tx, write_rx) = mpsc::unbounded_channel::<Vec<u8>>();
         let (resize_tx, resize_rx) = mpsc::unbounded_channel::<(u16, u16)>();
+        let (close_tx, close_rx) = oneshot::channel::<()>();
         
         // Spawn channel I/O handler - owns the channel exclusively
         let session_id = id.clone();
         let app_handle_clone = app_handle.clone();
         let streaming_started = Arc::new(AtomicBool::new(false));
         let streaming_flag = streaming_started.clone();
+        let pty_allocated = Arc::new(std::sync::Mutex::new(None));
+        let pty_state = pty_allocated.clone();
+        let paste_tracker = BracketedPasteTracker::new();
+        let bracketed_paste = paste_tracker.flag();
+        let handle = Arc::new(handle);
+        let keepalive_handle = handle.clone();
+        let keepalive = config.terminal.keepalive_secs.filter(|s| *s > 0).map(Duration::from_secs);
+        let scrollback = Scrollback::new(config.terminal.scrollback_bytes);
+        let output_scrollback = scrollback.clone();
+        let recording = Recording::new(config.terminal.cols, config.terminal.rows, config.terminal.term());
+        let output_recording = recording.clone();
+        let initial_command = PendingCommand::new(config.initial_command.as_ref());
         
-        tokio::spawn(async move {
+        let io_task = tokio::spawn(async move {
             Self::channel_io_loop(
                 channel,
-                write_rx,
-                resize_rx,
-                session_id,
-                app_handle_clone,
-                streaming_flag,
+                ChannelIo {
+                    handle: keepalive_handle,
+                    keepalive,
+                    write_rx,
+                    resize_rx,
+                    close_rx,
+                    session_id,
+                    app_handle: app_handle_clone,
+                    streaming_started: streaming_flag,
+                    pty_allocated: pty_state,
+                    paste_tracker,
+                    scrollback: output_scrollback,
+                    recording: output_recording,
+                    initial_command,
+                },
             ).await;
         });
         
@@ -97,23 +177,52 @@ impl SshTerminalSession {
             handle,
             write_tx,
             resize_tx,
+            close_tx: std::sync::Mutex::new(Some(close_tx)),
+            io_task: std::sync::Mutex::new(Some(io_task)),
             streaming_started,
+            connection_info,
+            pty_allocated,
+            bracketed_paste,
+            scrollback,
+            recording,
         })
     }
     
     /// Channel I/O loop - handles both reading and writing without mutex
-    async fn channel_io_loop(
-        mut channel: Channel<Msg>,
-        mut write_rx: mpsc::UnboundedReceiver<Vec<u8>>,
-        mut resize_rx: mpsc::UnboundedReceiver<(u16, u16)>,
-        session_id: String,
-        app_handle: AppHandle,
-        streaming_started: Arc<AtomicBool>,
-    ) {
+    async fn channel_io_loop(mut channel: Channel<Msg>, io: ChannelIo) {
+        let ChannelIo {
+            handle,
+            keepalive,
+            mut write_rx,
+            mut resize_rx,
+            mut close_rx,
+            session_id,
+            app_handle,
+            streaming_started,
+            pty_allocated,
+            mut paste_tracker,
+            scrollback,
+            recording,
+            mut initial_command,
+        } = io;
+
         debug!("SSH[{}] channel I/O loop started", session_id);
         
         // Buffer for data received before streaming starts
         let mut pending_buffer: Vec<String> = Vec::new();
+        // Hold back characters split across packets (stdout and stderr are separate streams)
+        let mut stdout_decoder = Utf8StreamDecoder::new();
+        let mut stderr_decoder = Utf8StreamDecoder::new();
+
+        // How the remote shell ended (ExitStatus/ExitSignal), reported once the channel closes
+        let mut process_exit: Option<TerminalExitEvent> = None;
+        // Server finished sending; the exit status may still follow before Close
+        let mut eof_received = false;
+
+        // Keepalive timer, reset whenever data flows either way
+        let mut keepalive_timer = keepalive.map(|period| {
+            tokio::time::interval_at(Instant::now() + period, period)
+        });
         
         loop {
             tokio::select! {
@@ -121,14 +230,56 @@ impl SshTerminalSession {
                 // This ensures responsive input handling for interactive programs like vi
                 biased;
 
+                // Close requested (or session dropped) - tell the server we're done
+                _ = &mut close_rx => {
+                    debug!("SSH[{}] closing channel", session_id);
+                    if let Err(e) = channel.eof().await {
+                        debug!("SSH[{}] EOF send error: {:?}", session_id, e);
+                    }
+                    if let Err(e) = channel.close().await {
+                        debug!("SSH[{}] channel close error: {:?}", session_id, e);
+                    }
+                    break;
+                }
+
                 // Handle write requests from FE (prioritized)
                 Some(data) = write_rx.recv() => {
                     if let Err(e) = channel.data(&data[..]).await {
                         warn!("SSH[{}] write error: {:?}", session_id, e);
                         break;
                     }
+                    if let Some(timer) = keepalive_timer.as_mut() {
+                        timer.reset();
+                    }
+                }
+
+                // Idle: ping the server so NAT/firewall state stays alive and a dead link is noticed
+                _ = Self::keepalive_tick(&mut keepalive_timer) => {
+                    match tokio::time::timeout(KEEPALIVE_REPLY_TIMEOUT, handle.send_ping()).await {
+                        Ok(Ok(())) => debug!("SSH[{}] keepalive answered", session_id),
+                        outcome => {
+                            warn!("SSH[{}] keepalive failed ({:?}) - connection lost", session_id, outcome.ok());
+                            let exit_event = TerminalExitEvent::connection_lost();
+                            let _ = app_handle.emit(&format!("terminal-exit:{}", session_id), exit_event);
+                            break;
+                        }
+                    }
                 }
                 
+                // Type the initial command once streaming started and the prompt settled
+                _ = tokio::time::sleep(CHECK_INTERVAL), if initial_command.is_pending() => {
+                    let now = std::time::Instant::now();
+                    if !streaming_started.load(Ordering::SeqCst) {
+                        initial_command.hold(now);
+                    } else if let Some(command) = initial_command.poll(now, false) {
+                        debug!("SSH[{}] sending initial command", session_id);
+                        if let Err(e) = channel.data(&command[..]).await {
+                            warn!("SSH[{}] write error: {:?}", session_id, e);
+                            break;
+                        }
+                    }
+                }
+
                 // Handle resize requests
                 Some((cols, rows)) = resize_rx.recv() => {
                     if let Err(e) = channel.window_change(cols as u32, rows as u32, 0, 0).await {
@@ -138,9 +289,16 @@ impl SshTerminalSession {
                 
                 // Handle incoming data from SSH server
                 msg = channel.wait() => {
+                    if let Some(timer) = keepalive_timer.as_mut() {
+                        timer.reset();
+                    }
                     match msg {
                         Some(ChannelMsg::Data { data }) => {
-                            let output = String::from_utf8_lossy(&data).to_string();
+                            paste_tracker.observe(&data);
+                            scrollback.push(&data);
+                            recording.output(&data);
+                            let output = stdout_decoder.decode(&data);
+                            initial_command.observe(&output, std::time::Instant::now());
 
                             if streaming_started.load(Ordering::SeqCst) {
                                 // Flush pending buffer first
@@ -163,7 +321,9 @@ impl SshTerminalSession {
                             }
                         }
                         Some(ChannelMsg::ExtendedData { data, .. }) => {
-                            let output = String::from_utf8_lossy(&data).to_string();
+                            scrollback.push(&data);
+                            recording.output(&data);
+                            let output = stderr_decoder.decode(&data);
                             if streaming_started.load(Ordering::SeqCst) {
                                 let _ = app_handle.emit(
                                     &format!("terminal-output:{}", session_id),
@@ -175,34 +335,51 @@ impl SshTerminalSession {
                         }
                         Some(ChannelMsg::Eof) => {
                             // EOF = Server closed write stream
-                            // NOTE: This could be normal session end OR unexpected disconnect
-                            // Frontend heartbeat mechanism will distinguish zombie connections
-                            debug!("SSH[{}] received channel EOF - connection closing", session_id);
-                            let exit_event = TerminalExitEvent::connection_lost();
-                            let _ = app_handle.emit(&format!("terminal-exit:{}", session_id), exit_event);
-                            break;
+                            // Keep reading: servers often send ExitStatus after EOF, then Close
+                            debug!("SSH[{}] received channel EOF - waiting for close", session_id);
+                            eof_received = true;
                         }
                         Some(ChannelMsg::Close) => {
                             // Close = Channel fully closed by server
-                            // Emitted after both sides agree to close
+                            // After an ExitStatus this is a normal logout, otherwise treat as lost
                             debug!("SSH[{}] received channel Close - connection terminated", session_id);
-                            let exit_event = TerminalExitEvent::connection_lost();
+                            let exit_event = Self::closed_event(process_exit.take());
                             let _ = app_handle.emit(&format!("terminal-exit:{}", session_id), exit_event);
                             break;
                         }
-                        Some(ChannelMsg::ExitStatus { exit_status }) => {
+                        Some(ChannelMsg::ExitStatus { exit_status: status }) => {
                             // Exit status from remote command
-                            debug!("SSH[{}] remote process exited with status: {}", session_id, exit_status);
+                            debug!("SSH[{}] remote process exited with status: {}", session_id, status);
+                            process_exit = Some(TerminalExitEvent::process_exited(status as i32));
                             // Don't break here - wait for EOF/Close
                         }
                         Some(ChannelMsg::ExitSignal { signal_name, .. }) => {
                             // Process killed by signal
                             debug!("SSH[{}] remote process killed by signal: {:?}", session_id, signal_name);
+                            let (name, number) = signal_info(&signal_name);
+                            process_exit = Some(TerminalExitEvent::process_signaled(&name, 128 + number));
                             // Don't break here - wait for EOF/Close
                         }
+                        Some(ChannelMsg::Success) | Some(ChannelMsg::Failure) => {
+                            // Only the PTY request asks for a reply
+                            let granted = matches!(msg, Some(ChannelMsg::Success));
+                            if let Ok(mut pty) = pty_allocated.lock() {
+                                if pty.is_none() {
+                                    debug!("SSH[{}] PTY request {}", session_id, if granted { "granted" } else { "refused" });
+                                    *pty = Some(granted);
+                                }
+                            }
+                        }
                         Some(_) => {
                             // Other channel messages (WindowAdjusted, etc.)
                         }
+                        None if eof_received => {
+                            // Channel ended after EOF without a Close: the session still finished
+                            debug!("SSH[{}] channel ended after EOF", session_id);
+                            let exit_event = Self::closed_event(process_exit.take());
+                            let _ = app_handle.emit(&format!("terminal-exit:{}", session_id), exit_event);
+                            break;
+                        }
                         None => {
                             // Channel wait() returned None = connection dropped unexpectedly
                             debug!("SSH[{}] channel wait returned None - network disconnected", session_id);
@@ -218,6 +395,22 @@ impl SshTerminalSession {
         debug!("SSH[{}] channel I/O loop ended", session_id);
     }
     
+    /// Wait for the next keepalive tick, or forever when keepalive is disabled
+    async fn keepalive_tick(timer: &mut Option<Interval>) {
+        match timer {
+            Some(timer) => {
+                timer.tick().await;
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Exit event for a channel closed by the server
+    /// The process's own exit if the server reported one, otherwise connection lost
+    fn closed_event(process_exit: Option<TerminalExitEvent>) -> TerminalExitEvent {
+        process_exit.unwrap_or_else(TerminalExitEvent::connection_lost)
+    }
+
     /// Start streaming output to frontend
     /// Call this AFTER frontend has setup event listener
     pub fn start_streaming(&self) {
@@ -230,8 +423,18 @@ impl SshTerminalSession {
 
     /// Execute a command and capture its output (non-interactive exec channel)
     /// Used for fetching command history, environment variables, etc.
-    /// Note: No timeout here - callers should add timeout if needed
+    /// Waits until the command exits - use execute_command_with_timeout to bound it
     pub async fn execute_command(&self, command: &str) -> Result<String, SshError> {
+        self.execute_command_with_timeout(command, None).await
+    }
+
+    /// Execute a command, giving up after `timeout` (None waits indefinitely)
+    /// On expiry the exec channel is closed so the remote command is not left running
+    pub async fn execute_command_with_timeout(
+        &self,
+        command: &str,
+        timeout: Option<Duration>,
+    ) -> Result<String, SshError> {
         info!("SSH[{}] executing command: {}", self.id, command);
 
         // Open a new exec channel (separate from the PTY)
@@ -242,6 +445,23 @@ impl SshTerminalSession {
         channel.exec(true, command).await?;
         debug!("SSH[{}] command execution started", self.id);
 
+        let Some(limit) = timeout else {
+            return self.collect_command_output(&mut channel).await;
+        };
+
+        match tokio::time::timeout(limit, self.collect_command_output(&mut channel)).await {
+            Ok(result) => result,
+            Err(_) => {
+                warn!("SSH[{}] command timed out after {:?}: {}", self.id, limit, command);
+                let _ = channel.eof().await;
+                let _ = channel.close().await;
+                Err(SshError::CommandFailed("timed out".to_string()))
+            }
+        }
+    }
+
+    /// Read an exec channel until it closes, returning stdout
+    async fn collect_command_output(&self, channel: &mut Channel<Msg>) -> Result<String, SshError> {
         // Collect all output
         let mut output = String::new();
         let mut error_output = String::new();
@@ -300,6 +520,27 @@ impl SshTerminalSession {
     }
 }
 
+/// Signal name (as in RFC 4254, without "SIG") and its usual POSIX number
+/// Unknown custom signals map to 0, so their exit code is 128
+fn signal_info(signal: &Sig) -> (String, i32) {
+    let (name, number) = match signal {
+        Sig::HUP => ("HUP", 1),
+        Sig::INT => ("INT", 2),
+        Sig::QUIT => ("QUIT", 3),
+        Sig::ILL => ("ILL", 4),
+        Sig::ABRT => ("ABRT", 6),
+        Sig::FPE => ("FPE", 8),
+        Sig::KILL => ("KILL", 9),
+        Sig::USR1 => ("USR1", 10),
+        Sig::SEGV => ("SEGV", 11),
+        Sig::PIPE => ("PIPE", 13),
+        Sig::ALRM => ("ALRM", 14),
+        Sig::TERM => ("TERM", 15),
+        Sig::Custom(name) => return (name.clone(), 0),
+    };
+    (name.to_string(), number)
+}
+
 #[async_trait]
 impl TerminalSession for SshTerminalSession {
     fn id(&self) -> &str {
@@ -322,8 +563,36 @@ impl TerminalSession for SshTerminalSession {
         Ok(())
     }
 
-    async fn close(&mut self) -> Result<(), SessionError> {
-        // Dropping the senders will cause the I/O loop to exit
+    /// Round-trip a keepalive request through the server
+    async fn ping(&self) -> bool {
+        let replied = matches!(tokio::time::timeout(PING_TIMEOUT, self.handle.send_ping()).await, Ok(Ok(())));
+        // send_ping also returns Ok when the connection drops before the reply arrives
+        replied && !self.handle.is_closed()
+    }
+
+    async fn close(&self) -> Result<(), SessionError> {
+        // Let the I/O loop send EOF/close on the shell channel
+        let close_tx = self.close_tx.lock().unwrap().take();
+        if let Some(close_tx) = close_tx {
+            let _ = close_tx.send(());
+        }
+        let io_task = self.io_task.lock().unwrap().take();
+        if let Some(mut io_task) = io_task {
+            if tokio::time::timeout(GRACEFUL_CLOSE_TIMEOUT, &mut io_task).await.is_err() {
+                warn!("SSH[{}] channel close timed out", self.id);
+                io_task.abort();
+            }
+        }
+
+        // Then disconnect the transport so the server drops the session promptly
+        let disconnect = self.handle.disconnect(russh::Disconnect::ByApplication, "", "en");
+        match tokio::time::timeout(GRACEFUL_CLOSE_TIMEOUT, disconnect).await {
+            Ok(Err(e)) => debug!("SSH[{}] disconnect error: {:?}", self.id, e),
+            Err(_) => warn!("SSH[{}] disconnect timed out", self.id),
+            Ok(Ok(())) => {}
+        }
+
+        info!("SSH[{}] session closed", self.id);
         Ok(())
     }
 
@@ -336,4 +605,44 @@ impl TerminalSession for SshTerminalSession {
             .await
             .map_err(SessionError::SshError)
     }
+
+    async fn execute_command_with_timeout(&self, command: &str, timeout: Duration) -> Result<String, SessionError> {
+        self.execute_command_with_timeout(command, Some(timeout))
+            .await
+            .map_err(SessionError::SshError)
+    }
+
+    fn bracketed_paste_enabled(&self) -> bool {
+        self.bracketed_paste.load(Ordering::SeqCst)
+    }
+
+    fn scrollback(&self) -> String {
+        self.scrollback.text()
+    }
+
+    fn recording(&self) -> Option<Recording> {
+        Some(self.recording.clone())
+    }
+
+    fn capabilities(&self) -> SessionCapabilities {
+        SessionCapabilities { exec: true, ..Default::default() }
+    }
+
+    fn session_info(&self) -> SessionInfo {
+        let mut info = SessionInfo::new(&self.id, SessionType::Ssh);
+        if let Ok(conn) = self.connection_info.lock() {
+            info.server_version = conn.server_version.clone();
+            info.kex = conn.kex.clone();
+            info.cipher = conn.cipher.clone();
+            info.mac = conn.mac.clone();
+            info.host_key_algorithm = conn.host_key_algorithm.clone();
+            info.agent_auth = conn.agent_auth.clone();
+        }
+        info.pty_allocated = self.pty_allocated.lock().ok().and_then(|p| *p);
+        info
+    }
+
+    fn ssh_handle(&self) -> Option<Arc<Handle<SshClient>>> {
+        Some(self.handle.clone())
+    }
 }
@@ -1,4 +1,13 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::login::AutoLoginOptions;
+use crate::core::initial_command::InitialCommand;
+use crate::ssh::config::{resolve_port, ConnectionType};
+
+/// Connect timeout when none is configured
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 30;
 
 /// Telnet connection configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +24,91 @@ pub struct TelnetConfig {
     pub username: Option<String>,
     /// Password for auto-login (optional)
     pub password: Option<String>,
+    /// Charset to agree to during CHARSET negotiation (default: UTF-8)
+    #[serde(default)]
+    pub charset: Option<String>,
+    /// Interval for IAC NOP keepalives in seconds (None = disabled)
+    #[serde(default)]
+    pub keepalive_secs: Option<u64>,
+    /// Local echo of typed input (None = auto, only when server refuses to echo)
+    #[serde(default)]
+    pub local_echo: Option<bool>,
+    /// Variables sent when the server asks via NEW-ENVIRON (e.g. LANG, DISPLAY)
+    #[serde(default)]
+    pub environment: BTreeMap<String, String>,
+    /// Custom auto-login prompts and prompt timeout
+    #[serde(default)]
+    pub auto_login: AutoLoginOptions,
+    /// Give up connecting after this many seconds (None or 0 = 30s)
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    /// Bytes of recent output kept for get_scrollback (None = 256KB, 0 = keep none)
+    #[serde(default)]
+    pub scrollback_bytes: Option<usize>,
+    /// Terminal type sent for TTYPE (None = offer xterm-256color, xterm, vt100 in turn)
+    #[serde(default)]
+    pub term: Option<String>,
+    /// Typed in once auto-login finished and the prompt is ready
+    #[serde(default)]
+    pub initial_command: Option<InitialCommand>,
+}
+
+/// Options of create_telnet_session (camelCase, like the command's other arguments)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelnetSessionInput {
+    pub hostname: String,
+    /// 0 or omitted = 23
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Terminal size (default 80x24)
+    #[serde(default)]
+    pub cols: Option<u16>,
+    #[serde(default)]
+    pub rows: Option<u16>,
+    #[serde(default)]
+    pub keepalive_secs: Option<u64>,
+    #[serde(default)]
+    pub local_echo: Option<bool>,
+    #[serde(default)]
+    pub environment: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    pub auto_login: Option<AutoLoginOptions>,
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub scrollback_bytes: Option<usize>,
+    #[serde(default)]
+    pub term: Option<String>,
+    #[serde(default)]
+    pub initial_command: Option<InitialCommand>,
+}
+
+impl TelnetSessionInput {
+    /// Convert to internal TelnetConfig
+    pub fn into_telnet_config(self) -> TelnetConfig {
+        TelnetConfig {
+            hostname: self.hostname,
+            port: resolve_port(self.port.unwrap_or(0), &ConnectionType::Telnet),
+            cols: self.cols.unwrap_or(80),
+            rows: self.rows.unwrap_or(24),
+            username: self.username,
+            password: self.password,
+            charset: None,
+            keepalive_secs: self.keepalive_secs,
+            local_echo: self.local_echo,
+            environment: self.environment.unwrap_or_default(),
+            auto_login: self.auto_login.unwrap_or_default(),
+            connect_timeout_secs: self.connect_timeout_secs,
+            scrollback_bytes: self.scrollback_bytes,
+            term: self.term,
+            initial_command: self.initial_command,
+        }
+    }
 }
 
 impl Default for TelnetConfig {
@@ -26,6 +120,15 @@ impl Default for TelnetConfig {
             rows: 24,
             username: None,
             password: None,
+            charset: None,
+            keepalive_secs: None,
+            local_echo: None,
+            environment: BTreeMap::new(),
+            auto_login: AutoLoginOptions::default(),
+            connect_timeout_secs: None,
+            scrollback_bytes: None,
+            term: None,
+            initial_command: None,
         }
     }
 }
@@ -53,4 +156,61 @@ impl TelnetConfig {
         self.password = password;
         self
     }
+
+    /// Set charset for CHARSET negotiation
+    pub fn with_charset(mut self, charset: Option<String>) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    /// Set keepalive interval
+    pub fn with_keepalive(mut self, keepalive_secs: Option<u64>) -> Self {
+        self.keepalive_secs = keepalive_secs;
+        self
+    }
+
+    /// Set local echo mode (None = auto)
+    pub fn with_local_echo(mut self, local_echo: Option<bool>) -> Self {
+        self.local_echo = local_echo;
+        self
+    }
+
+    /// Set variables for NEW-ENVIRON
+    pub fn with_environment(mut self, environment: BTreeMap<String, String>) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Set auto-login prompt patterns and timeout
+    pub fn with_auto_login(mut self, auto_login: AutoLoginOptions) -> Self {
+        self.auto_login = auto_login;
+        self
+    }
+
+    /// Set terminal type for TTYPE (None = defaults)
+    pub fn with_term(mut self, term: Option<String>) -> Self {
+        self.term = term;
+        self
+    }
+
+    /// Set command typed in once the session is ready
+    pub fn with_initial_command(mut self, initial_command: Option<InitialCommand>) -> Self {
+        self.initial_command = initial_command;
+        self
+    }
+
+    /// Set connect timeout (None = 30s)
+    pub fn with_connect_timeout(mut self, connect_timeout_secs: Option<u64>) -> Self {
+        self.connect_timeout_secs = connect_timeout_secs;
+        self
+    }
+
+    /// How long connecting may take
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.connect_timeout_secs
+                .filter(|&secs| secs > 0)
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS),
+        )
+    }
 }
@@ -3,6 +3,9 @@
 //! This module handles automatic detection of login and password prompts
 //! and sends saved credentials when detected.
 
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
 /// Login prompt detection patterns (case-insensitive)
 const LOGIN_PATTERNS: &[&str] = &[
     "login:",
@@ -22,6 +25,24 @@ const PASSWORD_PATTERNS: &[&str] = &[
     "secret:",
 ];
 
+/// How long to wait for each prompt when no timeout is configured
+const DEFAULT_PROMPT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// User overrides for prompt detection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoLoginOptions {
+    /// Login prompts to look for instead of the built-in ones (case-insensitive)
+    #[serde(default)]
+    pub login_patterns: Option<Vec<String>>,
+    /// Password prompts to look for instead of the built-in ones (case-insensitive)
+    #[serde(default)]
+    pub password_patterns: Option<Vec<String>>,
+    /// Seconds to wait for each prompt before giving up (default 30, 0 = forever)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
 /// Auto-login state machine
 #[derive(Debug, Clone, PartialEq)]
 pub enum LoginState {
@@ -36,7 +57,8 @@ pub enum LoginState {
 }
 
 /// Type of prompt detected
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PromptType {
     Login,
     Password,
@@ -55,10 +77,21 @@ pub struct AutoLogin {
     buffer: String,
     /// Maximum buffer size to prevent memory issues
     max_buffer_size: usize,
+    /// Lowercased login prompt patterns
+    login_patterns: Vec<String>,
+    /// Lowercased password prompt patterns
+    password_patterns: Vec<String>,
+    /// How long to wait for the next prompt (None = forever)
+    timeout: Option<Duration>,
+    /// When waiting for the current prompt gives up
+    deadline: Option<Instant>,
+    /// Each credential is sent at most once, so a re-prompt never gets the wrong one
+    username_sent: bool,
+    password_sent: bool,
 }
 
 impl AutoLogin {
-    /// Create a new auto-login handler
+    /// Create a new auto-login handler with the built-in prompts and default timeout
     pub fn new(username: Option<String>, password: Option<String>) -> Self {
         let state = if username.is_some() {
             LoginState::AwaitingLogin
@@ -66,17 +99,69 @@ impl AutoLogin {
             LoginState::Disabled
         };
 
-        Self {
+        let mut login = Self {
             state,
             username,
             password,
             buffer: String::with_capacity(256),
             max_buffer_size: 1024,
+            login_patterns: lowercase_all(LOGIN_PATTERNS),
+            password_patterns: lowercase_all(PASSWORD_PATTERNS),
+            timeout: Some(DEFAULT_PROMPT_TIMEOUT),
+            deadline: None,
+            username_sent: false,
+            password_sent: false,
+        };
+        login.restart_deadline();
+        login
+    }
+
+    /// Apply custom prompt patterns and timeout
+    pub fn with_options(mut self, options: AutoLoginOptions) -> Self {
+        if let Some(patterns) = options.login_patterns.filter(|p| !p.is_empty()) {
+            self.login_patterns = lowercase_all(&patterns);
+        }
+        if let Some(patterns) = options.password_patterns.filter(|p| !p.is_empty()) {
+            self.password_patterns = lowercase_all(&patterns);
+        }
+        if let Some(secs) = options.timeout_secs {
+            self.timeout = (secs > 0).then(|| Duration::from_secs(secs));
+        }
+        self.restart_deadline();
+        self
+    }
+
+    /// When waiting for the current prompt gives up, if it can
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Give up if the current prompt hasn't appeared by `now`
+    ///
+    /// Returns the prompt that was being waited for when auto-login is
+    /// disabled by this call, so the user can be told to type it themselves.
+    pub fn expire(&mut self, now: Instant) -> Option<PromptType> {
+        if self.deadline.is_none_or(|deadline| now < deadline) {
+            return None;
         }
+        let waiting_for = match self.state {
+            LoginState::AwaitingLogin => PromptType::Login,
+            LoginState::AwaitingPassword => PromptType::Password,
+            _ => return None,
+        };
+        self.state = LoginState::Disabled;
+        self.deadline = None;
+        self.buffer.clear();
+        Some(waiting_for)
+    }
+
+    fn restart_deadline(&mut self) {
+        let waiting = matches!(self.state, LoginState::AwaitingLogin | LoginState::AwaitingPassword);
+        self.deadline = self.timeout.filter(|_| waiting).map(|t| Instant::now() + t);
     }
 
     /// Process incoming data and check for login/password prompts
-    /// Returns bytes to send if a response is needed (username or password + newline)
+    /// Returns bytes to send if a response is needed (username or password + newline), not yet IAC-escaped
     pub fn process(&mut self, data: &str) -> Option<Vec<u8>> {
         // If disabled or authenticated, don't process
         if self.state == LoginState::Disabled || self.state == LoginState::Authenticated {
@@ -93,37 +178,31 @@ impl AutoLogin {
         }
 
         // Check for prompts based on current state
-        match self.state {
-            LoginState::AwaitingLogin => {
-                if let Some(PromptType::Login) = detect_prompt(&self.buffer) {
-                    // Found login prompt, send username
-                    if let Some(ref username) = self.username {
-                        let response = format!("{}\r\n", username);
-                        self.state = if self.password.is_some() {
-                            LoginState::AwaitingPassword
-                        } else {
-                            LoginState::Authenticated
-                        };
-                        self.buffer.clear();
-                        return Some(response.into_bytes());
-                    }
-                }
+        let prompt = detect_prompt(&self.buffer, &self.login_patterns, &self.password_patterns);
+        let response = match (&self.state, prompt) {
+            (LoginState::AwaitingLogin, Some(PromptType::Login)) if !self.username_sent => {
+                // Found login prompt, send username
+                let username = self.username.clone()?;
+                self.username_sent = true;
+                self.state = if self.password.is_some() {
+                    LoginState::AwaitingPassword
+                } else {
+                    LoginState::Authenticated
+                };
+                format!("{}\r\n", username)
             }
-            LoginState::AwaitingPassword => {
-                if let Some(PromptType::Password) = detect_prompt(&self.buffer) {
-                    // Found password prompt, send password
-                    if let Some(ref password) = self.password {
-                        let response = format!("{}\r\n", password);
-                        self.state = LoginState::Authenticated;
-                        self.buffer.clear();
-                        return Some(response.into_bytes());
-                    }
-                }
+            (LoginState::AwaitingPassword, Some(PromptType::Password)) if !self.password_sent => {
+                // Found password prompt, send password
+                let password = self.password.clone()?;
+                self.password_sent = true;
+                self.state = LoginState::Authenticated;
+                format!("{}\r\n", password)
             }
-            _ => {}
-        }
-
-        None
+            _ => return None,
+        };
+        self.buffer.clear();
+        self.restart_deadline();
+        Some(response.into_bytes())
     }
 
     /// Check if auto-login is complete
@@ -139,25 +218,28 @@ impl AutoLogin {
         } else {
             LoginState::Disabled
         };
+        self.username_sent = false;
+        self.password_sent = false;
+        self.restart_deadline();
     }
 }
 
-/// Detect if the buffer contains a login or password prompt
-fn detect_prompt(buffer: &str) -> Option<PromptType> {
+fn lowercase_all<S: AsRef<str>>(patterns: &[S]) -> Vec<String> {
+    patterns.iter().map(|p| p.as_ref().to_lowercase()).collect()
+}
+
+/// Detect if the buffer contains a login or password prompt (patterns lowercased)
+fn detect_prompt(buffer: &str, login_patterns: &[String], password_patterns: &[String]) -> Option<PromptType> {
     let lower = buffer.to_lowercase();
 
     // Check password patterns first (more specific)
-    for pattern in PASSWORD_PATTERNS {
-        if lower.contains(pattern) {
-            return Some(PromptType::Password);
-        }
+    if password_patterns.iter().any(|pattern| lower.contains(pattern.as_str())) {
+        return Some(PromptType::Password);
     }
 
     // Check login patterns
-    for pattern in LOGIN_PATTERNS {
-        if lower.contains(pattern) {
-            return Some(PromptType::Login);
-        }
+    if login_patterns.iter().any(|pattern| lower.contains(pattern.as_str())) {
+        return Some(PromptType::Login);
     }
 
     None
@@ -167,23 +249,27 @@ fn detect_prompt(buffer: &str) -> Option<PromptType> {
 mod tests {
     use super::*;
 
+    fn detect_default(buffer: &str) -> Option<PromptType> {
+        detect_prompt(buffer, &lowercase_all(LOGIN_PATTERNS), &lowercase_all(PASSWORD_PATTERNS))
+    }
+
     #[test]
     fn test_detect_login_prompt() {
-        assert_eq!(detect_prompt("login: "), Some(PromptType::Login));
-        assert_eq!(detect_prompt("Username: "), Some(PromptType::Login));
-        assert_eq!(detect_prompt("User: "), Some(PromptType::Login));
+        assert_eq!(detect_default("login: "), Some(PromptType::Login));
+        assert_eq!(detect_default("Username: "), Some(PromptType::Login));
+        assert_eq!(detect_default("User: "), Some(PromptType::Login));
     }
 
     #[test]
     fn test_detect_password_prompt() {
-        assert_eq!(detect_prompt("Password: "), Some(PromptType::Password));
-        assert_eq!(detect_prompt("password:"), Some(PromptType::Password));
+        assert_eq!(detect_default("Password: "), Some(PromptType::Password));
+        assert_eq!(detect_default("password:"), Some(PromptType::Password));
     }
 
     #[test]
     fn test_no_prompt() {
-        assert_eq!(detect_prompt("Hello World"), None);
-        assert_eq!(detect_prompt("Connected to server"), None);
+        assert_eq!(detect_default("Hello World"), None);
+        assert_eq!(detect_default("Connected to server"), None);
     }
 
     #[test]
@@ -219,9 +305,49 @@ mod tests {
         assert_eq!(login.state, LoginState::Authenticated);
     }
 
+    #[test]
+    fn test_auto_login_custom_patterns() {
+        let options = AutoLoginOptions {
+            login_patterns: Some(vec!["Benutzername".to_string()]),
+            password_patterns: Some(vec!["Kennwort".to_string()]),
+            timeout_secs: None,
+        };
+        let mut login = AutoLogin::new(Some("admin".to_string()), Some("secret".to_string())).with_options(options);
+
+        // Built-in prompts no longer match
+        assert!(login.process("login: ").is_none());
+        assert_eq!(login.process("\r\nBENUTZERNAME> "), Some(b"admin\r\n".to_vec()));
+        assert_eq!(login.process("Kennwort> "), Some(b"secret\r\n".to_vec()));
+        assert_eq!(login.state, LoginState::Authenticated);
+    }
+
+    #[test]
+    fn test_auto_login_timeout_disables() {
+        let options = AutoLoginOptions { timeout_secs: Some(5), ..Default::default() };
+        let mut login = AutoLogin::new(Some("admin".to_string()), Some("secret".to_string())).with_options(options);
+        let deadline = login.deadline().unwrap();
+
+        assert_eq!(login.expire(deadline - Duration::from_secs(1)), None);
+        assert_eq!(login.process("login: "), Some(b"admin\r\n".to_vec()));
+        // Sending the username restarts the wait for the password prompt
+        let deadline = login.deadline().unwrap();
+        assert_eq!(login.expire(deadline), Some(PromptType::Password));
+        assert_eq!(login.state, LoginState::Disabled);
+        assert!(login.deadline().is_none());
+        assert!(login.process("Password: ").is_none());
+    }
+
+    #[test]
+    fn test_no_timeout_when_zero() {
+        let options = AutoLoginOptions { timeout_secs: Some(0), ..Default::default() };
+        let login = AutoLogin::new(Some("admin".to_string()), None).with_options(options);
+        assert!(login.deadline().is_none());
+        assert!(AutoLogin::new(None, None).deadline().is_none());
+    }
+
     #[test]
     fn test_case_insensitive() {
-        assert_eq!(detect_prompt("LOGIN:"), Some(PromptType::Login));
-        assert_eq!(detect_prompt("PASSWORD:"), Some(PromptType::Password));
+        assert_eq!(detect_default("LOGIN:"), Some(PromptType::Login));
+        assert_eq!(detect_default("PASSWORD:"), Some(PromptType::Password));
     }
 }
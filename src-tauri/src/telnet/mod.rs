@@ -11,6 +11,6 @@ pub mod login;
 pub mod protocol;
 pub mod session;
 
-pub use config::TelnetConfig;
+pub use config::{TelnetConfig, TelnetSessionInput};
 pub use error::TelnetError;
 pub use session::TelnetTerminalSession;
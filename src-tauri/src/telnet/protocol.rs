@@ -1,4 +1,4 @@
-//! Telnet Protocol Implementation (RFC 854, 855, 1073, 1184)
+//! Telnet Protocol Implementation (RFC 854, 855, 856, 1073, 1091, 1184, 1572, 2066)
 //!
 //! This module handles telnet protocol parsing and option negotiation.
 //! Telnet uses IAC (Interpret As Command) sequences to communicate
@@ -23,12 +23,44 @@ pub const NOP: u8 = 241;   // No Operation
 pub const SE: u8 = 240;    // Subnegotiation End
 
 // Telnet options we care about
+pub const OPT_BINARY: u8 = 0;      // Binary Transmission
 pub const OPT_ECHO: u8 = 1;        // Echo
 pub const OPT_SGA: u8 = 3;         // Suppress Go Ahead
 pub const OPT_TTYPE: u8 = 24;      // Terminal Type
 pub const OPT_NAWS: u8 = 31;       // Negotiate About Window Size
 pub const OPT_LINEMODE: u8 = 34;   // Linemode
-pub const OPT_ENVIRON: u8 = 39;    // Environment Variables
+pub const OPT_ENVIRON: u8 = 39;    // New Environment Variables
+pub const OPT_CHARSET: u8 = 42;    // Character Set
+
+// Subnegotiation sub-commands (RFC 1091)
+pub const TTYPE_IS: u8 = 0;        // Terminal type IS <name>
+pub const TTYPE_SEND: u8 = 1;      // Terminal type SEND request
+
+// NEW-ENVIRON sub-commands and type bytes (RFC 1572)
+pub const ENVIRON_IS: u8 = 0;      // Variables follow
+pub const ENVIRON_SEND: u8 = 1;    // Request variables (all when none listed)
+pub const ENVIRON_VAR: u8 = 0;     // Well-known variable name follows
+pub const ENVIRON_VALUE: u8 = 1;   // Value of the preceding name follows
+pub const ENVIRON_ESC: u8 = 2;     // Next byte is literal
+pub const ENVIRON_USERVAR: u8 = 3; // User-defined variable name follows
+
+/// Variables RFC 1572 defines; anything else is sent as a USERVAR
+const WELL_KNOWN_VARS: [&str; 6] = ["USER", "JOB", "ACCT", "PRINTER", "SYSTEMTYPE", "DISPLAY"];
+
+/// Terminal types offered by default on successive TTYPE SENDs, most capable first
+pub const TERMINAL_TYPES: [&str; 3] = ["xterm-256color", "xterm", "vt100"];
+
+// CHARSET sub-commands (RFC 2066)
+pub const CHARSET_REQUEST: u8 = 1;     // Offer a list of charsets
+pub const CHARSET_ACCEPTED: u8 = 2;    // Accept one charset from the list
+pub const CHARSET_REJECTED: u8 = 3;    // Reject all offered charsets
+
+/// Charset requested when none is configured
+pub const DEFAULT_CHARSET: &str = "UTF-8";
+
+/// Upper bound on buffered subnegotiation payload (protects against a server
+/// that never sends IAC SE)
+const MAX_SB_LEN: usize = 4096;
 
 /// State machine for parsing telnet protocol data
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -44,6 +76,13 @@ enum ParseState {
     SbIac,
 }
 
+/// Subnegotiation received from the server that the protocol layer did not handle
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subnegotiation {
+    pub option: u8,
+    pub data: Vec<u8>,
+}
+
 /// Telnet protocol context for tracking negotiation state
 #[derive(Debug, Clone)]
 pub struct TelnetProtocol {
@@ -53,6 +92,32 @@ pub struct TelnetProtocol {
     pub sga_enabled: bool,
     /// Whether ECHO is being handled by remote
     pub echo_enabled: bool,
+    /// Whether the server explicitly refused to echo (WONT ECHO)
+    pub echo_refused: bool,
+    /// Whether we send in binary mode (server sent DO BINARY)
+    pub binary_enabled: bool,
+    /// Whether the server sends in binary mode (server sent WILL BINARY)
+    pub remote_binary: bool,
+    /// Our DO BINARY awaits the server's answer, which must not be acknowledged again
+    remote_binary_requested: bool,
+    /// Charset agreed with the server via CHARSET negotiation
+    pub charset: Option<String>,
+    /// Charset we accept when the server offers it
+    preferred_charset: String,
+    /// Variables sent in reply to NEW-ENVIRON SEND
+    environment: Vec<(String, String)>,
+    /// Terminal types offered on successive TTYPE SENDs
+    terminal_types: Vec<String>,
+    /// Index into terminal_types of the next TTYPE IS reply
+    ttype_index: usize,
+    /// Parser state, kept across reads so sequences split between packets survive
+    state: ParseState,
+    /// Option of the subnegotiation currently being collected
+    sb_option: u8,
+    /// Payload of the subnegotiation currently being collected
+    sb_buffer: Vec<u8>,
+    /// Subnegotiations not handled here, drained by the session for logging
+    unhandled: Vec<Subnegotiation>,
 }
 
 impl Default for TelnetProtocol {
@@ -67,9 +132,76 @@ impl TelnetProtocol {
             naws_enabled: false,
             sga_enabled: false,
             echo_enabled: false,
+            echo_refused: false,
+            binary_enabled: false,
+            remote_binary: false,
+            remote_binary_requested: false,
+            charset: None,
+            preferred_charset: DEFAULT_CHARSET.to_string(),
+            environment: Vec::new(),
+            terminal_types: TERMINAL_TYPES.iter().map(|t| t.to_string()).collect(),
+            ttype_index: 0,
+            state: ParseState::Data,
+            sb_option: 0,
+            sb_buffer: Vec::new(),
+            unhandled: Vec::new(),
         }
     }
 
+    /// Set the charset to agree to during CHARSET negotiation
+    pub fn with_charset(mut self, charset: impl Into<String>) -> Self {
+        self.preferred_charset = charset.into();
+        self
+    }
+
+    /// Offer only `name` for TTYPE instead of the default list
+    pub fn with_terminal_type(mut self, name: impl Into<String>) -> Self {
+        self.terminal_types = vec![name.into()];
+        self
+    }
+
+    /// Set the variables to send when the server asks via NEW-ENVIRON
+    pub fn with_environment(mut self, environment: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.environment = environment.into_iter().collect();
+        self
+    }
+
+    /// Whether typed input should be echoed locally
+    ///
+    /// `mode` is the user setting: `Some(true)` always, `Some(false)` never,
+    /// `None` (auto) only when the server has refused to echo.
+    pub fn local_echo_active(&self, mode: Option<bool>) -> bool {
+        mode.unwrap_or(self.echo_refused && !self.echo_enabled)
+    }
+
+    /// Negotiation to send right after connecting: ask the server to send binary
+    pub fn initial_negotiation(&mut self) -> Vec<u8> {
+        self.remote_binary_requested = true;
+        vec![IAC, DO, OPT_BINARY]
+    }
+
+    /// Encode user input for the wire
+    ///
+    /// IAC is always doubled. Outside binary mode a bare CR becomes CR NUL
+    /// (RFC 854); in binary mode bytes other than IAC pass through untouched.
+    pub fn encode_input(&self, data: &[u8]) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(data.len() + 1);
+        for (i, &byte) in data.iter().enumerate() {
+            encoded.push(byte);
+            if byte == IAC {
+                encoded.push(IAC);
+            } else if byte == b'\r' && !self.binary_enabled && data.get(i + 1) != Some(&b'\n') {
+                encoded.push(0);
+            }
+        }
+        encoded
+    }
+
+    /// Take subnegotiations received since the last call that were not handled
+    pub fn take_unhandled(&mut self) -> Vec<Subnegotiation> {
+        std::mem::take(&mut self.unhandled)
+    }
+
     /// Process incoming telnet data, returning (responses_to_send, clean_data, naws_requested)
     ///
     /// This function:
@@ -79,8 +211,7 @@ impl TelnetProtocol {
     pub fn process_data(&mut self, data: &[u8]) -> (Vec<u8>, Vec<u8>, bool) {
         let mut responses = Vec::new();
         let mut clean_data = Vec::new();
-        let mut state = ParseState::Data;
-        let mut sb_option: u8 = 0;
+        let mut state = self.state;
         let mut naws_requested = false;
 
         for &byte in data {
@@ -123,9 +254,17 @@ impl TelnetProtocol {
                 ParseState::Will => {
                     // Remote wants to enable an option
                     match byte {
+                        OPT_BINARY => {
+                            // Acknowledge unless this answers our own DO BINARY
+                            self.remote_binary = true;
+                            if !std::mem::take(&mut self.remote_binary_requested) {
+                                responses.extend_from_slice(&[IAC, DO, OPT_BINARY]);
+                            }
+                        }
                         OPT_ECHO => {
                             // Allow remote to echo
                             self.echo_enabled = true;
+                            self.echo_refused = false;
                             responses.extend_from_slice(&[IAC, DO, OPT_ECHO]);
                         }
                         OPT_SGA => {
@@ -133,6 +272,10 @@ impl TelnetProtocol {
                             self.sga_enabled = true;
                             responses.extend_from_slice(&[IAC, DO, OPT_SGA]);
                         }
+                        OPT_CHARSET => {
+                            // Let the server offer charsets
+                            responses.extend_from_slice(&[IAC, DO, OPT_CHARSET]);
+                        }
                         _ => {
                             // Refuse other options
                             responses.extend_from_slice(&[IAC, DONT, byte]);
@@ -144,7 +287,14 @@ impl TelnetProtocol {
                 ParseState::Wont => {
                     // Remote refuses to enable an option
                     match byte {
-                        OPT_ECHO => self.echo_enabled = false,
+                        OPT_BINARY => {
+                            self.remote_binary = false;
+                            self.remote_binary_requested = false;
+                        }
+                        OPT_ECHO => {
+                            self.echo_enabled = false;
+                            self.echo_refused = true;
+                        }
                         OPT_SGA => self.sga_enabled = false,
                         _ => {}
                     }
@@ -154,6 +304,11 @@ impl TelnetProtocol {
                 ParseState::Do => {
                     // Remote wants us to enable an option
                     match byte {
+                        OPT_BINARY => {
+                            // Send 8-bit data untranslated
+                            self.binary_enabled = true;
+                            responses.extend_from_slice(&[IAC, WILL, OPT_BINARY]);
+                        }
                         OPT_NAWS => {
                             // Accept NAWS - we will send window size
                             self.naws_enabled = true;
@@ -169,6 +324,14 @@ impl TelnetProtocol {
                             self.sga_enabled = true;
                             responses.extend_from_slice(&[IAC, WILL, OPT_SGA]);
                         }
+                        OPT_CHARSET => {
+                            // Accept charset negotiation
+                            responses.extend_from_slice(&[IAC, WILL, OPT_CHARSET]);
+                        }
+                        OPT_ENVIRON => {
+                            // Accept environment negotiation (sends nothing unless variables are set)
+                            responses.extend_from_slice(&[IAC, WILL, OPT_ENVIRON]);
+                        }
                         _ => {
                             // Refuse other options
                             responses.extend_from_slice(&[IAC, WONT, byte]);
@@ -180,6 +343,7 @@ impl TelnetProtocol {
                 ParseState::Dont => {
                     // Remote wants us to disable an option
                     match byte {
+                        OPT_BINARY => self.binary_enabled = false,
                         OPT_NAWS => self.naws_enabled = false,
                         OPT_SGA => self.sga_enabled = false,
                         _ => {}
@@ -191,33 +355,30 @@ impl TelnetProtocol {
 
                 ParseState::Sb => {
                     // Start of subnegotiation, byte is the option
-                    sb_option = byte;
+                    self.sb_option = byte;
+                    self.sb_buffer.clear();
                     state = ParseState::SbData;
                 }
 
                 ParseState::SbData => {
                     if byte == IAC {
                         state = ParseState::SbIac;
+                    } else if self.sb_buffer.len() < MAX_SB_LEN {
+                        self.sb_buffer.push(byte);
                     }
-                    // We don't collect subnegotiation data for now
-                    // (we only send, not receive NAWS/TTYPE subneg)
                 }
 
                 ParseState::SbIac => {
                     if byte == SE {
                         // End of subnegotiation
-                        // Handle terminal type request
-                        if sb_option == OPT_TTYPE {
-                            // Send terminal type: xterm-256color
-                            responses.extend_from_slice(&[
-                                IAC, SB, OPT_TTYPE, 0, // IS (0)
-                            ]);
-                            responses.extend_from_slice(b"xterm-256color");
-                            responses.extend_from_slice(&[IAC, SE]);
-                        }
+                        let data = std::mem::take(&mut self.sb_buffer);
+                        self.handle_subnegotiation(self.sb_option, data, &mut responses);
                         state = ParseState::Data;
                     } else if byte == IAC {
                         // Escaped IAC in subnegotiation data
+                        if self.sb_buffer.len() < MAX_SB_LEN {
+                            self.sb_buffer.push(IAC);
+                        }
                         state = ParseState::SbData;
                     } else {
                         // Unexpected byte after IAC in subnegotiation
@@ -227,8 +388,145 @@ impl TelnetProtocol {
             }
         }
 
+        self.state = state;
         (responses, clean_data, naws_requested)
     }
+
+    /// Dispatch a complete subnegotiation payload based on its option
+    fn handle_subnegotiation(&mut self, option: u8, data: Vec<u8>, responses: &mut Vec<u8>) {
+        match (option, data.first()) {
+            (OPT_TTYPE, Some(&TTYPE_SEND)) => {
+                // Each SEND gets the next type; repeating the last one tells the server the list ended
+                let name = &self.terminal_types[self.ttype_index];
+                self.ttype_index = (self.ttype_index + 1).min(self.terminal_types.len() - 1);
+                responses.extend_from_slice(&[IAC, SB, OPT_TTYPE, TTYPE_IS]);
+                responses.extend_from_slice(name.as_bytes());
+                responses.extend_from_slice(&[IAC, SE]);
+            }
+            (OPT_ENVIRON, Some(&ENVIRON_SEND)) => {
+                self.handle_environ_send(&data[1..], responses);
+            }
+            (OPT_CHARSET, Some(&CHARSET_REQUEST)) => {
+                self.handle_charset_request(&data[1..], responses);
+            }
+            _ => {
+                self.unhandled.push(Subnegotiation { option, data });
+            }
+        }
+    }
+
+    /// Reply to a NEW-ENVIRON SEND with the requested variables (all of them when none are named)
+    ///
+    /// Requested names we don't have are answered without a VALUE, meaning undefined.
+    fn handle_environ_send(&self, payload: &[u8], responses: &mut Vec<u8>) {
+        let requested = parse_environ_names(payload);
+
+        responses.extend_from_slice(&[IAC, SB, OPT_ENVIRON, ENVIRON_IS]);
+        if requested.is_empty() {
+            for (name, value) in &self.environment {
+                push_environ_var(responses, environ_type(name), name, Some(value));
+            }
+        } else {
+            for (kind, name) in &requested {
+                let value = self.environment.iter().find(|(n, _)| n == name).map(|(_, v)| v);
+                push_environ_var(responses, *kind, name, value);
+            }
+        }
+        responses.extend_from_slice(&[IAC, SE]);
+    }
+
+    /// Reply to a CHARSET REQUEST, accepting the preferred charset if offered
+    ///
+    /// Payload is `[TTABLE ] <sep> charset (<sep> charset)*`, where the
+    /// separator is whatever byte follows the REQUEST command.
+    fn handle_charset_request(&mut self, payload: &[u8], responses: &mut Vec<u8>) {
+        // Translation tables are not supported, skip the "[TTABLE] <version>" prefix
+        let payload = match payload.strip_prefix(b"[TTABLE]") {
+            Some(rest) => rest.get(1..).unwrap_or_default(),
+            None => payload,
+        };
+
+        let accepted = payload.split_first().and_then(|(&sep, list)| {
+            list.split(|&b| b == sep)
+                .map(|name| String::from_utf8_lossy(name).to_string())
+                .find(|name| name.eq_ignore_ascii_case(&self.preferred_charset))
+        });
+
+        responses.extend_from_slice(&[IAC, SB, OPT_CHARSET]);
+        match accepted {
+            Some(name) => {
+                responses.push(CHARSET_ACCEPTED);
+                responses.extend_from_slice(name.as_bytes());
+                self.charset = Some(name);
+            }
+            None => responses.push(CHARSET_REJECTED),
+        }
+        responses.extend_from_slice(&[IAC, SE]);
+    }
+}
+
+/// Names listed in a NEW-ENVIRON SEND, each with its VAR/USERVAR type
+fn parse_environ_names(payload: &[u8]) -> Vec<(u8, String)> {
+    let mut names: Vec<(u8, Vec<u8>)> = Vec::new();
+    let mut bytes = payload.iter();
+    while let Some(&byte) = bytes.next() {
+        match (byte, names.last_mut()) {
+            (ENVIRON_VAR | ENVIRON_USERVAR, _) => names.push((byte, Vec::new())),
+            (ENVIRON_ESC, Some((_, name))) => name.extend(bytes.next()),
+            (_, Some((_, name))) => name.push(byte),
+            (_, None) => {}
+        }
+    }
+    names
+        .into_iter()
+        .map(|(kind, name)| (kind, String::from_utf8_lossy(&name).to_string()))
+        .collect()
+}
+
+fn environ_type(name: &str) -> u8 {
+    if WELL_KNOWN_VARS.contains(&name) { ENVIRON_VAR } else { ENVIRON_USERVAR }
+}
+
+/// Append one `type name [VALUE value]` entry, escaping type bytes and IAC
+fn push_environ_var(out: &mut Vec<u8>, kind: u8, name: &str, value: Option<&String>) {
+    let push_escaped = |out: &mut Vec<u8>, text: &str| {
+        for &b in text.as_bytes() {
+            match b {
+                ENVIRON_VAR | ENVIRON_VALUE | ENVIRON_ESC | ENVIRON_USERVAR => out.extend_from_slice(&[ENVIRON_ESC, b]),
+                IAC => out.extend_from_slice(&[IAC, IAC]),
+                _ => out.push(b),
+            }
+        }
+    };
+    out.push(kind);
+    push_escaped(out, name);
+    if let Some(value) = value {
+        out.push(ENVIRON_VALUE);
+        push_escaped(out, value);
+    }
+}
+
+/// Convert typed input into the bytes to display for local echo
+///
+/// Enter becomes CRLF, backspace erases the previous cell, and escape
+/// sequences (arrow keys etc.) and other control characters are dropped.
+pub fn build_local_echo(input: &[u8]) -> Vec<u8> {
+    if input.first() == Some(&0x1b) {
+        return Vec::new();
+    }
+
+    let mut echo = Vec::with_capacity(input.len());
+    for &byte in input {
+        match byte {
+            b'\r' => echo.extend_from_slice(b"\r\n"),
+            b'\n' => {}
+            0x08 | 0x7f => echo.extend_from_slice(b"\x08 \x08"),
+            b'\t' => echo.push(byte),
+            0x00..=0x1f => {}
+            _ => echo.push(byte),
+        }
+    }
+    echo
 }
 
 /// Build NAWS (window size) subnegotiation message
@@ -305,6 +603,36 @@ mod tests {
         assert!(naws_requested);
     }
 
+    #[test]
+    fn test_binary_negotiation() {
+        let mut proto = TelnetProtocol::new();
+        assert_eq!(proto.initial_negotiation(), vec![IAC, DO, OPT_BINARY]);
+        // The server's WILL answers our DO: no acknowledgement loop
+        let (responses, _, _) = proto.process_data(&[IAC, WILL, OPT_BINARY]);
+        assert!(responses.is_empty());
+        assert!(proto.remote_binary);
+
+        let (responses, _, _) = proto.process_data(&[IAC, DO, OPT_BINARY]);
+        assert_eq!(responses, vec![IAC, WILL, OPT_BINARY]);
+        assert!(proto.binary_enabled);
+
+        let (responses, _, _) = proto.process_data(&[IAC, DONT, OPT_BINARY]);
+        assert_eq!(responses, vec![IAC, WONT, OPT_BINARY]);
+        assert!(!proto.binary_enabled);
+    }
+
+    #[test]
+    fn test_encode_input() {
+        let mut proto = TelnetProtocol::new();
+        assert_eq!(proto.encode_input(b"ls\r"), b"ls\r\0".to_vec());
+        assert_eq!(proto.encode_input(b"ls\r\n"), b"ls\r\n".to_vec());
+        assert_eq!(proto.encode_input(&[b'a', IAC]), vec![b'a', IAC, IAC]);
+
+        proto.process_data(&[IAC, DO, OPT_BINARY]);
+        assert_eq!(proto.encode_input(b"ls\r"), b"ls\r".to_vec());
+        assert_eq!(proto.encode_input(&[0xc3, IAC, b'\r']), vec![0xc3, IAC, IAC, b'\r']);
+    }
+
     #[test]
     fn test_escaped_iac() {
         let mut proto = TelnetProtocol::new();
@@ -313,4 +641,146 @@ mod tests {
         assert!(responses.is_empty());
         assert_eq!(clean, vec![b'A', IAC, b'B']);
     }
+
+    #[test]
+    fn test_ttype_send_split_across_reads() {
+        let mut proto = TelnetProtocol::new();
+        let (responses, _, _) = proto.process_data(&[IAC, SB, OPT_TTYPE]);
+        assert!(responses.is_empty());
+
+        let (responses, clean, _) = proto.process_data(&[TTYPE_SEND, IAC, SE]);
+        let mut expected = vec![IAC, SB, OPT_TTYPE, TTYPE_IS];
+        expected.extend_from_slice(b"xterm-256color");
+        expected.extend_from_slice(&[IAC, SE]);
+        assert_eq!(responses, expected);
+        assert!(clean.is_empty());
+    }
+
+    #[test]
+    fn test_ttype_send_cycles_terminal_types() {
+        let mut proto = TelnetProtocol::new();
+        let ttype_is = |name: &str| {
+            let mut expected = vec![IAC, SB, OPT_TTYPE, TTYPE_IS];
+            expected.extend_from_slice(name.as_bytes());
+            expected.extend_from_slice(&[IAC, SE]);
+            expected
+        };
+        let send = [IAC, SB, OPT_TTYPE, TTYPE_SEND, IAC, SE];
+
+        assert_eq!(proto.process_data(&send).0, ttype_is("xterm-256color"));
+        assert_eq!(proto.process_data(&send).0, ttype_is("xterm"));
+        assert_eq!(proto.process_data(&send).0, ttype_is("vt100"));
+        // The last type repeats to mark the end of the list
+        assert_eq!(proto.process_data(&send).0, ttype_is("vt100"));
+
+        // A configured type is the only one offered
+        let mut proto = TelnetProtocol::new().with_terminal_type("screen-256color");
+        assert_eq!(proto.process_data(&send).0, ttype_is("screen-256color"));
+        assert_eq!(proto.process_data(&send).0, ttype_is("screen-256color"));
+    }
+
+    #[test]
+    fn test_environ_send_replies_with_configured_variables() {
+        let mut proto = TelnetProtocol::new().with_environment([
+            ("USER".to_string(), "alice".to_string()),
+            ("LANG".to_string(), "en_US.UTF-8".to_string()),
+        ]);
+        let (responses, _, _) = proto.process_data(&[IAC, DO, OPT_ENVIRON]);
+        assert_eq!(responses, vec![IAC, WILL, OPT_ENVIRON]);
+
+        // SEND with no names: everything, well-known names as VAR
+        let (responses, _, _) = proto.process_data(&[IAC, SB, OPT_ENVIRON, ENVIRON_SEND, IAC, SE]);
+        let mut expected = vec![IAC, SB, OPT_ENVIRON, ENVIRON_IS, ENVIRON_VAR];
+        expected.extend_from_slice(b"USER");
+        expected.push(ENVIRON_VALUE);
+        expected.extend_from_slice(b"alice");
+        expected.push(ENVIRON_USERVAR);
+        expected.extend_from_slice(b"LANG");
+        expected.push(ENVIRON_VALUE);
+        expected.extend_from_slice(b"en_US.UTF-8");
+        expected.extend_from_slice(&[IAC, SE]);
+        assert_eq!(responses, expected);
+
+        // Named request: unknown names come back without a value
+        let mut data = vec![IAC, SB, OPT_ENVIRON, ENVIRON_SEND, ENVIRON_USERVAR];
+        data.extend_from_slice(b"LANG");
+        data.push(ENVIRON_VAR);
+        data.extend_from_slice(b"DISPLAY");
+        data.extend_from_slice(&[IAC, SE]);
+        let (responses, _, _) = proto.process_data(&data);
+        let mut expected = vec![IAC, SB, OPT_ENVIRON, ENVIRON_IS, ENVIRON_USERVAR];
+        expected.extend_from_slice(b"LANG");
+        expected.push(ENVIRON_VALUE);
+        expected.extend_from_slice(b"en_US.UTF-8");
+        expected.push(ENVIRON_VAR);
+        expected.extend_from_slice(b"DISPLAY");
+        expected.extend_from_slice(&[IAC, SE]);
+        assert_eq!(responses, expected);
+    }
+
+    #[test]
+    fn test_unhandled_subnegotiation_collected() {
+        let mut proto = TelnetProtocol::new();
+        let data = [IAC, SB, OPT_LINEMODE, 1, b'U', IAC, IAC, IAC, SE, b'x'];
+        let (responses, clean, _) = proto.process_data(&data);
+        assert!(responses.is_empty());
+        assert_eq!(clean, vec![b'x']);
+        assert_eq!(
+            proto.take_unhandled(),
+            vec![Subnegotiation { option: OPT_LINEMODE, data: vec![1, b'U', IAC] }]
+        );
+        assert!(proto.take_unhandled().is_empty());
+    }
+
+    #[test]
+    fn test_charset_request_accepts_utf8() {
+        let mut proto = TelnetProtocol::new();
+        let (responses, _, _) = proto.process_data(&[IAC, WILL, OPT_CHARSET]);
+        assert_eq!(responses, vec![IAC, DO, OPT_CHARSET]);
+
+        let mut data = vec![IAC, SB, OPT_CHARSET, CHARSET_REQUEST];
+        data.extend_from_slice(b";ISO-8859-1;utf-8");
+        data.extend_from_slice(&[IAC, SE]);
+        let (responses, _, _) = proto.process_data(&data);
+
+        let mut expected = vec![IAC, SB, OPT_CHARSET, CHARSET_ACCEPTED];
+        expected.extend_from_slice(b"utf-8");
+        expected.extend_from_slice(&[IAC, SE]);
+        assert_eq!(responses, expected);
+        assert_eq!(proto.charset.as_deref(), Some("utf-8"));
+    }
+
+    #[test]
+    fn test_charset_request_rejects_unknown() {
+        let mut proto = TelnetProtocol::new().with_charset("UTF-8");
+        let mut data = vec![IAC, SB, OPT_CHARSET, CHARSET_REQUEST];
+        data.extend_from_slice(b" KOI8-R");
+        data.extend_from_slice(&[IAC, SE]);
+        let (responses, _, _) = proto.process_data(&data);
+
+        assert_eq!(responses, vec![IAC, SB, OPT_CHARSET, CHARSET_REJECTED, IAC, SE]);
+        assert!(proto.charset.is_none());
+    }
+
+    #[test]
+    fn test_local_echo_auto_after_wont_echo() {
+        let mut proto = TelnetProtocol::new();
+        assert!(!proto.local_echo_active(None));
+        assert!(proto.local_echo_active(Some(true)));
+
+        proto.process_data(&[IAC, WONT, OPT_ECHO]);
+        assert!(proto.local_echo_active(None));
+        assert!(!proto.local_echo_active(Some(false)));
+
+        proto.process_data(&[IAC, WILL, OPT_ECHO]);
+        assert!(!proto.local_echo_active(None));
+    }
+
+    #[test]
+    fn test_build_local_echo() {
+        assert_eq!(build_local_echo(b"ls\r"), b"ls\r\n".to_vec());
+        assert_eq!(build_local_echo(&[b'a', 0x7f]), b"a\x08 \x08".to_vec());
+        assert!(build_local_echo(b"\x1b[A").is_empty());
+        assert!(build_local_echo(&[0x03]).is_empty());
+    }
 }
@@ -9,19 +9,48 @@ use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant, Interval};
 use uuid::Uuid;
 
 use crate::core::error::SessionError;
-use crate::core::session::TerminalSession;
+use crate::core::initial_command::{PendingCommand, CHECK_INTERVAL};
+use crate::core::output::Utf8StreamDecoder;
+use crate::core::paste::BracketedPasteTracker;
+use crate::core::recording::Recording;
+use crate::core::scrollback::Scrollback;
+use crate::core::session::{SessionInfo, TerminalSession, GRACEFUL_CLOSE_TIMEOUT, PING_TIMEOUT};
 use crate::core::terminal_events::TerminalExitEvent;
 use crate::terminal::session::SessionType;
 
 use super::client;
 use super::config::TelnetConfig;
 use super::error::TelnetError;
-use super::login::AutoLogin;
-use super::protocol::{build_naws, TelnetProtocol};
+use super::login::{AutoLogin, LoginState};
+use super::protocol::{build_local_echo, build_naws, TelnetProtocol, IAC, NOP, TERMINAL_TYPES};
+
+/// What the I/O loop works with besides the TCP stream
+struct TelnetIo {
+    write_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    resize_rx: mpsc::UnboundedReceiver<(u16, u16)>,
+    ping_rx: mpsc::UnboundedReceiver<oneshot::Sender<bool>>,
+    close_rx: oneshot::Receiver<()>,
+    session_id: String,
+    app_handle: AppHandle,
+    streaming_started: Arc<AtomicBool>,
+    initial_cols: u16,
+    initial_rows: u16,
+    auto_login: AutoLogin,
+    protocol: TelnetProtocol,
+    charset: Arc<std::sync::Mutex<Option<String>>>,
+    keepalive: Option<Duration>,
+    local_echo: Option<bool>,
+    paste_tracker: BracketedPasteTracker,
+    scrollback: Scrollback,
+    recording: Recording,
+    initial_command: PendingCommand,
+}
 
 /// Telnet terminal session implementing TerminalSession trait
 pub struct TelnetTerminalSession {
@@ -31,14 +60,32 @@ pub struct TelnetTerminalSession {
     write_tx: mpsc::UnboundedSender<Vec<u8>>,
     /// Channel to send resize commands
     resize_tx: mpsc::UnboundedSender<(u16, u16)>,
+    /// Asks the I/O loop to send an IAC NOP, replying whether it went out
+    ping_tx: mpsc::UnboundedSender<oneshot::Sender<bool>>,
+    /// Signals the I/O loop to shut down the TCP connection
+    close_tx: std::sync::Mutex<Option<oneshot::Sender<()>>>,
+    /// I/O loop task (awaited on close)
+    io_task: std::sync::Mutex<Option<JoinHandle<()>>>,
     /// Flag indicating if streaming has started
     streaming_started: Arc<AtomicBool>,
+    /// Charset negotiated with the server (updated by the I/O loop)
+    charset: Arc<std::sync::Mutex<Option<String>>>,
+    /// Whether the remote enabled bracketed paste (updated by the I/O loop)
+    bracketed_paste: Arc<AtomicBool>,
+    /// Recent output (appended by the I/O loop)
+    scrollback: Scrollback,
+    /// Output recording (teed by the I/O loop)
+    recording: Recording,
 }
 
 impl TelnetTerminalSession {
     /// Connect to a telnet server and create a new session
     pub async fn connect(config: TelnetConfig, app_handle: AppHandle) -> Result<Self, TelnetError> {
-        let id = Uuid::new_v4().to_string();
+        Self::connect_with_id(Uuid::new_v4().to_string(), config, app_handle).await
+    }
+
+    /// Connect under an existing session id, so events reach the listeners already on it
+    pub async fn connect_with_id(id: String, config: TelnetConfig, app_handle: AppHandle) -> Result<Self, TelnetError> {
 
         log::info!(
             "TELNET[{}] Connecting to {}:{}",
@@ -48,35 +95,72 @@ impl TelnetTerminalSession {
         );
 
         // Establish TCP connection
-        let stream = client::connect(&config).await?;
+        let mut stream = client::connect(&config).await?;
 
         // Create channels for write and resize commands
         let (write_tx, write_rx) = mpsc::unbounded_channel::<Vec<u8>>();
         let (resize_tx, resize_rx) = mpsc::unbounded_channel::<(u16, u16)>();
+        let (ping_tx, ping_rx) = mpsc::unbounded_channel::<oneshot::Sender<bool>>();
+        let (close_tx, close_rx) = oneshot::channel::<()>();
 
         let streaming_started = Arc::new(AtomicBool::new(false));
+        let charset = Arc::new(std::sync::Mutex::new(None));
 
         // Clone values for the I/O loop
         let session_id = id.clone();
         let streaming_flag = streaming_started.clone();
+        let charset_state = charset.clone();
+        let mut protocol = match config.charset.clone() {
+            Some(name) => TelnetProtocol::new().with_charset(name),
+            None => TelnetProtocol::new(),
+        }
+        .with_environment(config.environment.clone());
+        if let Some(term) = config.term.clone() {
+            protocol = protocol.with_terminal_type(term);
+        }
+
+        // Ask for binary mode up front so 8-bit output (UTF-8) isn't mangled
+        stream.write_all(&protocol.initial_negotiation()).await?;
         let initial_cols = config.cols;
         let initial_rows = config.rows;
+        let keepalive = config.keepalive_secs.filter(|s| *s > 0).map(Duration::from_secs);
+        let local_echo = config.local_echo;
+        let paste_tracker = BracketedPasteTracker::new();
+        let bracketed_paste = paste_tracker.flag();
+        let scrollback = Scrollback::new(config.scrollback_bytes);
+        let output_scrollback = scrollback.clone();
+        let recording = Recording::new(config.cols, config.rows, config.term.as_deref().unwrap_or(TERMINAL_TYPES[0]));
+        let output_recording = recording.clone();
+        let initial_command = PendingCommand::new(config.initial_command.as_ref());
 
         // Create auto-login handler
-        let auto_login = AutoLogin::new(config.username.clone(), config.password.clone());
+        let auto_login = AutoLogin::new(config.username.clone(), config.password.clone())
+            .with_options(config.auto_login.clone());
 
         // Spawn the I/O loop
-        tokio::spawn(async move {
+        let io_task = tokio::spawn(async move {
             Self::io_loop(
                 stream,
-                write_rx,
-                resize_rx,
-                session_id,
-                app_handle,
-                streaming_flag,
-                initial_cols,
-                initial_rows,
-                auto_login,
+                TelnetIo {
+                    write_rx,
+                    resize_rx,
+                    ping_rx,
+                    close_rx,
+                    session_id,
+                    app_handle,
+                    streaming_started: streaming_flag,
+                    initial_cols,
+                    initial_rows,
+                    auto_login,
+                    protocol,
+                    charset: charset_state,
+                    keepalive,
+                    local_echo,
+                    paste_tracker,
+                    scrollback: output_scrollback,
+                    recording: output_recording,
+                    initial_command,
+                },
             )
             .await;
         });
@@ -85,28 +169,52 @@ impl TelnetTerminalSession {
             id,
             write_tx,
             resize_tx,
+            ping_tx,
+            close_tx: std::sync::Mutex::new(Some(close_tx)),
+            io_task: std::sync::Mutex::new(Some(io_task)),
             streaming_started,
+            charset,
+            bracketed_paste,
+            scrollback,
+            recording,
         })
     }
 
     /// Main I/O loop handling read/write operations
-    async fn io_loop(
-        stream: TcpStream,
-        mut write_rx: mpsc::UnboundedReceiver<Vec<u8>>,
-        mut resize_rx: mpsc::UnboundedReceiver<(u16, u16)>,
-        session_id: String,
-        app_handle: AppHandle,
-        streaming_started: Arc<AtomicBool>,
-        initial_cols: u16,
-        initial_rows: u16,
-        auto_login: AutoLogin,
-    ) {
+    async fn io_loop(stream: TcpStream, io: TelnetIo) {
+        let TelnetIo {
+            mut write_rx,
+            mut resize_rx,
+            mut ping_rx,
+            mut close_rx,
+            session_id,
+            app_handle,
+            streaming_started,
+            initial_cols,
+            initial_rows,
+            auto_login,
+            mut protocol,
+            charset,
+            keepalive,
+            local_echo,
+            mut paste_tracker,
+            scrollback,
+            recording,
+            mut initial_command,
+        } = io;
+
         let (mut reader, mut writer) = stream.into_split();
         let mut buffer = [0u8; 8192];
         let mut pending_buffer: Vec<String> = Vec::new();
-        let mut protocol = TelnetProtocol::new();
+        // Holds back characters split across reads
+        let mut decoder = Utf8StreamDecoder::new();
         let auto_login = Arc::new(Mutex::new(auto_login));
 
+        // Keepalive timer, reset on every user write
+        let mut keepalive_timer = keepalive.map(|period| {
+            tokio::time::interval_at(Instant::now() + period, period)
+        });
+
         // Track current terminal size
         let mut current_cols = initial_cols;
         let mut current_rows = initial_rows;
@@ -114,10 +222,21 @@ impl TelnetTerminalSession {
         log::debug!("TELNET[{}] I/O loop started", session_id);
 
         loop {
+            let login_deadline = auto_login.lock().await.deadline();
+
             tokio::select! {
+                // Close requested (or session dropped) - shut down the connection cleanly
+                _ = &mut close_rx => {
+                    log::debug!("TELNET[{}] Shutting down connection", session_id);
+                    if let Err(e) = writer.shutdown().await {
+                        log::debug!("TELNET[{}] Shutdown error: {:?}", session_id, e);
+                    }
+                    break;
+                }
+
                 // Handle writes from frontend (user input)
                 Some(data) = write_rx.recv() => {
-                    if let Err(e) = writer.write_all(&data).await {
+                    if let Err(e) = writer.write_all(&protocol.encode_input(&data)).await {
                         log::warn!("TELNET[{}] Write error: {:?}", session_id, e);
                         break;
                     }
@@ -125,6 +244,88 @@ impl TelnetTerminalSession {
                         log::warn!("TELNET[{}] Flush error: {:?}", session_id, e);
                         break;
                     }
+                    if let Some(timer) = keepalive_timer.as_mut() {
+                        timer.reset();
+                    }
+
+                    // Echo typed input back when the server won't
+                    if protocol.local_echo_active(local_echo) {
+                        let echo = build_local_echo(&data);
+                        if !echo.is_empty() {
+                            let output = String::from_utf8_lossy(&echo).to_string();
+                            scrollback.push(output.as_bytes());
+                            recording.output(output.as_bytes());
+                            if streaming_started.load(Ordering::SeqCst) {
+                                let _ = app_handle.emit(
+                                    &format!("terminal-output:{}", session_id),
+                                    output
+                                );
+                            } else {
+                                pending_buffer.push(output);
+                            }
+                        }
+                    }
+                }
+
+                // Send keepalive when idle (NOP rather than AYT, which makes servers print "[Yes]")
+                _ = Self::keepalive_tick(&mut keepalive_timer) => {
+                    if let Err(e) = writer.write_all(&[IAC, NOP]).await {
+                        log::warn!("TELNET[{}] Keepalive error: {:?}", session_id, e);
+                        let exit_event = TerminalExitEvent::connection_error(e.to_string());
+                        let _ = app_handle.emit(&format!("terminal-exit:{}", session_id), exit_event);
+                        break;
+                    }
+                    log::debug!("TELNET[{}] Sent keepalive", session_id);
+                }
+
+                // Liveness probe: a NOP the server ignores, failing once the socket is dead
+                Some(reply) = ping_rx.recv() => {
+                    let sent = match writer.write_all(&[IAC, NOP]).await {
+                        Ok(()) => writer.flush().await,
+                        Err(e) => Err(e),
+                    };
+                    let _ = reply.send(sent.is_ok());
+                    if let Err(e) = sent {
+                        log::warn!("TELNET[{}] Ping error: {:?}", session_id, e);
+                        let exit_event = TerminalExitEvent::connection_error(e.to_string());
+                        let _ = app_handle.emit(&format!("terminal-exit:{}", session_id), exit_event);
+                        break;
+                    }
+                }
+
+                // Give up on auto-login when the expected prompt never shows up
+                _ = Self::login_timeout(login_deadline) => {
+                    if let Some(prompt) = auto_login.lock().await.expire(std::time::Instant::now()) {
+                        log::info!("TELNET[{}] No {:?} prompt seen, auto-login disabled", session_id, prompt);
+                        let _ = app_handle.emit(&format!("telnet-login-timeout:{}", session_id), prompt);
+                    }
+                }
+
+                // Type the initial command once logged in and the prompt settled
+                _ = tokio::time::sleep(CHECK_INTERVAL), if initial_command.is_pending() => {
+                    let now = std::time::Instant::now();
+                    let require_prompt = match auto_login.lock().await.state {
+                        LoginState::Authenticated => Some(false),
+                        // No credentials, or their prompt never came: only a shell prompt shows the user logged in
+                        LoginState::Disabled => Some(true),
+                        _ => None,
+                    };
+                    match require_prompt {
+                        Some(require_prompt) if streaming_started.load(Ordering::SeqCst) => {
+                            if let Some(command) = initial_command.poll(now, require_prompt) {
+                                log::debug!("TELNET[{}] Sending initial command", session_id);
+                                let sent = match writer.write_all(&protocol.encode_input(&command)).await {
+                                    Ok(()) => writer.flush().await,
+                                    Err(e) => Err(e),
+                                };
+                                if let Err(e) = sent {
+                                    log::warn!("TELNET[{}] Write error: {:?}", session_id, e);
+                                    break;
+                                }
+                            }
+                        }
+                        _ => initial_command.hold(now),
+                    }
                 }
 
                 // Handle resize requests
@@ -157,6 +358,21 @@ impl TelnetTerminalSession {
                             // Process telnet protocol data
                             let (responses, clean_data, naws_requested) = protocol.process_data(&buffer[..n]);
 
+                            for sub in protocol.take_unhandled() {
+                                log::debug!(
+                                    "TELNET[{}] Unhandled subnegotiation: option {} ({} bytes): {:?}",
+                                    session_id, sub.option, sub.data.len(), sub.data
+                                );
+                            }
+
+                            // Publish negotiated charset for session info queries
+                            if let Ok(mut current) = charset.lock() {
+                                if *current != protocol.charset {
+                                    log::info!("TELNET[{}] Negotiated charset: {:?}", session_id, protocol.charset);
+                                    current.clone_from(&protocol.charset);
+                                }
+                            }
+
                             // Send protocol responses
                             if !responses.is_empty() {
                                 if let Err(e) = writer.write_all(&responses).await {
@@ -176,20 +392,27 @@ impl TelnetTerminalSession {
 
                             // Convert clean data to string
                             if !clean_data.is_empty() {
-                                let output = String::from_utf8_lossy(&clean_data).to_string();
+                                paste_tracker.observe(&clean_data);
+                                let output = decoder.decode(&clean_data);
 
                                 // Check for auto-login prompts
                                 {
                                     let mut login = auto_login.lock().await;
+                                    // Output before login finished (prompts, banners) says nothing about the shell
+                                    if login.is_complete() {
+                                        initial_command.observe(&output, std::time::Instant::now());
+                                    }
                                     if let Some(response) = login.process(&output) {
                                         log::debug!("TELNET[{}] Auto-login: sending credentials", session_id);
-                                        if let Err(e) = writer.write_all(&response).await {
+                                        if let Err(e) = writer.write_all(&protocol.encode_input(&response)).await {
                                             log::warn!("TELNET[{}] Auto-login send error: {:?}", session_id, e);
                                         }
                                     }
                                 }
 
                                 // Emit to frontend
+                                scrollback.push(output.as_bytes());
+                                recording.output(output.as_bytes());
                                 if streaming_started.load(Ordering::SeqCst) {
                                     // Flush any pending buffer first
                                     if !pending_buffer.is_empty() {
@@ -224,6 +447,24 @@ impl TelnetTerminalSession {
 
         log::debug!("TELNET[{}] I/O loop ended", session_id);
     }
+
+    /// Resolve at the auto-login deadline, or never when no login is pending
+    async fn login_timeout(deadline: Option<std::time::Instant>) {
+        match deadline {
+            Some(deadline) => tokio::time::sleep_until(Instant::from_std(deadline)).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Wait for the next keepalive tick, or forever when keepalive is disabled
+    async fn keepalive_tick(timer: &mut Option<Interval>) {
+        match timer {
+            Some(timer) => {
+                timer.tick().await;
+            }
+            None => std::future::pending().await,
+        }
+    }
 }
 
 #[async_trait]
@@ -256,8 +497,27 @@ impl TerminalSession for TelnetTerminalSession {
         Ok(())
     }
 
-    async fn close(&mut self) -> Result<(), SessionError> {
-        // Dropping the senders will cause the I/O loop to exit
+    async fn ping(&self) -> bool {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        if self.ping_tx.send(reply_tx).is_err() {
+            return false; // I/O loop has ended
+        }
+        matches!(tokio::time::timeout(PING_TIMEOUT, reply_rx).await, Ok(Ok(true)))
+    }
+
+    async fn close(&self) -> Result<(), SessionError> {
+        // Let the I/O loop shut down the TCP connection
+        let close_tx = self.close_tx.lock().unwrap().take();
+        if let Some(close_tx) = close_tx {
+            let _ = close_tx.send(());
+        }
+        let io_task = self.io_task.lock().unwrap().take();
+        if let Some(mut io_task) = io_task {
+            if tokio::time::timeout(GRACEFUL_CLOSE_TIMEOUT, &mut io_task).await.is_err() {
+                log::warn!("TELNET[{}] Connection shutdown timed out", self.id);
+                io_task.abort();
+            }
+        }
         log::info!("TELNET[{}] Session closed", self.id);
         Ok(())
     }
@@ -269,4 +529,23 @@ impl TerminalSession for TelnetTerminalSession {
         }
         log::debug!("TELNET[{}] Streaming started", self.id);
     }
+
+    fn bracketed_paste_enabled(&self) -> bool {
+        self.bracketed_paste.load(Ordering::SeqCst)
+    }
+
+    fn scrollback(&self) -> String {
+        self.scrollback.text()
+    }
+
+    fn recording(&self) -> Option<Recording> {
+        Some(self.recording.clone())
+    }
+
+    fn session_info(&self) -> SessionInfo {
+        SessionInfo {
+            charset: self.charset.lock().ok().and_then(|c| c.clone()),
+            ..SessionInfo::new(&self.id, SessionType::Telnet)
+        }
+    }
 }
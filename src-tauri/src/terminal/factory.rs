@@ -2,22 +2,44 @@ use crate::ssh::config::SshConfig;
 use crate::ssh::terminal::SshTerminalSession;
 use crate::telnet::{TelnetConfig, TelnetTerminalSession};
 use crate::core::error::SessionError;
+use crate::core::initial_command::InitialCommand;
 use crate::core::session::TerminalSession;
 use crate::pty::session::LocalPtySession;
 use crate::terminal::session::SessionType;
+use std::collections::HashMap;
 use tauri::AppHandle;
 
 /// Session configuration
+#[derive(Clone)]
 pub enum SessionConfig {
     Local {
         shell: Option<String>,
+        args: Vec<String>,
         cols: u16,
         rows: u16,
+        env: HashMap<String, String>,
+        cwd: Option<String>,
+        binary_output: bool,
+        scrollback_bytes: Option<usize>,
+        initial_command: Option<InitialCommand>,
     },
     Ssh(SshConfig),
     Telnet(TelnetConfig),
 }
 
+impl SessionConfig {
+    /// Record the current terminal size so a reconnect opens the PTY at that size
+    pub fn set_size(&mut self, new_cols: u16, new_rows: u16) {
+        let (cols, rows) = match self {
+            SessionConfig::Local { cols, rows, .. } => (cols, rows),
+            SessionConfig::Ssh(config) => (&mut config.terminal.cols, &mut config.terminal.rows),
+            SessionConfig::Telnet(config) => (&mut config.cols, &mut config.rows),
+        };
+        *cols = new_cols;
+        *rows = new_rows;
+    }
+}
+
 /// Factory for creating terminal sessions (Factory Pattern)
 pub struct SessionFactory;
 
@@ -28,8 +50,8 @@ impl SessionFactory {
         app_handle: AppHandle,
     ) -> Result<Box<dyn TerminalSession>, SessionError> {
         match config {
-            SessionConfig::Local { shell, cols, rows } => {
-                let session = LocalPtySession::new(shell, cols, rows, app_handle)?;
+            SessionConfig::Local { shell, args, cols, rows, env, cwd, binary_output, scrollback_bytes, initial_command } => {
+                let session = LocalPtySession::new(shell, args, cols, rows, env, cwd, binary_output, scrollback_bytes, initial_command, app_handle)?;
                 Ok(Box::new(session))
             }
             SessionConfig::Ssh(ssh_config) => {
@@ -43,14 +65,41 @@ impl SessionFactory {
         }
     }
     
+    /// Re-establish a remote session under its original id
+    pub async fn reconnect(
+        session_id: String,
+        config: SessionConfig,
+        app_handle: AppHandle,
+    ) -> Result<Box<dyn TerminalSession>, SessionError> {
+        match config {
+            SessionConfig::Ssh(ssh_config) => {
+                let session = SshTerminalSession::connect_with_id(session_id, ssh_config, app_handle).await?;
+                Ok(Box::new(session))
+            }
+            SessionConfig::Telnet(telnet_config) => {
+                let session = TelnetTerminalSession::connect_with_id(session_id, telnet_config, app_handle).await?;
+                Ok(Box::new(session))
+            }
+            SessionConfig::Local { .. } => Err(SessionError::UnsupportedOperation(
+                "Local sessions cannot be reconnected".to_string(),
+            )),
+        }
+    }
+
     /// Convenience: create local session
     pub fn local(
         shell: Option<String>,
+        args: Vec<String>,
         cols: u16,
         rows: u16,
+        env: HashMap<String, String>,
+        cwd: Option<String>,
+        binary_output: bool,
+        scrollback_bytes: Option<usize>,
+        initial_command: Option<InitialCommand>,
         app_handle: AppHandle,
     ) -> Result<Box<dyn TerminalSession>, SessionError> {
-        let session = LocalPtySession::new(shell, cols, rows, app_handle)?;
+        let session = LocalPtySession::new(shell, args, cols, rows, env, cwd, binary_output, scrollback_bytes, initial_command, app_handle)?;
         Ok(Box::new(session))
     }
 }
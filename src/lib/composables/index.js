@@ -7,7 +7,7 @@ export { useForm } from './useForm.svelte.js';
 export { useToast } from './useToast.svelte.js';
 export { useModal } from './useModal.svelte.js';
 export { useTauri } from './useTauri.svelte.js';
-export { useXtermTerminal } from './useXtermTerminal.svelte.js';
+export { useXtermTerminal, fitTerminalSize } from './useXtermTerminal.svelte.js';
 export { handleHostConnect } from './useHostConnection.svelte.js';
 export { useHostManagement } from './useHostManagement.js';
 export { useSyncChecker, initSyncChecker } from './useSyncChecker.svelte.js';
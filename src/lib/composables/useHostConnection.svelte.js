@@ -1,6 +1,7 @@
 import { get } from 'svelte/store';
 import { tabsStore } from '$lib/stores';
-import { connectionFactory } from '$lib/services/connection';
+import { connectionFactory, createAttemptId } from '$lib/services/connection';
+import { fitTerminalSize } from './useXtermTerminal.svelte.js';
 
 /**
  * Handle connection to a host
@@ -38,8 +39,12 @@ export async function handleHostConnect(host) {
 			reconnectCancelled: false
 		});
 
-		// Connect using handler
-		const result = await handler.connect(host, updateLogs);
+		// Track attempt so the status card can cancel it
+		const attemptId = createAttemptId();
+		tabsStore.updateTabConnectionState(tabId, { attemptId });
+
+		// Connect using handler, opening the PTY at the size the terminal will have
+		const result = await handler.connect(host, updateLogs, { attemptId, ...fitTerminalSize(host) });
 
 		// Success - update tab to CONNECTED state with animation flag
 		tabsStore.updateTabConnectionState(tabId, {
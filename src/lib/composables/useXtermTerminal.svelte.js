@@ -36,6 +36,53 @@ import { useToast } from './useToast.svelte.js';
 import { getThemeById } from '$lib/constants/terminal-themes';
 import { defaultFontFamily, defaultFontSize, minFontSize } from '$lib/constants/terminal-fonts';
 
+/**
+ * Font a host's terminal uses (its custom appearance, else the defaults)
+ * @param {Object|null} host - Host configuration
+ * @returns {{fontSize: number, fontFamily: string}}
+ */
+function terminalFont(host) {
+	const { fontSize, fontFamily } = host?.terminalAppearance || {};
+	return {
+		fontSize: fontSize || defaultFontSize,
+		fontFamily: fontFamily && fontFamily !== 'default' ? fontFamily : defaultFontFamily
+	};
+}
+
+/**
+ * Fit a throwaway xterm.js instance to the terminal area ([data-terminal-area])
+ * Sizes the remote PTY before its terminal mounts, so programs start at the real size
+ * @param {Object|null} [host] - Host whose font settings the terminal will use
+ * @returns {{cols: number, rows: number}} Fitted size, 80x24 when there is nothing to measure
+ */
+export function fitTerminalSize(host = null) {
+	const fallback = { cols: 80, rows: 24 };
+	const area =
+		typeof document !== 'undefined' ? document.querySelector('[data-terminal-area]') : null;
+	if (!area) return fallback;
+
+	// Same box as RemoteTerminal's container, invisible
+	const probe = document.createElement('div');
+	probe.style.cssText =
+		'position: absolute; inset: 0; padding: 0 8px; box-sizing: border-box; visibility: hidden;';
+	area.appendChild(probe);
+
+	const probeTerminal = new Terminal(terminalFont(host));
+	const probeFit = new FitAddon();
+	probeTerminal.loadAddon(probeFit);
+	try {
+		probeTerminal.open(probe);
+		const size = probeFit.proposeDimensions();
+		return size?.cols > 0 && size?.rows > 0 ? { cols: size.cols, rows: size.rows } : fallback;
+	} catch (error) {
+		console.warn('Failed to measure terminal size:', error);
+		return fallback;
+	} finally {
+		probeTerminal.dispose();
+		probe.remove();
+	}
+}
+
 export function useXtermTerminal(config = {}) {
 	const {
 		mode = 'local',
@@ -137,36 +184,15 @@ export function useXtermTerminal(config = {}) {
 
 			// Get theme colors
 			let themeColors = getThemeColors();
-			let fontSize = defaultFontSize;
-			let fontFamily = defaultFontFamily;
-
-			// Override with host-specific settings if available
-			if (hostId) {
-				const host = getHostById(hostId);
-				if (host?.terminalAppearance) {
-					const {
-						themeId,
-						fontSize: customSize,
-						fontFamily: customFont
-					} = host.terminalAppearance;
-
-					// Apply custom theme
-					if (themeId) {
-						const theme = getThemeById(themeId);
-						if (theme) {
-							themeColors = theme.colors;
-						}
-					}
-
-					// Apply custom font size
-					if (customSize) {
-						fontSize = customSize;
-					}
-
-					// Apply custom font family
-					if (customFont && customFont !== 'default') {
-						fontFamily = customFont;
-					}
+			const host = hostId ? getHostById(hostId) : null;
+			const { fontSize, fontFamily } = terminalFont(host);
+
+			// Apply host-specific theme if available
+			const themeId = host?.terminalAppearance?.themeId;
+			if (themeId) {
+				const theme = getThemeById(themeId);
+				if (theme) {
+					themeColors = theme.colors;
 				}
 			}
 
@@ -292,9 +318,12 @@ export function useXtermTerminal(config = {}) {
 		});
 		eventListeners.push(outputUnlisten);
 
-		const exitUnlisten = await terminalEvents.onTerminalExit(sessionId, exitCode => {
+		const exitUnlisten = await terminalEvents.onTerminalExit(sessionId, exitEvent => {
+			const signal = exitEvent.reason?.split(':signal:')[1];
 			toast.show({
-				message: `Terminal exited with code ${exitCode}`,
+				message: signal
+					? `Terminal killed by signal ${signal} (exit code ${exitEvent.exit_code})`
+					: `Terminal exited with code ${exitEvent.exit_code}`,
 				type: 'info'
 			});
 		});
@@ -366,6 +395,18 @@ export function useXtermTerminal(config = {}) {
 						return;
 					}
 
+					// Remote shell exited (or was killed) - the connection is fine, don't reconnect
+					if (reason?.startsWith('process-exited')) {
+						const signal = reason.split(':signal:')[1];
+						tabsStore.updateTabConnectionState(tab.id, {
+							connectionState: 'FAILED',
+							connectionError: signal
+								? `Session ended by signal ${signal} (exit code ${exitCode})`
+								: `Session ended (exit code ${exitCode})`
+						});
+						return;
+					}
+
 					// Check if tab is still in tabs list and not cancelled
 					const currentTabs = get(tabsStore);
 					const currentTab = currentTabs.tabs.find(t => t.id === tab.id);
@@ -0,0 +1,28 @@
+/**
+ * Connection Cancel Service
+ * Aborts in-flight connection attempts on the backend
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+/**
+ * Create a new attempt ID to pass to a connect call
+ * @returns {string}
+ */
+export function createAttemptId() {
+	return crypto.randomUUID();
+}
+
+/**
+ * Cancel an in-progress connection attempt
+ * @param {string} attemptId - Attempt ID passed to the connect call
+ * @returns {Promise<boolean>} False if the attempt had already finished
+ */
+export async function cancelConnection(attemptId) {
+	try {
+		return await invoke('cancel_connection', { attemptId });
+	} catch (error) {
+		console.error('Failed to cancel connection:', error);
+		return false;
+	}
+}
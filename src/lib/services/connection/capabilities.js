@@ -0,0 +1,34 @@
+/**
+ * Session Capabilities Service
+ * Asks the backend which sessions are open and what they support
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { getErrorMessage } from '$lib/utils/data/errors.js';
+
+/**
+ * Get the operations a terminal or file session supports
+ * @param {string} sessionId - Terminal or file session ID
+ * @returns {Promise<{chmod: boolean, symlink: boolean, serverSideCopy: boolean, exec: boolean, recursiveDelete: boolean}>}
+ */
+export async function getSessionCapabilities(sessionId) {
+	try {
+		return await invoke('get_session_capabilities', { sessionId });
+	} catch (error) {
+		console.error('Failed to get session capabilities:', error);
+		throw new Error(`Failed to get session capabilities: ${getErrorMessage(error)}`);
+	}
+}
+
+/**
+ * List every open terminal and file session (for a connections panel)
+ * @returns {Promise<Array<{id: string, type: string, host: string|null, openedAt: number}>>} openedAt is Unix time in seconds
+ */
+export async function listActiveSessions() {
+	try {
+		return await invoke('list_active_sessions');
+	} catch (error) {
+		console.error('Failed to list sessions:', error);
+		throw new Error(`Failed to list sessions: ${getErrorMessage(error)}`);
+	}
+}
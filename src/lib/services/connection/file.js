@@ -6,12 +6,14 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { parseChain } from '$lib/utils';
+import { getErrorMessage } from '$lib/utils/data/errors.js';
 import { getKey } from '../data/keychain';
 import {
 	writeTempKeyFile,
 	prepareHopConfig,
 	resolveChain,
-	cleanupTempKeys
+	cleanupTempKeys,
+	listenHostKeys
 } from './ssh';
 
 /**
@@ -26,10 +28,15 @@ import {
  * @param {string} [host.keyId] - SSH key ID (for SFTP with key auth)
  * @param {string} [host.password] - Password
  * @param {string} [host.proxyJump] - JSON array of jump host IDs (SFTP only)
+ * @param {boolean} [host.passive] - FTP/FTPS passive mode (default true); false uses active mode
+ * @param {string} [host.activeBindAddress] - Local IPv4 address advertised in active mode (not a NAT public address)
+ * @param {number} [host.maxBytesPerSec] - Default bandwidth cap per transfer (bytes/sec)
  * @param {Function} onLog - Callback for each log entry
+ * @param {Object} [options]
+ * @param {string} [options.attemptId] - Attempt ID for cancel_connection
  * @returns {Promise<{sessionId: string, logs: string[]}>}
  */
-export async function connectFileTransfer(host, onLog) {
+export async function connectFileTransfer(host, onLog, { attemptId = null } = {}) {
 	const logs = [];
 	let tempKeyPaths = [];
 
@@ -79,6 +86,7 @@ export async function connectFileTransfer(host, onLog) {
 			} catch {
 				// Event listener setup failed, continue without real-time updates
 			}
+			const unlistenHostKeys = await listenHostKeys(addLog);
 
 			let sessionId;
 			try {
@@ -94,18 +102,24 @@ export async function connectFileTransfer(host, onLog) {
 					username: targetConfig.username,
 					password: targetConfig.password || null,
 					keyPath: targetConfig.key_path || null,
+					keyPassphrase: targetConfig.key_passphrase || null,
+					securityPolicy: host.securityPolicy || null,
+					maxBytesPerSec: host.maxBytesPerSec || null,
+					compression: host.compression || false,
+					algorithms: host.algorithms || null,
 					jumps: chain.slice(0, -1) // All except the last (target)
 				};
 
-				sessionId = await invoke('create_file_session', { config });
+				sessionId = await invoke('create_file_session', { config, attemptId });
 
 				addLog('✅ Connected successfully through chain');
 			} catch (invokeError) {
-				addLog('❌ Chain connection failed:', invokeError.toString());
+				addLog('❌ Chain connection failed:', getErrorMessage(invokeError));
 				throw invokeError;
 			} finally {
-				// Clean up event listener
+				// Clean up event listeners
 				if (unlisten) unlisten();
+				if (unlistenHostKeys) unlistenHostKeys();
 			}
 
 			cleanupTempKeys(tempKeyPaths);
@@ -121,6 +135,14 @@ export async function connectFileTransfer(host, onLog) {
 				username: host.username,
 				password: host.password || null,
 				keyPath: null,
+				keyPassphrase: host.keyPassphrase || null,
+				securityPolicy: host.securityPolicy || null,
+				passive: host.passive ?? true,
+				activeBindAddress: host.activeBindAddress || null,
+				maxBytesPerSec: host.maxBytesPerSec || null,
+				proxyCommand: host.proxyCommand || null,
+				compression: host.compression || false,
+				algorithms: host.algorithms || null,
 				jumps: []
 			};
 
@@ -139,7 +161,13 @@ export async function connectFileTransfer(host, onLog) {
 			addLog(`⚙️ Authenticating as:`, host.username);
 
 			// Create file session
-			const sessionId = await invoke('create_file_session', { config });
+			const unlistenHostKeys = connectionType === 'sftp' ? await listenHostKeys(addLog) : null;
+			let sessionId;
+			try {
+				sessionId = await invoke('create_file_session', { config, attemptId });
+			} finally {
+				if (unlistenHostKeys) unlistenHostKeys();
+			}
 
 			addLog(`✅ ${typeLabel} connected successfully`);
 
@@ -148,7 +176,7 @@ export async function connectFileTransfer(host, onLog) {
 			return { sessionId, logs };
 		}
 	} catch (error) {
-		addLog(`❌ ${typeLabel} connection failed:`, error.toString());
+		addLog(`❌ ${typeLabel} connection failed:`, getErrorMessage(error));
 		cleanupTempKeys(tempKeyPaths);
 		throw error;
 	}
@@ -26,9 +26,11 @@ export class BaseConnectionHandler {
 	 * Connect to the host
 	 * @param {Object} host - Host configuration
 	 * @param {Function} onLog - Log callback
+	 * @param {Object} [options] - Connect options
+	 * @param {string} [options.attemptId] - Attempt ID for cancel_connection
 	 * @returns {Promise<{sessionId: string, logs: string[]}>}
 	 */
-	async connect(host, onLog) {
+	async connect(host, onLog, options) {
 		throw new Error('connect must be implemented');
 	}
 
@@ -36,10 +38,11 @@ export class BaseConnectionHandler {
 	 * Retry connection
 	 * @param {Object} host - Host configuration
 	 * @param {Function} onLog - Log callback
+	 * @param {Object} [options] - Connect options
 	 * @returns {Promise<{sessionId: string, logs: string[]}>}
 	 */
-	async retry(host, onLog) {
-		return this.connect(host, onLog);
+	async retry(host, onLog, options) {
+		return this.connect(host, onLog, options);
 	}
 
 	/**
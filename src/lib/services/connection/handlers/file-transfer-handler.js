@@ -19,8 +19,8 @@ export class FileTransferConnectionHandler extends BaseConnectionHandler {
 		});
 	}
 
-	async connect(host, onLog) {
-		return await connectFileTransfer(host, onLog);
+	async connect(host, onLog, options) {
+		return await connectFileTransfer(host, onLog, options);
 	}
 
 	async close(sessionId) {
@@ -18,8 +18,8 @@ export class SSHConnectionHandler extends BaseConnectionHandler {
 		});
 	}
 
-	async connect(host, onLog) {
-		return await connectSSH(host, onLog);
+	async connect(host, onLog, options) {
+		return await connectSSH(host, onLog, options);
 	}
 
 	async close(sessionId) {
@@ -19,8 +19,8 @@ export class TelnetConnectionHandler extends BaseConnectionHandler {
 		});
 	}
 
-	async connect(host, onLog) {
-		return await connectTelnet(host, onLog);
+	async connect(host, onLog, options) {
+		return await connectTelnet(host, onLog, options);
 	}
 
 	async close(sessionId) {
@@ -101,7 +101,10 @@ class ConnectionHeartbeatManager {
 				setTimeout(() => reject(new Error('Ping timeout')), timeout)
 			);
 
-			await Promise.race([pingPromise, timeoutPromise]);
+			const alive = await Promise.race([pingPromise, timeoutPromise]);
+			if (!alive) {
+				throw new Error('Connection is dead');
+			}
 
 			// SUCCESS - reset failure counter
 			state.lastPongTime = Date.now();
@@ -11,6 +11,16 @@ export {
 	retrySSHConnection,
 	writeTempKeyFile,
 	prepareHopConfig,
+	keyPassphraseError,
+	onAuthPrompt,
+	respondAuthPrompt,
+	onHostKeyPrompt,
+	respondHostKeyPrompt,
+	startDynamicForward,
+	stopDynamicForward,
+	addLocalForward,
+	removeLocalForward,
+	resolveSshHost,
 	resolveChain,
 	cleanupTempKeys
 } from './ssh.js';
@@ -21,6 +31,12 @@ export { connectTelnet, retryTelnetConnection } from './telnet.js';
 // File Transfer Connection
 export { connectFileTransfer, isFileTransferType, isTerminalType } from './file.js';
 
+// Connection cancel
+export { createAttemptId, cancelConnection } from './cancel.js';
+
+// Session capabilities
+export { getSessionCapabilities, listActiveSessions } from './capabilities.js';
+
 // Heartbeat
 export { connectionHeartbeat } from './heartbeat.js';
 
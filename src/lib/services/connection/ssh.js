@@ -7,9 +7,11 @@ import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { appDataDir, join } from '@tauri-apps/api/path';
 import { tauriFs } from '../infra/tauri/fs';
+import { tauriDialog } from '../infra/tauri/dialog';
 import { getKey } from '../data/keychain';
 import { getHostById } from '../data/hosts';
 import { parseChain } from '$lib/utils';
+import { getErrorMessage } from '$lib/utils/data/errors.js';
 
 /**
  * Write temporary SSH key file
@@ -42,8 +44,9 @@ export async function writeTempKeyFile(keyContent) {
  * @returns {Promise<Object>} Hop config for backend
  */
 export async function prepareHopConfig(host) {
+	const fallbackAuthMethods = host.fallbackAuthMethods || [];
 	let keyPath = null;
-	if (host.authMethod === 'key' && host.keyId) {
+	if ((host.authMethod === 'key' || fallbackAuthMethods.includes('key')) && host.keyId) {
 		const key = getKey(host.keyId);
 		if (!key) {
 			throw new Error(`SSH key not found for host "${host.label || host.hostname}"`);
@@ -56,8 +59,18 @@ export async function prepareHopConfig(host) {
 		port: host.port,
 		username: host.username,
 		auth_method: host.authMethod,
+		// Tried in order on the same connection if authMethod is rejected
+		fallback_auth_methods: fallbackAuthMethods,
 		key_path: keyPath,
-		password: host.password || null
+		key_passphrase: host.keyPassphrase || null,
+		password: host.password || null,
+		algorithms: host.algorithms || null,
+		security_policy: host.securityPolicy || null,
+		connect_timeout_secs: host.connectTimeoutSecs || null,
+		proxy_command: host.proxyCommand || null,
+		compression: host.compression || false,
+		// Only honored for the target; jump hosts never get the agent
+		forward_agent: host.agentForward || false
 	};
 }
 
@@ -113,6 +126,179 @@ export function cleanupTempKeys(paths) {
 	}, 2000);
 }
 
+/**
+ * Whether a connection failed because of an encrypted key's passphrase
+ * @param {{code?: string, message?: string}} error - `{code, message}` rejection from a connect command
+ * @returns {'required'|'incorrect'|null} 'required' when none was given, 'incorrect' when it was wrong
+ */
+export function keyPassphraseError(error) {
+	if (error?.code === 'PASSPHRASE_REQUIRED') return 'required';
+	if (error?.code === 'PASSPHRASE_INCORRECT') return 'incorrect';
+	return null;
+}
+
+/**
+ * Listen for keyboard-interactive prompts (2FA codes, OTPs) raised while authenticating
+ * Answer each with respondAuthPrompt; the connection waits up to 5 minutes
+ * @param {(prompt: {promptId: string, hostname: string, username: string, name: string, instructions: string, prompts: Array<{prompt: string, echo: boolean}>}) => void} callback
+ * @returns {Promise<Function>} Unlisten function
+ */
+export async function onAuthPrompt(callback) {
+	return await listen('ssh-auth-prompt', event => callback(event.payload));
+}
+
+/**
+ * Answer a keyboard-interactive prompt
+ * @param {string} promptId - ID from the prompt event
+ * @param {string[]|null} responses - One answer per prompt field, or null to cancel
+ * @returns {Promise<boolean>} False if the prompt already expired
+ */
+export async function respondAuthPrompt(promptId, responses) {
+	return await invoke('respond_auth_prompt', { promptId, responses });
+}
+
+/**
+ * Listen for new or changed host keys waiting on the user's trust decision
+ * Answer each with respondHostKeyPrompt; the connection waits up to 5 minutes
+ * @param {(prompt: {promptId: string, hostname: string, port: number, algorithm: string, fingerprint: string, status: 'unknown'|'changed'}) => void} callback
+ * @returns {Promise<Function>} Unlisten function
+ */
+export async function onHostKeyPrompt(callback) {
+	return await listen('ssh-host-key-prompt', event => callback(event.payload));
+}
+
+/**
+ * Answer a host key prompt
+ * @param {string} promptId - ID from the prompt event
+ * @param {boolean} trust - True to trust the key (new keys are added to known_hosts), false to abort
+ * @returns {Promise<boolean>} False if the prompt already expired
+ */
+export async function respondHostKeyPrompt(promptId, trust) {
+	return await invoke('respond_host_key_prompt', { promptId, trust });
+}
+
+/**
+ * Look up a host alias in ~/.ssh/config to pre-fill the connection form
+ * An alias without a config entry comes back as a plain host on port 22
+ * @param {string} alias - Host alias as used with `ssh <alias>`
+ * @returns {Promise<{target: Object, jumps: Object[], terminal: {keepalive_secs: number|null}, agent_forward: boolean}>}
+ *   target/jumps: {hostname, port, username, auth: 'Agent' | {Key: {path}}, proxy_command, ...}; jumps come from ProxyJump
+ */
+export async function resolveSshHost(alias) {
+	return await invoke('resolve_ssh_host', { alias });
+}
+
+/**
+ * Start a SOCKS5 proxy on 127.0.0.1 that tunnels through an SSH session (like ssh -D)
+ * Stopped automatically when the session closes
+ * @param {string} sessionId - SSH terminal session ID
+ * @param {number} [bindPort=1080] - Local port, 0 = any free port
+ * @returns {Promise<number>} Bound port
+ */
+export async function startDynamicForward(sessionId, bindPort = 1080) {
+	return await invoke('start_dynamic_forward', { sessionId, bindPort });
+}
+
+/**
+ * Stop a SOCKS5 proxy started with startDynamicForward
+ * @param {string} sessionId - SSH terminal session ID
+ * @param {number} bindPort - Port returned by startDynamicForward
+ * @returns {Promise<void>}
+ */
+export async function stopDynamicForward(sessionId, bindPort) {
+	await invoke('stop_dynamic_forward', { sessionId, bindPort });
+}
+
+/**
+ * Forward 127.0.0.1:localPort to remoteHost:remotePort through an SSH session (like ssh -L)
+ * The remote host is resolved by the SSH server; stopped automatically when the session closes
+ * @param {string} sessionId - SSH terminal session ID
+ * @param {number} localPort - Local port, 0 = any free port
+ * @param {string} remoteHost - Destination host as seen from the server
+ * @param {number} remotePort - Destination port
+ * @returns {Promise<number>} Bound local port
+ */
+export async function addLocalForward(sessionId, localPort, remoteHost, remotePort) {
+	return await invoke('add_local_forward', { sessionId, localPort, remoteHost, remotePort });
+}
+
+/**
+ * Tear down a forward created with addLocalForward
+ * @param {string} sessionId - SSH terminal session ID
+ * @param {number} localPort - Port returned by addLocalForward
+ * @returns {Promise<void>}
+ */
+export async function removeLocalForward(sessionId, localPort) {
+	await invoke('remove_local_forward', { sessionId, localPort });
+}
+
+/**
+ * Ask the user to confirm a new or changed host key
+ * @param {{hostname: string, port: number, algorithm: string, fingerprint: string, status: string}} prompt
+ * @returns {Promise<boolean>} True if the user trusts the key
+ */
+async function confirmHostKey({ hostname, port, algorithm, fingerprint, status }) {
+	if (status === 'changed') {
+		return await tauriDialog.ask(
+			`The host key for ${hostname}:${port} has CHANGED.\n\n${algorithm} ${fingerprint}\n\n` +
+				'Someone could be intercepting the connection. Connect anyway, this time only?',
+			{ title: 'Host key changed', kind: 'warning' }
+		);
+	}
+	return await tauriDialog.ask(
+		`The authenticity of ${hostname}:${port} can't be established.\n\n` +
+			`${algorithm} ${fingerprint}\n\nTrust this host and remember its key?`,
+		{ title: 'Unknown host key', kind: 'info' }
+	);
+}
+
+/**
+ * Ask about and log new and changed host keys ('ssh-host-key-prompt' and 'ssh-host-key' events) while connecting
+ * @param {Function} addLog - Log callback
+ * @returns {Promise<Function|null>} Unlisten function
+ */
+export async function listenHostKeys(addLog) {
+	try {
+		const unlistenPrompts = await onHostKeyPrompt(async prompt => {
+			const trust = await confirmHostKey(prompt);
+			if (!trust) addLog(`❌ Host key for ${prompt.hostname}:${prompt.port} not trusted`);
+			await respondHostKeyPrompt(prompt.promptId, trust);
+		});
+		const unlistenKeys = await listen('ssh-host-key', event => {
+			const { hostname, port, algorithm, fingerprint, status, verdict } = event.payload;
+			if (status === 'changed') {
+				addLog(`⚠️ Host key for ${hostname}:${port} has CHANGED:`, `${algorithm} ${fingerprint}`);
+			} else if (verdict === 'remember') {
+				addLog(`🔑 Trusting new host key for ${hostname}:${port}:`, `${algorithm} ${fingerprint}`);
+			} else {
+				addLog(`❌ Unknown host key for ${hostname}:${port}:`, `${algorithm} ${fingerprint}`);
+			}
+		});
+		return () => {
+			unlistenPrompts();
+			unlistenKeys();
+		};
+	} catch {
+		// Event listener setup failed, continue without host key details
+		return null;
+	}
+}
+
+/**
+ * Listen for authentication failures raised while connecting
+ * @param {(failure: {method: string, remainingMethods: string[], message: string}) => void} callback
+ *   method/remainingMethods use SSH names ('password', 'publickey', 'keyboard-interactive')
+ * @returns {Promise<Function|null>} Unlisten function
+ */
+export async function listenAuthFailures(callback) {
+	try {
+		return await listen('ssh-auth-failed', event => callback(event.payload));
+	} catch {
+		// Event listener setup failed, errors still carry the message
+		return null;
+	}
+}
+
 /**
  * Connect to SSH host with progress logging
  * Supports host chaining (ProxyJump) through intermediate jump hosts
@@ -120,17 +306,36 @@ export function cleanupTempKeys(paths) {
  * @param {string} host.hostname - Hostname or IP
  * @param {number} host.port - SSH port
  * @param {string} host.username - SSH username
- * @param {string} host.authMethod - Auth method ('key' | 'password' | 'agent')
- * @param {string} [host.keyId] - SSH key ID (if authMethod === 'key')
+ * @param {string} host.authMethod - Auth method ('key' | 'password' | 'agent' | 'keyboard-interactive')
+ * @param {string[]} [host.fallbackAuthMethods] - Methods tried in order on the same connection if authMethod is rejected
+ * @param {string} [host.keyId] - SSH key ID (if authMethod or a fallback is 'key')
  * @param {string} [host.password] - Password (if authMethod === 'password')
+ * @param {string} [host.keyPassphrase] - Passphrase for an encrypted key (prompt when keyPassphraseError)
  * @param {string} [host.proxyJump] - JSON array of jump host IDs
+ * @param {boolean} [host.agentForward] - Forward local SSH agent to the target
+ * @param {number} [host.keepaliveSecs] - Send a keepalive after this many idle seconds (unset = disabled)
+ * @param {string} [host.securityPolicy] - Host key policy ('strict' | 'accept-new' | 'accept-all', default 'accept-new')
+ * @param {number} [host.connectTimeoutSecs] - Give up connecting after N seconds (default 30; each jump host uses its own)
+ * @param {Object} [host.algorithms] - {kex, cipher, mac, host_key} name lists; a list starting with '+name' adds to the defaults
+ * @param {boolean} [host.compression] - Prefer zlib compression (for slow, high-latency links)
+ * @param {string} [host.proxyCommand] - Connect through this command instead of TCP, e.g. 'cloudflared access ssh --hostname %h' (%h/%p/%r = host/port/user; ignored for a host reached through a jump host)
+ * @param {string} [host.initialCommand] - Typed into the shell once its prompt is ready (e.g. 'tmux attach || tmux')
+ * @param {number} [host.initialCommandDelayMs] - Wait at least this long after connecting before typing it
  * @param {Function} onLog - Callback for each log entry
+ * @param {Object} [size] - Initial terminal size for the remote PTY
+ * @param {number} [size.cols=80] - Terminal columns
+ * @param {number} [size.rows=24] - Terminal rows
+ * @param {string} [size.attemptId] - Attempt ID for cancel_connection
  * @returns {Promise<{sessionId: string, logs: string[]}>}
- * @throws {Error} If connection fails
+ * @throws {{code: string, message: string, logs: string[], authFailure: Object|null}} If connection fails;
+ *   code is the backend ErrorCode (e.g. 'AUTH_FAILED', 'PASSPHRASE_REQUIRED', 'CANCELLED'),
+ *   authFailure ({method, remainingMethods, message}) is set when the server refused the credentials,
+ *   e.g. remainingMethods ['password'] means asking for a password may succeed
  */
-export async function connectSSH(host, onLog) {
+export async function connectSSH(host, onLog, { cols = 80, rows = 24, attemptId = null } = {}) {
 	const logs = [];
 	let tempKeyPaths = [];
+	let authFailure = null;
 
 	const addLog = (message, detail = '') => {
 		const logEntry = detail ? `${message} ${detail}` : message;
@@ -197,23 +402,33 @@ export async function connectSSH(host, onLog) {
 			} catch {
 				// Event listener setup failed, continue without real-time updates
 			}
+			const unlistenHostKeys = await listenHostKeys(addLog);
+			const unlistenAuth = await listenAuthFailures(failure => (authFailure = failure));
 
 			let sessionId;
 			try {
 				sessionId = await invoke('create_chained_ssh_session', {
 					chain,
-					cols: 80,
-					rows: 24,
-					connectionType: connectionType
+					cols,
+					rows,
+					keepaliveSecs: host.keepaliveSecs || null,
+					term: host.term || null,
+					initialCommand: host.initialCommand
+						? { command: host.initialCommand, delayMs: host.initialCommandDelayMs || null }
+						: null,
+					connectionType: connectionType,
+					attemptId
 				});
 
 				addLog('✅ Connected successfully through chain');
 			} catch (invokeError) {
-				addLog('❌ Chain connection failed:', invokeError.toString());
+				addLog('❌ Chain connection failed:', getErrorMessage(invokeError));
 				throw invokeError;
 			} finally {
-				// Clean up event listener
+				// Clean up event listeners
 				if (unlisten) unlisten();
+				if (unlistenHostKeys) unlistenHostKeys();
+				if (unlistenAuth) unlistenAuth();
 			}
 
 			cleanupTempKeys(tempKeyPaths);
@@ -224,8 +439,9 @@ export async function connectSSH(host, onLog) {
 			addLog('⚙️ Starting connection to:', `"${host.hostname}:${host.port}"`);
 
 			// Prepare authentication
+			const fallbackAuthMethods = host.fallbackAuthMethods || [];
 			let keyPath = null;
-			if (host.authMethod === 'key' && host.keyId) {
+			if ((host.authMethod === 'key' || fallbackAuthMethods.includes('key')) && host.keyId) {
 				addLog('⚙️ Loading SSH key...');
 				const key = getKey(host.keyId);
 				if (!key) {
@@ -237,23 +453,45 @@ export async function connectSSH(host, onLog) {
 			}
 
 			addLog('⚙️ Establishing SSH connection...');
+			const unlistenHostKeys = await listenHostKeys(addLog);
+			const unlistenAuth = await listenAuthFailures(failure => (authFailure = failure));
 
 			let sessionId;
 			try {
 				sessionId = await invoke('create_ssh_session', {
-					hostname: host.hostname,
-					port: host.port,
-					username: host.username,
-					authMethod: host.authMethod,
-					keyPath: keyPath,
-					password: host.password || null,
-					connectionType: connectionType
+					options: {
+						hostname: host.hostname,
+						port: host.port,
+						username: host.username,
+						authMethod: host.authMethod,
+						fallbackAuthMethods,
+						keyPath: keyPath,
+						keyPassphrase: host.keyPassphrase || null,
+						password: host.password || null,
+						cols,
+						rows,
+						keepaliveSecs: host.keepaliveSecs || null,
+						term: host.term || null,
+						initialCommand: host.initialCommand
+							? { command: host.initialCommand, delayMs: host.initialCommandDelayMs || null }
+							: null,
+						agentForward: host.agentForward || false,
+						securityPolicy: host.securityPolicy || null,
+						connectTimeoutSecs: host.connectTimeoutSecs || null,
+						proxyCommand: host.proxyCommand || null,
+						compression: host.compression || false,
+						algorithms: host.algorithms || null
+					},
+					attemptId
 				});
 
 				addLog('✅ Connected successfully');
 			} catch (invokeError) {
-				addLog('❌ Connection failed:', invokeError.toString());
+				addLog('❌ Connection failed:', getErrorMessage(invokeError));
 				throw invokeError;
+			} finally {
+				if (unlistenHostKeys) unlistenHostKeys();
+				if (unlistenAuth) unlistenAuth();
 			}
 
 			cleanupTempKeys(tempKeyPaths);
@@ -265,9 +503,14 @@ export async function connectSSH(host, onLog) {
 
 		const errorMessage = typeof error === 'string' ? error : error.message || 'Unknown error';
 		addLog('❌ Connection failed:', errorMessage);
+		if (authFailure && authFailure.remainingMethods.length > 0) {
+			addLog('ℹ️ Server still accepts:', authFailure.remainingMethods.join(', '));
+		}
 		throw {
+			code: error?.code,
 			message: errorMessage,
-			logs
+			logs,
+			authFailure
 		};
 	}
 }
@@ -276,9 +519,10 @@ export async function connectSSH(host, onLog) {
  * Retry SSH connection
  * @param {Object} host - Host configuration
  * @param {Function} onLog - Callback for each log entry
+ * @param {Object} [size] - Initial terminal size ({ cols, rows })
  * @returns {Promise<{sessionId: string, logs: string[]}>}
  */
-export async function retrySSHConnection(host, onLog) {
+export async function retrySSHConnection(host, onLog, size) {
 	// Clear previous logs and retry
-	return connectSSH(host, onLog);
+	return connectSSH(host, onLog, size);
 }
@@ -12,10 +12,21 @@ import { invoke } from '@tauri-apps/api/core';
  * @param {number} host.port - Telnet port (default 23)
  * @param {string} [host.username] - Username for auto-login
  * @param {string} [host.password] - Password for auto-login
+ * @param {number} [host.keepaliveSecs] - Send keepalive every N seconds when idle
+ * @param {boolean|null} [host.localEcho] - Local echo: true/false, or null for auto
+ * @param {Object<string, string>} [host.environment] - Variables sent via NEW-ENVIRON (e.g. {LANG: 'en_US.UTF-8'})
+ * @param {Object} [host.autoLogin] - Auto-login tuning: {loginPatterns?: string[], passwordPatterns?: string[], timeoutSecs?: number}
+ *   Patterns replace the built-in prompts; timeoutSecs 0 waits forever (default 30)
+ * @param {number} [host.connectTimeoutSecs] - Give up connecting after N seconds (default 30)
+ * @param {string} [host.initialCommand] - Typed in once auto-login finished and a shell prompt shows
+ * @param {number} [host.initialCommandDelayMs] - Wait at least this long after login before typing it
  * @param {Function} onLog - Callback for each log entry
+ * @param {Object} [options]
+ * @param {string} [options.attemptId] - Attempt ID for cancel_connection
  * @returns {Promise<{sessionId: string, logs: string[]}>}
+ * @throws {{code: string, message: string, logs: string[]}} If connection fails (code as in connectSSH)
  */
-export async function connectTelnet(host, onLog) {
+export async function connectTelnet(host, onLog, { attemptId = null } = {}) {
 	const logs = [];
 
 	const addLog = (message, detail = '') => {
@@ -33,12 +44,24 @@ export async function connectTelnet(host, onLog) {
 		}
 
 		const sessionId = await invoke('create_telnet_session', {
-			hostname: host.hostname,
-			port: host.port || 23,
-			username: host.username || null,
-			password: host.password || null,
-			cols: 80,
-			rows: 24
+			options: {
+				hostname: host.hostname,
+				port: host.port || 23,
+				username: host.username || null,
+				password: host.password || null,
+				cols: 80,
+				rows: 24,
+				keepaliveSecs: host.keepaliveSecs || null,
+				localEcho: host.localEcho ?? null,
+				environment: host.environment || null,
+				autoLogin: host.autoLogin || null,
+				connectTimeoutSecs: host.connectTimeoutSecs || null,
+				term: host.term || null,
+				initialCommand: host.initialCommand
+					? { command: host.initialCommand, delayMs: host.initialCommandDelayMs || null }
+					: null
+			},
+			attemptId
 		});
 
 		addLog('✅ Connected successfully');
@@ -48,6 +71,7 @@ export async function connectTelnet(host, onLog) {
 		const errorMessage = typeof error === 'string' ? error : error.message || 'Unknown error';
 		addLog('❌ Connection failed:', errorMessage);
 		throw {
+			code: error?.code,
 			message: errorMessage,
 			logs
 		};
@@ -58,8 +82,9 @@ export async function connectTelnet(host, onLog) {
  * Retry Telnet connection
  * @param {Object} host - Host configuration
  * @param {Function} onLog - Callback for each log entry
+ * @param {Object} [options] - Connect options ({ attemptId })
  * @returns {Promise<{sessionId: string, logs: string[]}>}
  */
-export async function retryTelnetConnection(host, onLog) {
-	return connectTelnet(host, onLog);
+export async function retryTelnetConnection(host, onLog, options) {
+	return connectTelnet(host, onLog, options);
 }
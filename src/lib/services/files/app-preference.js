@@ -1,19 +1,51 @@
 /**
  * File App Preference Service
  * Manages which application to use for opening files by extension
+ * Associations are persisted by the backend in the app config dir
  */
 
+import { invoke } from '@tauri-apps/api/core';
+
+/** Legacy localStorage key (migrated to the backend on first read) */
 const STORAGE_KEY = 'file_app_preferences';
 
+/**
+ * Read and drop a preference left in localStorage by older versions
+ * @param {string} extension - File extension
+ * @returns {string|null}
+ */
+function takeLegacyPreference(extension) {
+	try {
+		const prefs = JSON.parse(localStorage.getItem(STORAGE_KEY) || '{}');
+		const appPath = prefs[extension] || null;
+		if (appPath) {
+			delete prefs[extension];
+			localStorage.setItem(STORAGE_KEY, JSON.stringify(prefs));
+		}
+		return appPath;
+	} catch {
+		return null;
+	}
+}
+
 /**
  * Get app preference for a file extension
  * @param {string} extension - File extension (e.g., 'txt', 'pdf')
- * @returns {string|null} App path or null if not set
+ * @returns {Promise<string|null>} App path or null if not set
  */
-export function getAppPreference(extension) {
+export async function getAppPreference(extension) {
+	const ext = extension.toLowerCase();
 	try {
-		const prefs = JSON.parse(localStorage.getItem(STORAGE_KEY) || '{}');
-		return prefs[extension.toLowerCase()] || null;
+		const appPath = await invoke('get_default_app', { extension: ext });
+		if (appPath) {
+			return appPath;
+		}
+
+		const legacy = takeLegacyPreference(ext);
+		if (legacy) {
+			await setAppPreference(ext, legacy);
+		}
+		return legacy;
 	} catch {
 		return null;
 	}
@@ -24,11 +56,9 @@ export function getAppPreference(extension) {
  * @param {string} extension - File extension (e.g., 'txt', 'pdf')
  * @param {string} appPath - Path to the application
  */
-export function setAppPreference(extension, appPath) {
+export async function setAppPreference(extension, appPath) {
 	try {
-		const prefs = JSON.parse(localStorage.getItem(STORAGE_KEY) || '{}');
-		prefs[extension.toLowerCase()] = appPath;
-		localStorage.setItem(STORAGE_KEY, JSON.stringify(prefs));
+		await invoke('set_default_app', { extension: extension.toLowerCase(), appPath });
 	} catch (e) {
 		console.error('Failed to save app preference:', e);
 	}
@@ -48,11 +78,9 @@ export function getFileExtension(path) {
  * Clear app preference for a file extension
  * @param {string} extension - File extension
  */
-export function clearAppPreference(extension) {
+export async function clearAppPreference(extension) {
 	try {
-		const prefs = JSON.parse(localStorage.getItem(STORAGE_KEY) || '{}');
-		delete prefs[extension.toLowerCase()];
-		localStorage.setItem(STORAGE_KEY, JSON.stringify(prefs));
+		await invoke('set_default_app', { extension: extension.toLowerCase(), appPath: null });
 	} catch (e) {
 		console.error('Failed to clear app preference:', e);
 	}
@@ -60,11 +88,11 @@ export function clearAppPreference(extension) {
 
 /**
  * Get all app preferences
- * @returns {Object} Map of extension -> app path
+ * @returns {Promise<Object>} Map of extension -> app path
  */
-export function getAllAppPreferences() {
+export async function getAllAppPreferences() {
 	try {
-		return JSON.parse(localStorage.getItem(STORAGE_KEY) || '{}');
+		return await invoke('list_default_apps');
 	} catch {
 		return {};
 	}
@@ -1,9 +1,14 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { isWin } from '$lib/utils';
 
 /**
  * File Browser Service
  * Handles both local and remote (SFTP/FTP) file operations
+ *
+ * Remote operations reject with {code, message}; code is stable for branching on, e.g.
+ * 'PERMISSION_DENIED', 'NOT_FOUND', 'ALREADY_EXISTS', 'NO_SPACE', 'AUTH_FAILED', 'TIMEOUT',
+ * 'CONNECTION_LOST', 'FILE_TOO_LARGE', 'CANCELLED' ('REMOTE_ERROR'/'UNKNOWN' when the cause isn't known)
  */
 
 // ============== Windows Drive Detection ==============
@@ -54,16 +59,39 @@ export async function closeFileSession(sessionId) {
 	return await invoke('close_file_session', { sessionId });
 }
 
+/**
+ * Close every file transfer session (e.g. before exiting)
+ * @returns {Promise<number>} Number of sessions closed
+ */
+export async function closeAllTransfers() {
+	return await invoke('close_all_transfers');
+}
+
 // ============== Directory Operations ==============
 
+/**
+ * List directory contents (remote), with warnings for entries that could not be read
+ * @param {string} sessionId - Session ID
+ * @param {string} path - Directory path
+ * @returns {Promise<{entries: Array, warnings: Array<{path: string, message: string}>}>}
+ */
+export async function listRemoteDirectoryWithWarnings(sessionId, path) {
+	return await invoke('list_directory', { sessionId, path });
+}
+
 /**
  * List directory contents (remote)
+ * Entries that could not be read are logged and left out
  * @param {string} sessionId - Session ID
  * @param {string} path - Directory path
  * @returns {Promise<Array>} List of files/directories
  */
 export async function listRemoteDirectory(sessionId, path) {
-	return await invoke('list_directory', { sessionId, path });
+	const { entries, warnings } = await listRemoteDirectoryWithWarnings(sessionId, path);
+	for (const warning of warnings) {
+		console.warn(`[FileBrowser] ${warning.path}: ${warning.message}`);
+	}
+	return entries;
 }
 
 /**
@@ -216,9 +244,36 @@ export async function createLocalDirectory(path) {
  * @param {string} remotePath - Remote file path
  * @param {string} localPath - Local destination path
  * @param {string} transferId - Unique transfer ID from frontend
+ * @param {Object} [options]
+ * @param {boolean} [options.resume=false] - Continue a partial local file (SFTP; restarts if the remote file changed)
+ * @param {number|null} [options.maxBytesPerSec=null] - Bandwidth cap for this transfer (null: session default, 0: unlimited)
+ * @param {boolean} [options.verifyChecksum=false] - Compare with the server's sha256sum/md5sum (SFTP; see isChecksumMismatch)
+ */
+export async function downloadFile(
+	sessionId,
+	remotePath,
+	localPath,
+	transferId,
+	{ resume = false, maxBytesPerSec = null, verifyChecksum = false } = {}
+) {
+	return await invoke('download_file', {
+		sessionId,
+		remotePath,
+		localPath,
+		transferId,
+		resume,
+		maxBytesPerSec,
+		verifyChecksum
+	});
+}
+
+/**
+ * Whether a download failed checksum verification (the corrupt file was deleted; offer a re-download)
+ * @param {unknown} error - Error from downloadFile
+ * @returns {boolean}
  */
-export async function downloadFile(sessionId, remotePath, localPath, transferId) {
-	return await invoke('download_file', { sessionId, remotePath, localPath, transferId });
+export function isChecksumMismatch(error) {
+	return error?.code === 'CHECKSUM_MISMATCH';
 }
 
 /**
@@ -227,18 +282,178 @@ export async function downloadFile(sessionId, remotePath, localPath, transferId)
  * @param {string} localPath - Local file path
  * @param {string} remotePath - Remote destination path
  * @param {string} transferId - Unique transfer ID from frontend
+ * @param {Object} [options]
+ * @param {string|null} [options.remoteDir=null] - Target directory; upload is rejected if the path escapes it
+ * @param {'overwrite'|'skip'|'rename'|null} [options.onConflict=null] - When the destination exists (backend default: rename)
+ * @param {boolean} [options.preserveAttributes=false] - Keep the local permissions and mtime (SFTP)
+ * @param {number|null} [options.maxBytesPerSec=null] - Bandwidth cap for this transfer (null: session default, 0: unlimited)
+ * @returns {Promise<{remotePath: string, skipped: boolean}>} Path actually written
+ */
+export async function uploadFile(
+	sessionId,
+	localPath,
+	remotePath,
+	transferId,
+	{ remoteDir = null, onConflict = null, preserveAttributes = false, maxBytesPerSec = null } = {}
+) {
+	return await invoke('upload_file', {
+		sessionId,
+		localPath,
+		remotePath,
+		remoteDir,
+		onConflict,
+		transferId,
+		preserveAttributes,
+		maxBytesPerSec
+	});
+}
+
+/**
+ * Upload a local directory tree into remoteDir (renamed "name (N)" if it already exists there)
+ * Progress: 'directory-upload-progress' (files/bytes so far), 'directory-upload-file' per finished file
+ * @param {string} sessionId - Session ID
+ * @param {string} localDir - Local directory to upload
+ * @param {string} remoteDir - Remote parent directory
+ * @param {string} transferId - Unique transfer ID from frontend
+ * @param {Object} [options]
+ * @param {'skip'|'follow'} [options.symlinks='skip'] - Leave symlinks out or upload their targets
+ * @returns {Promise<{remotePath: string, filesUploaded: number, bytesUploaded: number, failed: Array<{path: string, error: string}>, skippedSymlinks: string[]}>}
+ */
+export async function uploadDirectory(sessionId, localDir, remoteDir, transferId, { symlinks = 'skip' } = {}) {
+	return await invoke('upload_directory', { sessionId, localDir, remoteDir, symlinks, transferId });
+}
+
+/**
+ * Download a remote directory tree into localDir (renamed "name (N)" if it already exists there)
+ * Symlinked directories are not followed and broken symlinks are skipped (both listed in skippedSymlinks)
+ * Progress: 'directory-download-progress' (files/bytes so far), 'directory-download-file' per finished file
+ * @param {string} sessionId - Session ID
+ * @param {string} remoteDir - Remote directory to download
+ * @param {string} localDir - Local parent directory
+ * @param {string} transferId - Unique transfer ID from frontend
+ * @returns {Promise<{localPath: string, filesDownloaded: number, bytesDownloaded: number, failed: Array<{path: string, error: string}>, skippedSymlinks: string[]}>}
+ */
+export async function downloadDirectory(sessionId, remoteDir, localDir, transferId) {
+	return await invoke('download_directory', { sessionId, remoteDir, localDir, transferId });
+}
+
+/**
+ * Upload many files over one session, several at a time
+ * Progress is emitted as 'file-batch-progress' events (operation 'upload'); failures don't abort the batch
+ * @param {string} sessionId - Session ID
+ * @param {Array<{localPath: string, remotePath: string}>} items - Files to upload (existing destinations are overwritten)
+ * @param {Object} [options]
+ * @param {number} [options.concurrency=4] - Transfers in flight at once (capped at 16)
+ * @param {string|null} [options.batchId] - Echoed back in progress events; pass to cancelTransfer to stop the batch
+ * @returns {Promise<{succeeded: string[], failed: Array<{path: string, error: string}>}>}
  */
-export async function uploadFile(sessionId, localPath, remotePath, transferId) {
-	return await invoke('upload_file', { sessionId, localPath, remotePath, transferId });
+export async function uploadFiles(sessionId, items, { concurrency = 4, batchId = null } = {}) {
+	return await invoke('upload_files', { sessionId, items, concurrency, batchId });
+}
+
+/**
+ * Measure a remote directory tree (e.g. before downloading it)
+ * Progress is emitted as 'directory-size-progress' events; unreadable subdirectories are skipped and counted
+ * @param {string} sessionId - Session ID
+ * @param {string} path - Remote directory
+ * @param {string|null} [scanId] - Echoed back in progress events
+ * @returns {Promise<{totalBytes: number, fileCount: number, directoryCount: number, inaccessible: number}>}
+ */
+export async function calculateDirectorySize(sessionId, path, scanId = null) {
+	return await invoke('calculate_directory_size', { sessionId, path, scanId });
+}
+
+/**
+ * Cancel a running upload or download (single file, directory or uploadFiles batch)
+ * The transfer rejects with 'Transfer cancelled'; a partial download is deleted
+ * @param {string} transferId - Transfer ID passed when the transfer was started
+ * @returns {Promise<boolean>} False if the transfer was no longer running
+ */
+export async function cancelTransfer(transferId) {
+	return await invoke('cancel_transfer', { transferId });
+}
+
+/** Chunk size for streamed uploads (base64 adds a third on the wire) */
+const UPLOAD_CHUNK_SIZE = 512 * 1024;
+
+/**
+ * Upload a File/Blob that has no local path (e.g. dropped into the webview)
+ * Sent in order as base64 chunks; progress arrives as 'file-transfer-progress' events
+ * @param {string} sessionId - Session ID
+ * @param {Blob} blob - File content
+ * @param {string} remotePath - Remote destination path
+ * @param {string} transferId - Unique transfer ID from frontend
+ * @returns {Promise<number>} Bytes written
+ */
+export async function uploadBlob(sessionId, blob, remotePath, transferId) {
+	let offset = 0;
+	do {
+		const chunk = new Uint8Array(await blob.slice(offset, offset + UPLOAD_CHUNK_SIZE).arrayBuffer());
+		let binary = '';
+		for (const byte of chunk) {
+			binary += String.fromCharCode(byte);
+		}
+		const isLast = offset + chunk.length >= blob.size;
+		offset = await invoke('upload_bytes', {
+			sessionId,
+			remotePath,
+			chunkBase64: btoa(binary),
+			offset,
+			isLast,
+			totalBytes: blob.size,
+			transferId
+		});
+	} while (offset < blob.size);
+	return offset;
+}
+
+/**
+ * Mirror a local directory to a remote one (uploads new/changed files by size + mtime)
+ * Progress is emitted as 'directory-sync-progress' events
+ * @param {string} sessionId - Session ID
+ * @param {string} localDir - Local source directory
+ * @param {string} remoteDir - Remote target directory (created if missing)
+ * @param {Object} [options]
+ * @param {boolean} [options.delete=false] - Remove remote files not present locally
+ * @param {boolean} [options.dryRun=false] - Only return the planned actions
+ * @param {string|null} [syncId] - Echoed back in progress events
+ * @returns {Promise<{actions: Array<{action: 'mkdir'|'upload'|'skip'|'delete', path: string, isDirectory: boolean, size: number, reason: string}>, failed: Array<{path: string, error: string}>, dryRun: boolean}>}
+ */
+export async function syncDirectory(
+	sessionId,
+	localDir,
+	remoteDir,
+	{ delete: deleteExtra = false, dryRun = false } = {},
+	syncId = null
+) {
+	return await invoke('sync_directory', {
+		sessionId,
+		localDir,
+		remoteDir,
+		options: { delete: deleteExtra, dryRun },
+		syncId
+	});
 }
 
 /**
  * Create directory on remote
  * @param {string} sessionId - Session ID
  * @param {string} path - Directory path to create
+ * @param {Object} [options]
+ * @param {boolean} [options.recursive=false] - Create missing parents too; an existing directory is not an error
  */
-export async function createRemoteDirectory(sessionId, path) {
-	return await invoke('create_remote_directory', { sessionId, path });
+export async function createRemoteDirectory(sessionId, path, { recursive = false } = {}) {
+	return await invoke('create_remote_directory', { sessionId, path, recursive });
+}
+
+/**
+ * Size and free space of the remote filesystem holding a path (SFTP only; FTP rejects with code 'UNSUPPORTED')
+ * @param {string} sessionId - Session ID
+ * @param {string} path - Any path on the filesystem, e.g. the upload target directory
+ * @returns {Promise<{totalBytes: number, freeBytes: number, availableBytes: number}>} availableBytes is what the user can write
+ */
+export async function statRemoteFilesystem(sessionId, path) {
+	return await invoke('statfs', { sessionId, path });
 }
 
 /**
@@ -251,6 +466,32 @@ export async function deleteRemotePath(sessionId, path, isDirectory) {
 	return await invoke('delete_remote_path', { sessionId, path, isDirectory });
 }
 
+/**
+ * Delete a file or directory on remote, including everything inside it
+ * Progress is emitted as 'file-batch-progress' events (batchId = transferId); a locked file doesn't stop the rest
+ * @param {string} sessionId - Session ID
+ * @param {string} path - Path to delete
+ * @param {string|null} [transferId] - Pass to cancelTransfer to stop early
+ * @returns {Promise<{succeeded: string[], failed: Array<{path: string, error: string}>}>}
+ */
+export async function deleteRemoteRecursive(sessionId, path, transferId = null) {
+	return await invoke('delete_remote_recursive', { sessionId, path, transferId });
+}
+
+/**
+ * Delete many files/directories on remote in one call
+ * Progress is emitted as 'file-batch-progress' events; failures don't abort the batch
+ * @param {string} sessionId - Session ID
+ * @param {Array<{path: string, isDirectory: boolean}>} items - Paths to delete
+ * @param {string|null} [batchId] - Echoed back in progress events
+ * @param {Object} [options]
+ * @param {boolean} [options.dryRun=false] - Only return what would change (in `planned`), for a confirmation summary
+ * @returns {Promise<{succeeded: string[], failed: Array<{path: string, error: string}>, planned?: Array<{path: string, isDirectory: boolean, action: string, detail: string|null, problem: string|null}>}>}
+ */
+export async function deleteRemotePaths(sessionId, items, batchId = null, { dryRun = false } = {}) {
+	return await invoke('delete_remote_paths', { sessionId, items, batchId, dryRun });
+}
+
 /**
  * Rename file or directory on remote
  * @param {string} sessionId - Session ID
@@ -271,28 +512,38 @@ export async function renameLocalPath(oldPath, newPath) {
 }
 
 /**
- * Copy file or directory locally (recursive)
+ * Copy file or directory locally (recursive, keeps permissions and symlinks)
+ * Progress is emitted as 'local-transfer-progress' events
  * @param {string} sourcePath - Source path to copy from
  * @param {string} destPath - Destination path to copy to
+ * @param {string|null} [transferId] - Echoed back in progress events
+ * @param {Object} [options]
+ * @param {boolean} [options.overwrite=false] - Replace an existing destination instead of failing
  */
-export async function copyLocalPath(sourcePath, destPath) {
-	return await invoke('copy_local_path', { sourcePath, destPath });
+export async function copyLocalPath(sourcePath, destPath, transferId = null, { overwrite = false } = {}) {
+	return await invoke('copy_local_path', { sourcePath, destPath, transferId, overwrite });
 }
 
 /**
  * Move file or directory locally (atomic rename with copy+delete fallback)
+ * Progress is emitted as 'local-transfer-progress' events
  * @param {string} sourcePath - Source path to move from
  * @param {string} destPath - Destination path to move to
+ * @param {string|null} [transferId] - Echoed back in progress events
+ * @param {Object} [options]
+ * @param {boolean} [options.overwrite=false] - Replace an existing destination instead of failing
  */
-export async function moveLocalPath(sourcePath, destPath) {
-	return await invoke('move_local_path', { sourcePath, destPath });
+export async function moveLocalPath(sourcePath, destPath, transferId = null, { overwrite = false } = {}) {
+	return await invoke('move_local_path', { sourcePath, destPath, transferId, overwrite });
 }
 
 /**
  * Copy file or directory on remote (SFTP/FTP)
+ * Entries that can't be read are skipped; the rest is still copied
  * @param {string} sessionId - Session ID
  * @param {string} sourcePath - Source path to copy from
  * @param {string} destPath - Destination path to copy to
+ * @returns {Promise<Array<{path: string, message: string}>>} Skipped entries
  */
 export async function copyRemotePath(sessionId, sourcePath, destPath) {
 	return await invoke('copy_remote_path', { sessionId, sourcePath, destPath });
@@ -350,6 +601,26 @@ export async function openFileWithApp(path, appPath = null) {
 	return await invoke('open_file_with_app', { path, appPath });
 }
 
+/**
+ * Open a remote file in an external application
+ * Edits are re-uploaded on save; listen for 'remote-edit-sync' events for status
+ * @param {string} sessionId - File transfer session ID
+ * @param {string} remotePath - Remote file path
+ * @param {string|null} appPath - Application path (optional, uses preference if not provided)
+ * @returns {Promise<string>} Local temp path (pass to closeRemoteEdit when done)
+ */
+export async function openRemoteWithApp(sessionId, remotePath, appPath = null) {
+	return await invoke('open_remote_with_app', { sessionId, remotePath, appPath });
+}
+
+/**
+ * Stop syncing a remote file opened with openRemoteWithApp and delete its temp copy
+ * @param {string} localPath - Temp path returned by openRemoteWithApp
+ */
+export async function closeRemoteEdit(localPath) {
+	return await invoke('close_remote_edit', { localPath });
+}
+
 /**
  * Show "Open with" dialog to select application
  * @param {string} path - File path
@@ -361,26 +632,163 @@ export async function showOpenWithDialog(path) {
 }
 
 /**
- * Change file permissions (SFTP only)
+ * Change file permissions (FTP via SITE CHMOD where supported)
  */
 export async function chmodRemote(sessionId, path, mode) {
 	return await invoke('chmod_remote', { sessionId, path, mode });
 }
 
+/**
+ * Create a symlink on remote (SFTP only)
+ * @param {string} sessionId - Session ID
+ * @param {string} target - What the link points to (relative targets resolve from the link's directory)
+ * @param {string} linkPath - Path of the new link
+ */
+export async function createRemoteSymlink(sessionId, target, linkPath) {
+	return await invoke('create_remote_symlink', { sessionId, target, linkPath });
+}
+
+/**
+ * Run a shell command on the file session's host (SFTP only), e.g. `cd '/srv/app' && git pull`
+ * @param {string} sessionId - Session ID
+ * @param {string} command - Shell command (quote paths yourself)
+ * @returns {Promise<{stdout: string, exitCode: number}>} exitCode is -1 when the server reported none
+ */
+export async function runRemoteCommand(sessionId, command) {
+	return await invoke('run_remote_command', { sessionId, command });
+}
+
+/**
+ * Change permissions of many files in one call
+ * @param {string} sessionId - Session ID
+ * @param {Array<{path: string, mode: number}>} items - Paths and modes
+ * @param {string|null} [batchId] - Echoed back in progress events
+ * @param {Object} [options]
+ * @param {boolean} [options.dryRun=false] - Only return what would change (in `planned`), for a confirmation summary
+ * @returns {Promise<{succeeded: string[], failed: Array<{path: string, error: string}>, planned?: Array<{path: string, isDirectory: boolean, action: string, detail: string|null, problem: string|null}>}>}
+ */
+export async function chmodRemoteBatch(sessionId, items, batchId = null, { dryRun = false } = {}) {
+	return await invoke('chmod_remote_batch', { sessionId, items, batchId, dryRun });
+}
+
 /**
  * Read file content for editing (small files)
+ * @param {number} [maxBytes] - Refuse larger files (rejects with code 'FILE_TOO_LARGE') before downloading them
+ * @returns {Promise<string|{binary: true, size: number}>} Text, or a marker for binary files
  */
-export async function readFileContent(sessionId, path, isLocal) {
-	return await invoke('read_file_content', { sessionId, path, isLocal });
+export async function readFileContent(sessionId, path, isLocal, maxBytes = null) {
+	return await invoke('read_file_content', { sessionId, path, isLocal, maxBytes });
+}
+
+/**
+ * Read file bytes as base64 (e.g. to preview a binary file)
+ * @param {number} [maxBytes] - Refuse larger files (rejects with code 'FILE_TOO_LARGE')
+ * @returns {Promise<string>} Base64-encoded content
+ */
+export async function readFileBase64(sessionId, path, isLocal, maxBytes = null) {
+	return await invoke('read_file_base64', { sessionId, path, isLocal, maxBytes });
+}
+
+/**
+ * Read a window of a file, to page through files too large to load at once
+ * @param {number} offset - First byte to read
+ * @param {number} length - Bytes to read (at most 16 MiB)
+ * @returns {Promise<{dataBase64: string, eof: boolean}>} Base64 bytes (may end mid-character) and whether the file ends there
+ */
+export async function readFileRange(sessionId, path, offset, length, isLocal) {
+	return await invoke('read_file_range', { sessionId, path, offset, length, isLocal });
+}
+
+/**
+ * Whether a read failed because the file is over its maxBytes limit
+ * @param {unknown} error - Error from readFileContent/readFileBase64
+ * @returns {boolean}
+ */
+export function isFileTooLarge(error) {
+	return error?.code === 'FILE_TOO_LARGE';
 }
 
 /**
  * Write file content after editing
+ * The save itself is not reported by watchRemoteFile
+ * Rejects with code 'PERMISSION_DENIED' before writing when the remote file (or, for a new
+ * file, its directory) is read-only for the login user, e.g. to offer "save as" instead
  */
 export async function writeFileContent(sessionId, path, content, isLocal) {
 	return await invoke('write_file_content', { sessionId, path, content, isLocal });
 }
 
+/**
+ * Poll a remote file for changes by other processes (size/mtime)
+ * Changes arrive through onRemoteFileChanged; watching stops when the session closes
+ * @param {string} sessionId - Session ID
+ * @param {string} path - Remote file path
+ * @param {Object} [options]
+ * @param {number} [options.intervalSecs=5] - Seconds between polls (longer while stat keeps failing)
+ */
+export async function watchRemoteFile(sessionId, path, { intervalSecs = null } = {}) {
+	return await invoke('watch_remote_file', { sessionId, path, intervalSecs });
+}
+
+/**
+ * Stop polling a remote file
+ * @param {string} sessionId - Session ID
+ * @param {string} path - Remote file path
+ */
+export async function unwatchRemoteFile(sessionId, path) {
+	return await invoke('unwatch_remote_file', { sessionId, path });
+}
+
+/**
+ * Listen for changes to watched remote files
+ * @param {(change: {sessionId: string, path: string, size: number, modified: string|null}) => void} callback
+ * @returns {Promise<Function>} Unlisten function
+ */
+export async function onRemoteFileChanged(callback) {
+	return await listen('remote-file-changed', event => callback(event.payload));
+}
+
+/**
+ * Follow a remote file as it grows, like `tail -F` (SFTP sessions only)
+ * Starts with the last 10 lines; new text arrives through onRemoteFileTail
+ * @param {string} sessionId - Session ID
+ * @param {string} path - Remote file path
+ * @param {Object} [options]
+ * @param {boolean} [options.restart=true] - Start tail again if it exits
+ * @returns {Promise<string>} Follow ID
+ */
+export async function followRemoteFile(sessionId, path, { restart = true } = {}) {
+	return await invoke('follow_remote_file', { sessionId, path, restart });
+}
+
+/**
+ * Stop following a remote file
+ * @param {string} followId - ID returned by followRemoteFile
+ */
+export async function stopFollow(followId) {
+	return await invoke('stop_follow', { followId });
+}
+
+/**
+ * Listen for new text of a followed file (complete lines, newlines included)
+ * @param {string} followId - Follow ID
+ * @param {(text: string) => void} callback
+ * @returns {Promise<Function>} Unlisten function
+ */
+export async function onRemoteFileTail(followId, callback) {
+	return await listen(`remote-file-tail:${followId}`, event => callback(event.payload));
+}
+
+/**
+ * Listen for a follow stopping on its own (tail failed or the connection dropped)
+ * @param {string} followId - Follow ID
+ * @param {(end: {error: string|null}) => void} callback
+ * @returns {Promise<Function>} Unlisten function
+ */
+export async function onRemoteFileTailEnd(followId, callback) {
+	return await listen(`remote-file-tail-end:${followId}`, event => callback(event.payload));
+}
+
 // ============== Helper Functions ==============
 
 /**
@@ -501,22 +909,22 @@ export function createFileService(sessionId = null, isLocal = false) {
 			return listRemoteDirectory(sid || sessionId, path);
 		},
 
-		async downloadFile(remotePath, localPath, transferId = null) {
+		async downloadFile(remotePath, localPath, transferId = null, options = {}) {
 			if (!sessionId) {
 				throw new Error('No session ID provided');
 			}
 			// Generate transferId if not provided (for backward compatibility)
 			const finalTransferId = transferId || crypto.randomUUID();
-			return downloadFile(sessionId, remotePath, localPath, finalTransferId);
+			return downloadFile(sessionId, remotePath, localPath, finalTransferId, options);
 		},
 
-		async uploadFile(localPath, remotePath, transferId = null) {
+		async uploadFile(localPath, remotePath, transferId = null, options = {}) {
 			if (!sessionId) {
 				throw new Error('No session ID provided');
 			}
 			// Generate transferId if not provided (for backward compatibility)
 			const finalTransferId = transferId || crypto.randomUUID();
-			return uploadFile(sessionId, localPath, remotePath, finalTransferId);
+			return uploadFile(sessionId, localPath, remotePath, finalTransferId, options);
 		},
 
 		async createDirectory(path, isLocalDir = false) {
@@ -7,17 +7,30 @@ export {
 	createFileService,
 	listLocalDirectory as listLocalFiles,
 	listRemoteDirectory as listRemoteFiles,
+	listRemoteDirectoryWithWarnings,
 	downloadFile,
+	isChecksumMismatch,
 	uploadFile,
+	uploadBlob,
+	uploadDirectory,
+	uploadFiles,
+	downloadDirectory,
+	calculateDirectorySize,
+	cancelTransfer,
+	syncDirectory,
 	deleteLocalPath as deleteLocalFile,
 	deleteRemotePath as deleteRemoteFile,
+	deleteRemotePaths,
+	deleteRemoteRecursive,
 	createLocalDirectory,
 	createRemoteDirectory,
+	statRemoteFilesystem,
 	renameLocalPath as renameLocalFile,
 	renameRemotePath as renameRemoteFile,
 	getLocalFileStat,
 	getRemoteFileStat,
 	closeFileSession,
+	closeAllTransfers,
 	getParentPath,
 	isWindowsPath,
 	getHomeDirectory,
@@ -25,13 +38,28 @@ export {
 	showInFileManager,
 	openFileWithSystem,
 	openFileWithApp,
+	openRemoteWithApp,
+	closeRemoteEdit,
 	showOpenWithDialog,
 	formatFileSize,
 	getFileExtension,
 	isAbsolutePath,
 	chmodRemote,
+	chmodRemoteBatch,
+	createRemoteSymlink,
+	runRemoteCommand,
 	readFileContent,
+	readFileBase64,
+	readFileRange,
+	isFileTooLarge,
 	writeFileContent,
+	watchRemoteFile,
+	unwatchRemoteFile,
+	onRemoteFileChanged,
+	followRemoteFile,
+	stopFollow,
+	onRemoteFileTail,
+	onRemoteFileTailEnd,
 	copyLocalPath,
 	moveLocalPath,
 	copyRemotePath,
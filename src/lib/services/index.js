@@ -112,6 +112,16 @@ export {
 	retrySSHConnection,
 	writeTempKeyFile,
 	prepareHopConfig,
+	keyPassphraseError,
+	onAuthPrompt,
+	respondAuthPrompt,
+	onHostKeyPrompt,
+	respondHostKeyPrompt,
+	startDynamicForward,
+	stopDynamicForward,
+	addLocalForward,
+	removeLocalForward,
+	resolveSshHost,
 	resolveChain,
 	cleanupTempKeys,
 	connectTelnet,
@@ -119,6 +129,10 @@ export {
 	connectFileTransfer,
 	isFileTransferType,
 	isTerminalType,
+	createAttemptId,
+	cancelConnection,
+	getSessionCapabilities,
+	listActiveSessions,
 	connectionHeartbeat,
 	attemptReconnect,
 	cancelReconnect,
@@ -175,17 +189,30 @@ export {
 	createFileService,
 	listLocalFiles,
 	listRemoteFiles,
+	listRemoteDirectoryWithWarnings,
 	downloadFile,
+	isChecksumMismatch,
 	uploadFile,
+	uploadBlob,
+	uploadDirectory,
+	uploadFiles,
+	downloadDirectory,
+	calculateDirectorySize,
+	cancelTransfer,
+	syncDirectory,
 	deleteLocalFile,
 	deleteRemoteFile,
+	deleteRemotePaths,
+	deleteRemoteRecursive,
 	createLocalDirectory,
 	createRemoteDirectory,
+	statRemoteFilesystem,
 	renameLocalFile,
 	renameRemoteFile,
 	getLocalFileStat,
 	getRemoteFileStat,
 	closeFileSession,
+	closeAllTransfers,
 	getParentPath,
 	isWindowsPath,
 	getHomeDirectory,
@@ -193,13 +220,28 @@ export {
 	showInFileManager,
 	openFileWithSystem,
 	openFileWithApp,
+	openRemoteWithApp,
+	closeRemoteEdit,
 	showOpenWithDialog,
 	formatFileSize,
 	getFileExtension,
 	isAbsolutePath,
 	chmodRemote,
+	chmodRemoteBatch,
+	createRemoteSymlink,
+	runRemoteCommand,
 	readFileContent,
+	readFileBase64,
+	readFileRange,
+	isFileTooLarge,
 	writeFileContent,
+	watchRemoteFile,
+	unwatchRemoteFile,
+	onRemoteFileChanged,
+	followRemoteFile,
+	stopFollow,
+	onRemoteFileTail,
+	onRemoteFileTailEnd,
 	copyLocalPath,
 	moveLocalPath,
 	copyRemotePath,
@@ -1,7 +1,7 @@
 /**
  * Tauri dialog utilities for file and folder selection
  */
-import { open, save } from '@tauri-apps/plugin-dialog';
+import { ask, open, save } from '@tauri-apps/plugin-dialog';
 
 export const tauriDialog = {
 	/**
@@ -60,5 +60,22 @@ export const tauriDialog = {
 			console.error('Failed to open save dialog:', error);
 			throw new Error(`Failed to save file: ${error}`);
 		}
+	},
+
+	/**
+	 * Ask a yes/no question
+	 * @param {string} message - Question to show
+	 * @param {Object} options - Dialog options
+	 * @param {string} options.title - Dialog title
+	 * @param {'info'|'warning'|'error'} options.kind - Icon to show
+	 * @returns {Promise<boolean>} True if the user answered yes
+	 */
+	async ask(message, options = {}) {
+		try {
+			return await ask(message, options);
+		} catch (error) {
+			console.error('Failed to open ask dialog:', error);
+			return false;
+		}
 	}
 };
@@ -22,10 +22,34 @@ export const terminalEvents = {
 		}
 	},
 
+	/**
+	 * Listen for raw terminal output (sessions created with binaryOutput)
+	 * @param {string} sessionId - Terminal session ID
+	 * @param {(bytes: Uint8Array) => void} callback - Callback receiving the exact output bytes
+	 * @returns {Promise<Function>} Unlisten function
+	 */
+	async onTerminalOutputBytes(sessionId, callback) {
+		try {
+			return await listen(`terminal-output-bytes:${sessionId}`, event => {
+				const binary = atob(event.payload);
+				const bytes = new Uint8Array(binary.length);
+				for (let i = 0; i < binary.length; i++) {
+					bytes[i] = binary.charCodeAt(i);
+				}
+				callback(bytes);
+			});
+		} catch (error) {
+			console.error('Failed to listen for terminal output bytes:', error);
+			throw error;
+		}
+	},
+
 	/**
 	 * Listen for terminal exit
 	 * @param {string} sessionId - Terminal session ID
-	 * @param {Function} callback - Callback receiving exit event {exit_code: number, reason?: string}
+	 * @param {Function} callback - Callback receiving exit event {exit_code: number, reason?: string, reconnectable: boolean}
+	 *   reason 'process-exited' carries the remote exit status; 'process-exited:signal:TERM' a killing signal
+	 *   reconnectable is set when the connection dropped; terminalCommands.reconnectSession restores it
 	 * @returns {Promise<Function>} Unlisten function
 	 */
 	async onTerminalExit(sessionId, callback) {
@@ -62,5 +86,22 @@ export const terminalEvents = {
 			console.error('Failed to listen for terminal errors:', error);
 			throw error;
 		}
+	},
+
+	/**
+	 * Listen for Telnet auto-login giving up on a prompt that never appeared
+	 * @param {string} sessionId - Terminal session ID
+	 * @param {Function} callback - Callback receiving the awaited prompt: 'login' | 'password'
+	 * @returns {Promise<Function>} Unlisten function
+	 */
+	async onTelnetLoginTimeout(sessionId, callback) {
+		try {
+			return await listen(`telnet-login-timeout:${sessionId}`, event => {
+				callback(event.payload);
+			});
+		} catch (error) {
+			console.error('Failed to listen for telnet login timeout:', error);
+			throw error;
+		}
 	}
 };
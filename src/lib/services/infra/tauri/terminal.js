@@ -1,25 +1,50 @@
 import { invoke } from '@tauri-apps/api/core';
+import { getErrorMessage } from '$lib/utils/data/errors.js';
 
 /**
  * Terminal command wrappers (Facade Pattern)
  * Provides a clean interface to Tauri terminal commands
+ * The commands reject with `{code, message}`; wrappers rethrow an Error with the message
  */
 export const terminalCommands = {
 	/**
 	 * Create a new terminal session
 	 * @param {Object} config - Terminal configuration
 	 * @param {string} [config.shell] - Shell path (optional, uses system default)
+	 * @param {string[]} [config.args] - Shell arguments, one per element (e.g. ['-l'], ['exec', '-it', 'web', 'bash'] with docker)
 	 * @param {number} [config.cols=80] - Terminal columns
 	 * @param {number} [config.rows=24] - Terminal rows
+	 * @param {Object<string, string>} [config.env] - Extra environment variables (may override TERM)
+	 * @param {string} [config.cwd] - Starting directory (must exist)
+	 * @param {boolean} [config.binaryOutput=false] - Emit exact bytes (onTerminalOutputBytes) instead of text
+	 * @param {{command: string, delayMs?: number}} [config.initialCommand] - Typed in once the shell prompt is ready
 	 * @returns {Promise<string>} Session ID
 	 */
-	async createTerminal({ shell = null, cols = 80, rows = 24 } = {}) {
+	async createTerminal({
+		shell = null,
+		args = null,
+		cols = 80,
+		rows = 24,
+		env = null,
+		cwd = null,
+		binaryOutput = false,
+		initialCommand = null
+	} = {}) {
 		try {
-			const sessionId = await invoke('create_terminal', { shell, cols, rows });
+			const sessionId = await invoke('create_terminal', {
+				shell,
+				args,
+				cols,
+				rows,
+				env,
+				cwd,
+				binaryOutput,
+				initialCommand
+			});
 			return sessionId;
 		} catch (error) {
 			console.error('Failed to create terminal:', error);
-			throw new Error(`Failed to create terminal: ${error}`);
+			throw new Error(`Failed to create terminal: ${getErrorMessage(error)}`);
 		}
 	},
 
@@ -34,7 +59,58 @@ export const terminalCommands = {
 			await invoke('write_terminal', { sessionId, data });
 		} catch (error) {
 			console.error('Failed to write to terminal:', error);
-			throw new Error(`Failed to write to terminal: ${error}`);
+			throw new Error(`Failed to write to terminal: ${getErrorMessage(error)}`);
+		}
+	},
+
+	/**
+	 * Write raw bytes to terminal (control bytes, non-UTF-8 data)
+	 * @param {string} sessionId - Terminal session ID
+	 * @param {Uint8Array|number[]} bytes - Exact bytes to send
+	 * @returns {Promise<void>}
+	 */
+	async writeTerminalBytes(sessionId, bytes) {
+		let binary = '';
+		for (const byte of bytes) {
+			binary += String.fromCharCode(byte);
+		}
+		try {
+			await invoke('write_terminal_bytes', { sessionId, dataBase64: btoa(binary) });
+		} catch (error) {
+			console.error('Failed to write bytes to terminal:', error);
+			throw new Error(`Failed to write bytes to terminal: ${getErrorMessage(error)}`);
+		}
+	},
+
+	/**
+	 * Paste data into terminal (bracketed when the remote enabled it, chunked when large)
+	 * @param {string} sessionId - Terminal session ID
+	 * @param {string} data - Pasted text
+	 * @returns {Promise<void>}
+	 */
+	async pasteTerminal(sessionId, data) {
+		try {
+			await invoke('paste_terminal', { sessionId, data });
+		} catch (error) {
+			console.error('Failed to paste to terminal:', error);
+			throw new Error(`Failed to paste to terminal: ${getErrorMessage(error)}`);
+		}
+	},
+
+	/**
+	 * Paste data as one bracketed paste, even if the remote never enabled the mode
+	 * @param {string} sessionId - Terminal session ID
+	 * @param {string} data - Pasted text
+	 * @param {Object} [options]
+	 * @param {boolean} [options.sanitize=false] - Strip control characters other than newlines and tabs
+	 * @returns {Promise<void>}
+	 */
+	async writeTerminalPaste(sessionId, data, { sanitize = false } = {}) {
+		try {
+			await invoke('write_terminal_paste', { sessionId, data, sanitize });
+		} catch (error) {
+			console.error('Failed to paste to terminal:', error);
+			throw new Error(`Failed to paste to terminal: ${getErrorMessage(error)}`);
 		}
 	},
 
@@ -50,7 +126,7 @@ export const terminalCommands = {
 			await invoke('resize_terminal', { sessionId, cols, rows });
 		} catch (error) {
 			console.error('Failed to resize terminal:', error);
-			throw new Error(`Failed to resize terminal: ${error}`);
+			throw new Error(`Failed to resize terminal: ${getErrorMessage(error)}`);
 		}
 	},
 
@@ -64,7 +140,35 @@ export const terminalCommands = {
 			await invoke('close_terminal', { sessionId });
 		} catch (error) {
 			console.error('Failed to close terminal:', error);
-			throw new Error(`Failed to close terminal: ${error}`);
+			throw new Error(`Failed to close terminal: ${getErrorMessage(error)}`);
+		}
+	},
+
+	/**
+	 * Close every terminal session (e.g. before exiting)
+	 * @returns {Promise<number>} Number of sessions closed
+	 */
+	async closeAllTerminals() {
+		try {
+			return await invoke('close_all_terminals');
+		} catch (error) {
+			console.error('Failed to close terminals:', error);
+			throw new Error(`Failed to close terminals: ${getErrorMessage(error)}`);
+		}
+	},
+
+	/**
+	 * Reconnect an SSH/Telnet session after an exit event with reconnectable = true
+	 * Keeps the session ID, so existing output/exit listeners keep working
+	 * @param {string} sessionId - Terminal session ID
+	 * @returns {Promise<string>} The same session ID
+	 */
+	async reconnectSession(sessionId) {
+		try {
+			return await invoke('reconnect_session', { sessionId });
+		} catch (error) {
+			console.error('Failed to reconnect terminal:', error);
+			throw new Error(`Failed to reconnect terminal: ${getErrorMessage(error)}`);
 		}
 	},
 
@@ -93,7 +197,7 @@ export const terminalCommands = {
 			return result === true || result === 'true'; // Handle string or boolean
 		} catch (error) {
 			console.debug('Terminal ping failed:', sessionId, error);
-			throw new Error(`Terminal ping failed: ${error}`);
+			throw new Error(`Terminal ping failed: ${getErrorMessage(error)}`);
 		}
 	},
 
@@ -102,14 +206,71 @@ export const terminalCommands = {
 	 * Only works for SSH sessions
 	 * @param {string} sessionId - Terminal session ID
 	 * @param {string} command - Command to execute
+	 * @param {number|null} [timeoutMs] - Give up (and close the channel) after this many ms
 	 * @returns {Promise<string>} Command output
 	 */
-	async executeCommand(sessionId, command) {
+	async executeCommand(sessionId, command, timeoutMs = null) {
 		try {
-			return await invoke('execute_terminal_command', { sessionId, command });
+			return await invoke('execute_terminal_command', { sessionId, command, timeoutMs });
 		} catch (error) {
 			console.error('Failed to execute command:', error);
-			throw new Error(`Failed to execute command: ${error}`);
+			throw new Error(`Failed to execute command: ${getErrorMessage(error)}`);
+		}
+	},
+
+	/**
+	 * Get session information (type, negotiated charset, SSH banner, algorithms and agent key used)
+	 * @param {string} sessionId - Terminal session ID
+	 * @returns {Promise<{sessionId: string, sessionType: string, charset: string|null, serverVersion: string|null, kex: string|null, cipher: string|null, mac: string|null, hostKeyAlgorithm: string|null, ptyAllocated: boolean|null, agentAuth: {keyCount: number, acceptedKey: string|null, rejectedKeys: string[]}|null}>}
+	 */
+	async getSessionInfo(sessionId) {
+		try {
+			return await invoke('get_terminal_session_info', { sessionId });
+		} catch (error) {
+			console.error('Failed to get session info:', error);
+			throw new Error(`Failed to get session info: ${getErrorMessage(error)}`);
+		}
+	},
+
+	/**
+	 * Get recent output kept by the backend, to repaint a terminal after a reload
+	 * @param {string} sessionId - Terminal session ID
+	 * @returns {Promise<string>} Output text (base64 bytes for binaryOutput sessions); empty if disabled
+	 */
+	async getScrollback(sessionId) {
+		try {
+			return await invoke('get_scrollback', { sessionId });
+		} catch (error) {
+			console.error('Failed to get scrollback:', error);
+			throw new Error(`Failed to get scrollback: ${getErrorMessage(error)}`);
+		}
+	},
+
+	/**
+	 * Record a session to an asciinema v2 .cast file (stopped automatically when the session closes)
+	 * @param {string} sessionId - Terminal session ID
+	 * @param {string} path - Local file to write (replaced if it exists)
+	 * @param {boolean} [recordInput=false] - Also record typed input, passwords included
+	 */
+	async startRecording(sessionId, path, recordInput = false) {
+		try {
+			await invoke('start_recording', { sessionId, path, recordInput });
+		} catch (error) {
+			console.error('Failed to start recording:', error);
+			throw new Error(`Failed to start recording: ${getErrorMessage(error)}`);
+		}
+	},
+
+	/**
+	 * Stop recording a session
+	 * @param {string} sessionId - Terminal session ID
+	 */
+	async stopRecording(sessionId) {
+		try {
+			await invoke('stop_recording', { sessionId });
+		} catch (error) {
+			console.error('Failed to stop recording:', error);
+			throw new Error(`Failed to stop recording: ${getErrorMessage(error)}`);
 		}
 	},
 
@@ -124,7 +285,7 @@ export const terminalCommands = {
 			return await invoke('fetch_command_history', { sessionId, limit });
 		} catch (error) {
 			console.error('Failed to fetch command history:', error);
-			throw new Error(`Failed to fetch command history: ${error}`);
+			throw new Error(`Failed to fetch command history: ${getErrorMessage(error)}`);
 		}
 	},
 
@@ -140,7 +301,7 @@ export const terminalCommands = {
 			return await invoke('fetch_local_shell_history', { shell, limit });
 		} catch (error) {
 			console.error('Failed to fetch local shell history:', error);
-			throw new Error(`Failed to fetch local shell history: ${error}`);
+			throw new Error(`Failed to fetch local shell history: ${getErrorMessage(error)}`);
 		}
 	}
 };
@@ -158,8 +158,13 @@ export async function startWatching(tempFilePath) {
 		// Initialize file change listener if not already done
 		if (!fileChangeUnlisten) {
 			fileChangeUnlisten = await listen('file-changed', async event => {
-				const changedPath = event.payload;
-				await handleFileChange(changedPath);
+				// kind: 'modified' | 'created' (replaced, e.g. by an editor's rename-on-save) | 'removed'
+				const { path, kind } = event.payload;
+				if (kind === 'removed') {
+					console.warn('[TempFileManager] Temp file removed, not uploading:', path);
+					return;
+				}
+				await handleFileChange(path);
 			});
 		}
 
@@ -223,7 +228,9 @@ async function handleFileChange(tempFilePath) {
 
 		// Upload file back to remote
 		const { uploadFile } = await import('../files/browser');
-		await uploadFile(sessionId, tempFilePath, remotePath, crypto.randomUUID());
+		await uploadFile(sessionId, tempFilePath, remotePath, crypto.randomUUID(), {
+			onConflict: 'overwrite'
+		});
 
 		// Notify upload complete via store
 		statusBarStore.showUpload(fileName, 100, 'success');
@@ -804,7 +804,7 @@ export async function handleOpenFile(file, type, sessionId) {
 		}
 
 		const extension = getFileExtension(file.name);
-		const preferredApp = getAppPreference(extension);
+		const preferredApp = await getAppPreference(extension);
 
 		if (preferredApp) {
 			// Use preferred app
@@ -814,7 +814,7 @@ export async function handleOpenFile(file, type, sessionId) {
 			try {
 				const selectedApp = await showOpenWithDialog(filePath);
 				if (selectedApp) {
-					setAppPreference(extension, selectedApp);
+					await setAppPreference(extension, selectedApp);
 					await openFileWithApp(filePath, selectedApp);
 				} else {
 					// User cancelled or no app selected, use system default
@@ -864,7 +864,7 @@ export async function handleOpenWithFile(file, type, sessionId) {
 			const selectedApp = await showOpenWithDialog(filePath);
 			if (selectedApp) {
 				const extension = getFileExtension(file.name);
-				setAppPreference(extension, selectedApp);
+				await setAppPreference(extension, selectedApp);
 				await openFileWithApp(filePath, selectedApp);
 			}
 			// If cancelled, do nothing (user explicitly cancelled)
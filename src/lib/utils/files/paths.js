@@ -95,16 +95,28 @@ import { getRemoteFileStat, getLocalFileStat } from '$lib/services';
 
 const MAX_DUPLICATE_COUNTER = 1000;
 const MAX_FILENAME_LENGTH = 255;
+// Kept whole when numbering duplicates ("archive (1).tar.gz"), same list as the backend's path_utils
+const COMPOUND_EXTENSIONS = ['.tar.gz', '.tar.bz2', '.tar.xz', '.tar.zst'];
 
 /**
  * Parse filename into base name and extension
+ * Dotfiles have no extension, a trailing dot is not one, and compound extensions stay whole
  * @param {string} fileName - Full filename
  * @returns {{ baseName: string, ext: string }}
  */
 export function parseFileName(fileName) {
+	const lower = fileName.toLowerCase();
+	const compound = COMPOUND_EXTENSIONS.find(
+		ext => lower.length > ext.length && lower.endsWith(ext)
+	);
+	if (compound) {
+		const index = fileName.length - compound.length;
+		return { baseName: fileName.substring(0, index), ext: fileName.substring(index) };
+	}
+
 	const lastDotIndex = fileName.lastIndexOf('.');
-	// No extension or hidden file (starts with dot)
-	if (lastDotIndex <= 0) {
+	// No extension, hidden file (starts with dot) or trailing dot
+	if (lastDotIndex <= 0 || lastDotIndex === fileName.length - 1) {
 		return { baseName: fileName, ext: '' };
 	}
 	return {
@@ -224,12 +236,13 @@ export async function generateUniqueLocalPath(directory, fileName) {
  */
 function generateCounterName(baseName, ext, counter) {
 	const suffix = ` (${counter})`;
-	let newName = `${baseName}${suffix}${ext}`;
+	const base = baseName.trimEnd();
+	let newName = `${base}${suffix}${ext}`;
 
 	// Check filename length and truncate if necessary
 	if (newName.length > MAX_FILENAME_LENGTH) {
 		const maxBaseLength = MAX_FILENAME_LENGTH - suffix.length - ext.length;
-		const truncatedBase = baseName.substring(0, maxBaseLength);
+		const truncatedBase = base.substring(0, maxBaseLength);
 		newName = `${truncatedBase}${suffix}${ext}`;
 	}
 
@@ -287,11 +300,7 @@ export function generateUniqueName(baseName, existingNames = [], options = {}) {
 
 	// Extract base name and extension for files
 	// For tabs/labels without extension, treat entire string as base
-	const lastDotIndex = baseName.lastIndexOf('.');
-	const hasExtension = lastDotIndex > 0 && lastDotIndex < baseName.length - 1;
-
-	const base = hasExtension ? baseName.substring(0, lastDotIndex) : baseName;
-	const ext = hasExtension ? baseName.substring(lastDotIndex) : '';
+	const { baseName: base, ext } = parseFileName(baseName);
 
 	// Find next available number
 	let counter = 1;